@@ -8,11 +8,16 @@ use std::path::{Path, PathBuf};
 use log::{debug, info, LevelFilter};
 use structopt::StructOpt;
 
-use tydi::design::{Library, Project};
+use tydi::design::implementation::composer::parser::{dependency_order, impl_dependencies};
+use tydi::design::namespace::{self, Source};
+use tydi::design::Project;
+use tydi::generator::chisel::{ChiselBackEnd, ChiselConfig};
+use tydi::generator::dot::{DotBackend, DotConfig};
+use tydi::generator::sv::{SVBackEnd, SVConfig};
 use tydi::generator::vhdl::{VHDLBackEnd, VHDLConfig};
 use tydi::generator::GenerateProject;
-use tydi::UniquelyNamedBuilder;
-use tydi::{Logger, Result};
+use tydi::parser::printer;
+use tydi::{Error, Logger, Name, Result, UniquelyNamedBuilder};
 
 static LOGGER: Logger = Logger;
 
@@ -23,7 +28,11 @@ enum TargetOpt {
     /// Generate VHDL sources.
     VHDL(VHDLConfig),
     /// Generate Chisel sources.
-    Chisel,
+    Chisel(ChiselConfig),
+    /// Generate SystemVerilog sources.
+    SV(SVConfig),
+    /// Generate Graphviz DOT graphs of streamlets and their implementations.
+    Dot(DotConfig),
 }
 
 #[derive(Debug, StructOpt)]
@@ -38,6 +47,20 @@ struct GenerateOpts {
     )]
     inputs: Option<Vec<PathBuf>>,
 
+    #[structopt(
+        short = "m",
+        long = "impl",
+        help = "Implementation files to apply to the streamlets.\n\
+                If not supplied, all .impl files next to the Streamlet Definition Files are used."
+    )]
+    impls: Option<Vec<PathBuf>>,
+
+    /// Insert adapters on connections between streams with a different complexity or
+    /// throughput. The adapters are black boxes: only their components are generated, without
+    /// an architecture that performs the conversion.
+    #[structopt(long)]
+    adapters: bool,
+
     #[structopt(
         short,
         help = "Output directory for generated files.\n\
@@ -49,11 +72,106 @@ struct GenerateOpts {
     target: TargetOpt,
 }
 
+#[derive(Debug, StructOpt)]
+struct CheckOpts {
+    #[structopt(
+        short,
+        help = "Streamlet Definition Files to check.\n\
+                If not supplied, all .sdf files in the current directory are used."
+    )]
+    inputs: Option<Vec<PathBuf>>,
+
+    #[structopt(
+        short = "m",
+        long = "impl",
+        help = "Implementation files to check.\n\
+                If not supplied, all .impl files next to the Streamlet Definition Files are used."
+    )]
+    impls: Option<Vec<PathBuf>>,
+
+    /// Accept connections between streams with a different complexity or throughput, which
+    /// adapters would fix when generating with --adapters.
+    #[structopt(long)]
+    adapters: bool,
+}
+
+/// Formats a project can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+enum ExportFormat {
+    JSON,
+    YAML,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(ExportFormat::JSON),
+            "yaml" => Ok(ExportFormat::YAML),
+            _ => Err(Error::InvalidArgument(format!(
+                "{} is not a valid export format, expected json or yaml",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct ExportOpts {
+    /// Name of the project to export.
+    name: String,
+
+    #[structopt(
+        short,
+        help = "Streamlet Definition Files to export.\n\
+                If not supplied, all .sdf files in the current directory are used."
+    )]
+    inputs: Option<Vec<PathBuf>>,
+
+    #[structopt(
+        short = "m",
+        long = "impl",
+        help = "Implementation files to export.\n\
+                If not supplied, all .impl files next to the Streamlet Definition Files are used."
+    )]
+    impls: Option<Vec<PathBuf>>,
+
+    /// Export format, either json or yaml.
+    #[structopt(short, long, default_value = "json")]
+    format: ExportFormat,
+
+    #[structopt(
+        short,
+        help = "Output file of the export.\n\
+                If not supplied, the export is written to the standard output."
+    )]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct FmtOpts {
+    #[structopt(help = "Streamlet Definition Files to format.\n\
+                If not supplied, all .sdf files in the current directory are used.")]
+    inputs: Vec<PathBuf>,
+
+    /// Only check whether the files are formatted, without changing them.
+    #[structopt(long)]
+    check: bool,
+}
+
 /// Top-level CLI commands
 #[derive(Debug, StructOpt)]
 enum Command {
     /// Generate HDL output from Streamlet Definition Files.
     Generate(GenerateOpts),
+    /// Check Streamlet Definition Files and implementations, reporting all errors.
+    Check(CheckOpts),
+    /// Export Streamlet Definition Files and implementations as JSON or YAML.
+    Export(ExportOpts),
+    /// Format Streamlet Definition Files.
+    Fmt(FmtOpts),
 }
 
 #[derive(Debug, StructOpt)]
@@ -68,52 +186,248 @@ pub struct Opt {
     cmd: Command,
 }
 
-/// Return all .sdf files in a path.
-fn list_all_sdf(path: &Path) -> Result<Vec<PathBuf>> {
-    let sdf_files: Vec<PathBuf> = std::fs::read_dir(path)?
+/// Return all files with some extension in a path, in alphabetical order.
+fn list_all(path: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
         .filter_map(|e| e.ok())
         .map(|de| de.path())
-        .filter(|p| p.extension().unwrap_or_default() == "sdf")
+        .filter(|p| p.extension().unwrap_or_default() == extension)
         .collect();
-    Ok(sdf_files)
+    files.sort();
+    Ok(files)
 }
 
-/// Generate sources from options.
-fn generate(opts: GenerateOpts) -> Result<()> {
+/// Return all .sdf files in a path.
+fn list_all_sdf(path: &Path) -> Result<Vec<PathBuf>> {
+    list_all(path, "sdf")
+}
+
+/// Return all .impl files in the directories of the Streamlet Definition Files.
+fn list_impls_next_to(sdf_files: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut dirs: Vec<&Path> = vec![];
+    for file in sdf_files {
+        let dir = match file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    let mut impl_files = vec![];
+    for dir in dirs {
+        impl_files.extend(list_all(dir, "impl")?);
+    }
+    Ok(impl_files)
+}
+
+/// Add the implementations in [files] to [project], in dependency order, inserting adapters if
+/// [adapters] is set. Returns a message for every error that was found.
+fn add_implementations(project: &mut Project, files: &[PathBuf], adapters: bool) -> Vec<String> {
+    let mut errors = vec![];
+    let mut implementations = vec![];
+    for file in files.iter() {
+        debug!("Parsing {}", file.display());
+        let result = std::fs::read_to_string(file)
+            .map_err(Error::from)
+            .and_then(|input| Ok((impl_dependencies(&input)?, input)));
+        match result {
+            Ok((dependencies, input)) => implementations.push((file, dependencies, input)),
+            Err(err) => errors.push(format!("{}: {}", file.display(), err)),
+        }
+    }
+
+    let dependencies: Vec<_> = implementations.iter().map(|(_, d, _)| d.clone()).collect();
+    match dependency_order(&dependencies) {
+        Ok(order) => {
+            for index in order {
+                let (file, _, input) = &implementations[index];
+                if let Err(err) = project.add_implementation_with_adapters(input, adapters) {
+                    errors.push(format!("{}: {}", file.display(), err));
+                }
+            }
+        }
+        Err(err) => errors.push(err.to_string()),
+    }
+    errors
+}
+
+/// Load a project from Streamlet Definition Files and implementation files.
+///
+/// If no input files are given, all .sdf files in the current path are used. If no
+/// implementation files are given, all .impl files next to the input files are used. If
+/// [adapters] is set, adapters are inserted in the implementations where required.
+fn load_project(
+    name: String,
+    inputs: Option<Vec<PathBuf>>,
+    impls: Option<Vec<PathBuf>>,
+    adapters: bool,
+) -> Result<Project> {
     info!("Loading Streamlet Definition Files...");
-    // Obtain all input files from options.
-    // If no option is given, get all .sdf files in the current path.
-    let input_files = opts
-        .inputs
-        .unwrap_or(list_all_sdf(std::env::current_dir()?.as_path())?);
+    let input_files = match inputs {
+        Some(inputs) => inputs,
+        None => list_all_sdf(std::env::current_dir()?.as_path())?,
+    };
+    let impl_files = match impls {
+        Some(impls) => impls,
+        None => list_impls_next_to(&input_files)?,
+    };
 
-    let input_file_names: Vec<&str> = input_files.iter().filter_map(|pb| pb.to_str()).collect();
+    let input_file_names: Vec<&str> = input_files
+        .iter()
+        .chain(impl_files.iter())
+        .filter_map(|pb| pb.to_str())
+        .collect();
     debug!("Inputs: {}", input_file_names.join(", "));
 
-    // Build up a set of uniquely named libraries.
-    let mut lib_builder = UniquelyNamedBuilder::new();
-    for i in input_files {
-        lib_builder.add_item(Library::from_file(i.as_path())?);
+    // Construct the project from the libraries declared by the files, resolving imports
+    // between them.
+    let mut project = Project::from_files(name.try_into()?, &input_files)?;
+
+    info!("Loading implementations...");
+    let errors = add_implementations(&mut project, &impl_files, adapters);
+    if !errors.is_empty() {
+        return Err(Error::CLIError(errors.join("\n")));
     }
+    Ok(project)
+}
+
+/// Generate sources from options.
+fn generate(opts: GenerateOpts) -> Result<()> {
+    let project = load_project(opts.name, opts.inputs, opts.impls, opts.adapters)?;
 
-    // Construct the project from the libraries.
-    let project = Project::from_builder(opts.name.try_into()?, lib_builder)?;
+    let output = match opts.output {
+        Some(output) => output,
+        None => std::env::current_dir()?,
+    };
 
     info!("Generating sources...");
     match opts.target {
         TargetOpt::VHDL(cfg) => {
             let vhdl: VHDLBackEnd = cfg.into();
-            vhdl.generate(
-                &project,
-                opts.output.unwrap_or(std::env::current_dir()?).as_path(),
-            )?;
+            vhdl.generate(&project, output.as_path())?;
+        }
+        TargetOpt::Chisel(cfg) => {
+            let chisel: ChiselBackEnd = cfg.into();
+            chisel.generate(&project, output.as_path())?;
+        }
+        TargetOpt::SV(cfg) => {
+            let sv: SVBackEnd = cfg.into();
+            sv.generate(&project, output.as_path())?;
+        }
+        TargetOpt::Dot(cfg) => {
+            let dot: DotBackend = cfg.into();
+            dot.generate(&project, output.as_path())?;
         }
-        TargetOpt::Chisel => {}
     }
     info!("Done.");
     Ok(())
 }
 
+/// Check Streamlet Definition Files and implementations, without generating anything.
+///
+/// Unlike generation, checking continues after an error, such that all errors are reported at
+/// once. Returns an error if any were found.
+fn check(opts: CheckOpts) -> Result<()> {
+    let errors = check_errors(opts)?;
+    if errors.is_empty() {
+        info!("No errors found.");
+        Ok(())
+    } else {
+        for err in errors.iter() {
+            eprintln!("{}\n", err.trim_end());
+        }
+        Err(Error::CLIError(format!("{} error(s) found", errors.len())))
+    }
+}
+
+/// Returns a message for every error found by [check].
+fn check_errors(opts: CheckOpts) -> Result<Vec<String>> {
+    let input_files = match opts.inputs {
+        Some(inputs) => inputs,
+        None => list_all_sdf(std::env::current_dir()?.as_path())?,
+    };
+    let impl_files = match opts.impls {
+        Some(impls) => impls,
+        None => list_impls_next_to(&input_files)?,
+    };
+
+    let mut errors: Vec<String> = vec![];
+
+    info!("Checking Streamlet Definition Files...");
+    let mut sources = vec![];
+    for file in input_files.iter() {
+        debug!("Parsing {}", file.display());
+        match Source::from_file(file) {
+            Ok(source) => sources.push(source),
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+    let (libraries, resolve_errors) = namespace::resolve_all(&sources);
+    errors.extend(resolve_errors.iter().map(|err| err.to_string()));
+    let mut project = Project::from_builder(
+        Name::try_new("check")?,
+        UniquelyNamedBuilder::new().with_items(libraries),
+    )?;
+
+    info!("Checking implementations...");
+    errors.extend(add_implementations(
+        &mut project,
+        &impl_files,
+        opts.adapters,
+    ));
+    Ok(errors)
+}
+
+/// Export a project from options.
+fn export(opts: ExportOpts) -> Result<()> {
+    let project = load_project(opts.name, opts.inputs, opts.impls, false)?;
+    let exported = match opts.format {
+        ExportFormat::JSON => tydi::schema::to_json(&project)?,
+        ExportFormat::YAML => tydi::schema::to_yaml(&project)?,
+    };
+    match opts.output {
+        Some(output) => std::fs::write(output, exported)?,
+        None => println!("{}", exported),
+    }
+    Ok(())
+}
+
+/// Format Streamlet Definition Files from options.
+///
+/// In check mode, the files are not changed, and an error is returned if any file is not
+/// formatted.
+fn fmt(opts: FmtOpts) -> Result<()> {
+    let files = if opts.inputs.is_empty() {
+        list_all_sdf(std::env::current_dir()?.as_path())?
+    } else {
+        opts.inputs
+    };
+    let mut unformatted = 0;
+    for file in files.iter() {
+        let input = std::fs::read_to_string(file)?;
+        let formatted = printer::format(file.to_str(), &input)?;
+        if formatted == input {
+            continue;
+        }
+        if opts.check {
+            eprintln!("{} is not formatted", file.display());
+            unformatted += 1;
+        } else {
+            info!("Formatting {}", file.display());
+            std::fs::write(file, formatted)?;
+        }
+    }
+    if unformatted == 0 {
+        Ok(())
+    } else {
+        Err(Error::CLIError(format!(
+            "{} file(s) not formatted",
+            unformatted
+        )))
+    }
+}
+
 /// Internal main function wrapped with CLI main function.
 /// Useful for tests.
 pub fn internal_main(options: Opt) -> Result<()> {
@@ -129,6 +443,9 @@ pub fn internal_main(options: Opt) -> Result<()> {
 
     match options.cmd {
         Command::Generate(gen_opts) => generate(gen_opts),
+        Command::Check(check_opts) => check(check_opts),
+        Command::Export(export_opts) => export(export_opts),
+        Command::Fmt(fmt_opts) => fmt(fmt_opts),
     }
 }
 
@@ -157,9 +474,226 @@ mod tests {
         std::fs::remove_dir_all(tmpdir.path())?;
         Ok(())
     }
+
+    #[test]
+    fn generate_with_implementations() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let write = |name: &str, content: &str| -> Result<PathBuf> {
+            let path = tmpdir.path().join(name);
+            std::fs::write(&path, content)?;
+            Ok(path)
+        };
+        let lib = write(
+            "lib.sdf",
+            "Streamlet top (i: in Stream<Bits<8>>, o: out Stream<Bits<8>>)
+             Streamlet inner (i: in Stream<Bits<8>>, o: out Stream<Bits<8>>)
+             Streamlet leaf (i: in Stream<Bits<8>>, o: out Stream<Bits<8>>)",
+        )?;
+        // The implementation of top instantiates inner, so inner has to be implemented first.
+        write(
+            "a_top.impl",
+            "impl lib.top structural {
+                 inner_inst: lib.inner;
+                 inner_inst.i <= this.i;
+                 this.o <= inner_inst.o;
+             }",
+        )?;
+        write(
+            "b_inner.impl",
+            "impl lib.inner structural {
+                 leaf_inst: lib.leaf;
+                 leaf_inst.i <= this.i;
+                 this.o <= leaf_inst.o;
+             }",
+        )?;
+        let output = tmpdir.path().join("out");
+        generate(GenerateOpts {
+            name: "proj".to_string(),
+            inputs: Some(vec![lib]),
+            impls: None,
+            adapters: false,
+            output: Some(output.clone()),
+            target: TargetOpt::Dot(DotConfig::default()),
+        })?;
+        let dot = std::fs::read_to_string(output.join("proj/lib.dot"))?;
+        // The implementation of inner is shown within the instance of inner in top.
+        assert!(dot.contains("subgraph cluster_lib_top_impl_inner_impl_leaf_inst"));
+        Ok(())
+    }
+
+    #[test]
+    fn generate_patterns_of_multiple_implementations() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let lib = tmpdir.path().join("lib.sdf");
+        std::fs::write(
+            &lib,
+            "Streamlet a (i: in Stream<Bits<8>>, o: out Stream<Bits<8>>)
+             Streamlet b (i: in Stream<Bits<8>>, o: out Stream<Bits<8>>)",
+        )?;
+        // Both implementations use the same name for a pattern node.
+        for streamlet in &["a", "b"] {
+            std::fs::write(
+                tmpdir.path().join(format!("{}.impl", streamlet)),
+                format!(
+                    "impl lib.{} structural {{
+                         c: FIFO(depth=4);
+                         c.in <= this.i;
+                         this.o <= c.out;
+                     }}",
+                    streamlet
+                ),
+            )?;
+        }
+        let output = tmpdir.path().join("out");
+        generate(GenerateOpts {
+            name: "proj".to_string(),
+            inputs: Some(vec![lib]),
+            impls: None,
+            adapters: false,
+            output: Some(output.clone()),
+            target: TargetOpt::VHDL(VHDLConfig::default()),
+        })?;
+        let gen_pkg = std::fs::read_to_string(output.join("proj/gen_pkg.gen.vhd"))?;
+        assert!(gen_pkg.contains("component a_c_gen_com"));
+        assert!(gen_pkg.contains("component b_c_gen_com"));
+        Ok(())
+    }
+
+    #[test]
+    fn check_reports_all_errors() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let write = |name: &str, content: &str| -> Result<PathBuf> {
+            let path = tmpdir.path().join(name);
+            std::fs::write(&path, content)?;
+            Ok(path)
+        };
+        let lib = write(
+            "lib.sdf",
+            "Streamlet a (i: in Stream<Bits<8>>, o: out Stream<Bits<8>>)
+             Streamlet b (i: in Stream<Bits<8>>, o: out Stream<Bits<8>>)",
+        )?;
+        let typo = write("typo.sdf", "Streamlt c (i: in Stream<Bits<8>>)")?;
+        let unresolved = write("unresolved.sdf", "Streamlet d (i: in Stream<Byte>)")?;
+        write("a.impl", "impl lib.a structural { this.o <= this.i; }")?;
+        write("b.impl", "impl lib.b structural { x: lib.missing; }")?;
+
+        let opts = |inputs: Vec<PathBuf>| CheckOpts {
+            inputs: Some(inputs),
+            impls: None,
+            adapters: false,
+        };
+
+        // The implementations next to the files are picked up.
+        let errors = check_errors(opts(vec![lib.clone(), typo.clone(), unresolved.clone()]))?;
+        assert_eq!(
+            errors,
+            vec![
+                format!(
+                    "Parsing error: expected declaration (`use`, `type` or `Streamlet`), found \
+                     `Streamlt`\n --> {}:1:1\n  |\n1 | Streamlt c (i: in Stream<Bits<8>>)\n  \
+                     | ^^^^^^^^",
+                    typo.display()
+                ),
+                format!(
+                    "Project error: {}: unresolved name Byte in streamlet d; declare it with \
+                     `type Byte = ...;`, import it with `use <library>::Byte;` or declare a \
+                     parameter with this name",
+                    unresolved.display()
+                ),
+                format!(
+                    "{}: Project error: Streamlet missing not found in library lib",
+                    tmpdir.path().join("b.impl").display()
+                ),
+            ]
+        );
+        let err = check(opts(vec![lib.clone(), typo, unresolved])).unwrap_err();
+        assert_eq!(err, Error::CLIError("3 error(s) found".to_string()));
+
+        let err = check(CheckOpts {
+            inputs: Some(vec![lib.clone()]),
+            impls: Some(vec![tmpdir.path().join("a.impl")]),
+            adapters: false,
+        });
+        assert!(err.is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn fmt_check() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let file = tmpdir.path().join("lib.sdf");
+        std::fs::write(&file, "Streamlet x(a:in Stream<Bits<1>>)")?;
+        let opts = |check| FmtOpts {
+            inputs: vec![file.clone()],
+            check,
+        };
+
+        assert_eq!(
+            fmt(opts(true)),
+            Err(Error::CLIError("1 file(s) not formatted".to_string()))
+        );
+        fmt(opts(false))?;
+        assert_eq!(
+            std::fs::read_to_string(&file)?,
+            "Streamlet x (\n    a: in Stream<Bits<1>>\n)\n"
+        );
+        fmt(opts(true))?;
+        Ok(())
+    }
+
+    #[test]
+    fn export_round_trip() -> Result<()> {
+        use tydi::design::implementation::composer::GenericComponent;
+        use tydi::Identify;
+
+        let tmpdir = tempfile::tempdir()?;
+        let lib = tmpdir.path().join("lib.sdf");
+        std::fs::write(
+            &lib,
+            "Streamlet a (i: in Stream<Bits<8>>, o: out Stream<Bits<8>>)",
+        )?;
+        std::fs::write(
+            tmpdir.path().join("a.impl"),
+            "impl lib.a structural { this.o <= this.i; }",
+        )?;
+        for (format, from) in [
+            (
+                "json",
+                tydi::schema::from_json as fn(&str) -> Result<Project>,
+            ),
+            ("yaml", tydi::schema::from_yaml),
+        ]
+        .iter()
+        {
+            let output = tmpdir.path().join(format!("proj.{}", format));
+            export(ExportOpts {
+                name: "proj".to_string(),
+                inputs: Some(vec![lib.clone()]),
+                impls: None,
+                format: format.parse()?,
+                output: Some(output.clone()),
+            })?;
+            let project = from(&std::fs::read_to_string(output)?)?;
+            assert_eq!(project.identifier(), "proj");
+            let streamlet = project
+                .get_lib(Name::try_new("lib")?)?
+                .get_streamlet(Name::try_new("a")?)?;
+            assert!(streamlet.get_implementation().is_some());
+        }
+        assert_eq!(
+            "xml".parse::<ExportFormat>(),
+            Err(Error::InvalidArgument(
+                "xml is not a valid export format, expected json or yaml".to_string()
+            ))
+        );
+        Ok(())
+    }
 }
 
 /// CLI main function.
-fn main() -> Result<()> {
-    internal_main(Opt::from_args())
+fn main() {
+    if let Err(err) = internal_main(Opt::from_args()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,317 @@
+//! Insertion of adapters between interfaces with incompatible types.
+//!
+//! A source can only be connected to a sink if their types are compatible, see
+//! [LogicalType::mismatches]. Mismatches in the complexity and the throughput of streams can be
+//! resolved by converting the streams. [insert_adapters] replaces every edge with only such
+//! mismatches by a path through a [ComplexityConverter] and/or a [LaneConverter] node. A
+//! [LaneConverter] is only inserted if the number of element lanes of a physical stream
+//! differs, throughputs that result in the same number of lanes (e.g. 2.5 and 3) are connected
+//! directly. The streamlets of these nodes are added to the library of generated streamlets,
+//! such that they show up in the generated HDL like any other node.
+//!
+//! The converters are black boxes: only their components (e.g. `top_b_in_lanes_gen`) are
+//! generated, without an architecture that performs the conversion. Their behaviour has to be
+//! supplied separately.
+
+use std::rc::Rc;
+
+use crate::design::implementation::composer::impl_graph::{Edge, ImplementationGraph, Node};
+use crate::design::implementation::composer::patterns::{ComplexityConverter, LaneConverter};
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{
+    IFKey, LibKey, Library, NodeIFHandle, NodeKey, Project, StreamletHandle, GEN_LIB,
+};
+use crate::logical::{LogicalType, Mismatch, MismatchKind};
+use crate::{cat, Error, Name, Result};
+
+/// Returns true if [mismatch] can be resolved by inserting an adapter.
+pub fn is_adaptable(mismatch: &Mismatch) -> bool {
+    matches!(
+        mismatch.kind(),
+        MismatchKind::Complexity(_, _) | MismatchKind::Throughput(_, _)
+    )
+}
+
+/// Insert adapters on all edges of [graph] of which the source type is not compatible with the
+/// sink type. Complexity mismatches are resolved first, followed by throughput mismatches that
+/// change the number of element lanes. The adapters are black boxes, see the
+/// [module documentation](self).
+///
+/// The adapter nodes are named after the sink they are inserted for, e.g. `b_in_complexity` and
+/// `b_in_lanes`. Returns the keys of the inserted nodes, or an error if an edge has mismatches
+/// that cannot be resolved by an adapter.
+pub fn insert_adapters(
+    project: &mut Project,
+    graph: &mut ImplementationGraph,
+) -> Result<Vec<NodeKey>> {
+    let gen_key = LibKey::try_new(GEN_LIB)?;
+    if project.get_lib(gen_key.clone()).is_err() {
+        project.add_lib(Library::new(gen_key))?;
+    }
+
+    let mut inserted = vec![];
+    for edge in std::mem::take(&mut graph.edges) {
+        let iface_type = |handle: &NodeIFHandle| -> Result<LogicalType> {
+            Ok(graph.get_node(handle.node())?.iface(handle.iface())?.typ())
+        };
+        let source_type = iface_type(&edge.source)?;
+        let sink_type = iface_type(&edge.sink)?;
+        let mismatches = source_type.mismatches(&sink_type);
+        if mismatches.is_empty() {
+            graph.edges.push(edge);
+            continue;
+        }
+        if let Some(mismatch) = mismatches.iter().find(|m| !is_adaptable(m)) {
+            return Err(Error::ComposerError(format!(
+                "Cannot insert an adapter between sink {}.{} and source {}.{}: {}",
+                edge.sink.node(),
+                edge.sink.iface(),
+                edge.source.node(),
+                edge.source.iface(),
+                mismatch
+            )));
+        }
+
+        let mut source = edge.source.clone();
+        let mut typ = source_type;
+        let adapter_key =
+            |kind: &str| NodeKey::try_new(cat!(edge.sink.node(), edge.sink.iface(), kind));
+        if mismatches
+            .iter()
+            .any(|m| matches!(m.kind(), MismatchKind::Complexity(_, _)))
+        {
+            let key = adapter_key("complexity")?;
+            let mut component =
+                ComplexityConverter::try_new(graph.gen_name(&key)?, typ, &sink_type)?;
+            let handle = gen_handle(graph, &key)?;
+            component.with_backend(handle.streamlet(), handle)?;
+            typ = insert(project, graph, key.clone(), component.finish(), &mut source)?;
+            inserted.push(key);
+        }
+        if mismatches
+            .iter()
+            .any(|m| matches!(m.kind(), MismatchKind::Throughput(_, _)))
+            && element_lanes_differ(&typ, &sink_type)
+        {
+            let key = adapter_key("lanes")?;
+            let mut component =
+                LaneConverter::try_new(graph.gen_name(&key)?, typ, sink_type.clone())?;
+            let handle = gen_handle(graph, &key)?;
+            component.with_backend(handle.streamlet(), handle)?;
+            insert(project, graph, key.clone(), component.finish(), &mut source)?;
+            inserted.push(key);
+        }
+        graph.edges.push(Edge {
+            source,
+            sink: edge.sink,
+        });
+    }
+    Ok(inserted)
+}
+
+/// Returns true if a physical stream of [source] has a different number of element lanes than
+/// the corresponding physical stream of [sink].
+fn element_lanes_differ(source: &LogicalType, sink: &LogicalType) -> bool {
+    let (source, sink) = (source.synthesize(), sink.synthesize());
+    let differ = source
+        .streams()
+        .zip(sink.streams())
+        .any(|((_, source), (_, sink))| source.element_lanes() != sink.element_lanes());
+    differ
+}
+
+fn gen_handle(graph: &ImplementationGraph, key: &NodeKey) -> Result<StreamletHandle> {
+    Ok(StreamletHandle {
+        lib: Name::try_new(GEN_LIB)?,
+        streamlet: graph.gen_name(key)?,
+    })
+}
+
+/// Add [component] as node [key] to [graph], connecting its input to [source]. Updates
+/// [source] to the output of the component, and returns the type of that output.
+fn insert<C: GenericComponent + 'static>(
+    project: &mut Project,
+    graph: &mut ImplementationGraph,
+    key: NodeKey,
+    component: C,
+    source: &mut NodeIFHandle,
+) -> Result<LogicalType> {
+    if graph.nodes.contains_key(&key) {
+        return Err(Error::ComposerError(format!(
+            "Cannot insert adapter {}, an instance with that name already exists.",
+            key
+        )));
+    }
+    let output = component.get_interface(IFKey::try_new("out")?)?.typ();
+    let handle = project
+        .get_lib_mut(Name::try_new(GEN_LIB)?)?
+        .add_streamlet(component.streamlet().clone())?;
+    graph.nodes.insert(
+        key.clone(),
+        Node {
+            key: key.clone(),
+            item: Rc::new(component),
+            streamlet: handle,
+            parameters: vec![],
+        },
+    );
+    graph.edges.push(Edge {
+        source: source.clone(),
+        sink: NodeIFHandle::new(key.clone(), IFKey::try_new("in")?),
+    });
+    *source = NodeIFHandle::new(key, IFKey::try_new("out")?);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::design::implementation::Implementation;
+    use crate::generator::dot::DotBackend;
+    use crate::generator::vhdl::VHDLBackEnd;
+    use crate::generator::GenerateProject;
+    use crate::{Document, UniquelyNamedBuilder};
+
+    use super::*;
+
+    fn project() -> Result<Project> {
+        let streamlets = crate::parser::nom::list_of_streamlets(
+            "Streamlet a (o: out Stream<Bits<8>, c=7>)
+             Streamlet b (i: in Stream<Bits<8>, t=4, c=2>)
+             Streamlet c (i: in Stream<Bits<8>, d=1>)
+             Streamlet d (i: in Stream<Bits<8>, c=2>, o: out Stream<Bits<8>, c=2>)
+             Streamlet e (i: in Stream<Bits<8>, c=7>)
+             Streamlet f (o: out Stream<Bits<8>, t=2.5>)
+             Streamlet g (i: in Stream<Bits<8>, t=3>)
+             Streamlet top ()",
+        )
+        .unwrap()
+        .1;
+        let lib = Library::try_new(Name::try_new("lib")?, vec![], streamlets)?;
+        Project::from_builder(
+            Name::try_new("proj")?,
+            UniquelyNamedBuilder::new().with_items(vec![lib]),
+        )
+    }
+
+    #[test]
+    fn adapters() -> Result<()> {
+        let mut prj = project()?;
+        let input = "impl lib.top structural { a: lib.a; b: lib.b; b.i <= a.o; }";
+        assert!(prj.add_implementation(input).is_err());
+
+        let handle = prj.add_implementation_with_adapters(input, true)?;
+        let top = prj.get_streamlet(handle)?;
+        let graph = match top.get_implementation().as_deref() {
+            Some(Implementation::Structural(graph)) => graph.clone(),
+            _ => unreachable!(),
+        };
+        let edges: Vec<String> = graph
+            .edges()
+            .map(|e| {
+                format!(
+                    "{}.{} <= {}.{}",
+                    e.sink().node(),
+                    e.sink().iface(),
+                    e.source().node(),
+                    e.source().iface()
+                )
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                "b_i_complexity.in <= a.o",
+                "b_i_lanes.in <= b_i_complexity.out",
+                "b.i <= b_i_lanes.out"
+            ]
+        );
+        let gen = prj.get_lib(Name::try_new(GEN_LIB)?)?;
+        assert_eq!(
+            gen.get_streamlet(Name::try_new("top_b_i_complexity_gen")?)?
+                .doc()
+                .unwrap(),
+            "Converts the physical stream from complexity 7 to 2."
+        );
+        assert_eq!(
+            gen.get_streamlet(Name::try_new("top_b_i_lanes_gen")?)?
+                .doc()
+                .unwrap(),
+            "Widens the physical stream from 1 to 4 element lanes."
+        );
+
+        // The adapters are instantiated in the generated HDL and shown in the DOT graph.
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::default().generate(&prj, tmpdir.path())?;
+        let vhdl = std::fs::read_to_string(tmpdir.path().join("proj/top_impl.gen.vhd"))?;
+        assert!(vhdl.contains("b_i_complexity: top_b_i_complexity_gen_com"));
+        assert!(vhdl.contains("b_i_lanes: top_b_i_lanes_gen_com"));
+        DotBackend::default().generate(&prj, tmpdir.path())?;
+        let dot = std::fs::read_to_string(tmpdir.path().join("proj/lib.dot"))?;
+        assert!(dot.contains("b_i_complexity"));
+        Ok(())
+    }
+
+    #[test]
+    fn only_required_adapters() -> Result<()> {
+        let mut prj = project()?;
+        let handle = prj.add_implementation_with_adapters(
+            "impl lib.top structural { a: lib.a; d: lib.d; e: lib.e; d.i <= a.o; e.i <= d.o; }",
+            true,
+        )?;
+        let top = prj.get_streamlet(handle)?;
+        let graph = match top.get_implementation().as_deref() {
+            Some(Implementation::Structural(graph)) => graph.clone(),
+            _ => unreachable!(),
+        };
+        // Only the complexity differs, so no lane converter is inserted, and edges between
+        // compatible types are left alone, even if the types are not equal.
+        let mut nodes: Vec<String> = graph.nodes().map(|n| n.key().to_string()).collect();
+        nodes.sort();
+        assert_eq!(nodes, vec!["a", "d", "d_i_complexity", "e", "this"]);
+        assert_eq!(graph.edges().count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn equal_element_lanes() -> Result<()> {
+        let mut prj = project()?;
+        let input = "impl lib.top structural { f: lib.f; g: lib.g; g.i <= f.o; }";
+        assert!(prj.add_implementation(input).is_err());
+        let handle = prj.add_implementation_with_adapters(input, true)?;
+        let top = prj.get_streamlet(handle)?;
+        let graph = match top.get_implementation().as_deref() {
+            Some(Implementation::Structural(graph)) => graph.clone(),
+            _ => unreachable!(),
+        };
+        // Throughputs 2.5 and 3 both result in 3 element lanes, so no lane converter is needed.
+        let mut nodes: Vec<String> = graph.nodes().map(|n| n.key().to_string()).collect();
+        nodes.sort();
+        assert_eq!(nodes, vec!["f", "g", "this"]);
+        assert_eq!(graph.edges().count(), 1);
+        assert!(prj
+            .get_lib(Name::try_new(GEN_LIB)?)?
+            .get_streamlet(Name::try_new("top_g_i_lanes_gen")?)
+            .is_err());
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::default().generate(&prj, tmpdir.path())?;
+        let vhdl = std::fs::read_to_string(tmpdir.path().join("proj/top_impl.gen.vhd"))?;
+        assert!(!vhdl.contains("lanes"));
+        Ok(())
+    }
+
+    #[test]
+    fn not_adaptable() -> Result<()> {
+        let mut prj = project()?;
+        let err = prj
+            .add_implementation_with_adapters(
+                "impl lib.top structural { a: lib.a; c: lib.c; c.i <= a.o; }",
+                true,
+            )
+            .unwrap_err()
+            .to_string();
+        // Only the mismatches that can be adapted are accepted.
+        assert!(err
+            .contains("\n  dimensionality of source (0) differs from dimensionality of sink (1)"));
+        Ok(())
+    }
+}
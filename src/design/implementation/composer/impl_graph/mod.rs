@@ -2,15 +2,17 @@ use std::cell::{Ref, RefMut};
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt::Debug;
+use std::ops::Deref;
 use std::rc::Rc;
 
 use nom::lib::std::fmt::Formatter;
 
 use crate::design::implementation::composer::GenericComponent;
-use crate::design::{IFKey, Interface, NodeIFHandle, NodeKey, StreamletHandle, StreamletKey};
-use crate::{Error, Result};
-
-
+use crate::design::param::Assignments;
+use crate::design::{
+    IFKey, Interface, NodeIFHandle, NodeKey, Streamlet, StreamletHandle, StreamletKey,
+};
+use crate::{cat, Error, Name, Result, Reversed, UniqueKeyBuilder};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Edge {
@@ -31,6 +33,11 @@ impl Edge {
 pub struct Node {
     pub(crate) key: NodeKey,
     pub(crate) item: Rc<dyn GenericComponent>,
+    /// The streamlet this node is an instance of.
+    pub(crate) streamlet: StreamletHandle,
+    /// The parameters of the streamlet bound by this instance, other parameters keep their
+    /// default values.
+    pub(crate) parameters: Assignments,
 }
 
 impl Node {
@@ -38,6 +45,16 @@ impl Node {
         self.key.clone()
     }
 
+    /// Returns a handle to the streamlet this node is an instance of.
+    pub fn streamlet_handle(&self) -> StreamletHandle {
+        self.streamlet.clone()
+    }
+
+    /// Returns the parameters of the streamlet bound by this instance.
+    pub fn parameters(&self) -> &Assignments {
+        &self.parameters
+    }
+
     pub fn iface(&self, key: IFKey) -> Result<Ref<Interface>> {
         /*match self.key().deref() {
             THIS_KEY => {
@@ -87,9 +104,42 @@ pub struct ImplementationGraph {
 }
 
 impl ImplementationGraph {
+    /// Construct an implementation of [streamlet] without any instances. The streamlet itself
+    /// is the node [NodeKey::this], with reversed interfaces.
+    pub(crate) fn try_new(handle: StreamletHandle, streamlet: &Streamlet) -> Result<Self> {
+        let this_streamlet = Streamlet::from_builder(
+            streamlet.key().clone(),
+            UniqueKeyBuilder::new()
+                .with_items(streamlet.interfaces().map(|i| i.deref().reversed())),
+            None,
+        )?;
+        Ok(ImplementationGraph {
+            streamlet: handle.clone(),
+            edges: vec![],
+            nodes: vec![(
+                NodeKey::this(),
+                Node {
+                    key: NodeKey::this(),
+                    item: Rc::new(this_streamlet),
+                    streamlet: handle,
+                    parameters: vec![],
+                },
+            )]
+            .into_iter()
+            .collect::<HashMap<NodeKey, Node>>(),
+        })
+    }
+
     pub fn streamlet_key(&self) -> StreamletKey {
         self.streamlet.streamlet().clone()
     }
+
+    /// Returns the name of the streamlet generated for node [key], e.g. for a pattern or an
+    /// adapter. The generated streamlets of all implementations end up in the same library, so
+    /// the name includes the implemented streamlet.
+    pub(crate) fn gen_name(&self, key: &NodeKey) -> Result<Name> {
+        Name::try_new(cat!(self.streamlet_key(), key, "gen"))
+    }
     pub fn nodes(&self) -> impl Iterator<Item = &Node> {
         self.nodes.iter().map(|(_, i)| i)
     }
@@ -6,6 +6,7 @@ use crate::design::{ComponentKey, IFKey, Interface, Mode, Project, Streamlet};
 use crate::generator::dot::DotStyle;
 use crate::Result;
 
+pub mod adapters;
 pub mod impl_backend;
 pub mod impl_graph;
 pub mod misc;
@@ -60,7 +61,7 @@ pub trait GenericComponent {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::design::*;
     use std::convert::TryFrom;
@@ -3,20 +3,31 @@ use std::convert::{TryFrom, TryInto};
 use std::ops::Deref;
 use std::rc::Rc;
 
-use pest::{Parser, RuleType};
 use pest::iterators::Pair;
+use pest::{Parser, RuleType};
 
-use crate::{Error, Name, Result, Reversed, UniqueKeyBuilder};
-use crate::design::{
-    GEN_LIB, IFKey, LibKey, Library, Mode, NodeIFHandle, NodeKey, Project, Streamlet,
-    StreamletHandle, StreamletKey,
-};
+use crate::design::implementation::composer::adapters::{insert_adapters, is_adaptable};
 use crate::design::implementation::composer::impl_graph::{Edge, ImplementationGraph, Node};
+use crate::design::implementation::composer::misc::{
+    FlattenStream, GroupSplit, SequenceStream, StreamFIFO, StreamSync,
+};
+use crate::design::implementation::composer::patterns::{
+    CloneStream, ConcatStructBuilder, ConcatVariantBuilder, DemuxConcatVariant, DemuxPackedVariant,
+    DesyncStructBuilder, FilterStream, MapStream, MapVector, PackedVariantBuilder, ReduceStream,
+    ReduceVector, SplitConcatStruct, SplitDesyncStruct, VectorToSeq,
+};
 use crate::design::implementation::composer::GenericComponent;
-use crate::design::implementation::composer::patterns::{FilterStream, MapStream, ReduceStream};
 use crate::design::implementation::Implementation;
 use crate::design::implementation::Implementation::Structural;
+use crate::design::param::{Assignments, ParameterVariant};
+use crate::design::{
+    IFKey, LibKey, Library, Mode, NodeIFHandle, NodeKey, ParamKey, Project, StreamletHandle,
+    StreamletKey, GEN_LIB,
+};
 use crate::error::LineErr;
+use crate::logical::LogicalType;
+use crate::parser::nom::logical_stream_type;
+use crate::{cat, Error, Name, Result};
 
 #[derive(Parser)]
 #[grammar = "design/implementation/composer/impl.pest"]
@@ -46,23 +57,172 @@ fn match_rule<T>(
     }
 }
 
+/// Parse the implementation in [input], without transforming it.
+fn parse_implementation(input: &str) -> Result<Pair<'_, Rule>> {
+    Ok(ImplDef::parse(Rule::implementation, input)
+        .map_err(|e| {
+            Error::ImplParsingError(LineErr::new(
+                0,
+                format!("Implementation parsing error: {}", e),
+            ))
+        })?
+        .next()
+        .unwrap())
+}
+
+/// Returns the streamlet that the implementation in [input] implements, and the streamlets it
+/// instantiates.
+pub fn impl_dependencies(input: &str) -> Result<(StreamletHandle, Vec<StreamletHandle>)> {
+    let mut handles = parse_implementation(input)?
+        .into_inner()
+        .flatten()
+        .filter(|pair| pair.as_rule() == Rule::streamlet_handle)
+        .map(StreamletHandle::try_from)
+        .collect::<Result<Vec<StreamletHandle>>>()?;
+    // Pest guarantees that the implementation starts with the implemented streamlet.
+    let implemented = handles.remove(0);
+    Ok((implemented, handles))
+}
+
+/// Returns the order in which implementations should be added to a project, given the
+/// dependencies of every implementation as returned by [impl_dependencies].
+///
+/// Instantiating a streamlet copies it into the implementation graph, so a streamlet has to be
+/// implemented before it is instantiated for its implementation to show up in the graph.
+pub fn dependency_order(
+    implementations: &[(StreamletHandle, Vec<StreamletHandle>)],
+) -> Result<Vec<usize>> {
+    let handle = |h: &StreamletHandle| format!("{}.{}", h.lib, h.streamlet);
+    let mut implemented: HashMap<&StreamletHandle, usize> = HashMap::new();
+    for (index, (streamlet, _)) in implementations.iter().enumerate() {
+        if implemented.insert(streamlet, index).is_some() {
+            return Err(Error::ProjectError(format!(
+                "Streamlet {} is implemented more than once",
+                handle(streamlet)
+            )));
+        }
+    }
+
+    fn visit(
+        index: usize,
+        implementations: &[(StreamletHandle, Vec<StreamletHandle>)],
+        implemented: &HashMap<&StreamletHandle, usize>,
+        stack: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> std::result::Result<(), Vec<usize>> {
+        if order.contains(&index) {
+            return Ok(());
+        }
+        if let Some(start) = stack.iter().position(|&i| i == index) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(index);
+            return Err(cycle);
+        }
+        stack.push(index);
+        for dependency in implementations[index].1.iter() {
+            if let Some(&dependency) = implemented.get(dependency) {
+                visit(dependency, implementations, implemented, stack, order)?;
+            }
+        }
+        stack.pop();
+        order.push(index);
+        Ok(())
+    }
+
+    let mut order = vec![];
+    for index in 0..implementations.len() {
+        visit(
+            index,
+            implementations,
+            &implemented,
+            &mut vec![],
+            &mut order,
+        )
+        .map_err(|cycle| {
+            Error::ProjectError(format!(
+                "Cyclic implementation: {}",
+                cycle
+                    .iter()
+                    .map(|&i| handle(&implementations[i].0))
+                    .collect::<Vec<String>>()
+                    .join(" -> ")
+            ))
+        })?;
+    }
+    Ok(order)
+}
+
+/// Check that [edge] may be added to [graph]: it must connect an output to an input that are
+/// not connected yet, and the type of the source must be compatible with the type of the sink.
+/// Mismatches that can be resolved by an adapter are allowed if [adapters] is set.
+pub(crate) fn check_edge(graph: &ImplementationGraph, edge: &Edge, adapters: bool) -> Result<()> {
+    let src_if = graph
+        .get_node(edge.source().node)?
+        .iface(edge.source().iface)?
+        .deref()
+        .clone();
+    let dst_if = graph
+        .get_node(edge.sink().node)?
+        .iface(edge.sink().iface)?
+        .deref()
+        .clone();
+
+    if src_if.mode() != Mode::Out {
+        Err(Error::ComposerError(format!(
+            "Interface {:?} is not an output.",
+            edge.source()
+        )))
+    } else if dst_if.mode() != Mode::In {
+        Err(Error::ComposerError(format!(
+            "Interface {:?} is not an input.",
+            edge.sink()
+        )))
+    } else if graph.get_edge(edge.source()).is_ok() {
+        Err(Error::ComposerError(format!(
+            "Cannot connect {:?} to {:?}, source is already connected.",
+            edge.sink(),
+            edge.source()
+        )))
+    } else if graph.get_edge(edge.sink()).is_ok() {
+        Err(Error::ComposerError(format!(
+            "Cannot connect {:?} to {:?}, sink is already connected.",
+            edge.sink(),
+            edge.source()
+        )))
+    } else if src_if
+        .typ()
+        .mismatches(&dst_if.typ())
+        .iter()
+        .any(|mismatch| !(adapters && is_adaptable(mismatch)))
+    {
+        let mismatches: Vec<String> = src_if
+            .typ()
+            .mismatches(&dst_if.typ())
+            .iter()
+            .map(|mismatch| format!("\n  {}", mismatch))
+            .collect();
+        Err(Error::ComposerError(format!(
+            "Type incompatibility between sink {:?} and source {:?}:{}",
+            edge.sink(),
+            edge.source(),
+            mismatches.concat()
+        )))
+    } else {
+        Ok(())
+    }
+}
+
 pub struct ImplParser<'i> {
     project: &'i mut Project,
     body: Pair<'i, Rule>,
     imp: Implementation,
+    /// Whether to insert adapters on edges with mismatching complexities or throughputs.
+    adapters: bool,
 }
 
 impl<'i> ImplParser<'i> {
     pub fn try_new(project: &'i mut Project, input: &'i str) -> Result<Self> {
-        let pair = ImplDef::parse(Rule::implementation, input)
-            .map_err(|e| {
-                Error::ImplParsingError(LineErr::new(
-                    0,
-                    format!("Implementation parsing error: {}", e),
-                ))
-            })?
-            .next()
-            .unwrap();
+        let pair = parse_implementation(input)?;
 
         let mut pairs = pair.into_inner();
         let streamlet_handle: StreamletHandle = pairs.next().unwrap().try_into()?;
@@ -74,36 +234,28 @@ impl<'i> ImplParser<'i> {
             .get_streamlet(streamlet_handle.streamlet())?
             .clone();
 
-        let gen_lib = Library::new(LibKey::try_new(GEN_LIB).unwrap());
-        project.add_lib(gen_lib)?;
-
-        //Create a streamlet with reversed interfaces
-        let this_streamlet = Streamlet::from_builder(
-            StreamletKey::try_from(s.key()).unwrap(),
-            UniqueKeyBuilder::new().with_items(s.interfaces().map(|i| i.deref().reversed())),
-            None,
-        )?;
+        // Generated streamlets of all implementations end up in the same library.
+        let gen_key = LibKey::try_new(GEN_LIB).unwrap();
+        if project.get_lib(gen_key.clone()).is_err() {
+            project.add_lib(Library::new(gen_key))?;
+        }
 
         Ok(ImplParser {
             project,
             //Safe to unwrap, Pest guarantees that there's an implementation body.
             body: pairs.next().unwrap(),
-            imp: Implementation::Structural(ImplementationGraph {
-                streamlet: streamlet_handle,
-                edges: vec![],
-                nodes: vec![(
-                    NodeKey::this(),
-                    Node {
-                        key: NodeKey::this(),
-                        item: Rc::new(this_streamlet.clone()),
-                    },
-                )]
-                .into_iter()
-                .collect::<HashMap<NodeKey, Node>>(),
-            }),
+            imp: Implementation::Structural(ImplementationGraph::try_new(streamlet_handle, &s)?),
+            adapters: false,
         })
     }
 
+    /// Insert adapters on edges between interfaces of which only the complexity or throughput
+    /// of the streams does not match, instead of rejecting them. See [insert_adapters].
+    pub fn with_adapters(mut self, adapters: bool) -> Self {
+        self.adapters = adapters;
+        self
+    }
+
     pub fn transform_body(&mut self) -> Result<()> {
         match &mut self.body.as_rule() {
             Rule::structural => self.transform_structural(),
@@ -136,6 +288,11 @@ impl<'i> ImplParser<'i> {
                 _ => unimplemented!(),
             }
         }
+        if self.adapters {
+            if let Structural(ref mut s) = &mut self.imp {
+                insert_adapters(self.project, s)?;
+            }
+        }
         Ok(())
     }
 
@@ -143,7 +300,7 @@ impl<'i> ImplParser<'i> {
         &mut self,
         pair: Pair<Rule>,
     ) -> Result<(Name, Node, StreamletHandle, Vec<Edge>)> {
-        //{ ident ~ ":" ~  (pattern | streamlet_inst) }
+        //{ ident ~ ":" ~  (pattern | builder | unwrap | misc | streamlet_inst) }
         let mut pairs = pair.into_inner();
         //ident
         let name_pair = pairs.next().unwrap();
@@ -152,10 +309,12 @@ impl<'i> ImplParser<'i> {
         let pair = pairs.next().unwrap();
         match pair.as_rule() {
             Rule::streamlet_inst => {
-                let node_tuple = self.transform_streamlet_inst(pair, key.clone())?;
+                let node_tuple = self.transform_streamlet_inst(pair)?;
                 let node = Node {
                     key: key.clone(),
                     item: node_tuple.0,
+                    streamlet: node_tuple.1.clone(),
+                    parameters: node_tuple.2,
                 };
                 Ok((key.clone(), node, node_tuple.1, Vec::new()))
             }
@@ -164,6 +323,38 @@ impl<'i> ImplParser<'i> {
                 let node = Node {
                     key: key.clone(),
                     item: node_tuple.0,
+                    streamlet: node_tuple.1.clone(),
+                    parameters: vec![],
+                };
+                Ok((key.clone(), node, node_tuple.1, node_tuple.2))
+            }
+            Rule::builder => {
+                let node_tuple = self.transform_builder(pair, key.clone())?;
+                let node = Node {
+                    key: key.clone(),
+                    item: node_tuple.0,
+                    streamlet: node_tuple.1.clone(),
+                    parameters: vec![],
+                };
+                Ok((key.clone(), node, node_tuple.1, node_tuple.2))
+            }
+            Rule::unwrap => {
+                let node_tuple = self.transform_unwrap(pair, key.clone())?;
+                let node = Node {
+                    key: key.clone(),
+                    item: node_tuple.0,
+                    streamlet: node_tuple.1.clone(),
+                    parameters: vec![],
+                };
+                Ok((key.clone(), node, node_tuple.1, node_tuple.2))
+            }
+            Rule::misc => {
+                let node_tuple = self.transform_misc(pair, key.clone())?;
+                let node = Node {
+                    key: key.clone(),
+                    item: node_tuple.0,
+                    streamlet: node_tuple.1.clone(),
+                    parameters: vec![],
                 };
                 Ok((key.clone(), node, node_tuple.1, node_tuple.2))
             }
@@ -174,9 +365,9 @@ impl<'i> ImplParser<'i> {
     pub fn transform_streamlet_inst(
         &mut self,
         pair: Pair<Rule>,
-        _key: Name,
-    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
-        //{ streamlet_handle ~ ("[" ~ (parameter_assign)+ ~ "]")? }
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Assignments)> {
+        //{ streamlet_handle ~ ("[" ~ parameter_assign ~ (","? ~ parameter_assign)* ~ "]")? }
+        let line = pair.line_num();
         let mut pairs = pair.into_inner();
 
         //streamlet_handle
@@ -187,7 +378,80 @@ impl<'i> ImplParser<'i> {
             .get_lib(streamlet_handle.lib())?
             .get_streamlet(streamlet_handle.streamlet())?
             .clone();
-        Ok((Rc::new(streamlet.clone()), streamlet_handle, Vec::new()))
+
+        //parameter_assign
+        let assignments = pairs
+            .map(|pair| self.transform_parameter_assign(pair, &streamlet_handle))
+            .collect::<Result<Assignments>>()?;
+        if assignments.is_empty() {
+            return Ok((Rc::new(streamlet), streamlet_handle, assignments));
+        }
+
+        // The node remains an instance of the streamlet, of which the interfaces are resolved
+        // using the bound values. Back-ends pass the bound values on to the instance.
+        let instance = streamlet
+            .instantiate(streamlet.key().clone(), assignments.clone())
+            .map_err(|e| Error::ImplParsingError(LineErr::new(line, e.to_string())))?;
+        Ok((Rc::new(instance), streamlet_handle, assignments))
+    }
+
+    /// Transform a parameter assignment of an instance of [instantiated]. Identifiers refer to
+    /// named types, declared in or imported by the library of the implemented streamlet, or
+    /// declared in the library of the instantiated streamlet.
+    pub fn transform_parameter_assign(
+        &mut self,
+        pair: Pair<Rule>,
+        instantiated: &StreamletHandle,
+    ) -> Result<(ParamKey, ParameterVariant)> {
+        //{ ident ~ ":=" ~ (logical_type | string | number | ident)}
+        let line = pair.line_num();
+        let mut pairs = pair.into_inner();
+        let key = Name::try_from(pairs.next().unwrap())?;
+        let value_pair = pairs.next().unwrap();
+        let value = value_pair.as_str();
+        let err = |msg: String| Error::ImplParsingError(LineErr::new(line, msg));
+        let value = match value_pair.as_rule() {
+            Rule::logical_type => match logical_stream_type(value) {
+                Ok(("", typ)) => ParameterVariant::Type(typ),
+                _ => return Err(err(format!("Invalid logical type: {}", value))),
+            },
+            Rule::string => ParameterVariant::String(value.trim_matches('"').to_string()),
+            Rule::number => ParameterVariant::UInt(value.parse().map_err(|_| {
+                err(format!(
+                    "Invalid value for parameter {}: {} is not an unsigned integer",
+                    key, value
+                ))
+            })?),
+            Rule::ident => {
+                let name = Name::try_new(value).map_err(|e| err(e.to_string()))?;
+                let implementing = self.project.get_lib(self.imp.streamlet_handle().lib())?;
+                let typ = implementing
+                    .get_type(&name)
+                    .ok()
+                    .or_else(|| {
+                        implementing
+                            .imports()
+                            .find(|(_, typ)| *typ.key() == name)
+                            .map(|(_, typ)| typ)
+                    })
+                    .or_else(|| {
+                        self.project
+                            .get_lib(instantiated.lib())
+                            .ok()?
+                            .get_type(&name)
+                            .ok()
+                    })
+                    .ok_or_else(|| {
+                        err(format!(
+                            "Invalid value for parameter {}: {} is not a named type in scope",
+                            key, value
+                        ))
+                    })?;
+                ParameterVariant::Type(typ.logical_type().clone())
+            }
+            _ => unreachable!(),
+        };
+        Ok((key, value))
     }
 
     pub fn transform_pattern(
@@ -195,16 +459,338 @@ impl<'i> ImplParser<'i> {
         pair: Pair<Rule>,
         key: Name,
     ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
-        //{ map_stream | filter_stream | reduce_stream }
+        //{ map_stream | filter_stream | reduce_stream
+        //  | map_vector | reduce_vector | vector_to_seq | clone_stream }
         let pair = pair.into_inner().next().unwrap();
         match pair.as_rule() {
             Rule::map_stream => self.transform_map_stream(pair, key),
             Rule::reduce_stream => self.transform_reduce_stream(pair, key),
             Rule::filter_stream => self.transform_filter_stream(pair, key),
+            Rule::map_vector => self.transform_map_vector(pair, key),
+            Rule::reduce_vector => self.transform_reduce_vector(pair, key),
+            Rule::vector_to_seq => self.transform_vector_to_seq(pair, key),
+            Rule::clone_stream => self.transform_clone_stream(pair, key),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the name of the streamlet generated for pattern node [key].
+    fn gen_name(&self, key: &NodeKey) -> Result<Name> {
+        match &self.imp {
+            Structural(s) => s.gen_name(key),
             _ => unreachable!(),
         }
     }
 
+    /// Returns the type of an interface of a node that is already part of the implementation.
+    fn iface_type(&self, handle: &NodeIFHandle) -> Result<LogicalType> {
+        match &self.imp {
+            Structural(s) => Ok(s.get_node(handle.node())?.iface(handle.iface())?.typ()),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Adds the streamlet of a pattern to the library of generated streamlets.
+    fn add_gen_component<C: GenericComponent + 'static>(
+        &mut self,
+        component: C,
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle)> {
+        let handle = self
+            .project
+            .get_lib_mut(Name::try_from(GEN_LIB)?)?
+            .add_streamlet(component.streamlet().clone())?;
+        Ok((Rc::new(component), handle))
+    }
+
+    pub fn transform_builder(
+        &mut self,
+        pair: Pair<Rule>,
+        key: Name,
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
+        //{ concat_struct_builder | desync_struct_builder
+        //  | concat_variant_builder | packed_variant_builder }
+        let pair = pair.into_inner().next().unwrap();
+        let rule = pair.as_rule();
+
+        //{ ident ~ "<=" ~ node_if_handle }+
+        let mut inputs = vec![];
+        let mut edges = vec![];
+        for connection in pair.into_inner() {
+            let mut pairs = connection.into_inner();
+            let iface = Name::try_from(pairs.next().unwrap())?;
+            let source = NodeIFHandle::try_from(pairs.next().unwrap())?;
+            inputs.push((iface.clone(), self.iface_type(&source)?));
+            edges.push(Edge {
+                source,
+                sink: NodeIFHandle {
+                    node: key.clone(),
+                    iface,
+                },
+            });
+        }
+
+        let name = self.gen_name(&key)?;
+        let handle = StreamletHandle {
+            lib: Name::try_new(GEN_LIB)?,
+            streamlet: name.clone(),
+        };
+        let (object, handle) = match rule {
+            Rule::concat_struct_builder => {
+                let mut component = ConcatStructBuilder::try_new(name.clone(), inputs)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::desync_struct_builder => {
+                let mut component = DesyncStructBuilder::try_new(name.clone(), inputs)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::concat_variant_builder => {
+                let mut component = ConcatVariantBuilder::try_new(name.clone(), inputs)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::packed_variant_builder => {
+                let mut component = PackedVariantBuilder::try_new(name.clone(), inputs)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            _ => unreachable!(),
+        }?;
+        Ok((object, handle, edges))
+    }
+
+    pub fn transform_unwrap(
+        &mut self,
+        pair: Pair<Rule>,
+        key: Name,
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
+        //{ split_concat_struct | split_desync_struct
+        //  | demux_packed_variant | demux_concat_variant }
+        let pair = pair.into_inner().next().unwrap();
+        let rule = pair.as_rule();
+        let source = NodeIFHandle::try_from(pair.into_inner().next().unwrap())?;
+        let input = self.iface_type(&source)?;
+
+        let name = self.gen_name(&key)?;
+        let handle = StreamletHandle {
+            lib: Name::try_new(GEN_LIB)?,
+            streamlet: name.clone(),
+        };
+        let (object, handle) = match rule {
+            Rule::split_concat_struct => {
+                let mut component = SplitConcatStruct::try_new(name.clone(), input)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::split_desync_struct => {
+                let mut component = SplitDesyncStruct::try_new(name.clone(), input)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::demux_packed_variant => {
+                let mut component = DemuxPackedVariant::try_new(name.clone(), input)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::demux_concat_variant => {
+                let mut component = DemuxConcatVariant::try_new(name.clone(), input)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            _ => unreachable!(),
+        }?;
+        let edges = vec![Edge {
+            source,
+            sink: NodeIFHandle {
+                node: key,
+                iface: IFKey::try_new("in")?,
+            },
+        }];
+        Ok((object, handle, edges))
+    }
+
+    pub fn transform_misc(
+        &mut self,
+        pair: Pair<Rule>,
+        key: Name,
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
+        //{ fifo | flatten_stream | sequence_stream | stream_sync | group_split }
+        let pair = pair.into_inner().next().unwrap();
+        let line = pair.line_num();
+        let rule = pair.as_rule();
+        let mut pairs = pair.into_inner();
+
+        // Connect the sources of the component to the inputs of the new node.
+        let mut edges = vec![];
+        let mut connect = |source: NodeIFHandle, iface: &str| -> Result<()> {
+            edges.push(Edge {
+                source,
+                sink: NodeIFHandle {
+                    node: key.clone(),
+                    iface: IFKey::try_new(iface)?,
+                },
+            });
+            Ok(())
+        };
+
+        let name = self.gen_name(&key)?;
+        let handle = StreamletHandle {
+            lib: Name::try_new(GEN_LIB)?,
+            streamlet: name.clone(),
+        };
+        let (object, handle) = match rule {
+            Rule::fifo => {
+                //{ "FIFO" ~ "(" ~ "depth" ~ "=" ~ int ~ ")" }
+                let depth = pairs.next().unwrap().as_str();
+                let depth = depth.parse().map_err(|_| {
+                    Error::ImplParsingError(LineErr::new(
+                        line,
+                        format!("Invalid FIFO depth: {}", depth),
+                    ))
+                })?;
+                let mut component = StreamFIFO::try_new(name.clone(), depth)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::flatten_stream => {
+                let source = NodeIFHandle::try_from(pairs.next().unwrap())?;
+                let input = self.iface_type(&source)?;
+                connect(source, "in")?;
+                let mut component = FlattenStream::try_new(name.clone(), input)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::sequence_stream => {
+                let element = NodeIFHandle::try_from(pairs.next().unwrap())?;
+                let count = NodeIFHandle::try_from(pairs.next().unwrap())?;
+                let (element_type, count_type) =
+                    (self.iface_type(&element)?, self.iface_type(&count)?);
+                connect(element, "element")?;
+                connect(count, "count")?;
+                let mut component =
+                    SequenceStream::try_new(name.clone(), element_type, count_type)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::stream_sync => {
+                // The inputs are named after the node and interface of their source.
+                let mut inputs = vec![];
+                for pair in pairs {
+                    let source = NodeIFHandle::try_from(pair)?;
+                    let iface = cat!(source.node(), source.iface());
+                    inputs.push((Name::try_new(&iface)?, self.iface_type(&source)?));
+                    connect(source, &iface)?;
+                }
+                let mut component = StreamSync::try_new(name.clone(), inputs)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            Rule::group_split => {
+                let source = NodeIFHandle::try_from(pairs.next().unwrap())?;
+                let input = self.iface_type(&source)?;
+                connect(source, "in")?;
+                let fields = pairs.map(Name::try_from).collect::<Result<Vec<_>>>()?;
+                let mut component = GroupSplit::try_new(name.clone(), input, fields)?;
+                component.with_backend(name, handle)?;
+                self.add_gen_component(component.finish())
+            }
+            _ => unreachable!(),
+        }?;
+        Ok((object, handle, edges))
+    }
+
+    pub fn transform_map_vector(
+        &mut self,
+        pair: Pair<Rule>,
+        key: Name,
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
+        let op = self.transform_node(pair.into_inner().next().unwrap())?;
+
+        let name = self.gen_name(&key)?;
+        let mut component = MapVector::try_new(self.project, name.clone(), op.2)?;
+        component.with_backend(
+            name.clone(),
+            StreamletHandle {
+                lib: Name::try_new(GEN_LIB)?,
+                streamlet: name,
+            },
+        )?;
+        let (object, handle) = self.add_gen_component(component.finish())?;
+        Ok((object, handle, Vec::new()))
+    }
+
+    pub fn transform_reduce_vector(
+        &mut self,
+        pair: Pair<Rule>,
+        key: Name,
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
+        let op = self.transform_node(pair.into_inner().next().unwrap())?;
+
+        let name = self.gen_name(&key)?;
+        let mut component = ReduceVector::try_new(self.project, name.clone(), op.2)?;
+        component.with_backend(
+            name.clone(),
+            StreamletHandle {
+                lib: Name::try_new(GEN_LIB)?,
+                streamlet: name,
+            },
+        )?;
+        let (object, handle) = self.add_gen_component(component.finish())?;
+        Ok((object, handle, Vec::new()))
+    }
+
+    pub fn transform_vector_to_seq(
+        &mut self,
+        pair: Pair<Rule>,
+        key: Name,
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
+        let source = NodeIFHandle::try_from(pair.into_inner().next().unwrap())?;
+        let input = self.iface_type(&source)?;
+
+        let name = self.gen_name(&key)?;
+        let mut component = VectorToSeq::try_new(name.clone(), input)?;
+        component.with_backend(
+            name.clone(),
+            StreamletHandle {
+                lib: Name::try_new(GEN_LIB)?,
+                streamlet: name,
+            },
+        )?;
+        let (object, handle) = self.add_gen_component(component.finish())?;
+        let edges = vec![Edge {
+            source,
+            sink: NodeIFHandle {
+                node: key,
+                iface: IFKey::try_new("in")?,
+            },
+        }];
+        Ok((object, handle, edges))
+    }
+
+    pub fn transform_clone_stream(
+        &mut self,
+        pair: Pair<Rule>,
+        key: Name,
+    ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
+        let outputs = pair
+            .into_inner()
+            .map(Name::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        let name = self.gen_name(&key)?;
+        let mut component = CloneStream::try_new(name.clone(), outputs)?;
+        component.with_backend(
+            name.clone(),
+            StreamletHandle {
+                lib: Name::try_new(GEN_LIB)?,
+                streamlet: name,
+            },
+        )?;
+        let (object, handle) = self.add_gen_component(component.finish())?;
+        Ok((object, handle, Vec::new()))
+    }
+
     pub fn transform_map_stream(
         &mut self,
         pair: Pair<Rule>,
@@ -212,7 +798,7 @@ impl<'i> ImplParser<'i> {
     ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
         let op = self.transform_node(pair.into_inner().next().unwrap())?;
 
-        let name = Name::try_from(format!("{}_gen", key.to_string()))?;
+        let name = self.gen_name(&key)?;
         let mut component = MapStream::try_new(self.project, name.clone(), op.2)?;
         component.with_backend(
             name.clone(),
@@ -236,7 +822,7 @@ impl<'i> ImplParser<'i> {
     ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
         let op = self.transform_node(pair.into_inner().next().unwrap())?;
 
-        let name = Name::try_from(format!("{}_gen", key.to_string()))?;
+        let name = self.gen_name(&key)?;
         let mut component = ReduceStream::try_new(self.project, name.clone(), op.2)?;
         component.with_backend(
             name.clone(),
@@ -260,7 +846,7 @@ impl<'i> ImplParser<'i> {
     ) -> Result<(Rc<dyn GenericComponent>, StreamletHandle, Vec<Edge>)> {
         let predicate = NodeIFHandle::try_from(pair.into_inner().next().unwrap())?;
 
-        let name = Name::try_from(format!("{}_gen", key.to_string()))?;
+        let name = self.gen_name(&key)?;
         let mut component = FilterStream::try_new(self.project, name.clone())?;
         component.with_backend(
             name.clone(),
@@ -350,6 +936,7 @@ impl<'i> ImplParser<'i> {
     }
 
     pub fn connect(&mut self, edge: Edge) -> Result<()> {
+        let adapters = self.adapters;
         match &mut self.imp {
             Structural(ref mut s) => {
                 //Deal with type inferences
@@ -381,52 +968,8 @@ impl<'i> ImplParser<'i> {
                     .component()
                     .connect_action()?;
 
-                //Refresh the data types afgter propagation for checks
-                let src_if = s
-                    .get_node(edge.clone().source().node)?
-                    .iface(edge.clone().source().iface)?
-                    .deref()
-                    .clone();
-                let dst_if = s
-                    .get_node(edge.clone().sink().node)?
-                    .iface(edge.clone().sink().iface)?
-                    .deref()
-                    .clone();
-
-                if src_if.mode() != Mode::Out {
-                    Err(Error::ComposerError(format!(
-                        "Interface {:?} is not an output.",
-                        edge.clone().source()
-                    )))
-                } else if dst_if.mode() != Mode::In {
-                    Err(Error::ComposerError(format!(
-                        "Interface {:?} is not an input.",
-                        edge.clone().source()
-                    )))
-                } else if s.get_edge(edge.clone().source()).is_ok() {
-                    Err(Error::ComposerError(format!(
-                        "Cannot connect {:?} to {:?}, source is already connected.",
-                        edge.clone().sink(),
-                        edge.clone().source()
-                    )))
-                } else if s.get_edge(edge.clone().sink()).is_ok() {
-                    Err(Error::ComposerError(format!(
-                        "Cannot connect {:?} to {:?}, sink is already connected.",
-                        edge.clone().sink(),
-                        edge.clone().source()
-                    )))
-                } else if src_if.typ() != dst_if.typ() {
-                    Err(Error::ComposerError(format!(
-                        "Type incompatibility between sink {:?} : {:?}, and source {:?} : {:?}.",
-                        edge.clone().sink(),
-                        dst_type,
-                        edge.clone().source(),
-                        src_type
-                    )))
-                } else {
-                    s.edges.push(edge);
-                    Ok(())
-                }?;
+                check_edge(s, &edge, adapters)?;
+                s.edges.push(edge);
             }
             _ => unreachable!(),
         }
@@ -520,9 +1063,9 @@ impl<'i> TryFrom<Pair<'i, Rule>> for Edge {
 pub mod tests {
     use std::convert::TryFrom;
 
-    use crate::{Name, Result};
     use crate::design::implementation::composer::tests::composition_test_proj;
     use crate::design::StreamletHandle;
+    use crate::{Name, Result, UniquelyNamedBuilder};
 
     use super::*;
 
@@ -554,5 +1097,71 @@ pub mod tests {
         let _imp = builder.finish();
         Ok(())
     }
-}
 
+    #[test]
+    fn incompatible_types() -> Result<()> {
+        let streamlets = crate::parser::nom::list_of_streamlets(
+            "Streamlet a (o: out Stream<Group<x: Stream<Group<y: Bits<8>>, c=7>>, d=1>)
+             Streamlet b (i: in Stream<Group<x: Stream<Group<y: Bits<4>>, c=4>>, d=1, c=8>)
+             Streamlet top ()",
+        )
+        .unwrap()
+        .1;
+        let lib = Library::try_new(Name::try_new("lib")?, vec![], streamlets)?;
+        let mut prj = Project::from_builder(
+            Name::try_new("proj")?,
+            UniquelyNamedBuilder::new().with_items(vec![lib]),
+        )?;
+
+        let err = prj
+            .add_implementation("impl lib.top structural { a: lib.a; b: lib.b; b.i <= a.o; }")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Type incompatibility between sink"));
+        assert!(err.ends_with(
+            "\n  x: complexity of source (7) is higher than complexity of sink (4)\
+             \n  x.y: source is Bits<8>, sink is Bits<4>"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn dependencies() -> Result<()> {
+        let handle = |lib: &str, streamlet: &str| StreamletHandle {
+            lib: Name::try_from(lib).unwrap(),
+            streamlet: Name::try_from(streamlet).unwrap(),
+        };
+        let top_impl = include_str!("../../../../tests/implementations/composition_example.impl");
+        let (top, deps) = impl_dependencies(top_impl)?;
+        assert_eq!(top, handle("compositions", "Top_level"));
+        assert_eq!(
+            deps,
+            vec![
+                handle("primitives", "Magic"),
+                handle("primitives", "test_op"),
+                handle("primitives", "test_op")
+            ]
+        );
+
+        let magic =
+            impl_dependencies("impl primitives.Magic structural { inst: primitives.test_op; }")?;
+        let op = impl_dependencies("impl primitives.test_op structural { }")?;
+        let implementations = vec![(top.clone(), deps), magic.clone(), op.clone()];
+        assert_eq!(dependency_order(&implementations)?, vec![2, 1, 0]);
+
+        let cyclic =
+            impl_dependencies("impl primitives.test_op structural { inst: primitives.Magic; }")?;
+        assert_eq!(
+            dependency_order(&[magic.clone(), cyclic])
+                .unwrap_err()
+                .to_string(),
+            "Project error: Cyclic implementation: primitives.Magic -> primitives.test_op -> \
+             primitives.Magic"
+        );
+        assert_eq!(
+            dependency_order(&[op.clone(), op]).unwrap_err().to_string(),
+            "Project error: Streamlet primitives.test_op is implemented more than once"
+        );
+        Ok(())
+    }
+}
@@ -1,13 +1,17 @@
 use std::borrow::Borrow;
 use std::convert::TryFrom;
 
+use crate::design::implementation::composer::impl_backend::ImplementationBackend;
 use crate::design::implementation::composer::GenericComponent;
 use crate::design::implementation::Implementation;
 use crate::design::{IFKey, Interface, Mode, Project, Streamlet, StreamletHandle, StreamletKey};
-use crate::logical::{Direction, LogicalType, Stream, Synchronicity};
-use crate::physical::Complexity;
-use crate::{Error, Name, NonZeroReal, Result, UniqueKeyBuilder};
-use crate::design::implementation::composer::impl_backend::ImplementationBackend;
+use crate::logical::{
+    Direction, Group, LogicalSplitItem, LogicalType, Stream, Synchronicity, Union,
+};
+use crate::physical::{Complexity, PhysicalStream};
+use crate::{
+    Error, Name, NonNegative, NonZeroReal, PathName, PositiveReal, Result, UniqueKeyBuilder,
+};
 
 ///! MapStream construct
 #[derive(Clone, Debug)]
@@ -344,14 +348,939 @@ impl ImplementationBackend for FilterStreamBackend {
     }
 }
 
+/// Implements [ImplementationBackend] for the backend of a pattern, and attaches it to the pattern
+/// through `with_backend`.
+macro_rules! pattern_backend {
+    ($pattern:ident, $backend:ident) => {
+        impl $pattern {
+            pub fn with_backend(
+                &mut self,
+                name: Name,
+                streamlet_handle: StreamletHandle,
+            ) -> Result<()> {
+                self.streamlet
+                    .attach_implementation(Implementation::Backend(Box::new($backend {
+                        name,
+                        streamlet_handle,
+                    })))?;
+                Ok(())
+            }
+
+            pub fn finish(self) -> $pattern {
+                self
+            }
+        }
+
+        pub struct $backend {
+            name: Name,
+            streamlet_handle: StreamletHandle,
+        }
+
+        impl ImplementationBackend for $backend {
+            fn name(&self) -> Name {
+                self.name.clone()
+            }
+
+            fn streamlet_handle(&self) -> StreamletHandle {
+                self.streamlet_handle.clone()
+            }
+        }
+    };
+}
+
+pub(super) use pattern_backend;
+
+/// Returns the stream of a logical type, or an error naming the pattern that requires it.
+pub(super) fn expect_stream(typ: &LogicalType, pattern: &str) -> Result<Stream> {
+    match typ {
+        LogicalType::Stream(s) => Ok(s.clone()),
+        _ => Err(Error::ComposerError(format!(
+            "The data type for the {} pattern is required to be a Stream, got {:?}.",
+            pattern, typ
+        ))),
+    }
+}
+
+/// Construct a stream carrying `data`, with the complexity, direction and user signals of stream
+/// `like`.
+pub(super) fn derive_stream(
+    like: &Stream,
+    data: LogicalType,
+    throughput: PositiveReal,
+    dimensionality: NonNegative,
+    synchronicity: Synchronicity,
+) -> Stream {
+    Stream::new(
+        data,
+        throughput,
+        dimensionality,
+        synchronicity,
+        like.complexity().clone(),
+        like.direction(),
+        like.user().cloned(),
+        false,
+    )
+}
+
+/// Returns the stream that carries a field of the element type of a `parent` stream on its own.
+///
+/// Fields that are streams themselves are split off the parent stream, as they are when the
+/// parent stream is synthesized.
+pub(super) fn field_stream(parent: &Stream, field: &LogicalType) -> Stream {
+    match field {
+        LogicalType::Stream(child) => parent.child(child.clone()),
+        element => derive_stream(
+            parent,
+            element.clone(),
+            parent.throughput(),
+            parent.dimensionality(),
+            parent.synchronicity(),
+        ),
+    }
+}
+
+/// Checks that the streams that are combined by builder `pattern` carry only elements, and that
+/// they have the same dimensionality, throughput, complexity and user signals, such that their
+/// transfers can be combined in lockstep. Returns the dimensionality.
+fn lockstep_dimensionality(inputs: &[(IFKey, Stream)], pattern: &str) -> Result<NonNegative> {
+    let (_, first) = inputs
+        .first()
+        .ok_or_else(|| Error::ComposerError(format!("The {} pattern has no inputs.", pattern)))?;
+    let dimensionality = first.dimensionality();
+    for (key, stream) in inputs {
+        if !stream.data().is_element_only() {
+            return Err(Error::ComposerError(format!(
+                "Input {} of the {} pattern carries nested streams.",
+                key, pattern
+            )));
+        }
+        if stream.dimensionality() != dimensionality {
+            return Err(Error::ComposerError(format!(
+                "Input {} of the {} pattern has dimensionality {}, expected {}.",
+                key,
+                pattern,
+                stream.dimensionality(),
+                dimensionality
+            )));
+        }
+        if stream.throughput() != first.throughput() {
+            return Err(Error::ComposerError(format!(
+                "Input {} of the {} pattern has throughput {}, expected {}.",
+                key,
+                pattern,
+                stream.throughput().get(),
+                first.throughput().get()
+            )));
+        }
+        if stream.complexity() != first.complexity() {
+            return Err(Error::ComposerError(format!(
+                "Input {} of the {} pattern has complexity {}, expected {}.",
+                key,
+                pattern,
+                stream.complexity(),
+                first.complexity()
+            )));
+        }
+        if stream.user() != first.user() {
+            return Err(Error::ComposerError(format!(
+                "Input {} of the {} pattern has user signals {:?}, expected {:?}.",
+                key,
+                pattern,
+                stream.user(),
+                first.user()
+            )));
+        }
+    }
+    Ok(dimensionality)
+}
+
+/// Constructs the streamlet of a pattern with inputs and outputs of known types.
+pub(super) fn pattern_streamlet(
+    name: Name,
+    inputs: Vec<(IFKey, LogicalType)>,
+    outputs: Vec<(IFKey, LogicalType)>,
+) -> Result<Streamlet> {
+    let mut ifaces: Vec<Interface> = vec![];
+    for (key, typ) in inputs {
+        ifaces.push(Interface::try_new(key, Mode::In, typ, None)?);
+    }
+    for (key, typ) in outputs {
+        ifaces.push(Interface::try_new(key, Mode::Out, typ, None)?);
+    }
+    Streamlet::from_builder(name, UniqueKeyBuilder::new().with_items(ifaces), None)
+}
+
+/// Converts the types of the inputs of a builder pattern into streams.
+fn builder_inputs(inputs: &[(IFKey, LogicalType)], pattern: &str) -> Result<Vec<(IFKey, Stream)>> {
+    inputs
+        .iter()
+        .map(|(key, typ)| Ok((key.clone(), expect_stream(typ, pattern)?)))
+        .collect()
+}
+
+/// ConcatStructBuilder construct
+///
+/// Combines streams of equal dimensionality, throughput and complexity into a single stream of
+/// which the elements are groups of the elements of the input streams, named after the inputs.
+#[derive(Clone, Debug)]
+pub struct ConcatStructBuilder {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for ConcatStructBuilder {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl ConcatStructBuilder {
+    pub fn try_new(name: Name, inputs: Vec<(IFKey, LogicalType)>) -> Result<Self> {
+        let streams = builder_inputs(&inputs, "ConcatStructBuilder")?;
+        let dimensionality = lockstep_dimensionality(&streams, "ConcatStructBuilder")?;
+        let group = Group::try_new(
+            streams
+                .iter()
+                .map(|(key, s)| (key.clone(), s.data().clone())),
+        )?;
+        let (_, first) = &streams[0];
+        let output = derive_stream(
+            first,
+            group.into(),
+            first.throughput(),
+            dimensionality,
+            Synchronicity::Sync,
+        );
+        Ok(ConcatStructBuilder {
+            streamlet: pattern_streamlet(
+                name,
+                inputs,
+                vec![(IFKey::try_new("out")?, output.into())],
+            )?,
+        })
+    }
+}
+
+pattern_backend!(ConcatStructBuilder, ConcatStructBuilderBackend);
+
+/// DesyncStructBuilder construct
+///
+/// Combines streams into a group of desynchronized child streams, named after the inputs.
+#[derive(Clone, Debug)]
+pub struct DesyncStructBuilder {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for DesyncStructBuilder {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl DesyncStructBuilder {
+    pub fn try_new(name: Name, inputs: Vec<(IFKey, LogicalType)>) -> Result<Self> {
+        let streams = builder_inputs(&inputs, "DesyncStructBuilder")?;
+        let group = Group::try_new(streams.iter().map(|(key, s)| {
+            (
+                key.clone(),
+                LogicalType::from(derive_stream(
+                    s,
+                    s.data().clone(),
+                    s.throughput(),
+                    s.dimensionality(),
+                    Synchronicity::Desync,
+                )),
+            )
+        }))?;
+        Ok(DesyncStructBuilder {
+            streamlet: pattern_streamlet(
+                name,
+                inputs,
+                vec![(
+                    IFKey::try_new("out")?,
+                    Stream::new_basic(group.into()).into(),
+                )],
+            )?,
+        })
+    }
+}
+
+pattern_backend!(DesyncStructBuilder, DesyncStructBuilderBackend);
+
+/// PackedVariantBuilder construct
+///
+/// Merges streams of equal dimensionality, throughput and complexity into a single stream of
+/// which the elements are unions of the elements of the input streams, named after the inputs.
+#[derive(Clone, Debug)]
+pub struct PackedVariantBuilder {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for PackedVariantBuilder {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl PackedVariantBuilder {
+    pub fn try_new(name: Name, inputs: Vec<(IFKey, LogicalType)>) -> Result<Self> {
+        let streams = builder_inputs(&inputs, "PackedVariantBuilder")?;
+        let dimensionality = lockstep_dimensionality(&streams, "PackedVariantBuilder")?;
+        let union = Union::try_new(
+            streams
+                .iter()
+                .map(|(key, s)| (key.clone(), s.data().clone())),
+        )?;
+        let (_, first) = &streams[0];
+        let output = derive_stream(
+            first,
+            union.into(),
+            first.throughput(),
+            dimensionality,
+            Synchronicity::Sync,
+        );
+        Ok(PackedVariantBuilder {
+            streamlet: pattern_streamlet(
+                name,
+                inputs,
+                vec![(IFKey::try_new("out")?, output.into())],
+            )?,
+        })
+    }
+}
+
+pattern_backend!(PackedVariantBuilder, PackedVariantBuilderBackend);
+
+/// ConcatVariantBuilder construct
+///
+/// Merges streams into a stream of unions, of which the variants are carried by synchronized
+/// child streams, named after the inputs.
+#[derive(Clone, Debug)]
+pub struct ConcatVariantBuilder {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for ConcatVariantBuilder {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl ConcatVariantBuilder {
+    pub fn try_new(name: Name, inputs: Vec<(IFKey, LogicalType)>) -> Result<Self> {
+        let streams = builder_inputs(&inputs, "ConcatVariantBuilder")?;
+        let union = Union::try_new(streams.iter().map(|(key, s)| {
+            (
+                key.clone(),
+                LogicalType::from(derive_stream(
+                    s,
+                    s.data().clone(),
+                    s.throughput(),
+                    s.dimensionality(),
+                    Synchronicity::Sync,
+                )),
+            )
+        }))?;
+        Ok(ConcatVariantBuilder {
+            streamlet: pattern_streamlet(
+                name,
+                inputs,
+                vec![(
+                    IFKey::try_new("out")?,
+                    Stream::new_basic(union.into()).into(),
+                )],
+            )?,
+        })
+    }
+}
+
+pattern_backend!(ConcatVariantBuilder, ConcatVariantBuilderBackend);
+
+/// Returns the fields of the element type of the stream that is unwrapped by `pattern`.
+///
+/// If `nested` is true, all fields are required to be streams, otherwise none of them may be.
+fn unwrap_fields(
+    input: &Stream,
+    fields: Vec<(Name, LogicalType)>,
+    nested: bool,
+    pattern: &str,
+) -> Result<Vec<(IFKey, LogicalType)>> {
+    fields
+        .into_iter()
+        .map(|(key, typ)| {
+            let is_stream = matches!(typ, LogicalType::Stream(_));
+            if nested && !is_stream {
+                Err(Error::ComposerError(format!(
+                    "Field {} unwrapped by the {} pattern is required to be a Stream.",
+                    key, pattern
+                )))
+            } else if !nested && !typ.is_element_only() {
+                Err(Error::ComposerError(format!(
+                    "Field {} unwrapped by the {} pattern carries nested streams.",
+                    key, pattern
+                )))
+            } else {
+                Ok((key, field_stream(input, &typ).into()))
+            }
+        })
+        .collect()
+}
+
+/// Returns the fields of a group carried by the stream that is unwrapped by `pattern`.
+fn group_fields(input: &Stream, pattern: &str) -> Result<Vec<(Name, LogicalType)>> {
+    match input.data() {
+        LogicalType::Group(group) => Ok(group
+            .iter()
+            .map(|(key, typ)| (key.clone(), typ.clone()))
+            .collect()),
+        other => Err(Error::ComposerError(format!(
+            "The {} pattern requires a Stream of a Group, got a Stream of {:?}.",
+            pattern, other
+        ))),
+    }
+}
+
+/// Returns the variants of a union carried by the stream that is unwrapped by `pattern`.
+fn union_fields(input: &Stream, pattern: &str) -> Result<Vec<(Name, LogicalType)>> {
+    match input.data() {
+        LogicalType::Union(union) => Ok(union
+            .iter()
+            .map(|(key, typ)| (key.clone(), typ.clone()))
+            .collect()),
+        other => Err(Error::ComposerError(format!(
+            "The {} pattern requires a Stream of a Union, got a Stream of {:?}.",
+            pattern, other
+        ))),
+    }
+}
+
+/// SplitConcatStruct construct
+///
+/// Splits a stream of groups into a stream per field, named after the fields.
+#[derive(Clone, Debug)]
+pub struct SplitConcatStruct {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for SplitConcatStruct {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl SplitConcatStruct {
+    pub fn try_new(name: Name, input: LogicalType) -> Result<Self> {
+        let stream = expect_stream(&input, "SplitConcatStruct")?;
+        let fields = group_fields(&stream, "SplitConcatStruct")?;
+        Ok(SplitConcatStruct {
+            streamlet: pattern_streamlet(
+                name,
+                vec![(IFKey::try_new("in")?, input)],
+                unwrap_fields(&stream, fields, false, "SplitConcatStruct")?,
+            )?,
+        })
+    }
+}
+
+pattern_backend!(SplitConcatStruct, SplitConcatStructBackend);
+
+/// SplitDesyncStruct construct
+///
+/// Splits a group of child streams into its child streams, named after the fields.
+#[derive(Clone, Debug)]
+pub struct SplitDesyncStruct {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for SplitDesyncStruct {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl SplitDesyncStruct {
+    pub fn try_new(name: Name, input: LogicalType) -> Result<Self> {
+        let stream = expect_stream(&input, "SplitDesyncStruct")?;
+        let fields = group_fields(&stream, "SplitDesyncStruct")?;
+        Ok(SplitDesyncStruct {
+            streamlet: pattern_streamlet(
+                name,
+                vec![(IFKey::try_new("in")?, input)],
+                unwrap_fields(&stream, fields, true, "SplitDesyncStruct")?,
+            )?,
+        })
+    }
+}
+
+pattern_backend!(SplitDesyncStruct, SplitDesyncStructBackend);
+
+/// DemuxPackedVariant construct
+///
+/// Demultiplexes a stream of unions into a stream per variant, named after the variants.
+#[derive(Clone, Debug)]
+pub struct DemuxPackedVariant {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for DemuxPackedVariant {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl DemuxPackedVariant {
+    pub fn try_new(name: Name, input: LogicalType) -> Result<Self> {
+        let stream = expect_stream(&input, "DemuxPackedVariant")?;
+        let fields = union_fields(&stream, "DemuxPackedVariant")?;
+        Ok(DemuxPackedVariant {
+            streamlet: pattern_streamlet(
+                name,
+                vec![(IFKey::try_new("in")?, input)],
+                unwrap_fields(&stream, fields, false, "DemuxPackedVariant")?,
+            )?,
+        })
+    }
+}
+
+pattern_backend!(DemuxPackedVariant, DemuxPackedVariantBackend);
+
+/// DemuxConcatVariant construct
+///
+/// Demultiplexes a stream of unions of which the variants are carried by child streams into the
+/// child streams, named after the variants.
+#[derive(Clone, Debug)]
+pub struct DemuxConcatVariant {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for DemuxConcatVariant {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl DemuxConcatVariant {
+    pub fn try_new(name: Name, input: LogicalType) -> Result<Self> {
+        let stream = expect_stream(&input, "DemuxConcatVariant")?;
+        let fields = union_fields(&stream, "DemuxConcatVariant")?;
+        Ok(DemuxConcatVariant {
+            streamlet: pattern_streamlet(
+                name,
+                vec![(IFKey::try_new("in")?, input)],
+                unwrap_fields(&stream, fields, true, "DemuxConcatVariant")?,
+            )?,
+        })
+    }
+}
+
+pattern_backend!(DemuxConcatVariant, DemuxConcatVariantBackend);
+
+/// Returns the input and output stream types of the operation of a pattern.
+fn op_streams(project: &Project, op: StreamletHandle, pattern: &str) -> Result<(Stream, Stream)> {
+    let op = project.get_lib(op.lib())?.get_streamlet(op.streamlet())?;
+    let input = match op.inputs().next() {
+        Some(iface) => expect_stream(&iface.typ(), pattern),
+        None => Err(Error::ComposerError(format!(
+            "The operation of the {} pattern has no input.",
+            pattern
+        ))),
+    }?;
+    let output = match op.outputs().next() {
+        Some(iface) => expect_stream(&iface.typ(), pattern),
+        None => Err(Error::ComposerError(format!(
+            "The operation of the {} pattern has no output.",
+            pattern
+        ))),
+    }?;
+    Ok((input, output))
+}
+
+/// MapVector construct
+///
+/// Applies an operation to every element lane of a stream.
+#[derive(Clone, Debug)]
+pub struct MapVector {
+    streamlet: Streamlet,
+    op_output: Stream,
+}
+
+impl GenericComponent for MapVector {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+    fn connect_action(&self) -> Result<()> {
+        let input_stream = expect_stream(
+            &self.streamlet.get_interface(IFKey::try_from("in")?)?.typ(),
+            "MapVector",
+        )?;
+
+        let output_stream = derive_stream(
+            &self.op_output,
+            self.op_output.data().clone(),
+            input_stream.throughput(),
+            input_stream.dimensionality(),
+            input_stream.synchronicity(),
+        );
+
+        self.streamlet
+            .get_interface_mut(IFKey::try_from("out")?)?
+            .infer_type(LogicalType::from(output_stream))
+    }
+}
+
+impl MapVector {
+    pub fn try_new(project: &Project, name: Name, op: StreamletHandle) -> Result<Self> {
+        let (op_input, op_output) = op_streams(project, op, "MapVector")?;
+
+        let input_if =
+            Interface::try_new("in", Mode::In, op_input, None)?.with_type_inference(|i| {
+                expect_stream(&i, "MapVector")?;
+                Ok(i)
+            });
+        let output_if =
+            Interface::try_new("out", Mode::Out, op_output.clone(), None)?.with_type_inference(Ok);
+
+        Ok(MapVector {
+            streamlet: Streamlet::from_builder(
+                name,
+                UniqueKeyBuilder::new().with_items(vec![input_if, output_if]),
+                None,
+            )?,
+            op_output,
+        })
+    }
+}
+
+pattern_backend!(MapVector, MapVectorBackend);
+
+/// ReduceVector construct
+///
+/// Reduces the element lanes of a stream to a single element per transfer using an operation.
+#[derive(Clone, Debug)]
+pub struct ReduceVector {
+    streamlet: Streamlet,
+    op_output: Stream,
+}
+
+impl GenericComponent for ReduceVector {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+    fn connect_action(&self) -> Result<()> {
+        let input_type = self.streamlet.get_interface(IFKey::try_from("in")?)?.typ();
+        // The output can only be derived once the input is connected.
+        if input_type.is_null() {
+            return Ok(());
+        }
+        let input_stream = expect_stream(&input_type, "ReduceVector")?;
+
+        let output_stream = derive_stream(
+            &self.op_output,
+            self.op_output.data().clone(),
+            PositiveReal::new(1.0)?,
+            input_stream.dimensionality(),
+            input_stream.synchronicity(),
+        );
+
+        self.streamlet
+            .get_interface_mut(IFKey::try_from("out")?)?
+            .infer_type(LogicalType::from(output_stream))
+    }
+}
+
+impl ReduceVector {
+    pub fn try_new(project: &Project, name: Name, op: StreamletHandle) -> Result<Self> {
+        let (_, op_output) = op_streams(project, op, "ReduceVector")?;
+
+        let input_if = Interface::try_new("in", Mode::In, LogicalType::Null, None)?
+            .with_type_inference(|i| {
+                expect_stream(&i, "ReduceVector")?;
+                Ok(i)
+            });
+        let output_if =
+            Interface::try_new("out", Mode::Out, op_output.clone(), None)?.with_type_inference(Ok);
+
+        Ok(ReduceVector {
+            streamlet: Streamlet::from_builder(
+                name,
+                UniqueKeyBuilder::new().with_items(vec![input_if, output_if]),
+                None,
+            )?,
+            op_output,
+        })
+    }
+}
+
+pattern_backend!(ReduceVector, ReduceVectorBackend);
+
+/// VectorToSeq construct
+///
+/// Serializes the element lanes of a stream into a sequence of single elements, adding a
+/// dimension.
+#[derive(Clone, Debug)]
+pub struct VectorToSeq {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for VectorToSeq {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl VectorToSeq {
+    pub fn try_new(name: Name, input: LogicalType) -> Result<Self> {
+        let stream = expect_stream(&input, "VectorToSeq")?;
+        let output = derive_stream(
+            &stream,
+            stream.data().clone(),
+            PositiveReal::new(1.0)?,
+            stream.dimensionality() + 1,
+            stream.synchronicity(),
+        );
+        Ok(VectorToSeq {
+            streamlet: pattern_streamlet(
+                name,
+                vec![(IFKey::try_new("in")?, input)],
+                vec![(IFKey::try_new("out")?, output.into())],
+            )?,
+        })
+    }
+}
+
+pattern_backend!(VectorToSeq, VectorToSeqBackend);
+
+/// CloneStream construct
+///
+/// Duplicates a stream onto multiple outputs.
+#[derive(Clone, Debug)]
+pub struct CloneStream {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for CloneStream {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+    fn connect_action(&self) -> Result<()> {
+        let input_type = self.streamlet.get_interface(IFKey::try_from("in")?)?.typ();
+        // The outputs can only be derived once the input is connected.
+        if input_type.is_null() {
+            return Ok(());
+        }
+        let keys: Vec<IFKey> = self
+            .streamlet
+            .outputs()
+            .map(|iface| iface.key().clone())
+            .collect();
+        for key in keys {
+            self.streamlet
+                .get_interface_mut(key)?
+                .infer_type(input_type.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl CloneStream {
+    pub fn try_new(name: Name, outputs: Vec<IFKey>) -> Result<Self> {
+        let mut ifaces: Vec<Interface> =
+            vec![
+                Interface::try_new("in", Mode::In, LogicalType::Null, None)?.with_type_inference(
+                    |i| {
+                        expect_stream(&i, "CloneStream")?;
+                        Ok(i)
+                    },
+                ),
+            ];
+        for key in outputs {
+            ifaces.push(
+                Interface::try_new(key, Mode::Out, LogicalType::Null, None)?
+                    .with_type_inference(Ok),
+            );
+        }
+
+        Ok(CloneStream {
+            streamlet: Streamlet::from_builder(
+                name,
+                UniqueKeyBuilder::new().with_items(ifaces),
+                None,
+            )?,
+        })
+    }
+}
+
+pattern_backend!(CloneStream, CloneStreamBackend);
+
+/// Returns [typ] with the complexity of every stream replaced by the complexity of the
+/// corresponding stream in [like], which must have the same structure.
+fn with_complexities(typ: &LogicalType, like: &LogicalType) -> Result<LogicalType> {
+    let fields = |typ: &mut dyn Iterator<Item = (&Name, &LogicalType)>,
+                  like: &mut dyn Iterator<Item = (&Name, &LogicalType)>| {
+        typ.zip(like)
+            .map(|((name, typ), (_, like))| Ok((name.clone(), with_complexities(typ, like)?)))
+            .collect::<Result<Vec<(Name, LogicalType)>>>()
+    };
+    Ok(match (typ, like) {
+        (LogicalType::Group(typ), LogicalType::Group(like)) => {
+            Group::try_new(fields(&mut typ.iter(), &mut like.iter())?)?.into()
+        }
+        (LogicalType::Union(typ), LogicalType::Union(like)) => {
+            Union::try_new(fields(&mut typ.iter(), &mut like.iter())?)?.into()
+        }
+        (LogicalType::Stream(typ), LogicalType::Stream(like)) => Stream::new(
+            with_complexities(typ.data(), like.data())?,
+            typ.throughput(),
+            typ.dimensionality(),
+            typ.synchronicity(),
+            like.complexity().clone(),
+            typ.direction(),
+            typ.user().cloned(),
+            typ.keep(),
+        )
+        .into(),
+        (typ, _) => typ.clone(),
+    })
+}
+
+/// Describes the conversion of every physical stream of [input] into the corresponding physical
+/// stream of [output], as far as [describe] returns a description for it.
+fn physical_conversions(
+    input: &LogicalType,
+    output: &LogicalType,
+    describe: impl Fn(&str, &PhysicalStream, &PhysicalStream) -> Option<String>,
+) -> Option<String> {
+    let conversions: Vec<String> = input
+        .split()
+        .zip(output.split())
+        .filter_map(|items| match items {
+            (LogicalSplitItem::Stream(input), LogicalSplitItem::Stream(output)) => {
+                let name = if input.path_name().is_empty() {
+                    "the physical stream".to_string()
+                } else {
+                    format!(
+                        "physical stream {}",
+                        PathName::new(input.path_name().iter().cloned())
+                    )
+                };
+                describe(&name, &input.into(), &output.into())
+            }
+            _ => None,
+        })
+        .collect();
+    if conversions.is_empty() {
+        None
+    } else {
+        Some(conversions.join("\n"))
+    }
+}
+
+/// ComplexityConverter construct
+///
+/// Converts streams to the lower complexity of the sink they are connected to. Only the
+/// complexity of the streams of the input type differs from the output type.
+#[derive(Clone, Debug)]
+pub struct ComplexityConverter {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for ComplexityConverter {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl ComplexityConverter {
+    /// Construct a converter from [input] to the complexities of the streams in [sink]. The
+    /// output type has the complexities of [sink], but is otherwise equal to [input].
+    pub fn try_new(name: Name, input: LogicalType, sink: &LogicalType) -> Result<Self> {
+        let output = with_complexities(&input, sink)?;
+        let doc = physical_conversions(&input, &output, |name, input, output| {
+            if input.complexity() == output.complexity() {
+                None
+            } else {
+                Some(format!(
+                    "Converts {} from complexity {} to {}.",
+                    name,
+                    input.complexity(),
+                    output.complexity()
+                ))
+            }
+        });
+        let ifaces = vec![
+            Interface::try_new("in", Mode::In, input, None)?,
+            Interface::try_new("out", Mode::Out, output, None)?,
+        ];
+        Ok(ComplexityConverter {
+            streamlet: Streamlet::from_builder(
+                name,
+                UniqueKeyBuilder::new().with_items(ifaces),
+                doc.as_deref(),
+            )?,
+        })
+    }
+}
+
+pattern_backend!(ComplexityConverter, ComplexityConverterBackend);
+
+/// LaneConverter construct
+///
+/// Converts streams to the throughput of the sink they are connected to, widening or narrowing
+/// the element lanes of their physical streams.
+#[derive(Clone, Debug)]
+pub struct LaneConverter {
+    streamlet: Streamlet,
+}
+
+impl GenericComponent for LaneConverter {
+    fn streamlet(&self) -> &Streamlet {
+        self.streamlet.borrow()
+    }
+}
+
+impl LaneConverter {
+    /// Construct a converter from [input] to [output], of which only the throughput of the
+    /// streams may differ.
+    pub fn try_new(name: Name, input: LogicalType, output: LogicalType) -> Result<Self> {
+        let doc = physical_conversions(&input, &output, |name, input, output| {
+            let (from, to) = (input.element_lanes(), output.element_lanes());
+            if from < to {
+                Some(format!(
+                    "Widens {} from {} to {} element lanes.",
+                    name, from, to
+                ))
+            } else if from > to {
+                Some(format!(
+                    "Narrows {} from {} to {} element lanes.",
+                    name, from, to
+                ))
+            } else {
+                None
+            }
+        });
+        let ifaces = vec![
+            Interface::try_new("in", Mode::In, input, None)?,
+            Interface::try_new("out", Mode::Out, output, None)?,
+        ];
+        Ok(LaneConverter {
+            streamlet: Streamlet::from_builder(
+                name,
+                UniqueKeyBuilder::new().with_items(ifaces),
+                doc.as_deref(),
+            )?,
+        })
+    }
+}
+
+pattern_backend!(LaneConverter, LaneConverterBackend);
+
 #[cfg(test)]
 mod tests {
     use std::convert::TryFrom;
 
-    use crate::design::StreamletHandle;
-    use crate::{Name, Result};
     use super::*;
     use crate::design::implementation::composer::tests::composition_test_proj;
+    use crate::design::StreamletHandle;
+    use crate::{Name, Result};
 
     #[test]
     fn test_map() -> Result<()> {
@@ -372,4 +1301,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_builder_user() -> Result<()> {
+        let typ = |input: &str| crate::parser::nom::logical_stream_type(input).unwrap().1;
+        let inputs = |b: &str| -> Result<Vec<(IFKey, LogicalType)>> {
+            Ok(vec![
+                (IFKey::try_new("a")?, typ("Stream<Bits<8>, u=Bits<1>>")),
+                (IFKey::try_new("b")?, typ(b)),
+            ])
+        };
+        assert!(
+            ConcatStructBuilder::try_new(Name::try_new("c")?, inputs("Stream<Bits<4>>")?).is_err()
+        );
+        let builder = ConcatStructBuilder::try_new(
+            Name::try_new("c")?,
+            inputs("Stream<Bits<4>, u=Bits<1>>")?,
+        )?;
+        assert_eq!(
+            builder.streamlet().outputs().next().unwrap().typ(),
+            typ("Stream<Group<a: Bits<8>, b: Bits<4>>, u=Bits<1>>")
+        );
+        Ok(())
+    }
 }
@@ -6,20 +6,104 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use log::debug;
+use indexmap::IndexMap;
 
 use crate::design::implementation::composer::GenericComponent;
+use crate::design::namespace::{self, Source};
 use crate::design::param::ParameterStore;
 use crate::design::{LibKey, ParamStoreKey, Streamlet, StreamletHandle, StreamletKey};
-use crate::error::Error::{FileIOError, ParsingError};
-use crate::parser::nom::list_of_streamlets;
+use crate::error::Error::FileIOError;
+use crate::logical::LogicalType;
 use crate::traits::Identify;
-use crate::{Error, Name, Result, UniqueKeyBuilder};
+use crate::{Document, Error, Name, PathName, Result, UniqueKeyBuilder};
+
+/// A logical type that is declared with a name in a library, e.g. `type Pixel = Bits<24>;`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct NamedType {
+    key: Name,
+    typ: LogicalType,
+    doc: Option<String>,
+    /// The named types this type is composed of.
+    type_names: Vec<TypeName>,
+}
+
+impl NamedType {
+    pub fn new(key: Name, typ: LogicalType, doc: Option<&str>) -> Self {
+        NamedType {
+            key,
+            typ,
+            doc: doc.map(|s| s.to_string()),
+            type_names: vec![],
+        }
+    }
+
+    /// Return this type with the named types it is composed of.
+    pub fn with_type_names(mut self, type_names: Vec<TypeName>) -> Self {
+        self.type_names = type_names;
+        self
+    }
+
+    pub fn key(&self) -> &Name {
+        &self.key
+    }
+
+    pub fn logical_type(&self) -> &LogicalType {
+        &self.typ
+    }
+
+    /// Return the named types this type is composed of.
+    pub fn type_names(&self) -> &[TypeName] {
+        &self.type_names
+    }
+}
+
+impl Identify for NamedType {
+    fn identifier(&self) -> &str {
+        self.key.as_ref()
+    }
+}
+
+impl Document for NamedType {
+    fn doc(&self) -> Option<String> {
+        self.doc.clone()
+    }
+}
+
+/// A use of a named type in the type of an interface, e.g. `Pixel` in
+/// `i: in Stream<Group<a: Pixel>>`. The path consists of the names of the group and union fields
+/// that lead to the use, which is `a` in this example; streams do not add to the path.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TypeName {
+    path: PathName,
+    lib: LibKey,
+    key: Name,
+}
+
+impl TypeName {
+    pub fn new(path: PathName, lib: LibKey, key: Name) -> Self {
+        TypeName { path, lib, key }
+    }
+
+    pub fn path(&self) -> &PathName {
+        &self.path
+    }
+
+    /// Returns the library that declares the type.
+    pub fn lib(&self) -> &LibKey {
+        &self.lib
+    }
+
+    pub fn key(&self) -> &Name {
+        &self.key
+    }
+}
 
 /// A collection of Streamlets.
 #[derive(PartialEq, Debug)]
 pub struct Library {
     key: Name,
+    types: IndexMap<Name, NamedType>,
+    imports: IndexMap<(LibKey, Name), NamedType>,
     parameter_stores: HashMap<ParamStoreKey, ParameterStore>,
     streamlets: HashMap<StreamletKey, Streamlet>,
 }
@@ -38,6 +122,8 @@ impl Library {
     pub fn new(key: impl Into<LibKey>) -> Library {
         Library {
             key: key.into(),
+            types: IndexMap::new(),
+            imports: IndexMap::new(),
             parameter_stores: HashMap::new(),
             streamlets: HashMap::new(),
         }
@@ -63,6 +149,8 @@ impl Library {
     ) -> Result<Self> {
         Ok(Library {
             key: name,
+            types: IndexMap::new(),
+            imports: IndexMap::new(),
             parameter_stores: parameter_stores
                 .finish()?
                 .into_iter()
@@ -76,49 +164,74 @@ impl Library {
         })
     }
 
-    /// Construct a Library from a Streamlet Definition File.
+    /// Construct a Library from a Streamlet Definition File. The file can only use types that
+    /// are declared in the file itself, use [Project::from_files] to resolve imports between
+    /// files.
+    ///
+    /// [Project::from_files]: crate::design::Project::from_files
     pub fn from_file(path: &Path) -> Result<Self> {
-        if path.is_dir() {
-            Err(FileIOError(format!(
-                "Expected Streamlet Definition File, got directory: \"{}\"",
-                path.to_str()
-                    .ok_or_else(|| FileIOError("Invalid path.".to_string()))?
-            )))
-        } else {
-            debug!(
-                "Parsing: {}",
-                path.to_str()
-                    .ok_or_else(|| FileIOError("Invalid path.".to_string()))?
-            );
-            let streamlets: Vec<Streamlet> = list_of_streamlets(
-                std::fs::read_to_string(&path)
-                    .map_err(|e| FileIOError(e.to_string()))?
-                    .as_str(),
-            )
-            .map_err(|e| ParsingError(e.to_string()))?
-            .1;
-            debug!("Parsed streamlets: {}", {
-                let sln: Vec<&str> = streamlets.iter().map(|s| s.identifier()).collect();
-                sln.join(", ")
-            });
-            Library::from_builder(
-                Name::try_new(
-                    path.file_stem()
-                        .ok_or_else(|| FileIOError("Invalid file name.".to_string()))?
-                        .to_str()
-                        .unwrap(),
-                )?,
-                // TODO: No support for parameter groups yet
-                UniqueKeyBuilder::new().with_items(vec![]),
-                UniqueKeyBuilder::new().with_items(streamlets),
-            )
-        }
+        namespace::resolve(&[Source::from_file(path)?])?
+            .pop()
+            .ok_or_else(|| FileIOError("Invalid file name.".to_string()))
     }
 
     pub fn key(&self) -> &LibKey {
         &self.key
     }
 
+    /// Return an iterator over the parameter stores of this library.
+    pub fn parameter_stores(&self) -> impl Iterator<Item = &ParameterStore> {
+        self.parameter_stores.values()
+    }
+
+    pub fn get_parameter_store(&self, key: ParamStoreKey) -> Result<&ParameterStore> {
+        self.parameter_stores.get(&key).ok_or_else(|| {
+            Error::LibraryError(format!(
+                "Parameter store {} not found in library {}",
+                key,
+                self.identifier()
+            ))
+        })
+    }
+
+    /// Return an iterator over the named types of this library. Types are ordered such that
+    /// every type comes after the types it refers to.
+    pub fn types(&self) -> impl Iterator<Item = &NamedType> {
+        self.types.values()
+    }
+
+    pub fn get_type(&self, key: &Name) -> Result<&NamedType> {
+        self.types.get(key).ok_or_else(|| {
+            Error::LibraryError(format!(
+                "Type {} not found in library {}",
+                key,
+                self.identifier()
+            ))
+        })
+    }
+
+    pub fn add_type(&mut self, typ: NamedType) -> Result<()> {
+        let key = typ.key().clone();
+        match self.types.insert(key.clone(), typ) {
+            None => Ok(()),
+            Some(_) => Err(Error::LibraryError(format!(
+                "Type {} is declared more than once in library {}",
+                key, self.key
+            ))),
+        }
+    }
+
+    /// Return an iterator over the named types this library imports from other libraries, with
+    /// the library that declares them.
+    pub fn imports(&self) -> impl Iterator<Item = (&LibKey, &NamedType)> {
+        self.imports.iter().map(|((lib, _), typ)| (lib, typ))
+    }
+
+    /// Record that this library uses a named type declared in library [lib].
+    pub fn add_import(&mut self, lib: LibKey, typ: NamedType) {
+        self.imports.insert((lib, typ.key().clone()), typ);
+    }
+
     pub fn add_streamlet(&mut self, streamlet: Streamlet) -> Result<StreamletHandle> {
         let key = streamlet.key().clone();
         match self.streamlets.insert(streamlet.key().clone(), streamlet) {
@@ -173,6 +286,22 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_library_parameters() -> Result<()> {
+        let tmpdir = tempfile::tempdir().map_err(|e| FileIOError(e.to_string()))?;
+        let path = tmpdir.path().join("test.sdf");
+        std::fs::write(
+            path.as_path(),
+            "Streamlet a<W: UInt = 8> (i: in Bits<W>)\nStreamlet b (i: in Null)",
+        )
+        .map_err(|e| FileIOError(e.to_string()))?;
+        let lib = Library::from_file(path.as_path())?;
+        assert_eq!(lib.parameter_stores().count(), 1);
+        assert!(lib.get_parameter_store(Name::try_new("a")?).is_ok());
+        assert!(lib.get_parameter_store(Name::try_new("b")?).is_err());
+        Ok(())
+    }
+
     /// Libraries that can be used for testing purposes throughout the crate.
     pub(crate) mod libs {
         use super::*;
@@ -180,6 +309,8 @@ pub mod tests {
         pub(crate) fn empty_lib() -> Library {
             Library {
                 key: Name::try_new("lib").unwrap(),
+                types: IndexMap::new(),
+                imports: IndexMap::new(),
                 parameter_stores: HashMap::new(),
                 streamlets: HashMap::new(),
             }
@@ -9,6 +9,7 @@ use crate::Name;
 
 pub mod implementation;
 pub mod library;
+pub mod namespace;
 pub mod param;
 pub mod project;
 pub mod streamlet;
@@ -36,7 +37,7 @@ impl NodeKey {
 }
 
 /// Handles for objects inside a project, through project hierarchy
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct StreamletHandle {
     pub lib: Name,
     pub streamlet: Name,
@@ -58,6 +59,9 @@ pub struct NodeIFHandle {
 }
 
 impl NodeIFHandle {
+    pub fn new(node: NodeKey, iface: IFKey) -> Self {
+        NodeIFHandle { node, iface }
+    }
     pub fn node(&self) -> NodeKey {
         self.node.clone()
     }
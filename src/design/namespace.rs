@@ -0,0 +1,733 @@
+//! Resolution of names across Streamlet Definition Files.
+//!
+//! Every file belongs to a library: the one named by its `namespace` (or `library`) declaration,
+//! or the one named after the file otherwise. Files that declare the same namespace together
+//! form one library.
+//!
+//! Names used in the types of streamlets and type declarations are resolved in the following
+//! order: parameters of the streamlet, types declared in the library itself, and types imported
+//! with `use other_lib::TypeName;`. Imports only apply to the file that contains them.
+//!
+//! Only types can be imported. Streamlet declarations do not refer to other streamlets, and
+//! implementations already refer to the streamlets of any library as `other_lib.Streamlet`, so
+//! importing a streamlet is reported as an error.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use log::debug;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::library::{NamedType, TypeName};
+use crate::design::param::{ParamType, ParameterStore};
+use crate::design::{LibKey, Library, Streamlet};
+use crate::error::Error::{FileIOError, SDFParsingError};
+use crate::logical::LogicalType;
+use crate::parser::nom::{definitions, diagnostic, Definitions, TypeDecl};
+use crate::{Error, Identify, Name, PathName, Result, UniqueKeyBuilder};
+
+/// The declarations of a Streamlet Definition File, and the library they belong to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Source {
+    file: Option<String>,
+    lib: LibKey,
+    definitions: Definitions,
+}
+
+impl Source {
+    /// Construct a source from parsed definitions. [lib] is used as the library of the
+    /// definitions if they do not declare a namespace.
+    pub fn new(file: Option<&str>, lib: LibKey, definitions: Definitions) -> Self {
+        Source {
+            file: file.map(|f| f.to_string()),
+            lib: definitions.namespace.clone().unwrap_or(lib),
+            definitions,
+        }
+    }
+
+    /// Parse a Streamlet Definition File. If the file does not declare a namespace, its
+    /// declarations belong to the library named after the file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let display = path
+            .to_str()
+            .ok_or_else(|| FileIOError("Invalid path.".to_string()))?;
+        if path.is_dir() {
+            return Err(FileIOError(format!(
+                "Expected Streamlet Definition File, got directory: \"{}\"",
+                display
+            )));
+        }
+        debug!("Parsing: {}", display);
+        let source = std::fs::read_to_string(path).map_err(|e| FileIOError(e.to_string()))?;
+        let definitions = definitions(source.as_str())
+            .map_err(|e| SDFParsingError(diagnostic(Some(display), source.as_str(), e)))?
+            .1;
+        let stem = Name::try_new(
+            path.file_stem()
+                .ok_or_else(|| FileIOError("Invalid file name.".to_string()))?
+                .to_str()
+                .unwrap(),
+        )?;
+        Ok(Source::new(Some(display), stem, definitions))
+    }
+
+    /// Returns the library the declarations of this source belong to.
+    pub fn lib(&self) -> &LibKey {
+        &self.lib
+    }
+
+    /// Returns a prefix for error messages about this source.
+    fn location(&self) -> String {
+        match &self.file {
+            Some(file) => format!("{}: ", file),
+            None => String::new(),
+        }
+    }
+}
+
+/// Parse the Streamlet Definition Files at [paths] and resolve them into libraries.
+pub fn from_files(paths: &[impl AsRef<Path>]) -> Result<Vec<Library>> {
+    let sources = paths
+        .iter()
+        .map(|path| Source::from_file(path.as_ref()))
+        .collect::<Result<Vec<Source>>>()?;
+    resolve(&sources)
+}
+
+/// Resolve all names in [sources] and construct a library per namespace, in order of first
+/// appearance.
+pub fn resolve(sources: &[Source]) -> Result<Vec<Library>> {
+    let (libraries, mut errors) = resolve_all(sources);
+    if errors.is_empty() {
+        Ok(libraries)
+    } else {
+        Err(errors.remove(0))
+    }
+}
+
+/// Resolve all names in [sources] like [resolve], but continue after an error. Returns the
+/// libraries with every declaration that could be resolved, and every error that was found.
+pub fn resolve_all(sources: &[Source]) -> (Vec<Library>, Vec<Error>) {
+    let (resolved, mut errors) = Resolver::new(sources).run();
+    let mut libraries = vec![];
+    for (key, resolved) in resolved {
+        let Resolved {
+            types,
+            imports,
+            streamlets,
+        } = resolved;
+        // Every parameterized streamlet has a parameter store with its default values.
+        let parameter_stores = streamlets
+            .iter()
+            .filter_map(|s| s.parameters().cloned())
+            .collect::<Vec<ParameterStore>>();
+        debug!("Resolved library {}: {}", key, {
+            let sln: Vec<&str> = streamlets.iter().map(|s| s.identifier()).collect();
+            sln.join(", ")
+        });
+        let library = Library::from_builder(
+            key,
+            UniqueKeyBuilder::new().with_items(parameter_stores),
+            UniqueKeyBuilder::new().with_items(streamlets),
+        )
+        .and_then(|mut lib| {
+            for typ in types {
+                lib.add_type(typ)?;
+            }
+            for (from, typ) in imports {
+                lib.add_import(from, typ);
+            }
+            Ok(lib)
+        });
+        match library {
+            Ok(lib) => libraries.push(lib),
+            Err(err) => errors.push(err),
+        }
+    }
+    (libraries, errors)
+}
+
+/// Resolve the streamlets of a single file, which may only use types declared in that file.
+pub(crate) fn resolve_streamlets(definitions: Definitions) -> Result<Vec<Streamlet>> {
+    let sources = [Source::new(None, Name::try_new("sdf")?, definitions)];
+    let (resolved, mut errors) = Resolver::new(&sources).run();
+    if !errors.is_empty() {
+        return Err(errors.remove(0));
+    }
+    Ok(resolved
+        .into_iter()
+        .flat_map(|(_, resolved)| resolved.streamlets)
+        .collect())
+}
+
+/// A reference to a named type in a library.
+type TypeRef = (LibKey, Name);
+
+fn display(typ: &TypeRef) -> String {
+    format!("{}::{}", typ.0, typ.1)
+}
+
+/// The named types, imported types and streamlets of a library, in order of resolution.
+#[derive(Default)]
+struct Resolved {
+    types: Vec<NamedType>,
+    imports: Vec<(LibKey, NamedType)>,
+    streamlets: Vec<Streamlet>,
+}
+
+struct Resolver<'a> {
+    sources: &'a [Source],
+    /// The source in which every named type is declared, and its declaration.
+    declared: HashMap<TypeRef, (usize, &'a TypeDecl)>,
+    resolved: HashMap<TypeRef, LogicalType>,
+    libraries: IndexMap<LibKey, Resolved>,
+    /// The types that could not be resolved.
+    failed: HashSet<TypeRef>,
+    /// The types that are currently being resolved, used to detect cycles.
+    stack: Vec<TypeRef>,
+    errors: Vec<Error>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(sources: &'a [Source]) -> Self {
+        let mut errors = vec![];
+        let mut libraries = IndexMap::new();
+        let mut declared = HashMap::new();
+        for (index, source) in sources.iter().enumerate() {
+            libraries
+                .entry(source.lib.clone())
+                .or_insert_with(Resolved::default);
+            for decl in source.definitions.types.iter() {
+                let key = (source.lib.clone(), decl.name.clone());
+                match declared.entry(key) {
+                    Entry::Occupied(_) => errors.push(Error::LibraryError(format!(
+                        "{}type {} is declared more than once in library {}",
+                        source.location(),
+                        decl.name,
+                        source.lib
+                    ))),
+                    Entry::Vacant(entry) => {
+                        entry.insert((index, decl));
+                    }
+                }
+            }
+        }
+
+        // Validate the imports of every source.
+        for source in sources.iter() {
+            let mut imported: HashMap<&Name, &LibKey> = HashMap::new();
+            for import in source.definitions.uses.iter() {
+                let statement = format!("use {}::{};", import.lib, import.name);
+                if !libraries.contains_key(&import.lib) {
+                    errors.push(Error::ProjectError(format!(
+                        "{}cannot resolve `{}`, library {} does not exist",
+                        source.location(),
+                        statement,
+                        import.lib
+                    )));
+                    continue;
+                }
+                if !declared.contains_key(&(import.lib.clone(), import.name.clone())) {
+                    let is_streamlet = sources.iter().any(|s| {
+                        s.lib == import.lib
+                            && s.definitions
+                                .streamlets
+                                .iter()
+                                .any(|d| d.name == import.name)
+                    });
+                    if is_streamlet {
+                        errors.push(Error::ProjectError(format!(
+                            "{}cannot resolve `{}`, {} is a streamlet and only types can be \
+                             imported; refer to it as {}.{} in implementations instead",
+                            source.location(),
+                            statement,
+                            import.name,
+                            import.lib,
+                            import.name
+                        )));
+                        continue;
+                    }
+                    errors.push(Error::ProjectError(format!(
+                        "{}cannot resolve `{}`, library {} does not declare a type named {}",
+                        source.location(),
+                        statement,
+                        import.lib,
+                        import.name
+                    )));
+                    continue;
+                }
+                if import.lib != source.lib
+                    && declared.contains_key(&(source.lib.clone(), import.name.clone()))
+                {
+                    errors.push(Error::ProjectError(format!(
+                        "{}`{}` conflicts with type {} declared in library {}",
+                        source.location(),
+                        statement,
+                        import.name,
+                        source.lib
+                    )));
+                    continue;
+                }
+                if let Some(lib) = imported.insert(&import.name, &import.lib) {
+                    if lib != &import.lib {
+                        errors.push(Error::ProjectError(format!(
+                            "{}`{}` conflicts with `use {}::{};`",
+                            source.location(),
+                            statement,
+                            lib,
+                            import.name
+                        )));
+                    }
+                }
+            }
+        }
+
+        Resolver {
+            sources,
+            declared,
+            resolved: HashMap::new(),
+            libraries,
+            failed: HashSet::new(),
+            stack: vec![],
+            errors,
+        }
+    }
+
+    /// Resolve all declarations. Named types that are not used are resolved as well, such that
+    /// errors in them are reported. Declarations with errors are left out of the result.
+    fn run(mut self) -> (IndexMap<LibKey, Resolved>, Vec<Error>) {
+        let sources = self.sources;
+        for source in sources.iter() {
+            for decl in source.definitions.types.iter() {
+                // Errors are recorded while resolving.
+                let _ = self.resolve_type((source.lib.clone(), decl.name.clone()));
+            }
+        }
+        for (index, source) in sources.iter().enumerate() {
+            for decl in source.definitions.streamlets.iter() {
+                if self.libraries[&source.lib]
+                    .streamlets
+                    .iter()
+                    .any(|s| decl.name == *s.identifier())
+                {
+                    self.errors.push(Error::LibraryError(format!(
+                        "{}streamlet {} is declared more than once in library {}",
+                        source.location(),
+                        decl.name,
+                        source.lib
+                    )));
+                    continue;
+                }
+                let used_in = format!("streamlet {}", decl.name);
+                let result = decl
+                    .clone()
+                    .into_streamlet(&mut |name| self.lookup(index, name, &used_in));
+                let result = result.and_then(|streamlet| {
+                    for iface in decl.interfaces.iter() {
+                        let mut type_names = vec![];
+                        self.type_names(
+                            index,
+                            &iface.typ,
+                            streamlet.parameters(),
+                            &PathName::new_empty(),
+                            &mut type_names,
+                        );
+                        streamlet
+                            .get_interface_mut(iface.name.clone())?
+                            .set_type_names(type_names);
+                    }
+                    Ok(streamlet)
+                });
+                match result {
+                    Ok(streamlet) => self.libraries[&source.lib].streamlets.push(streamlet),
+                    Err(err) => self.report(err),
+                }
+            }
+        }
+        (self.libraries, self.errors)
+    }
+
+    /// Record [err], unless it merely signals an error that was recorded before.
+    fn report(&mut self, err: Error) {
+        if err != reported() {
+            self.errors.push(err);
+        }
+    }
+
+    /// Look up a name used in [source].
+    fn lookup(&mut self, source: usize, name: &Name, used_in: &str) -> Result<LogicalType> {
+        let src = &self.sources[source];
+        let local = (src.lib.clone(), name.clone());
+        if self.declared.contains_key(&local) {
+            return self.resolve_type(local);
+        }
+        match src.definitions.uses.iter().find(|u| &u.name == name) {
+            Some(import) => {
+                let (into, from) = (src.lib.clone(), import.lib.clone());
+                if !self.declared.contains_key(&(from.clone(), name.clone())) {
+                    // The import itself is invalid, which was reported on construction.
+                    return Err(reported());
+                }
+                let resolved = self.resolve_type((from.clone(), name.clone()))?;
+                self.import(&into, &from, name);
+                Ok(resolved)
+            }
+            None => Err(Error::ProjectError(format!(
+                "{}unresolved name {} in {}; declare it with `type {} = ...;`, import it with \
+                 `use <library>::{};` or declare a parameter with this name",
+                src.location(),
+                name,
+                used_in,
+                name,
+                name
+            ))),
+        }
+    }
+
+    fn resolve_type(&mut self, typ: TypeRef) -> Result<LogicalType> {
+        if let Some(resolved) = self.resolved.get(&typ) {
+            return Ok(resolved.clone());
+        }
+        if self.failed.contains(&typ) {
+            return Err(reported());
+        }
+        if let Some(start) = self.stack.iter().position(|t| t == &typ) {
+            let cycle = self.stack[start..]
+                .iter()
+                .chain(std::iter::once(&typ))
+                .map(display)
+                .collect::<Vec<String>>();
+            return Err(Error::ProjectError(format!(
+                "cyclic type declaration: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        let (source, decl) = self.declared[&typ];
+        let used_in = format!("type {}", display(&typ));
+        self.stack.push(typ.clone());
+        let result = decl
+            .typ
+            .with_types(None, &mut |name| self.lookup(source, name, &used_in))
+            .and_then(|typ| LogicalType::try_from(&typ));
+        self.stack.pop();
+        let resolved = match result {
+            Ok(resolved) => resolved,
+            Err(err) => {
+                self.report(err);
+                self.failed.insert(typ);
+                return Err(reported());
+            }
+        };
+        self.resolved.insert(typ.clone(), resolved.clone());
+        let mut type_names = vec![];
+        self.type_names(
+            source,
+            &decl.typ,
+            None,
+            &PathName::new_empty(),
+            &mut type_names,
+        );
+        self.libraries[&typ.0].types.push(
+            NamedType::new(typ.1, resolved.clone(), decl.doc.as_deref())
+                .with_type_names(type_names),
+        );
+        Ok(resolved)
+    }
+
+    /// Record that library [into] uses the type [name] declared in library [from], along with
+    /// the named types it is composed of.
+    fn import(&mut self, into: &LibKey, from: &LibKey, name: &Name) {
+        if into == from {
+            return;
+        }
+        let (source, decl) = self.declared[&(from.clone(), name.clone())];
+        let root = PathName::new_empty();
+        let mut used = vec![TypeName::new(root.clone(), from.clone(), name.clone())];
+        self.type_names(source, &decl.typ, None, &root, &mut used);
+        // Keep the order in which the types were resolved, such that types are imported after
+        // the types they are composed of.
+        let imports = self
+            .libraries
+            .iter()
+            .filter(|(lib, _)| *lib != into)
+            .flat_map(|(lib, resolved)| resolved.types.iter().map(move |t| (lib, t)))
+            .filter(|(lib, t)| used.iter().any(|u| u.lib() == *lib && u.key() == t.key()))
+            .map(|(lib, t)| (lib.clone(), t.clone()))
+            .collect::<Vec<_>>();
+        let imported = &mut self.libraries[into].imports;
+        for import in imports {
+            if !imported.contains(&import) {
+                imported.push(import);
+            }
+        }
+    }
+
+    /// Returns the declaration [name] refers to when it is used in [source], if any.
+    fn declaration(&self, source: usize, name: &Name) -> Option<TypeRef> {
+        let src = &self.sources[source];
+        let local = (src.lib.clone(), name.clone());
+        if self.declared.contains_key(&local) {
+            return Some(local);
+        }
+        src.definitions
+            .uses
+            .iter()
+            .find(|u| &u.name == name)
+            .map(|u| (u.lib.clone(), name.clone()))
+            .filter(|typ| self.declared.contains_key(typ))
+    }
+
+    /// Collect the named types used in [typ], which is used in [source] at [path], including
+    /// the named types these consist of. Names of the parameters in [params] do not refer to
+    /// named types. Only types that were resolved may be used, as these do not contain cycles.
+    fn type_names(
+        &self,
+        source: usize,
+        typ: &ParamType,
+        params: Option<&ParameterStore>,
+        path: &PathName,
+        names: &mut Vec<TypeName>,
+    ) {
+        match typ {
+            ParamType::Group(fields) | ParamType::Union(fields) => {
+                for (field, typ) in fields {
+                    let mut path = path.clone();
+                    path.push(field.clone());
+                    self.type_names(source, typ, params, &path, names);
+                }
+            }
+            ParamType::Stream(stream) => {
+                self.type_names(source, &stream.data, params, path, names);
+                if let Some(user) = &stream.user {
+                    self.type_names(source, user, params, path, names);
+                }
+            }
+            ParamType::Param(key)
+                if params.map(|p| p.get(key.clone()).is_err()).unwrap_or(true) =>
+            {
+                if let Some(typ) = self.declaration(source, key) {
+                    let (declaring, decl) = self.declared[&typ];
+                    names.push(TypeName::new(path.clone(), typ.0, typ.1));
+                    self.type_names(declaring, &decl.typ, None, path, names);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The error returned for a declaration that could not be resolved because of an error that was
+/// recorded already.
+fn reported() -> Error {
+    Error::ProjectError(String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::design::implementation::composer::GenericComponent;
+    use crate::parser::nom::stream;
+    use crate::Document;
+
+    use super::*;
+
+    fn source(lib: &str, input: &str) -> Source {
+        Source::new(
+            Some(format!("{}.sdf", lib).as_str()),
+            Name::try_new(lib).unwrap(),
+            definitions(input).unwrap().1,
+        )
+    }
+
+    fn error(sources: &[Source]) -> String {
+        resolve(sources).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn imports() -> Result<()> {
+        let libs = resolve(&[
+            source(
+                "app",
+                "use common::Pixel;
+                 type Frame = Stream<Pixel, d=2>;
+                 Streamlet blur (i: in Frame, o: out Frame)",
+            ),
+            source(
+                "common",
+                "type Channel = Bits<8>;
+                 /// An RGB pixel.
+                 type Pixel = Group<r: Channel, g: Channel, b: Channel>;",
+            ),
+        ])?;
+        assert_eq!(libs.len(), 2);
+        let common = &libs[1];
+        assert_eq!(
+            common.types().map(|t| t.identifier()).collect::<Vec<_>>(),
+            vec!["Channel", "Pixel"]
+        );
+        let pixel = common.get_type(&Name::try_new("Pixel")?)?;
+        assert_eq!(pixel.doc(), Some(" An RGB pixel.".to_string()));
+        // Named types record the named types they are composed of as well.
+        assert_eq!(
+            pixel
+                .type_names()
+                .iter()
+                .map(|t| format!("{}: {}::{}", t.path(), t.lib(), t.key()))
+                .collect::<Vec<_>>(),
+            vec![
+                "r: common::Channel",
+                "g: common::Channel",
+                "b: common::Channel"
+            ]
+        );
+        // The types that app uses from common are recorded, including those Pixel consists of.
+        assert_eq!(
+            libs[0]
+                .imports()
+                .map(|(lib, t)| format!("{}::{}", lib, t.identifier()))
+                .collect::<Vec<_>>(),
+            vec!["common::Channel", "common::Pixel"]
+        );
+        let blur = libs[0].get_streamlet(Name::try_new("blur")?)?;
+        assert_eq!(
+            blur.get_interface(Name::try_new("i")?)?.typ(),
+            stream("Stream<Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>, d=2>")
+                .unwrap()
+                .1
+        );
+        // The interface records which named types its type consists of, and where.
+        assert_eq!(
+            blur.get_interface(Name::try_new("i")?)?
+                .type_names()
+                .iter()
+                .map(|t| format!("{}: {}::{}", t.path(), t.lib(), t.key()))
+                .collect::<Vec<_>>(),
+            vec![
+                ": app::Frame",
+                ": common::Pixel",
+                "r: common::Channel",
+                "g: common::Channel",
+                "b: common::Channel"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn namespaces() -> Result<()> {
+        let libs = resolve(&[
+            source("a", "namespace shared; type T = Bits<1>;"),
+            source("b", "library shared; Streamlet s (i: in T)"),
+        ])?;
+        assert_eq!(libs.len(), 1);
+        assert_eq!(libs[0].identifier(), "shared");
+        assert_eq!(libs[0].streamlets().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn parameters_shadow_types() -> Result<()> {
+        let libs = resolve(&[source(
+            "a",
+            "type T = Bits<1>; Streamlet s<T: Type = Bits<2>> (i: in T)",
+        )])?;
+        let s = libs[0].get_streamlet(Name::try_new("s")?)?;
+        assert_eq!(
+            s.get_interface(Name::try_new("i")?)?.typ(),
+            LogicalType::try_new_bits(2)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error(&[source("a", "Streamlet s (i: in Pixel)")]),
+            "Project error: a.sdf: unresolved name Pixel in streamlet s; declare it with \
+             `type Pixel = ...;`, import it with `use <library>::Pixel;` or declare a parameter \
+             with this name"
+        );
+        assert_eq!(
+            error(&[
+                source("a", "use b::A; type B = Group<a: A>;"),
+                source("b", "use a::B; type A = Stream<B>;"),
+            ]),
+            "Project error: cyclic type declaration: a::B -> b::A -> a::B"
+        );
+        assert_eq!(
+            error(&[source("a", "type A = A;")]),
+            "Project error: cyclic type declaration: a::A -> a::A"
+        );
+        assert_eq!(
+            error(&[source("a", "use c::A;")]),
+            "Project error: a.sdf: cannot resolve `use c::A;`, library c does not exist"
+        );
+        assert_eq!(
+            error(&[source("a", "use b::C;"), source("b", "")]),
+            "Project error: a.sdf: cannot resolve `use b::C;`, library b does not declare a \
+             type named C"
+        );
+        assert_eq!(
+            error(&[
+                source("a", "use b::s;"),
+                source("b", "Streamlet s (i: in Null)")
+            ]),
+            "Project error: a.sdf: cannot resolve `use b::s;`, s is a streamlet and only types \
+             can be imported; refer to it as b.s in implementations instead"
+        );
+        assert_eq!(
+            error(&[
+                source("a", "use b::T; type T = Null;"),
+                source("b", "type T = Null;")
+            ]),
+            "Project error: a.sdf: `use b::T;` conflicts with type T declared in library a"
+        );
+        assert_eq!(
+            error(&[
+                source("a", "namespace n; type T = Null;"),
+                source("b", "namespace n; type T = Null;")
+            ]),
+            "Library error: b.sdf: type T is declared more than once in library n"
+        );
+    }
+
+    #[test]
+    fn all_errors() {
+        let (libs, errors) = resolve_all(&[
+            source(
+                "a",
+                "use c::X;
+                 type A = Group<x: X>;
+                 type C = Group<y: Y>;
+                 Streamlet s (i: in A)
+                 Streamlet t (i: in Stream<Bits<8>>)
+                 Streamlet t (o: out Stream<Bits<8>>)
+                 Streamlet u (i: in Stream<Z>)",
+            ),
+            source("b", "type B = B; Streamlet v (i: in Stream<B>)"),
+        ]);
+        assert_eq!(
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            vec![
+                "Project error: a.sdf: cannot resolve `use c::X;`, library c does not exist",
+                "Project error: a.sdf: unresolved name Y in type a::C; declare it with \
+                 `type Y = ...;`, import it with `use <library>::Y;` or declare a parameter \
+                 with this name",
+                "Project error: cyclic type declaration: b::B -> b::B",
+                "Library error: a.sdf: streamlet t is declared more than once in library a",
+                "Project error: a.sdf: unresolved name Z in streamlet u; declare it with \
+                 `type Z = ...;`, import it with `use <library>::Z;` or declare a parameter \
+                 with this name",
+            ]
+        );
+        // Declarations without errors are still resolved.
+        assert_eq!(
+            libs.iter()
+                .map(|l| l.streamlets().map(|s| s.identifier()).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec!["t"], vec![]]
+        );
+    }
+}
@@ -1,12 +1,25 @@
+//! Generic parameters of streamlets.
+//!
+//! A streamlet can declare typed parameters, each with a default value. Parameters of kind
+//! [ParameterVariant::UInt] and [ParameterVariant::Type] can be referenced in the types of the
+//! interfaces of the streamlet, e.g. `Bits<W>`. Such interface types are kept as a
+//! [ParamType], which is resolved into a [LogicalType] once all parameters have a value.
+
 use std::collections::HashMap;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+
+use indexmap::IndexMap;
 
-///! Generic parameter type
 use crate::design::{ParamHandle, ParamKey, ParamStoreKey};
-use crate::logical::LogicalType;
-use crate::{Document, Error, Identify, Result, UniqueKeyBuilder};
+use crate::logical::{Direction, Group, LogicalType, Stream, Synchronicity, Union};
+use crate::physical::Complexity;
+use crate::{Document, Error, Identify, Name, NonNegative, PositiveReal, Result, UniqueKeyBuilder};
+
+/// Values assigned to parameters, e.g. `[W := 16, depth := 32]`.
+pub type Assignments = Vec<(ParamKey, ParameterVariant)>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ParameterVariant {
     Type(LogicalType),
     String(String),
@@ -14,7 +27,28 @@ pub enum ParameterVariant {
     //...
 }
 
-#[derive(Debug, PartialEq)]
+impl ParameterVariant {
+    /// Returns the name of the kind of this parameter, as used in Streamlet Definition Files.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParameterVariant::Type(_) => "Type",
+            ParameterVariant::String(_) => "String",
+            ParameterVariant::UInt(_) => "UInt",
+        }
+    }
+}
+
+impl fmt::Display for ParameterVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterVariant::Type(t) => write!(f, "{:?}", t),
+            ParameterVariant::String(s) => write!(f, "\"{}\"", s),
+            ParameterVariant::UInt(u) => write!(f, "{}", u),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct NamedParameter {
     key: ParamKey,
     item: ParameterVariant,
@@ -55,10 +89,10 @@ impl Document for NamedParameter {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ParameterStore {
     key: ParamStoreKey,
-    params: HashMap<ParamKey, NamedParameter>,
+    params: IndexMap<ParamKey, NamedParameter>,
 }
 
 impl Identify for ParameterStore {
@@ -78,7 +112,7 @@ impl ParameterStore {
                 .finish()?
                 .into_iter()
                 .map(|p| (p.key().clone(), p))
-                .collect::<HashMap<ParamKey, NamedParameter>>(),
+                .collect::<IndexMap<ParamKey, NamedParameter>>(),
         })
     }
 
@@ -106,7 +140,340 @@ impl ParameterStore {
         })
     }
 
+    /// Assign a new value to an existing parameter. The value must be of the same kind as the
+    /// current value of the parameter.
+    pub fn assign(&mut self, key: ParamKey, item: ParameterVariant) -> Result<()> {
+        let store = self.key.clone();
+        let param = self.params.get_mut(&key).ok_or_else(|| {
+            Error::LibraryError(format!("{} has no parameter named {}", store, key))
+        })?;
+        if param.item.kind() != item.kind() {
+            return Err(Error::LibraryError(format!(
+                "Parameter {} of {} expects a value of kind {}, got {} ({})",
+                key,
+                store,
+                param.item.kind(),
+                item.kind(),
+                item
+            )));
+        }
+        param.item = item;
+        Ok(())
+    }
+
+    /// Returns an iterator over the parameters in this store, in order of declaration.
+    pub fn params(&self) -> impl Iterator<Item = &NamedParameter> {
+        self.params.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
     pub fn key(&self) -> &ParamStoreKey {
         &self.key
     }
+
+    pub(crate) fn set_key(&mut self, key: ParamStoreKey) {
+        self.key = key;
+    }
+}
+
+/// An unsigned integer that is either a literal or a reference to a parameter of kind
+/// [ParameterVariant::UInt].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamUInt {
+    Literal(NonNegative),
+    Param(ParamKey),
+}
+
+impl ParamUInt {
+    fn resolve(&self, params: Option<&ParameterStore>) -> Result<NonNegative> {
+        match self {
+            ParamUInt::Literal(value) => Ok(*value),
+            ParamUInt::Param(key) => match lookup(params, key)? {
+                ParameterVariant::UInt(value) => Ok(*value as NonNegative),
+                other => Err(kind_mismatch(key, "UInt", other)),
+            },
+        }
+    }
+}
+
+/// The properties of a [Stream] type that may depend on parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamStream {
+    pub data: ParamType,
+    pub throughput: PositiveReal,
+    pub dimensionality: ParamUInt,
+    pub synchronicity: Synchronicity,
+    pub complexity: Complexity,
+    pub direction: Direction,
+    pub user: Option<ParamType>,
+    pub keep: bool,
+}
+
+/// A logical type which may refer to parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamType {
+    Null,
+    Bits(ParamUInt),
+    Group(Vec<(Name, ParamType)>),
+    Union(Vec<(Name, ParamType)>),
+    Stream(Box<ParamStream>),
+    /// A reference to a parameter of kind [ParameterVariant::Type].
+    Param(ParamKey),
+}
+
+impl ParamType {
+    /// Returns true if this type refers to any parameter.
+    pub fn is_parameterized(&self) -> bool {
+        match self {
+            ParamType::Null => false,
+            ParamType::Bits(ParamUInt::Literal(_)) => false,
+            ParamType::Bits(ParamUInt::Param(_)) | ParamType::Param(_) => true,
+            ParamType::Group(fields) | ParamType::Union(fields) => {
+                fields.iter().any(|(_, typ)| typ.is_parameterized())
+            }
+            ParamType::Stream(stream) => {
+                matches!(stream.dimensionality, ParamUInt::Param(_))
+                    || stream.data.is_parameterized()
+                    || stream
+                        .user
+                        .as_ref()
+                        .map(|user| user.is_parameterized())
+                        .unwrap_or(false)
+            }
+        }
+    }
+
+    /// Resolve this type into a logical type, using the current values of the parameters in
+    /// [params].
+    pub fn resolve(&self, params: &ParameterStore) -> Result<LogicalType> {
+        self.resolve_with(Some(params))
+    }
+
+    fn resolve_with(&self, params: Option<&ParameterStore>) -> Result<LogicalType> {
+        let fields = |fields: &Vec<(Name, ParamType)>| -> Result<Vec<(Name, LogicalType)>> {
+            fields
+                .iter()
+                .map(|(name, typ)| Ok((name.clone(), typ.resolve_with(params)?)))
+                .collect()
+        };
+        match self {
+            ParamType::Null => Ok(LogicalType::Null),
+            ParamType::Bits(width) => LogicalType::try_new_bits(width.resolve(params)?),
+            ParamType::Group(f) => Group::try_new(fields(f)?).map(Into::into),
+            ParamType::Union(f) => Union::try_new(fields(f)?).map(Into::into),
+            ParamType::Stream(stream) => Ok(Stream::new(
+                stream.data.resolve_with(params)?,
+                stream.throughput,
+                stream.dimensionality.resolve(params)?,
+                stream.synchronicity,
+                stream.complexity.clone(),
+                stream.direction,
+                match &stream.user {
+                    Some(user) => Some(user.resolve_with(params)?),
+                    None => None,
+                },
+                stream.keep,
+            )
+            .into()),
+            ParamType::Param(key) => match lookup(params, key)? {
+                ParameterVariant::Type(typ) => Ok(typ.clone()),
+                other => Err(kind_mismatch(key, "Type", other)),
+            },
+        }
+    }
+    /// Replace references to names that are not parameters in [params] by the named types
+    /// returned by [types], so that the result only refers to parameters.
+    pub fn with_types(
+        &self,
+        params: Option<&ParameterStore>,
+        types: &mut dyn FnMut(&Name) -> Result<LogicalType>,
+    ) -> Result<ParamType> {
+        let mut fields = |fields: &Vec<(Name, ParamType)>| -> Result<Vec<(Name, ParamType)>> {
+            fields
+                .iter()
+                .map(|(name, typ)| Ok((name.clone(), typ.with_types(params, types)?)))
+                .collect()
+        };
+        Ok(match self {
+            ParamType::Group(f) => ParamType::Group(fields(f)?),
+            ParamType::Union(f) => ParamType::Union(fields(f)?),
+            ParamType::Stream(stream) => ParamType::Stream(Box::new(ParamStream {
+                data: stream.data.with_types(params, types)?,
+                user: match &stream.user {
+                    Some(user) => Some(user.with_types(params, types)?),
+                    None => None,
+                },
+                ..(**stream).clone()
+            })),
+            ParamType::Param(key)
+                if !params
+                    .map(|store| store.params.contains_key(key))
+                    .unwrap_or(false) =>
+            {
+                ParamType::from(&types(key)?)
+            }
+            other => other.clone(),
+        })
+    }
+}
+
+impl From<&LogicalType> for ParamType {
+    fn from(typ: &LogicalType) -> Self {
+        let fields = |fields: &mut dyn Iterator<Item = (&Name, &LogicalType)>| {
+            fields
+                .map(|(name, typ)| (name.clone(), ParamType::from(typ)))
+                .collect()
+        };
+        match typ {
+            LogicalType::Null => ParamType::Null,
+            LogicalType::Bits(width) => ParamType::Bits(ParamUInt::Literal(width.get())),
+            LogicalType::Group(group) => ParamType::Group(fields(&mut group.iter())),
+            LogicalType::Union(union) => ParamType::Union(fields(&mut union.iter())),
+            LogicalType::Stream(stream) => ParamType::Stream(Box::new(ParamStream {
+                data: ParamType::from(stream.data()),
+                throughput: stream.throughput(),
+                dimensionality: ParamUInt::Literal(stream.dimensionality()),
+                synchronicity: stream.synchronicity(),
+                complexity: stream.complexity().clone(),
+                direction: stream.direction(),
+                user: stream.user().map(ParamType::from),
+                keep: stream.keep(),
+            })),
+        }
+    }
+}
+
+impl TryFrom<&ParamType> for LogicalType {
+    type Error = Error;
+
+    /// Convert a type that does not refer to any parameters into a logical type.
+    fn try_from(typ: &ParamType) -> Result<Self> {
+        typ.resolve_with(None)
+    }
+}
+
+fn lookup<'a>(params: Option<&'a ParameterStore>, key: &ParamKey) -> Result<&'a ParameterVariant> {
+    params
+        .and_then(|store| store.params.get(key))
+        .map(|param| param.item())
+        .ok_or_else(|| Error::InvalidArgument(format!("Unknown parameter {}", key)))
+}
+
+fn kind_mismatch(key: &ParamKey, expected: &str, actual: &ParameterVariant) -> Error {
+    Error::InvalidArgument(format!(
+        "Parameter {} is used as {}, but is declared as {}",
+        key,
+        expected,
+        actual.kind()
+    ))
+}
+
+/// Validate a list of parameter assignments against the parameters of [store] and return a
+/// copy of the store with the assigned values.
+pub fn bind(store: &ParameterStore, assignments: Assignments) -> Result<ParameterStore> {
+    let mut bound = store.clone();
+    let mut assigned = HashMap::new();
+    for (key, value) in assignments {
+        if assigned.insert(key.clone(), ()).is_some() {
+            return Err(Error::LibraryError(format!(
+                "Parameter {} of {} is assigned more than once",
+                key,
+                store.identifier()
+            )));
+        }
+        bound.assign(key, value)?;
+    }
+    Ok(bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> Result<ParameterStore> {
+        ParameterStore::from_builder(
+            Name::try_new("test")?,
+            UniqueKeyBuilder::new().with_items(vec![
+                NamedParameter::try_new("W", ParameterVariant::UInt(8), None)?,
+                NamedParameter::try_new("T", ParameterVariant::Type(LogicalType::Null), None)?,
+                NamedParameter::try_new("re", ParameterVariant::String("a*".to_string()), None)?,
+            ]),
+        )
+    }
+
+    #[test]
+    fn resolve() -> Result<()> {
+        let store = store()?;
+        let typ = ParamType::Group(vec![
+            (
+                Name::try_new("a")?,
+                ParamType::Bits(ParamUInt::Param(Name::try_new("W")?)),
+            ),
+            (Name::try_new("b")?, ParamType::Param(Name::try_new("T")?)),
+        ]);
+        assert!(typ.is_parameterized());
+        assert_eq!(
+            typ.resolve(&store)?,
+            LogicalType::try_new_group(vec![
+                ("a", LogicalType::try_new_bits(8)?),
+                ("b", LogicalType::Null),
+            ])?
+        );
+        assert!(LogicalType::try_from(&typ).is_err());
+
+        // Parameters must be used according to their kind.
+        assert!(ParamType::Param(Name::try_new("W")?)
+            .resolve(&store)
+            .is_err());
+        assert!(ParamType::Bits(ParamUInt::Param(Name::try_new("re")?))
+            .resolve(&store)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn bind_params() -> Result<()> {
+        let store = store()?;
+        let bound = bind(
+            &store,
+            vec![(Name::try_new("W")?, ParameterVariant::UInt(16))],
+        )?;
+        assert_eq!(
+            bound.get(Name::try_new("W")?)?.item(),
+            &ParameterVariant::UInt(16)
+        );
+        assert_eq!(
+            bound.get(Name::try_new("re")?)?.item(),
+            &ParameterVariant::String("a*".to_string())
+        );
+
+        // Unknown parameter.
+        assert!(bind(
+            &store,
+            vec![(Name::try_new("X")?, ParameterVariant::UInt(1))]
+        )
+        .is_err());
+        // Wrong kind.
+        assert!(bind(
+            &store,
+            vec![(
+                Name::try_new("W")?,
+                ParameterVariant::String("1".to_string())
+            )]
+        )
+        .is_err());
+        // Duplicate assignment.
+        assert!(bind(
+            &store,
+            vec![
+                (Name::try_new("W")?, ParameterVariant::UInt(1)),
+                (Name::try_new("W")?, ParameterVariant::UInt(2))
+            ]
+        )
+        .is_err());
+        Ok(())
+    }
 }
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
+use std::path::Path;
 
+use crate::design::implementation::composer::parser::ImplParser;
 use crate::design::implementation::Implementation;
+use crate::design::namespace;
 use crate::design::{LibKey, Library, Streamlet, StreamletHandle};
 use crate::util::UniquelyNamedBuilder;
 use crate::{Error, Result};
@@ -38,6 +41,15 @@ impl Project {
         })
     }
 
+    /// Construct a Project from Streamlet Definition Files. Files that declare the same
+    /// namespace form one library, and types can be imported from other libraries with `use`.
+    pub fn from_files(name: Name, paths: &[impl AsRef<Path>]) -> Result<Self> {
+        Self::from_builder(
+            name,
+            UniquelyNamedBuilder::new().with_items(namespace::from_files(paths)?),
+        )
+    }
+
     // Return an iterator over the libraries in this project.
     pub fn libraries(&self) -> impl Iterator<Item = &Library> {
         self.libraries.iter().map(|(_, l)| l)
@@ -91,12 +103,98 @@ impl Project {
         self.get_streamlet_mut(streamlet)?
             .attach_implementation(implementation)
     }
+
+    /// Parse an implementation (`impl lib.streamlet ...`) and attach it to the streamlet it
+    /// implements. Returns a handle to that streamlet.
+    pub fn add_implementation(&mut self, input: &str) -> Result<StreamletHandle> {
+        self.add_implementation_with_adapters(input, false)
+    }
+
+    /// Like [Project::add_implementation], but if [adapters] is set, adapters are inserted on
+    /// connections between streams of which only the complexity or throughput does not match.
+    pub fn add_implementation_with_adapters(
+        &mut self,
+        input: &str,
+        adapters: bool,
+    ) -> Result<StreamletHandle> {
+        let mut parser = ImplParser::try_new(self, input)?.with_adapters(adapters);
+        parser.transform_body()?;
+        let implementation = parser.finish();
+        let handle = implementation.streamlet_handle();
+        self.add_streamlet_impl(handle.clone(), implementation)?;
+        Ok(handle)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
+    use crate::design::implementation::composer::GenericComponent;
+
     use super::*;
 
+    #[test]
+    fn from_files() -> Result<()> {
+        let tmpdir = tempfile::tempdir().map_err(|e| Error::FileIOError(e.to_string()))?;
+        let write = |name: &str, content: &str| {
+            let path = tmpdir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        let files = vec![
+            write("types.sdf", "namespace common; type Byte = Bits<8>;"),
+            write(
+                "more_types.sdf",
+                "namespace common; type Pair = Group<a: Byte, b: Byte>;",
+            ),
+            write(
+                "app.sdf",
+                "use common::Pair; Streamlet s (i: in Stream<Pair>)",
+            ),
+        ];
+        let project = Project::from_files(Name::try_new("proj")?, &files)?;
+        assert_eq!(project.libraries().count(), 2);
+        assert_eq!(
+            project.get_lib(Name::try_new("common")?)?.types().count(),
+            2
+        );
+        assert!(project
+            .get_streamlet(StreamletHandle {
+                lib: Name::try_new("app")?,
+                streamlet: Name::try_new("s")?,
+            })
+            .is_ok());
+
+        let broken = write("broken.sdf", "Streamlet s (i: in Pair)");
+        match Project::from_files(Name::try_new("proj")?, &[broken]) {
+            Err(e) => assert!(e
+                .to_string()
+                .contains("broken.sdf: unresolved name Pair in streamlet s")),
+            Ok(_) => panic!("expected an unresolved name error"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn add_implementation() -> Result<()> {
+        let mut prj = crate::design::implementation::composer::tests::composition_test_proj()?;
+        let handle = prj.add_implementation(include_str!(
+            "../../tests/implementations/composition_example.impl"
+        ))?;
+        assert_eq!(handle.streamlet(), Name::try_new("Top_level")?);
+        assert!(prj.get_streamlet(handle)?.get_implementation().is_some());
+
+        // The library of generated streamlets is shared by all implementations.
+        let handle = prj.add_implementation(
+            "impl primitives.Magic structural {
+                 m: MapStream(op_inst: primitives.test_op);
+                 m.in <= this.in;
+                 this.out <= m.out;
+             }",
+        )?;
+        assert!(prj.get_streamlet(handle)?.get_implementation().is_some());
+        Ok(())
+    }
+
     /// Some projects that can be used throughout the crate for testing.
     pub mod proj {
 
@@ -11,6 +11,8 @@ use std::str::FromStr;
 
 use crate::design::implementation::composer::GenericComponent;
 use crate::design::implementation::Implementation;
+use crate::design::library::TypeName;
+use crate::design::param::{self, ParamType, ParameterStore};
 use crate::design::{ComponentKey, IFKey};
 use crate::logical::LogicalType;
 use crate::traits::Identify;
@@ -64,6 +66,8 @@ pub struct Interface {
     inf_f: Option<Box<fn(LogicalType) -> Result<LogicalType>>>,
     /// The documentation string of the interface, if any.
     doc: Option<String>,
+    /// The named types used in the type of the interface.
+    type_names: Vec<TypeName>,
 }
 
 impl Identify for Interface {
@@ -116,6 +120,7 @@ impl Interface {
                 typ: t,
                 inf_f: None,
                 doc: doc.map(|d| d.to_string()),
+                type_names: vec![],
             }),
         }
     }
@@ -125,6 +130,12 @@ impl Interface {
         self
     }
 
+    /// Return this interface with the named types used in its type.
+    pub fn with_type_names(mut self, type_names: Vec<TypeName>) -> Self {
+        self.type_names = type_names;
+        self
+    }
+
     pub fn with_type_inference(mut self, inf_f: fn(LogicalType) -> Result<LogicalType>) -> Self {
         self.inf_f = Option::from(Box::new(inf_f));
         self
@@ -153,6 +164,21 @@ impl Interface {
     pub fn typ(&self) -> LogicalType {
         self.typ.clone()
     }
+
+    /// Return the named types used in the type of the interface.
+    pub fn type_names(&self) -> &[TypeName] {
+        &self.type_names
+    }
+
+    pub(crate) fn set_type_names(&mut self, type_names: Vec<TypeName>) {
+        self.type_names = type_names;
+    }
+}
+
+impl Document for Interface {
+    fn doc(&self) -> Option<String> {
+        self.doc.clone()
+    }
 }
 
 impl Reverse for Interface {
@@ -172,6 +198,10 @@ pub struct Streamlet {
     doc: Option<String>,
     /// Placeholder for future implementation of the streamlet. If this is None, it is a primitive.
     implementation: Option<Rc<Implementation>>,
+    /// The parameters of the streamlet, if any.
+    parameters: Option<ParameterStore>,
+    /// The types of the interfaces that depend on the parameters of the streamlet.
+    templates: BTreeMap<IFKey, ParamType>,
 }
 
 impl PartialEq for Streamlet {
@@ -271,9 +301,75 @@ impl Streamlet {
                 .collect::<BTreeMap<IFKey, Rc<RefCell<Interface>>>>(),
             doc: doc.map(|d| d.to_string()),
             implementation: None,
+            parameters: None,
+            templates: BTreeMap::new(),
         })
     }
 
+    /// Return this streamlet with parameters. The types of the interfaces that depend on the
+    /// parameters are described by [templates], and are resolved again when the streamlet is
+    /// instantiated with other parameter values.
+    pub fn with_parameters(
+        mut self,
+        mut parameters: ParameterStore,
+        templates: BTreeMap<IFKey, ParamType>,
+    ) -> Self {
+        parameters.set_key(self.key.clone());
+        self.parameters = Some(parameters);
+        self.templates = templates;
+        self
+    }
+
+    /// Return the parameters of this streamlet, if it has any.
+    pub fn parameters(&self) -> Option<&ParameterStore> {
+        self.parameters.as_ref()
+    }
+
+    /// Return the type of interface [key] in terms of the parameters of this streamlet, if it
+    /// depends on them.
+    pub fn template(&self, key: &IFKey) -> Option<&ParamType> {
+        self.templates.get(key)
+    }
+
+    /// Instantiate this streamlet with the parameters in [assignments] bound to new values,
+    /// resulting in a streamlet named [key]. The assignments are validated against the
+    /// parameters of this streamlet, and the types of the interfaces that depend on them are
+    /// resolved using the new values. The implementation of this streamlet, if any, is carried
+    /// over.
+    pub fn instantiate(
+        &self,
+        key: ComponentKey,
+        assignments: param::Assignments,
+    ) -> Result<Streamlet> {
+        let parameters = match &self.parameters {
+            Some(params) => param::bind(params, assignments)?,
+            None if assignments.is_empty() => return Ok(self.clone()),
+            None => {
+                return Err(Error::LibraryError(format!(
+                    "Streamlet {} does not have any parameters",
+                    self.identifier()
+                )))
+            }
+        };
+        let mut interfaces = BTreeMap::new();
+        for (iface_key, iface) in self.interfaces.iter() {
+            let mut iface = iface.borrow().clone();
+            if let Some(template) = self.templates.get(iface_key) {
+                iface.typ = template.resolve(&parameters)?;
+            }
+            interfaces.insert(iface_key.clone(), Rc::new(RefCell::new(iface)));
+        }
+        Ok(Streamlet {
+            key: key.clone(),
+            interfaces,
+            doc: self.doc.clone(),
+            implementation: self.implementation.clone(),
+            parameters: None,
+            templates: BTreeMap::new(),
+        }
+        .with_parameters(parameters, self.templates.clone()))
+    }
+
     pub(crate) fn set_key(&mut self, key: ComponentKey) {
         self.key = key;
     }
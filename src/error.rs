@@ -25,6 +25,8 @@ pub enum Error {
     ParsingError(String),
     /// Parsing error.
     ImplParsingError(LineErr),
+    /// Streamlet Definition File parsing error.
+    SDFParsingError(Diagnostic),
     /// Invalid target.
     InvalidTarget(String),
     /// Back-end error.
@@ -37,6 +39,8 @@ pub enum Error {
     ComposerError(String),
     /// Library error
     LibraryError(String),
+    /// Physical stream protocol violation
+    ProtocolError(String),
 }
 
 ///Error variants for implementation parser
@@ -52,6 +56,59 @@ impl LineErr {
     }
 }
 
+/// A diagnostic pointing at the location of an error in a source file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    /// The name of the file, if any.
+    pub file: Option<String>,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number, starting at 1.
+    pub column: usize,
+    /// The number of characters to mark, starting at the column.
+    pub width: usize,
+    /// The line of the source containing the error.
+    pub source_line: String,
+    /// What went wrong, e.g. "expected `>`, found `,`".
+    pub message: String,
+    /// An optional hint on how to resolve the error.
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    /// Render the diagnostic, e.g.:
+    /// ```text
+    /// expected synchronicity, found `sync`
+    ///  --> lib.sdf:1:37
+    ///   |
+    /// 1 | Streamlet x (a: in Stream<Bits<8>, s=sync>)
+    ///   |                                     ^^^^
+    ///   = hint: unknown synchronicity 'sync', expected one of Sync, Flatten, Desync, FlatDesync
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        // Keep tabs in front of the caret, so it lines up with the source line.
+        let indent = self
+            .source_line
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        writeln!(f, "{}", self.message)?;
+        match &self.file {
+            Some(file) => writeln!(f, "{}--> {}:{}:{}", gutter, file, self.line, self.column)?,
+            None => writeln!(f, "{}--> {}:{}", gutter, self.line, self.column)?,
+        }
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{} | {}{}", gutter, indent, "^".repeat(self.width))?;
+        if let Some(hint) = &self.hint {
+            write!(f, "\n{} = hint: {}", gutter, hint)?;
+        }
+        Ok(())
+    }
+}
+
 impl LineErr {
     pub fn on_line(self, n: usize) -> LineErr {
         LineErr {
@@ -76,12 +133,14 @@ impl fmt::Display for Error {
                 "Implementation parsing error on line: {}:{}",
                 err.line, err.err
             ),
+            Error::SDFParsingError(ref diagnostic) => write!(f, "Parsing error: {}", diagnostic),
             Error::InvalidTarget(ref msg) => write!(f, "Invalid target: {}", msg),
             Error::BackEndError(ref msg) => write!(f, "Back-end error: {}", msg),
             Error::InterfaceError(ref msg) => write!(f, "Interface error: {}", msg),
             Error::ProjectError(ref msg) => write!(f, "Project error: {}", msg),
             Error::ComposerError(ref msg) => write!(f, "Composer error: {}", msg),
             Error::LibraryError(ref msg) => write!(f, "Library error: {}", msg),
+            Error::ProtocolError(ref msg) => write!(f, "Protocol error: {}", msg),
         }
     }
 }
@@ -1,11 +1,13 @@
 //! Implementations of Chisel traits for common representation.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use crate::error::Error::BackEndError;
 use crate::generator::chisel::{Analyze, ChiselIdentifier, DeclareChisel, FieldMode};
 use crate::generator::chisel::{ChiselMode, DeclareChiselType, IsDecoupled};
-use crate::generator::common::{Component, Field, Mode, Package, Port, Record, Type};
+use crate::generator::common::{
+    Component, Field, Mode, Package, ParameterValue, Port, Record, Type,
+};
 use crate::traits::Identify;
 use crate::{cat, Document, Result};
 
@@ -31,10 +33,10 @@ impl ChiselIdentifier for ChiselMode {
 
 impl ChiselIdentifier for Type {
     fn chisel_identifier(&self) -> Result<String> {
-        // Records and arrays use type definitions.
+        // Records and unions use class definitions.
         // Any other types are used directly.
         match self {
-            Type::Record(rec) => match rec.is_decupled() {
+            Type::Record(rec) | Type::Union(rec) => match rec.is_decupled() {
                 true => Ok(format!("Decoupled(new {})", rec.chisel_identifier()?)),
                 false => Ok(format!("new {}", rec.chisel_identifier()?)),
             },
@@ -55,49 +57,27 @@ impl ChiselIdentifier for Record {
 //refactoring in common.
 impl IsDecoupled for Record {
     fn is_decupled(&self) -> bool {
-        for f in self.fields() {
-            if f.identifier() == "ready" {
-                return true;
-            }
-        }
-        return false;
+        self.fields().any(|f| f.identifier() == "ready")
     }
 }
 
 impl IsDecoupled for Type {
     fn is_decupled(&self) -> bool {
         match self {
-            Type::Record(r) => {
-                if r.is_decupled() {
-                    return true;
-                } else {
-                    return false;
-                }
-            }
-            _ => {
-                return false;
-            }
+            Type::Record(r) | Type::Union(r) => r.is_decupled(),
+            _ => false,
         }
     }
 }
 
 impl FieldMode for Field {
     fn field_mode(&self) -> Result<ChiselMode> {
-        match self.typ() {
-            Type::Record(_r) => {
-                if self.is_reversed() {
-                    return Ok(ChiselMode::Reverse);
-                } else {
-                    return Ok(ChiselMode::Forward);
-                }
-            }
-            _ => {
-                if self.is_reversed() {
-                    return Ok(ChiselMode::Out);
-                } else {
-                    return Ok(ChiselMode::In);
-                }
-            }
+        // Fields take on the direction of the port they are part of,
+        // so only reversed fields need to be flipped.
+        if self.is_reversed() {
+            Ok(ChiselMode::Forward)
+        } else {
+            Ok(ChiselMode::Reverse)
         }
     }
 }
@@ -105,7 +85,7 @@ impl FieldMode for Field {
 impl FieldMode for Port {
     fn field_mode(&self) -> Result<ChiselMode> {
         match self.typ() {
-            Type::Record(_r) => match self.mode() {
+            Type::Record(_) | Type::Union(_) => match self.mode() {
                 Mode::In => Ok(ChiselMode::Forward),
                 Mode::Out => Ok(ChiselMode::Reverse),
             },
@@ -117,34 +97,50 @@ impl FieldMode for Port {
     }
 }
 
-fn declare_rec(rec: &Record) -> Result<String> {
+/// Wrap a Chisel type in the specifier of a mode, if the mode has one.
+fn with_mode(mode: ChiselMode, typ: String) -> Result<String> {
+    let specifier = mode.chisel_identifier()?;
+    if specifier.is_empty() {
+        Ok(typ)
+    } else {
+        Ok(format!("{}({})", specifier, typ))
+    }
+}
+
+fn declare_rec(rec: &Record, is_root_type: bool) -> Result<String> {
     let mut children = String::new();
-    let mut this = format!(
-        "class {} extends Bundle {{ \n",
-        cat!(rec.chisel_identifier()?)
-    );
+    let mut this = format!("class {} extends Bundle {{\n", rec.chisel_identifier()?);
 
     for field in rec.fields() {
-        // Declare all nested record types first.
-        if let Type::Record(nested) = field.typ() {
-            children.push_str(nested.declare(false)?.clone().as_str());
-            children.push_str("\n\n");
-        };
+        // Declare all nested record types first, when declaring a root type.
+        if is_root_type {
+            for nested in field.typ().list_record_types() {
+                children.push_str(nested.declare(false)?.as_str());
+                children.push_str("\n\n");
+            }
+        }
+
+        // Valid and ready are part of Decoupled.
+        if rec.is_decupled() && (field.identifier() == "ready" || field.identifier() == "valid") {
+            continue;
+        }
 
-        if field.identifier() != "ready" && field.identifier() != "valid" {
-            // Declare this record.
-            this.push_str(
-                format!(
-                    "   val {} = {}({})\n",
-                    field.identifier(),
-                    field.field_mode()?.chisel_identifier()?,
-                    field.typ().chisel_identifier()?
-                )
-                .as_str(),
-            );
+        if let Some(doc) = field.doc() {
+            this.push_str("  //");
+            this.push_str(doc.replace("\n", "\n  //").as_str());
+            this.push('\n');
         }
+
+        this.push_str(
+            format!(
+                "  val {} = {}\n",
+                field.identifier(),
+                with_mode(field.field_mode()?, field.typ().chisel_identifier()?)?
+            )
+            .as_str(),
+        );
     }
-    this.push_str("}");
+    this.push('}');
     if !children.is_empty() {
         Ok(format!("{}{}", children, this))
     } else {
@@ -156,13 +152,34 @@ impl DeclareChiselType for Type {
     fn declare(&self, is_root_type: bool) -> Result<String> {
         match self {
             Type::Bit => Ok("Bool()".to_string()),
-            Type::BitVec { width } => {
+            Type::BitVec { width, .. } => {
                 let actual_width = if *width == 0 { 1 } else { *width };
                 Ok(format!("UInt({}.W)", actual_width))
             }
             Type::Record(rec) => rec.declare(is_root_type),
             Type::Union(rec) => rec.declare(is_root_type),
-            Type::Array(_) => todo!(),
+            Type::Array(arr) => Ok(format!(
+                "Vec({}, {})",
+                arr.width(),
+                arr.typ().chisel_identifier()?
+            )),
+        }
+    }
+}
+
+impl Analyze for Type {
+    fn list_record_types(&self) -> Vec<Type> {
+        match self {
+            Type::Record(rec) | Type::Union(rec) => {
+                let mut result: Vec<Type> = vec![self.clone()];
+                for f in rec.fields() {
+                    result.extend(f.typ().list_record_types());
+                }
+                result
+            }
+            // Arrays are declared in place, but can contain records.
+            Type::Array(arr) => arr.typ().list_record_types(),
+            _ => vec![],
         }
     }
 }
@@ -184,10 +201,9 @@ impl DeclareChisel for Port {
 
         result.push_str(
             format!(
-                "val {} = {}({})\n",
+                "val {} = {}\n",
                 self.identifier(),
-                self.field_mode()?.chisel_identifier()?,
-                self.typ().chisel_identifier()?
+                with_mode(self.field_mode()?, self.typ().chisel_identifier()?)?
             )
             .as_str(),
         );
@@ -196,91 +212,128 @@ impl DeclareChisel for Port {
     }
 }
 
-impl DeclareChisel for Component {
-    fn declare(&self) -> Result<String> {
-        let mut result = String::new();
-        if let Some(doc) = self.doc() {
-            result.push_str("//");
-            result.push_str(doc.replace("\n", "\n//").as_str());
-            result.push_str("\n");
+/// Declare a Chisel Module for a component, using the provided module body.
+///
+/// Clock and reset are implicit in Chisel, and are therefore not part of the IO bundle.
+pub(crate) fn declare_module(component: &Component, body: &str) -> Result<String> {
+    let mut result = String::new();
+    if let Some(doc) = component.doc() {
+        result.push_str("//");
+        result.push_str(doc.replace("\n", "\n//").as_str());
+        result.push('\n');
+    }
+    // Component parameters become constructor parameters with their values as defaults.
+    let params = component
+        .parameters()
+        .iter()
+        .map(|p| match &p.value {
+            ParameterValue::Natural(value) => format!("val {}: Int = {}", p.name, value),
+            ParameterValue::String(value) => format!("val {}: String = \"{}\"", p.name, value),
+        })
+        .collect::<Vec<String>>();
+    let params = if params.is_empty() {
+        String::new()
+    } else {
+        format!("({})", params.join(", "))
+    };
+    result.push_str(
+        format!(
+            "class {}{} extends Module {{\n",
+            component.identifier(),
+            params
+        )
+        .as_str(),
+    );
+    result.push_str("  val io = IO(new Bundle {\n");
+    for p in component.ports() {
+        if p.identifier() != "clk" && p.identifier() != "rst" {
+            result.push_str("    ");
+            result.push_str(p.declare()?.as_str());
         }
-        result.push_str(format!("class {} extends Module {{\n", self.identifier()).as_str());
-        if !self.ports().is_empty() {
-            let mut ports = self.ports().iter().peekable();
-            result.push_str(" val io =  IO(new Bundle{\n");
-            while let Some(p) = ports.next() {
-                if p.identifier() != "clk" && p.identifier() != "rst" {
-                    result.push_str("    ");
-                    result.push_str(p.declare()?.to_string().as_str());
-                }
+    }
+    result.push_str("  })\n\n");
+    result.push_str(body);
+    result.push('}');
+    Ok(result)
+}
 
-                /*if ports.peek().is_some() {
-                    result.push_str(";\n");
-                } else {
-                    result.push_str("\n");
-                }*/
-                //result.push_str("\n");
-            }
-            result.push_str("  })\n")
-        }
-        result.push_str("\n\n// User code\n\n");
-        result.push_str("}");
-        Ok(result)
+impl DeclareChisel for Component {
+    fn declare(&self) -> Result<String> {
+        declare_module(self, "  // User code\n  io <> DontCare\n")
     }
 }
 
 impl Analyze for Component {
     fn list_record_types(&self) -> Vec<Type> {
-        let mut result: Vec<Type> = vec![];
-        for p in self.ports().iter() {
-            if let Type::Record(_) = p.typ() {
-                result.push(p.typ())
-            }
-        }
-        result
+        self.ports()
+            .iter()
+            .flat_map(|p| p.typ().list_record_types())
+            .collect()
     }
 }
 
-impl DeclareChisel for Package {
-    fn declare(&self) -> Result<String> {
-        // Whatever generated the common representation is responsible to not to use the same
-        // identifiers for different types.
-        // Use a set to remember which type identifiers we've already used, so we don't declare
-        // them twice.
-        let mut type_ids = HashMap::<String, Type>::new();
-        let mut result = String::new();
-        result.push_str(format!("package {};\n\nimport chisel3._ \n\n", self.identifier).as_str());
-        for c in &self.components {
-            let comp_records = c.list_record_types();
-            for r in comp_records.iter() {
-                match type_ids.get(&r.chisel_identifier()?) {
-                    None => {
-                        type_ids.insert(r.chisel_identifier()?, r.clone());
-                        result.push_str(format!("{}\n\n", r.declare(true)?).as_str());
-                    }
-                    Some(already_defined_type) => {
-                        if r != already_defined_type {
-                            return Err(BackEndError(format!(
-                                "Type name conflict: {}",
-                                already_defined_type
-                                    .chisel_identifier()
-                                    .unwrap_or_else(|_| "".to_string())
-                            )));
-                        }
+/// Declare a Chisel package. Modules with an entry in `bodies` are declared with that body,
+/// other modules are declared as stubs for the user to implement. Other packages, e.g. those of
+/// instantiated modules, can be imported through `imports`.
+pub(crate) fn declare_package(
+    package: &Package,
+    bodies: &HashMap<String, String>,
+    imports: &BTreeSet<String>,
+) -> Result<String> {
+    // Whatever generated the common representation is responsible to not to use the same
+    // identifiers for different types.
+    // Use a set to remember which type identifiers we've already used, so we don't declare
+    // them twice.
+    let mut type_ids = HashMap::<String, Type>::new();
+    let mut result = String::new();
+    result.push_str(
+        format!(
+            "package {}\n\nimport chisel3._\nimport chisel3.util._\n",
+            package.identifier
+        )
+        .as_str(),
+    );
+    for import in imports {
+        result.push_str(format!("import {}._\n", import).as_str());
+    }
+    result.push('\n');
+    for c in &package.components {
+        let comp_records = c.list_record_types();
+        for r in comp_records.iter() {
+            let id = match r {
+                Type::Record(rec) | Type::Union(rec) => rec.chisel_identifier()?,
+                _ => unreachable!(),
+            };
+            match type_ids.get(&id) {
+                None => {
+                    type_ids.insert(id, r.clone());
+                    result.push_str(format!("{}\n\n", r.declare(false)?).as_str());
+                }
+                Some(already_defined_type) => {
+                    if r != already_defined_type {
+                        return Err(BackEndError(format!("Type name conflict: {}", id)));
                     }
                 }
             }
-            result.push_str(format!("{}\n\n", c.declare()?).as_str());
         }
-        Ok(result)
+        let module = match bodies.get(c.identifier()) {
+            Some(body) => declare_module(c, body)?,
+            None => c.declare()?,
+        };
+        result.push_str(format!("{}\n\n", module).as_str());
+    }
+    Ok(result)
+}
+
+impl DeclareChisel for Package {
+    fn declare(&self) -> Result<String> {
+        declare_package(self, &HashMap::new(), &BTreeSet::new())
     }
 }
 
 impl DeclareChiselType for Record {
-    fn declare(&self, _is_root_type: bool) -> Result<String> {
-        let mut result = String::new();
-        result.push_str(declare_rec(&self)?.as_str());
-        Ok(result)
+    fn declare(&self, is_root_type: bool) -> Result<String> {
+        declare_rec(self, is_root_type)
     }
 }
 
@@ -288,6 +341,7 @@ impl DeclareChiselType for Record {
 mod test {
     use crate::generator::chisel::DeclareChiselType;
     use crate::generator::common::test::*;
+    use crate::generator::common::Parameter;
 
     use super::*;
 
@@ -304,7 +358,7 @@ mod test {
         let t0 = Type::Bit;
         println!("{}", t0.declare(true).unwrap());
 
-        let t1 = Type::BitVec { width: 8 };
+        let t1 = Type::bitvec(8);
         println!("{}", t1.declare(true).unwrap());
     }
 
@@ -317,11 +371,17 @@ mod test {
         println!("{}", t1.declare(true).unwrap());
     }
 
+    #[test]
+    fn array_type_decl() {
+        let t = Type::array("arr", Type::bitvec(8), 4);
+        assert_eq!(t.declare(true).unwrap(), "Vec(4, UInt(8.W))");
+    }
+
     #[test]
     fn port_decl() {
-        let p0 = Port::new("test", Mode::In, Type::BitVec { width: 10 });
+        let p0 = Port::new("test", Mode::In, Type::bitvec(10));
         println!("{}", p0.declare().unwrap());
-        let p1 = Port::new("test", Mode::Out, Type::BitVec { width: 10 });
+        let p1 = Port::new("test", Mode::Out, Type::bitvec(10));
         println!("{}", p1.declare().unwrap());
     }
 
@@ -332,6 +392,29 @@ mod test {
         println!("{}", c.declare().unwrap());
     }
 
+    #[test]
+    fn comp_decl_params() -> Result<()> {
+        let c = Component::new(
+            "test_comp",
+            vec![
+                Parameter {
+                    name: "W".to_string(),
+                    value: ParameterValue::Natural(8),
+                },
+                Parameter {
+                    name: "re".to_string(),
+                    value: ParameterValue::String("a*".to_string()),
+                },
+            ],
+            vec![],
+            None,
+        );
+        assert!(c.declare()?.starts_with(
+            "class test_comp(val W: Int = 8, val re: String = \"a*\") extends Module {\n"
+        ));
+        Ok(())
+    }
+
     #[test]
     fn package_decl() {
         let p = Package {
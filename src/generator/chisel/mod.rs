@@ -1,5 +1,6 @@
 //! Chisel back-end.
 
+use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
 use std::str::FromStr;
 
@@ -7,14 +8,18 @@ use log::debug;
 #[cfg(feature = "cli")]
 use structopt::StructOpt;
 
+use crate::cat;
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::Implementation;
 use crate::design::Project;
-use crate::generator::common::convert::Packify;
+use crate::generator::common::convert::{Packify, CANON_SUFFIX};
 use crate::generator::common::*;
 use crate::generator::GenerateProject;
 use crate::traits::Identify;
 use crate::{Error, Result};
 
 mod impls;
+mod structural;
 
 /// Modes for Chisel elements.
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -170,13 +175,36 @@ impl GenerateProject for ChiselBackEnd {
                 None => "scala".to_string(),
                 Some(s) => format!("{}.scala", s),
             });
+            let abstraction = self.config().abstraction();
+            let pak = match abstraction {
+                AbstractionLevel::Canonical => lib.canonical(),
+                AbstractionLevel::Fancy => lib.fancy(),
+            };
+            // Structural implementations replace the stub of the module that is implemented.
+            let mut bodies = HashMap::new();
+            let mut imports = BTreeSet::new();
+            for streamlet in lib.streamlets() {
+                if let Some(Implementation::Structural(graph)) =
+                    streamlet.get_implementation().as_deref()
+                {
+                    let (body, libs) = structural::declare_structural(project, graph, abstraction)?;
+                    let module = match abstraction {
+                        AbstractionLevel::Canonical => {
+                            cat!(streamlet.identifier(), CANON_SUFFIX.unwrap())
+                        }
+                        AbstractionLevel::Fancy => streamlet.identifier().to_string(),
+                    };
+                    bodies.insert(module, body);
+                    imports.extend(
+                        libs.into_iter()
+                            .filter(|l| l != lib.key())
+                            .map(|l| l.to_string()),
+                    );
+                }
+            }
             std::fs::write(
                 pkg.as_path(),
-                match self.config().abstraction() {
-                    AbstractionLevel::Canonical => lib.canonical(),
-                    AbstractionLevel::Fancy => lib.fancy(),
-                }
-                .declare()?,
+                impls::declare_package(&pak, &bodies, &imports)?,
             )?;
             debug!("Wrote {}.", pkg.as_path().to_str().unwrap_or(""));
         }
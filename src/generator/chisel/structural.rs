@@ -0,0 +1,157 @@
+//! Chisel module bodies for structural implementations.
+
+use std::collections::BTreeSet;
+use std::ops::Deref;
+
+use log::warn;
+
+use crate::cat;
+use crate::design::implementation::composer::impl_graph::ImplementationGraph;
+use crate::design::param::ParameterVariant;
+use crate::design::{Interface, LibKey, NodeKey, Project, THIS_KEY};
+use crate::generator::chisel::AbstractionLevel;
+use crate::generator::common::convert::{Portify, CANON_SUFFIX};
+use crate::stdlib::utils::structural::component_library;
+use crate::traits::Identify;
+use crate::Result;
+
+/// Returns the port names of an interface relative to the interface, for an abstraction level.
+fn port_suffixes(iface: &Interface, abstraction: AbstractionLevel) -> Vec<String> {
+    let ports = match abstraction {
+        AbstractionLevel::Canonical => iface.canonical(""),
+        AbstractionLevel::Fancy => iface.fancy("", ""),
+    };
+    ports.iter().map(|p| p.identifier().to_string()).collect()
+}
+
+/// Returns a reference to a port of a node, "this" refers to the IO of the module itself.
+fn port_ref(node: &NodeKey, iface: impl Into<String>, suffix: &str) -> String {
+    match node.deref() {
+        THIS_KEY => format!("io.{}", cat!(iface.into(), suffix)),
+        _ => format!("{}.io.{}", node, cat!(iface.into(), suffix)),
+    }
+}
+
+/// Declare the body of a Chisel module implementing the streamlet of an implementation graph.
+///
+/// Every node (other than "this") is instantiated as a child module, with the parameters it binds
+/// as arguments, and every edge is connected using bulk connections. Interfaces which are not
+/// connected are connected to DontCare. Returns the body and the libraries of all instantiated
+/// modules.
+pub(crate) fn declare_structural(
+    project: &Project,
+    graph: &ImplementationGraph,
+    abstraction: AbstractionLevel,
+) -> Result<(String, BTreeSet<LibKey>)> {
+    let mut libs = BTreeSet::new();
+    let mut result = String::from("  // Structural implementation\n");
+
+    let mut nodes = graph.nodes().collect::<Vec<_>>();
+    nodes.sort_by_key(|node| node.key());
+
+    for node in nodes.iter().filter(|node| node.key().deref() != THIS_KEY) {
+        let component = node.component();
+        libs.insert(component_library(project, &component.key())?);
+        let module = match abstraction {
+            AbstractionLevel::Canonical => cat!(component.key(), CANON_SUFFIX.unwrap()),
+            AbstractionLevel::Fancy => component.key().to_string(),
+        };
+        // Bound parameters are passed on to the constructor of the module, types are reflected
+        // in the types of its IO.
+        let arguments = node
+            .parameters()
+            .iter()
+            .filter_map(|(key, value)| match value {
+                ParameterVariant::UInt(value) => Some(format!("{} = {}", key, value)),
+                ParameterVariant::String(value) => Some(format!("{} = \"{}\"", key, value)),
+                ParameterVariant::Type(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let module = if arguments.is_empty() {
+            module
+        } else {
+            format!("{}({})", module, arguments.join(", "))
+        };
+        result.push_str(format!("  val {} = Module(new {})\n", node.key(), module).as_str());
+    }
+    result.push('\n');
+
+    for edge in graph.edges() {
+        let (source, sink) = (edge.source(), edge.sink());
+        let iface = graph.get_node(source.node())?.iface(source.iface())?;
+        for suffix in port_suffixes(&iface, abstraction) {
+            result.push_str(
+                format!(
+                    "  {} <> {}\n",
+                    port_ref(&sink.node(), sink.iface().to_string(), &suffix),
+                    port_ref(&source.node(), source.iface().to_string(), &suffix)
+                )
+                .as_str(),
+            );
+        }
+    }
+
+    for node in nodes {
+        for iface in node.component().interfaces() {
+            let handle = node.io(iface.identifier())?;
+            if graph.get_edge(handle).is_ok() {
+                continue;
+            }
+            warn!(
+                "Interface {} of node {} in the implementation of {} is not connected.",
+                iface.identifier(),
+                node.key(),
+                graph.streamlet_key()
+            );
+            for suffix in port_suffixes(&iface, abstraction) {
+                result.push_str(
+                    format!(
+                        "  {} <> DontCare\n",
+                        port_ref(&node.key(), iface.identifier(), &suffix)
+                    )
+                    .as_str(),
+                );
+            }
+        }
+    }
+
+    Ok((result, libs))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::design::implementation::composer::GenericComponent;
+    use crate::design::implementation::Implementation;
+    use crate::stdlib::utils::structural::tests::structural_test_proj;
+    use crate::Name;
+
+    use super::*;
+
+    #[test]
+    fn structural_module() -> Result<()> {
+        let prj = structural_test_proj()?;
+        let lib = prj.get_lib(Name::try_from("test")?)?;
+        let top = lib.get_streamlet(Name::try_from("top")?)?;
+        let (body, libs) = match top.get_implementation().as_deref() {
+            Some(Implementation::Structural(graph)) => {
+                declare_structural(&prj, graph, AbstractionLevel::Fancy)?
+            }
+            _ => panic!("Expected a structural implementation"),
+        };
+        assert_eq!(
+            body,
+            "  // Structural implementation
+  val first = Module(new pass)
+  val second = Module(new pass)
+
+  first.io.i <> io.a
+  second.io.i <> first.io.o
+  io.b <> second.io.o
+"
+        );
+        assert!(libs.contains(&Name::try_from("test")?));
+        Ok(())
+    }
+}
@@ -6,13 +6,17 @@ use std::borrow::Borrow;
 use std::cell::Ref;
 
 use crate::design::implementation::composer::GenericComponent;
-use crate::design::{Interface, Streamlet};
+use crate::design::param::ParameterVariant;
+use crate::design::{Interface, Library, Streamlet};
 pub use crate::error::{Error, Result};
-use crate::generator::common::{Component, Mode, Package, Port, Project, Record, Type};
+use crate::generator::common::{
+    Array, Component, Field, GenericWidth, Mode, Package, Parameter, ParameterValue, Port, Project,
+    Record, Type,
+};
 use crate::logical::{Group, LogicalType, Stream, Union};
 use crate::physical::{Origin, Signal, Width};
 use crate::traits::Identify;
-use crate::{cat, Document, NonZeroReal};
+use crate::{cat, Document, Name, NonNegative, NonZeroReal, PathName};
 
 // Generator-global constants:
 
@@ -28,6 +32,11 @@ pub trait Typify {
     fn fancy(&self, _prefix: impl Into<String>) -> Option<Type> {
         None
     }
+    /// Like [Typify::fancy], but records of types that have a name in [names] are named after
+    /// the type, rather than after the prefix.
+    fn fancy_named(&self, prefix: impl Into<String>, _names: &TypeNames) -> Option<Type> {
+        self.fancy(prefix)
+    }
 }
 
 /// Trait to create common representation ports from things in the canonical
@@ -41,6 +50,15 @@ pub trait Portify {
     ) -> Vec<Port> {
         Vec::new()
     }
+    /// Like [Portify::fancy], using [TypeNames] to name the records of named types.
+    fn fancy_named(
+        &self,
+        port_name: impl Into<String>,
+        port_type_prefix: impl Into<String>,
+        _names: &TypeNames,
+    ) -> Vec<Port> {
+        self.fancy(port_name, port_type_prefix)
+    }
 }
 
 /// Trait to create common representation components from things in the canonical
@@ -50,6 +68,11 @@ pub trait Componentify {
     fn fancy(&self, _suffix: Option<&str>) -> Option<Component> {
         None
     }
+    /// Like [Componentify::fancy], naming the records of the named types that are declared in
+    /// or imported by [library], if any, after those types.
+    fn fancy_named(&self, suffix: Option<&str>, _library: Option<&Library>) -> Option<Component> {
+        self.fancy(suffix)
+    }
 }
 
 /// Trait to create common representation of a package.
@@ -64,6 +87,68 @@ pub trait Projectify {
     fn fancy(&self) -> Project;
 }
 
+/// The record names of the named types used in the type of an interface, by the path at which
+/// they are used. Records of types imported from other libraries are prefixed with the name of
+/// the library that declares them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TypeNames(Vec<(PathName, LogicalType, String)>);
+
+impl TypeNames {
+    /// Returns the record names for the type of [interface], as used by a streamlet of [library].
+    pub fn new(interface: &Interface, library: &Library) -> Self {
+        TypeNames(
+            interface
+                .type_names()
+                .iter()
+                .filter_map(|name| {
+                    let (typ, record) = if name.lib() == library.key() {
+                        let typ = library.types().find(|t| t.key() == name.key())?;
+                        (typ, name.key().to_string())
+                    } else {
+                        let (_, typ) = library
+                            .imports()
+                            .find(|(lib, t)| *lib == name.lib() && t.key() == name.key())?;
+                        (typ, cat!(name.lib().to_string(), name.key()))
+                    };
+                    Some((name.path().clone(), typ.logical_type().clone(), record))
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the record name for [typ], if it is the named type used at the root of these
+    /// names. Only groups, unions and streams result in records, so other types are never
+    /// named. Types that differ from the named type, e.g. streams that were split, are not
+    /// named either.
+    pub fn get(&self, typ: &LogicalType) -> Option<&str> {
+        match typ {
+            LogicalType::Group(_) | LogicalType::Union(_) | LogicalType::Stream(_) => self
+                .0
+                .iter()
+                .find(|(path, t, _)| path.is_empty() && t == typ)
+                .map(|(_, _, name)| name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the names used within [path], relative to [path].
+    pub fn within(&self, path: &PathName) -> Self {
+        TypeNames(
+            self.0
+                .iter()
+                .filter(|(p, _, _)| p.as_ref().starts_with(path.as_ref()))
+                .map(|(p, t, name)| {
+                    (
+                        p.as_ref()[path.len()..].iter().cloned().collect(),
+                        t.clone(),
+                        name.clone(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
 pub trait Multilane {
     fn with_throughput(
         &self,
@@ -86,15 +171,29 @@ impl Typify for LogicalType {
     }
 
     fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+        self.fancy_named(prefix, &TypeNames::default())
+    }
+
+    fn fancy_named(&self, prefix: impl Into<String>, names: &TypeNames) -> Option<Type> {
         // This implementation for LogicalType assumes the LogicalType has already been
         // flattened through synthesize.
-        match self {
+        let (prefix, named) = match names.get(self) {
+            Some(name) => (name.to_string(), true),
+            None => (prefix.into(), false),
+        };
+        let result = match self {
             LogicalType::Null => None,
             LogicalType::Bits(width) => Some(Type::bitvec(width.get())),
-            LogicalType::Group(group) => group.fancy(prefix),
-            LogicalType::Stream(stream) => stream.fancy(prefix),
-            LogicalType::Union(union) => union.fancy(prefix),
-        }
+            LogicalType::Group(group) => group.fancy_named(prefix, names),
+            LogicalType::Stream(stream) => stream.fancy_named(prefix, names),
+            LogicalType::Union(union) => union.fancy_named(prefix, names),
+        };
+        // Mark the records of named types, such that they keep their name when split.
+        result.map(|typ| match typ {
+            Type::Record(rec) => Type::Record(rec.with_named(named)),
+            Type::Union(rec) => Type::Union(rec.with_named(named)),
+            typ => typ,
+        })
     }
 }
 
@@ -110,10 +209,17 @@ impl Typify for Group {
     }
 
     fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+        self.fancy_named(prefix, &TypeNames::default())
+    }
+
+    fn fancy_named(&self, prefix: impl Into<String>, names: &TypeNames) -> Option<Type> {
         let n: String = prefix.into();
         let mut rec = Record::new_empty(n.clone());
         for (field_name, field_logical) in self.iter() {
-            if let Some(field_common_type) = field_logical.fancy(cat!(n.clone(), field_name)) {
+            if let Some(field_common_type) = field_logical.fancy_named(
+                cat!(n.clone(), field_name),
+                &names.within(&PathName::from(field_name.clone())),
+            ) {
                 rec.insert_new_field(field_name.to_string(), field_common_type, false, None)
             }
         }
@@ -136,6 +242,10 @@ impl Typify for Union {
     }
 
     fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+        self.fancy_named(prefix, &TypeNames::default())
+    }
+
+    fn fancy_named(&self, prefix: impl Into<String>, names: &TypeNames) -> Option<Type> {
         let n: String = prefix.into();
         let mut rec = Record::new_empty(n.clone());
         if let Some((tag_name, tag_bc)) = self.tag() {
@@ -151,7 +261,10 @@ impl Typify for Union {
             );
         }
         for (field_name, field_logical) in self.iter() {
-            if let Some(field_common_type) = field_logical.fancy(cat!(n.clone(), field_name)) {
+            if let Some(field_common_type) = field_logical.fancy_named(
+                cat!(n.clone(), field_name),
+                &names.within(&PathName::from(field_name.clone())),
+            ) {
                 rec.insert_new_field(field_name, field_common_type, false, None);
             }
         }
@@ -181,6 +294,10 @@ impl Typify for Stream {
     }
 
     fn fancy(&self, prefix: impl Into<String>) -> Option<Type> {
+        self.fancy_named(prefix, &TypeNames::default())
+    }
+
+    fn fancy_named(&self, prefix: impl Into<String>, names: &TypeNames) -> Option<Type> {
         // This implementation for Stream assumes the parent LogicalType has already been
         // flattened through synthesize.
         let pre: String = prefix.into();
@@ -210,7 +327,7 @@ impl Typify for Stream {
             });
 
             let prefix = cat!(pre, name, "data");
-            let data = self.data().fancy(&prefix).unwrap();
+            let data = self.data().fancy_named(&prefix, names).unwrap();
             // Insert data record. There must be something there since it is not null.
             // TODO: The fancy version doesn't account for throughput.
             rec.insert_new_field(
@@ -306,6 +423,15 @@ impl Portify for Interface {
     }
 
     fn fancy(&self, name: impl Into<String>, type_name: impl Into<String>) -> Vec<Port> {
+        self.fancy_named(name, type_name, &TypeNames::default())
+    }
+
+    fn fancy_named(
+        &self,
+        name: impl Into<String>,
+        type_name: impl Into<String>,
+        names: &TypeNames,
+    ) -> Vec<Port> {
         let n: String = name.into();
         let tn: String = type_name.into();
 
@@ -313,13 +439,15 @@ impl Portify for Interface {
 
         let split = self.typ().split_streams();
 
-        if let Some(sig_type) = split.signal().fancy(tn.clone()) {
+        if let Some(sig_type) = split.signal().fancy_named(tn.clone(), names) {
             result.push(Port::new(cat!(n), self.mode().into(), sig_type));
         }
 
         // Split the LogicalType up into discrete, simple streams.
         for (path, simple_stream) in self.typ().split_streams().streams() {
-            if let Some(typ) = simple_stream.fancy(cat!(tn.clone(), path)) {
+            if let Some(typ) =
+                simple_stream.fancy_named(cat!(tn.clone(), path), &names.within(path))
+            {
                 result.push(Port::new(cat!(n, path), self.mode().into(), typ));
             }
         }
@@ -337,42 +465,278 @@ impl From<crate::design::Mode> for Mode {
     }
 }
 
+/// Returns the parameters of a streamlet that are passed on to its component. Parameters of
+/// kind Type are omitted, as they are reflected in the types of the ports.
+fn streamlet_parameters(streamlet: &Streamlet) -> Vec<Parameter> {
+    streamlet
+        .parameters()
+        .map(|params| {
+            params
+                .params()
+                .filter_map(|param| {
+                    let value = match param.item() {
+                        ParameterVariant::UInt(value) => ParameterValue::Natural(*value),
+                        ParameterVariant::String(value) => ParameterValue::String(value.clone()),
+                        ParameterVariant::Type(_) => return None,
+                    };
+                    Some(Parameter {
+                        name: param.identifier().to_string(),
+                        value,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the record of [other] if it has the same structure as record or union [typ].
+fn same_record<'a>(typ: &Type, other: &'a Type) -> Option<&'a Record> {
+    match (typ, other) {
+        (Type::Record(a), Type::Record(b)) | (Type::Union(a), Type::Union(b))
+            if a.identifier == b.identifier
+                && a.fields.len() == b.fields.len()
+                && a.fields
+                    .iter()
+                    .zip(b.fields.iter())
+                    .all(|(x, y)| x.identifier() == y.identifier() && x.reversed == y.reversed) =>
+        {
+            Some(b)
+        }
+        _ => None,
+    }
+}
+
+/// Returns [typ] with the widths of its bit vectors expressed in the natural parameters in
+/// [naturals], which are passed on as generics. [samples] are the same type with each parameter
+/// in turn incremented by one, and [check] is the same type with all parameters incremented by
+/// two, which has to agree with the fitted widths. Returns `None` if the structure of the type
+/// depends on the parameters, or if a width is not affine in the parameters.
+fn fit_type(
+    typ: &Type,
+    samples: &[&Type],
+    check: &Type,
+    naturals: &[(String, NonNegative)],
+) -> Option<Type> {
+    match typ {
+        Type::Bit => samples
+            .iter()
+            .chain(Some(&check))
+            .all(|t| **t == Type::Bit)
+            .then_some(Type::Bit),
+        Type::BitVec { width, .. } => {
+            let width_of = |t: &Type| match t {
+                Type::BitVec { width, .. } => Some(*width),
+                _ => None,
+            };
+            let coefficients = samples
+                .iter()
+                .map(|t| width_of(t)?.checked_sub(*width))
+                .collect::<Option<Vec<NonNegative>>>()?;
+            let constant = coefficients
+                .iter()
+                .zip(naturals.iter())
+                .fold(*width as i64, |acc, (c, (_, value))| {
+                    acc - *c as i64 * *value as i64
+                });
+            let generic = GenericWidth::new(
+                constant,
+                coefficients
+                    .into_iter()
+                    .zip(naturals.iter().map(|(name, _)| name.clone()))
+                    .collect(),
+            );
+            let incremented = naturals
+                .iter()
+                .map(|(name, value)| (name.clone(), value + 2))
+                .collect::<Vec<_>>();
+            if generic.evaluate(&incremented) != Some(width_of(check)?) {
+                return None;
+            }
+            Some(Type::BitVec {
+                width: *width,
+                generic: if generic.is_constant() {
+                    None
+                } else {
+                    Some(generic)
+                },
+            })
+        }
+        Type::Record(rec) | Type::Union(rec) => {
+            let others = samples
+                .iter()
+                .map(|t| same_record(typ, t))
+                .collect::<Option<Vec<&Record>>>()?;
+            let check = same_record(typ, check)?;
+            let fields = rec
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    Some(Field {
+                        typ: fit_type(
+                            &field.typ,
+                            &others.iter().map(|r| &r.fields[i].typ).collect::<Vec<_>>(),
+                            &check.fields[i].typ,
+                            naturals,
+                        )?,
+                        ..field.clone()
+                    })
+                })
+                .collect::<Option<Vec<Field>>>()?;
+            let rec = Record {
+                fields,
+                ..rec.clone()
+            };
+            Some(match typ {
+                Type::Record(_) => Type::Record(rec),
+                _ => Type::Union(rec),
+            })
+        }
+        Type::Array(arr) => {
+            let element = |t: &'_ Type| match t {
+                Type::Array(other)
+                    if other.identifier == arr.identifier && other.width == arr.width =>
+                {
+                    Some(other.typ().clone())
+                }
+                _ => None,
+            };
+            let others = samples
+                .iter()
+                .map(|t| element(t))
+                .collect::<Option<Vec<Type>>>()?;
+            let typ = fit_type(
+                arr.typ(),
+                &others.iter().collect::<Vec<_>>(),
+                &element(check)?,
+                naturals,
+            )?;
+            Some(Type::Array(Array {
+                typ: Box::new(typ),
+                ..arr.clone()
+            }))
+        }
+    }
+}
+
+/// Returns the ports returned by [ports] for [streamlet], with the widths of their bit vectors
+/// expressed in the natural parameters of the streamlet, which are passed on as generics of its
+/// components. The widths are derived from the ports of instances of the streamlet with other
+/// values for the parameters. If the ports can not be expressed in the generics, e.g. because
+/// parameters change their structure, the ports for the current values are returned.
+fn generic_ports(streamlet: &Streamlet, ports: impl Fn(&Streamlet) -> Vec<Port>) -> Vec<Port> {
+    let base = ports(streamlet);
+    let naturals = streamlet
+        .parameters()
+        .map(|params| {
+            params
+                .params()
+                .filter_map(|param| match param.item() {
+                    ParameterVariant::UInt(value) => Some((param.key().to_string(), *value)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if naturals.is_empty() {
+        return base;
+    }
+    let instance_ports = |offset: &dyn Fn(usize) -> NonNegative| -> Option<Vec<Port>> {
+        let assignments = naturals
+            .iter()
+            .enumerate()
+            .map(|(i, (name, value))| {
+                Some((
+                    Name::try_new(name).ok()?,
+                    ParameterVariant::UInt(value.checked_add(offset(i))?),
+                ))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(ports(
+            &streamlet.instantiate(streamlet.key(), assignments).ok()?,
+        ))
+    };
+    let fit = || -> Option<Vec<Port>> {
+        let samples = (0..naturals.len())
+            .map(|i| instance_ports(&|j| if i == j { 1 } else { 0 }))
+            .collect::<Option<Vec<_>>>()?;
+        let check = instance_ports(&|_| 2)?;
+        if samples
+            .iter()
+            .chain(Some(&check))
+            .any(|sample| sample.len() != base.len())
+        {
+            return None;
+        }
+        base.iter()
+            .enumerate()
+            .map(|(i, port)| {
+                let same =
+                    |other: &Port| other.identifier == port.identifier && other.mode == port.mode;
+                if !samples.iter().all(|sample| same(&sample[i])) || !same(&check[i]) {
+                    return None;
+                }
+                Some(Port {
+                    typ: fit_type(
+                        &port.typ,
+                        &samples.iter().map(|s| &s[i].typ).collect::<Vec<_>>(),
+                        &check[i].typ,
+                        &naturals,
+                    )?,
+                    ..port.clone()
+                })
+            })
+            .collect()
+    };
+    fit().unwrap_or(base)
+}
+
 impl Componentify for Streamlet {
     fn canonical(&self, suffix: Option<&str>) -> Component {
         Component::new(
             cat!(self.identifier().to_string(), suffix.unwrap_or("")),
-            vec![],
-            {
+            streamlet_parameters(self),
+            generic_ports(self, |streamlet| {
                 // Always add clock and reset for now.
                 // TODO(johanpel): at some point we need to associate interfaces with clock domains.
                 let mut all_ports = vec![
                     Port::new_documented("clk", Mode::In, Type::Bit, None),
                     Port::new_documented("rst", Mode::In, Type::Bit, None),
                 ];
-                self.inputs().for_each(|interface| {
+                streamlet.inputs().for_each(|interface| {
                     all_ports.extend(interface.borrow().canonical(interface.identifier()));
                 });
-                self.outputs().for_each(|interface| {
+                streamlet.outputs().for_each(|interface| {
                     all_ports.extend(interface.borrow().canonical(interface.identifier()));
                 });
                 all_ports
-            },
+            }),
             self.doc(),
         )
     }
 
     fn fancy(&self, suffix: Option<&str>) -> Option<Component> {
+        self.fancy_named(suffix, None)
+    }
+
+    fn fancy_named(&self, suffix: Option<&str>, library: Option<&Library>) -> Option<Component> {
         Some(Component::new(
             cat!(self.identifier().to_string(), suffix.unwrap_or("")),
-            vec![],
-            {
+            streamlet_parameters(self),
+            generic_ports(self, |streamlet| {
                 let collect_ports =
                     |interfaces: Box<(dyn Iterator<Item = Ref<Interface>>)>| -> Vec<Port> {
                         interfaces
                             .flat_map(|interface| {
-                                interface.borrow().fancy(
+                                interface.borrow().fancy_named(
                                     interface.identifier(),
-                                    cat!(self.identifier().to_string(), interface.identifier()),
+                                    cat!(
+                                        streamlet.identifier().to_string(),
+                                        interface.identifier()
+                                    ),
+                                    &library
+                                        .map(|library| TypeNames::new(&interface, library))
+                                        .unwrap_or_default(),
                                 )
                             })
                             .collect::<Vec<Port>>()
@@ -382,10 +746,10 @@ impl Componentify for Streamlet {
                     Port::new_documented("clk", Mode::In, Type::Bit, None),
                     Port::new_documented("rst", Mode::In, Type::Bit, None),
                 ];
-                all_ports.extend(collect_ports(self.inputs()));
-                all_ports.extend(collect_ports(self.outputs()));
+                all_ports.extend(collect_ports(streamlet.inputs()));
+                all_ports.extend(collect_ports(streamlet.outputs()));
                 all_ports
-            },
+            }),
             self.doc(),
         ))
     }
@@ -411,7 +775,7 @@ impl Packify for crate::design::Library {
                 .into_iter()
                 .flat_map(|s| {
                     let mut result = vec![s.canonical(CANON_SUFFIX)];
-                    if let Some(user) = s.fancy(None) {
+                    if let Some(user) = s.fancy_named(None, Some(self)) {
                         result.push(user);
                     }
                     result
@@ -452,10 +816,8 @@ impl Multilane for Type {
         let element_lanes = throughput.0.ceil() as u32;
         if element_lanes > 1 {
             match self {
-                Type::Bit => Ok(Type::BitVec {
-                    width: element_lanes,
-                }),
-                Type::BitVec { width: _ } | Type::Record(_) | Type::Union(_) | Type::Array(_) => {
+                Type::Bit => Ok(Type::bitvec(element_lanes)),
+                Type::BitVec { .. } | Type::Record(_) | Type::Union(_) | Type::Array(_) => {
                     Ok(Type::array(
                         format!("{}_array", identity.into()),
                         self.clone(),
@@ -689,6 +1051,48 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    fn generic_widths() -> Result<()> {
+        let (_, streamlet) = crate::parser::nom::streamlet(
+            "Streamlet x <W: UInt = 8, D: UInt = 1, T: Type = Bits<2>> (
+                a: in Stream<Group<p: Bits<W>, q: Bits<4>>, t=2, d=D>,
+                b: out Stream<T>
+            )",
+        )
+        .unwrap();
+        let declared = |component: Component| -> Result<String> {
+            component
+                .ports()
+                .iter()
+                .map(|port| Ok(port.declare()? + "\n"))
+                .collect()
+        };
+        let canonical = declared(streamlet.canonical(None))?;
+        assert!(canonical.contains("a_data : in std_logic_vector(2*W+7 downto 0)\n"));
+        assert!(canonical.contains("a_last : in std_logic_vector(D-1 downto 0)\n"));
+        assert!(canonical.contains("b_data : out std_logic_vector(1 downto 0)\n"));
+        let fancy = declared(streamlet.fancy(None).unwrap())?;
+        assert!(
+            fancy.contains("a : in x_a_type(data(open)(p(W-1 downto 0)), last(D-1 downto 0))\n")
+        );
+        assert_eq!(
+            streamlet
+                .canonical(None)
+                .bound_ports(&[("W".to_string(), 16)])[4]
+                .typ(),
+            Type::bitvec(40)
+        );
+
+        // Widths which are not affine in the parameters remain fixed.
+        let (_, streamlet) = crate::parser::nom::streamlet(
+            "Streamlet x <W: UInt = 11> (a: in Stream<Union<p: Bits<W>, q: Bits<12>>>)",
+        )
+        .unwrap();
+        let canonical = declared(streamlet.canonical(None))?;
+        assert!(canonical.contains("a_data : in std_logic_vector(12 downto 0)\n"));
+        Ok(())
+    }
+
     #[test]
     pub(crate) fn nested_streams_streamlet() -> Result<()> {
         let streamlet = Streamlet::from_builder(
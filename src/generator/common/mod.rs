@@ -23,8 +23,8 @@ pub mod convert;
 /// let port = Port::new("example",
 ///     Mode::In,
 ///     Type::record("rec", vec![              // Shortcut to Type::Record(Record::new(...
-///         Field::new("a", Type::Bit, false), // This field will have a port Mode::In
-///         Field::new("b", Type::Bit, true)   // This field will have a port Mode::Out
+///         Field::new("a", Type::Bit, false, None), // This field will have a port Mode::In
+///         Field::new("b", Type::Bit, true, None)   // This field will have a port Mode::Out
 ///     ])
 /// );
 /// ```
@@ -99,6 +99,9 @@ pub struct Record {
     identifier: String,
     /// The fields of the record.
     fields: Vec<Field>,
+    /// Whether this record represents a type with a user-declared name, in which case its
+    /// identifier is kept when the record is split, if it does not need to be split itself.
+    named: bool,
 }
 
 impl Identify for Record {
@@ -114,6 +117,7 @@ impl Record {
         Record {
             identifier: name.into(),
             fields,
+            named: false,
         }
     }
 
@@ -122,6 +126,7 @@ impl Record {
         Record {
             identifier: name.into(),
             fields: vec![],
+            named: false,
         }
     }
 
@@ -133,6 +138,7 @@ impl Record {
                 Field::new("valid", Type::Bit, false, None),
                 Field::new("ready", Type::Bit, true, None),
             ],
+            named: false,
         }
     }
 
@@ -147,6 +153,17 @@ impl Record {
         self.fields.push(Field::new(name, typ, reversed, doc));
     }
 
+    /// Returns this record, marked as representing a type with a user-declared name or not.
+    pub fn with_named(mut self, named: bool) -> Self {
+        self.named = named;
+        self
+    }
+
+    /// Returns true if this record represents a type with a user-declared name.
+    pub fn is_named(&self) -> bool {
+        self.named
+    }
+
     /// Add a field to the record.
     pub fn insert(&mut self, field: Field) {
         self.fields.push(field);
@@ -179,7 +196,7 @@ impl Record {
     /// Append a string to the name of this record, and any nested records or unions.
     pub fn append_name_nested(&self, with: impl Into<String>) -> Self {
         let p: String = with.into();
-        let mut result = Record::new_empty(cat!(self.identifier, p));
+        let mut result = Record::new_empty(cat!(self.identifier, p)).with_named(self.named);
         for f in self.fields() {
             result.insert(match f.typ() {
                 Type::Record(r) => Field::new(
@@ -205,6 +222,26 @@ impl Record {
         }
         result
     }
+
+    /// Append a string to the name of this record, and any nested records, arrays or unions,
+    /// before splitting the record in a downstream and upstream part. Nested records of named
+    /// types without reversed fields end up in one of the parts as they are, and keep their
+    /// names, such that a named type is declared only once.
+    pub fn append_name_split(&self, with: impl Into<String>) -> Self {
+        let p: String = with.into();
+        Record {
+            identifier: cat!(self.identifier, p),
+            named: self.named,
+            fields: self
+                .fields
+                .iter()
+                .map(|f| Field {
+                    typ: f.typ.append_name_split(p.clone()),
+                    ..f.clone()
+                })
+                .collect(),
+        }
+    }
 }
 
 /// Inner struct for `Type::Array`
@@ -237,7 +274,7 @@ impl Array {
         let p: String = with.into();
         let new_name = cat!(self.identifier(), p);
         match self.typ() {
-            Type::Bit | Type::BitVec { width: _ } => {
+            Type::Bit | Type::BitVec { .. } => {
                 Array::new(new_name, self.typ().clone(), self.width())
             }
             Type::Record(rec) => Array::new(
@@ -257,6 +294,16 @@ impl Array {
             ),
         }
     }
+    /// Append a string to the name of this array, and to its elements, see
+    /// [Record::append_name_split].
+    pub fn append_name_split(&self, with: impl Into<String>) -> Self {
+        let p: String = with.into();
+        Array {
+            identifier: cat!(self.identifier, p),
+            typ: Box::new(self.typ.append_name_split(p)),
+            width: self.width,
+        }
+    }
 }
 
 impl Identify for Array {
@@ -274,6 +321,9 @@ pub enum Type {
     BitVec {
         /// The width of the vector.
         width: NonNegative,
+        /// The width of the vector in terms of the generics of its component, if it depends on
+        /// them. [width] is the width for the default values of the generics.
+        generic: Option<GenericWidth>,
     },
     /// A record.
     Record(Record),
@@ -286,13 +336,117 @@ pub enum Type {
     Array(Array),
 }
 
+/// The width of a bit vector as an affine function of the natural generics of its component:
+/// [constant] plus the sum of each coefficient times the value of its generic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericWidth {
+    constant: i64,
+    terms: Vec<(NonNegative, String)>,
+}
+
+impl GenericWidth {
+    /// Construct a generic width. Terms with a coefficient of zero are dropped.
+    pub fn new(constant: i64, terms: Vec<(NonNegative, String)>) -> GenericWidth {
+        GenericWidth {
+            constant,
+            terms: terms.into_iter().filter(|(c, _)| *c != 0).collect(),
+        }
+    }
+
+    /// Returns true if the width does not depend on any generic.
+    pub fn is_constant(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Evaluate the width for the values of the generics in [values], or `None` if the width
+    /// is negative. Values for generics that are not part of the width are ignored, and generics
+    /// of the width without a value are taken to be zero.
+    pub fn evaluate(&self, values: &[(String, NonNegative)]) -> Option<NonNegative> {
+        let width = self.terms.iter().fold(self.constant, |acc, (c, generic)| {
+            acc + *c as i64
+                * values
+                    .iter()
+                    .find(|(name, _)| name == generic)
+                    .map(|(_, value)| *value as i64)
+                    .unwrap_or(0)
+        });
+        std::convert::TryFrom::try_from(width).ok()
+    }
+
+    /// Returns the expression for the width plus [offset], e.g. `W-1` for the index of the most
+    /// significant bit.
+    pub fn expression(&self, offset: i64) -> String {
+        let mut result = self
+            .terms
+            .iter()
+            .map(|(c, generic)| match c {
+                1 => generic.clone(),
+                _ => format!("{}*{}", c, generic),
+            })
+            .collect::<Vec<String>>()
+            .join("+");
+        let constant = self.constant + offset;
+        if result.is_empty() {
+            result = constant.to_string();
+        } else if constant > 0 {
+            result.push_str(format!("+{}", constant).as_str());
+        } else if constant < 0 {
+            result.push_str(constant.to_string().as_str());
+        }
+        result
+    }
+}
+
 /// Bundle of names and types. Useful to represent flattened types.
 pub type TypeBundle = Vec<(Vec<String>, Type, bool)>;
 
 impl Type {
     /// Construct a bit vector type.
     pub fn bitvec(width: NonNegative) -> Type {
-        Type::BitVec { width }
+        Type::BitVec {
+            width,
+            generic: None,
+        }
+    }
+
+    /// Returns true if the width of this type, or of any type nested in it, depends on generics.
+    pub fn is_generic(&self) -> bool {
+        match self {
+            Type::Bit => false,
+            Type::BitVec { generic, .. } => generic.is_some(),
+            Type::Record(rec) | Type::Union(rec) => rec.fields().any(|f| f.typ().is_generic()),
+            Type::Array(arr) => arr.typ().is_generic(),
+        }
+    }
+
+    /// Returns this type with the widths that depend on generics evaluated for the values of
+    /// the generics in [values].
+    pub fn bind_generics(&self, values: &[(String, NonNegative)]) -> Type {
+        let bind_rec = |rec: &Record| Record {
+            identifier: rec.identifier.clone(),
+            named: rec.named,
+            fields: rec
+                .fields
+                .iter()
+                .map(|f| Field {
+                    typ: f.typ.bind_generics(values),
+                    ..f.clone()
+                })
+                .collect(),
+        };
+        match self {
+            Type::BitVec {
+                width,
+                generic: Some(generic),
+            } => Type::bitvec(generic.evaluate(values).unwrap_or(*width)),
+            Type::Record(rec) => Type::Record(bind_rec(rec)),
+            Type::Union(rec) => Type::Union(bind_rec(rec)),
+            Type::Array(arr) => Type::Array(Array {
+                typ: Box::new(arr.typ().bind_generics(values)),
+                ..arr.clone()
+            }),
+            other => other.clone(),
+        }
     }
 
     /// Construct a record type.
@@ -334,6 +488,21 @@ impl Type {
         result
     }
 
+    /// Append a string to the names of the records, arrays and unions in this type, except for
+    /// records of named types without reversed fields, see [Record::append_name_split].
+    pub fn append_name_split(&self, with: impl Into<String>) -> Type {
+        let p: String = with.into();
+        match self {
+            Type::Record(rec) | Type::Union(rec) if rec.named && !rec.has_reversed() => {
+                self.clone()
+            }
+            Type::Record(rec) => Type::Record(rec.append_name_split(p)),
+            Type::Union(rec) => Type::Union(rec.append_name_split(p)),
+            Type::Array(arr) => Type::Array(arr.append_name_split(p)),
+            _ => self.clone(),
+        }
+    }
+
     // Returns true if the type contains a reversed field.
     pub fn has_reversed(&self) -> bool {
         match self {
@@ -345,11 +514,21 @@ impl Type {
     }
 }
 
+/// The value of a component parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterValue {
+    /// A natural number.
+    Natural(u32),
+    /// A string.
+    String(String),
+}
+
 /// A parameter for components.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameter {
     pub name: String,
-    pub typ: Type,
+    /// The default value of the parameter, which also determines its type.
+    pub value: ParameterValue,
 }
 
 /// Modes for ports.
@@ -509,6 +688,26 @@ impl Component {
     pub fn set_doc(&mut self, doc: impl Into<String>) {
         self.doc = Some(doc.into())
     }
+
+    /// Returns the ports of this component for the values of its generics in [values], which
+    /// take their default values otherwise.
+    pub fn bound_ports(&self, values: &[(String, NonNegative)]) -> Vec<Port> {
+        let mut values = values.to_vec();
+        for parameter in self.parameters() {
+            if let ParameterValue::Natural(value) = parameter.value {
+                if !values.iter().any(|(name, _)| name == &parameter.name) {
+                    values.push((parameter.name.clone(), value));
+                }
+            }
+        }
+        self.ports
+            .iter()
+            .map(|port| Port {
+                typ: port.typ.bind_generics(&values),
+                ..port.clone()
+            })
+            .collect()
+    }
 }
 
 /// A library of components and types.
@@ -1,14 +1,19 @@
 use std::cell::Ref;
 use std::ops::Deref;
 use std::path::Path;
+use std::process::Command;
+
+use log::debug;
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
 
 use crate::cat;
 use crate::design::implementation::composer::impl_graph::{Edge, ImplementationGraph, Node};
 use crate::design::implementation::composer::{GenDot, GenericComponent};
 use crate::design::implementation::Implementation;
-use crate::design::{Interface, Library, Mode, Project, THIS_KEY};
+use crate::design::{Interface, Library, Mode, Project, Streamlet, THIS_KEY};
 use crate::generator::GenerateProject;
-use crate::{Identify, Result};
+use crate::{Error, Identify, Name, Result};
 
 fn tab(n: usize) -> String {
     "\t".repeat(n)
@@ -59,17 +64,26 @@ impl Default for Colors {
 
 pub struct DotStyle {
     colors: Colors,
+    /// Whether to show the implementation of instantiated streamlets.
+    expand_nested: bool,
 }
 
 impl Default for DotStyle {
     fn default() -> Self {
         DotStyle {
             colors: Colors::default(),
+            expand_nested: true,
         }
     }
 }
 
 impl DotStyle {
+    /// Set whether the structural implementations of instantiated streamlets are expanded.
+    pub fn with_expand_nested(mut self, expand_nested: bool) -> Self {
+        self.expand_nested = expand_nested;
+        self
+    }
+
     pub fn node(&self, color: usize) -> String {
         format!(
             "fillcolor=\"{}\", color=\"{}\"",
@@ -123,8 +137,15 @@ impl GenDot for Node {
         prefix: &str,
         _label: &str,
     ) -> String {
-        self.component()
-            .gen_dot(style, project, l, prefix, self.key().as_ref())
+        component_dot(
+            self.component().deref(),
+            style,
+            project,
+            l,
+            prefix,
+            self.key().as_ref(),
+            style.expand_nested,
+        )
     }
 }
 
@@ -268,37 +289,65 @@ impl GenDot for dyn GenericComponent {
         prefix: &str,
         label: &str,
     ) -> String {
-        let p = match label.is_empty() {
-            true => format!("{}_{}", prefix, self.key()),
-            false => format!("{}_{}", prefix, label),
-        };
-        format!(
-            "{}subgraph cluster_{} {{ \n {}{}}}",
-            tab(l),
-            p,
-            //self.key(),
-            format!(
-                "\n{}{}{}{}{}\n",
-                format!("{}label = \"{}\\n{}\";\n", tab(l + 1), self.key(), label),
-                style.cluster(1, l + 1),
-                if_subgraph(style, project, l + 1, p.as_str(), "inputs", self.inputs()),
-                if_subgraph(style, project, l + 1, p.as_str(), "outputs", self.outputs()),
-                // implementation
-                if self.get_implementation().is_some() {
-                    self.get_implementation().unwrap().gen_dot(
-                        style,
-                        project,
-                        l + 1,
-                        format!("{}_{}", prefix, self.key()).as_ref(),
-                        "",
-                    )
-                } else {
-                    String::new()
-                }
-            ),
-            tab(l),
-        )
+        component_dot(self, style, project, l, prefix, label, true)
+    }
+}
+
+/// Generate a cluster for a component, with its implementation if [expand] is set.
+fn component_dot(
+    component: &dyn GenericComponent,
+    style: &DotStyle,
+    project: &Project,
+    l: usize,
+    prefix: &str,
+    label: &str,
+    expand: bool,
+) -> String {
+    let p = match label.is_empty() {
+        true => format!("{}_{}", prefix, component.key()),
+        false => format!("{}_{}", prefix, label),
+    };
+    let mut body = format!(
+        "{}label = \"{}\\n{}\";\n",
+        tab(l + 1),
+        component.key(),
+        label
+    );
+    body.push_str(&style.cluster(1, l + 1));
+    body.push_str(&if_subgraph(
+        style,
+        project,
+        l + 1,
+        p.as_str(),
+        "inputs",
+        component.inputs(),
+    ));
+    body.push_str(&if_subgraph(
+        style,
+        project,
+        l + 1,
+        p.as_str(),
+        "outputs",
+        component.outputs(),
+    ));
+    // implementation
+    match component.get_implementation() {
+        Some(implementation) if expand => body.push_str(&implementation.gen_dot(
+            style,
+            project,
+            l + 1,
+            format!("{}_{}", prefix, component.key()).as_ref(),
+            "",
+        )),
+        _ => (),
     }
+    format!(
+        "{}subgraph cluster_{} {{ \n \n{}\n{}}}",
+        tab(l),
+        p,
+        body,
+        tab(l),
+    )
 }
 
 impl GenDot for Library {
@@ -310,66 +359,189 @@ impl GenDot for Library {
         _prefix: &str,
         _label: &str,
     ) -> String {
-        format!(
-            "digraph  {{\n{}\n{}}}",
-            format!(
-                "{}{}{}{}{}{}{}",
-                format!("{}rankdir=LR;\n", tab(l + 1)),
-                format!("{}graph [fontname=\"Bitstream Charter\"];\n", tab(l + 1)),
-                format!("{}node [fontname=\"Bitstream Charter\"];\n", tab(l + 1)),
-                format!(
-                    "{}node [shape=box, style=\"rounded, filled\"]\n",
-                    tab(l + 1)
-                ),
-                format!("{}edge [fontname=\"Bitstream Charter\"];\n", tab(l + 1)),
-                format!("{}splines=compound;\n", tab(l + 1)),
-                self.streamlets()
-                    .map(|s| s as &dyn GenericComponent)
-                    .map(|s| s.gen_dot(style, project, l + 1, self.identifier(), ""))
-                    .collect::<Vec<String>>()
-                    .join("\n"),
-            ),
-            tab(l)
-        )
+        library_dot(self, self.streamlets(), style, project, l)
+    }
+}
+
+/// Generate a graph with [streamlets] of [library].
+fn library_dot<'a>(
+    library: &Library,
+    streamlets: impl Iterator<Item = &'a Streamlet>,
+    style: &DotStyle,
+    project: &Project,
+    l: usize,
+) -> String {
+    let mut body = String::new();
+    for line in &[
+        "rankdir=LR;",
+        "graph [fontname=\"Bitstream Charter\"];",
+        "node [fontname=\"Bitstream Charter\"];",
+        "node [shape=box, style=\"rounded, filled\"]",
+        "edge [fontname=\"Bitstream Charter\"];",
+        "splines=compound;",
+    ] {
+        body.push_str(&format!("{}{}\n", tab(l + 1), line));
     }
+    body.push_str(
+        &streamlets
+            .map(|s| s as &dyn GenericComponent)
+            .map(|s| s.gen_dot(style, project, l + 1, library.identifier(), ""))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    );
+    format!("digraph  {{\n{}\n{}}}", body, tab(l))
 }
 
-/// A configurable VHDL back-end entry point.
+/// Graphviz DOT back-end configuration parameters.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "cli", derive(StructOpt))]
+pub struct DotConfig {
+    /// Only generate a graph for this library.
+    #[cfg_attr(feature = "cli", structopt(short, long))]
+    library: Option<String>,
+
+    /// Only show this streamlet.
+    #[cfg_attr(feature = "cli", structopt(short, long))]
+    streamlet: Option<String>,
+
+    /// Show instances of streamlets without their structural implementation.
+    #[cfg_attr(feature = "cli", structopt(short, long))]
+    collapse: bool,
+
+    /// Render the graphs in this format (e.g. svg or png) using the `dot` binary of Graphviz,
+    /// next to the generated .dot files.
+    #[cfg_attr(feature = "cli", structopt(short, long))]
+    render: Option<String>,
+}
+
+impl DotConfig {
+    /// Only generate a graph for [library].
+    pub fn with_library(mut self, library: impl Into<String>) -> Self {
+        self.library = Some(library.into());
+        self
+    }
+
+    /// Only show [streamlet].
+    pub fn with_streamlet(mut self, streamlet: impl Into<String>) -> Self {
+        self.streamlet = Some(streamlet.into());
+        self
+    }
+
+    /// Show instances of streamlets without their structural implementation.
+    pub fn with_collapse(mut self, collapse: bool) -> Self {
+        self.collapse = collapse;
+        self
+    }
+
+    /// Render the graphs in [format] using the `dot` binary.
+    pub fn with_render(mut self, format: impl Into<String>) -> Self {
+        self.render = Some(format.into());
+        self
+    }
+}
+
+/// A configurable Graphviz DOT back-end entry point.
 #[derive(Default)]
-pub struct DotBackend {}
+pub struct DotBackend {
+    /// Configuration for the DOT back-end.
+    config: DotConfig,
+}
+
+impl DotBackend {
+    pub fn config(&self) -> &DotConfig {
+        &self.config
+    }
+}
+
+impl From<DotConfig> for DotBackend {
+    fn from(config: DotConfig) -> Self {
+        DotBackend { config }
+    }
+}
 
 impl GenerateProject for DotBackend {
     fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()> {
+        let style = DotStyle::default().with_expand_nested(!self.config.collapse);
+
         // Create the project directory.
-        let dir = path.as_ref().to_path_buf();
+        let mut dir = path.as_ref().to_path_buf();
+        dir.push(project.identifier());
+        std::fs::create_dir_all(dir.as_path())?;
 
-        for lib in project.libraries() {
-            // Create sub-directory for each lib
-            let mut lib_dir = dir.clone();
-            lib_dir.push(project.identifier());
-            std::fs::create_dir_all(lib_dir.as_path())?;
+        let libraries: Vec<&Library> = match &self.config.library {
+            Some(library) => vec![project.get_lib(Name::try_new(library)?)?],
+            None => project.libraries().collect(),
+        };
+
+        let mut found = false;
+        for lib in libraries {
+            let streamlets: Vec<&Streamlet> = lib
+                .streamlets()
+                .filter(|s| match &self.config.streamlet {
+                    Some(streamlet) => s.identifier() == streamlet,
+                    None => true,
+                })
+                .collect();
+            if streamlets.is_empty() && self.config.streamlet.is_some() {
+                continue;
+            }
+            found = true;
 
             // Determine output file
-            let mut lib_path = lib_dir.clone();
-            lib_path.push(lib.identifier());
+            let mut lib_path = dir.clone();
+            lib_path.push(match &self.config.streamlet {
+                Some(streamlet) => format!("{}_{}", lib.identifier(), streamlet),
+                None => lib.identifier().to_string(),
+            });
             lib_path.set_extension("dot");
 
-            let dot = lib.gen_dot(&DotStyle::default(), project, 0, "", "");
+            let dot = library_dot(lib, streamlets.into_iter(), &style, project, 0);
+            std::fs::write(lib_path.as_path(), dot)?;
+            debug!("Wrote {}.", lib_path.as_path().to_str().unwrap_or(""));
 
-            // TODO: remove this
-            println!("{}", dot);
+            if let Some(format) = &self.config.render {
+                render(lib_path.as_path(), format)?;
+            }
+        }
 
-            std::fs::write(lib_path, dot)?;
+        match &self.config.streamlet {
+            Some(streamlet) if !found => Err(Error::InvalidArgument(format!(
+                "Streamlet {} does not exist",
+                streamlet
+            ))),
+            _ => Ok(()),
         }
-        Ok(())
     }
 }
 
+/// Render the graph in [path] with the `dot` binary, to a file with [format] as extension.
+fn render(path: &Path, format: &str) -> Result<()> {
+    let output = path.with_extension(format);
+    let result = Command::new("dot")
+        .arg(format!("-T{}", format))
+        .arg(path)
+        .arg("-o")
+        .arg(output.as_path())
+        .output()
+        .map_err(|e| Error::BackEndError(format!("Unable to run dot: {}", e)))?;
+    if !result.status.success() {
+        return Err(Error::BackEndError(format!(
+            "dot failed to render {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+    debug!("Wrote {}.", output.as_path().to_str().unwrap_or(""));
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::design::implementation::composer::parser::tests::impl_parser_test;
-    use crate::generator::dot::DotBackend;
+    use crate::design::implementation::composer::tests::composition_test_proj;
+    use crate::generator::dot::{DotBackend, DotConfig};
     use crate::generator::GenerateProject;
+    use crate::Result;
 
     #[test]
     fn dot_impl() {
@@ -377,9 +549,43 @@ mod tests {
 
         let prj = impl_parser_test().unwrap();
         //let prj = pow2_example().unwrap();
-        let dot = DotBackend {};
+        let dot = DotBackend::default();
         // TODO: implement actual test.
 
         assert!(dot.generate(&prj, tmpdir).is_ok());
     }
+
+    #[test]
+    fn dot_config() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let mut prj = composition_test_proj()?;
+        prj.add_implementation("impl primitives.Magic structural { this.out <= this.in; }")?;
+        prj.add_implementation(include_str!(
+            "../../../tests/implementations/composition_example.impl"
+        ))?;
+
+        let generate = |config: DotConfig| -> Result<String> {
+            DotBackend::from(
+                config
+                    .with_library("compositions")
+                    .with_streamlet("Top_level"),
+            )
+            .generate(&prj, tmpdir.path())?;
+            Ok(std::fs::read_to_string(
+                tmpdir.path().join("TestProj/compositions_Top_level.dot"),
+            )?)
+        };
+        let nested = "subgraph cluster_compositions_Top_level_impl_Magic_impl";
+        assert!(generate(DotConfig::default())?.contains(nested));
+        assert!(!generate(DotConfig::default().with_collapse(true))?.contains(nested));
+
+        assert_eq!(
+            DotBackend::from(DotConfig::default().with_streamlet("Bottom_level"))
+                .generate(&prj, tmpdir.path())
+                .unwrap_err()
+                .to_string(),
+            "Invalid argument: Streamlet Bottom_level does not exist"
+        );
+        Ok(())
+    }
 }
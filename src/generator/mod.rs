@@ -6,6 +6,7 @@ use crate::Result;
 pub mod chisel;
 pub mod common;
 pub mod dot;
+pub mod sv;
 pub mod vhdl;
 
 /// Concatenate stuff using format with an underscore in between.
@@ -0,0 +1,586 @@
+//! Implementations of SystemVerilog traits for common representation.
+
+use std::collections::HashMap;
+
+use crate::error::Error::BackEndError;
+use crate::generator::common::{Array, Component, Field, Mode, Package, Port, Record, Type};
+use crate::generator::sv::{
+    package_identifier, Analyze, DeclareSV, DeclareSVInPackage, DeclareSVType, SVIdentifier,
+};
+use crate::traits::Identify;
+use crate::{cat, Document, NonNegative, Result, Reversed};
+
+/// Push documentation as SystemVerilog comments onto [result], with some indentation.
+fn push_doc(result: &mut String, doc: Option<String>, indent: &str) {
+    if let Some(doc) = doc {
+        for line in doc.lines() {
+            result.push_str(format!("{}//{}\n", indent, line).as_str());
+        }
+    }
+}
+
+impl SVIdentifier for Mode {
+    fn sv_identifier(&self) -> Result<String> {
+        match self {
+            Mode::In => Ok("input".to_string()),
+            Mode::Out => Ok("output".to_string()),
+        }
+    }
+}
+
+impl SVIdentifier for Type {
+    fn sv_identifier(&self) -> Result<String> {
+        // Records, unions and arrays use type definitions or interfaces.
+        // Any other types are used directly.
+        match self {
+            Type::Bit => Ok("logic".to_string()),
+            Type::BitVec { width, .. } => {
+                let actual_width = if *width == 0 { 1 } else { *width };
+                Ok(format!("logic [{}:0]", actual_width - 1))
+            }
+            Type::Record(rec) | Type::Union(rec) => rec.sv_identifier(),
+            Type::Array(arr) => arr.sv_identifier(),
+        }
+    }
+}
+
+impl SVIdentifier for Record {
+    fn sv_identifier(&self) -> Result<String> {
+        if self.has_reversed() {
+            Ok(cat!(self.identifier(), "if"))
+        } else {
+            Ok(cat!(self.identifier(), "t"))
+        }
+    }
+}
+
+impl SVIdentifier for Array {
+    fn sv_identifier(&self) -> Result<String> {
+        if self.typ().has_reversed() {
+            Err(BackEndError(format!(
+                "Array {} has elements with reversed fields, which can not be packed.",
+                self.identifier()
+            )))
+        } else {
+            Ok(cat!(self.identifier(), "t"))
+        }
+    }
+}
+
+impl DeclareSVType for Record {
+    fn declare(&self) -> Result<String> {
+        let mut result = "typedef struct packed {\n".to_string();
+        for field in self.fields() {
+            push_doc(&mut result, field.doc(), "  ");
+            result.push_str(
+                format!(
+                    "  {} {};\n",
+                    field.typ().sv_identifier()?,
+                    field.identifier()
+                )
+                .as_str(),
+            );
+        }
+        result.push_str(format!("}} {};", self.sv_identifier()?).as_str());
+        Ok(result)
+    }
+}
+
+/// Split the fields of a union into its tag, if it has one, and its variants.
+fn union_fields(rec: &Record) -> (Option<&Field>, Vec<&Field>) {
+    let mut fields: Vec<&Field> = rec.fields().collect();
+    if !fields.is_empty() && fields[0].identifier() == "tag" {
+        let tag = fields.remove(0);
+        (Some(tag), fields)
+    } else {
+        (None, fields)
+    }
+}
+
+/// Returns the number of bits of a type when it is packed.
+fn packed_width(typ: &Type) -> NonNegative {
+    match typ {
+        Type::Bit => 1,
+        Type::BitVec { width, .. } => {
+            if *width == 0 {
+                1
+            } else {
+                *width
+            }
+        }
+        Type::Record(rec) => rec.fields().map(|f| packed_width(f.typ())).sum(),
+        Type::Union(rec) => {
+            let (tag, variants) = union_fields(rec);
+            tag.map_or(0, |f| packed_width(f.typ()))
+                + variants
+                    .iter()
+                    .map(|f| packed_width(f.typ()))
+                    .max()
+                    .unwrap_or(0)
+        }
+        Type::Array(arr) => arr.width() * packed_width(arr.typ()),
+    }
+}
+
+/// Declare a union as a packed union of its variants, with the narrower variants padded to the
+/// width of the widest one, and a packed struct holding the tag and the packed union.
+fn declare_union(rec: &Record) -> Result<String> {
+    let (tag, variants) = union_fields(rec);
+    let width = variants
+        .iter()
+        .map(|f| packed_width(f.typ()))
+        .max()
+        .unwrap_or(0);
+    let data = cat!(rec.identifier(), "union_t");
+
+    let mut result = String::new();
+    if !variants.is_empty() {
+        result.push_str("typedef union packed {\n");
+        for variant in variants.iter() {
+            push_doc(&mut result, variant.doc(), "  ");
+            let padding = width - packed_width(variant.typ());
+            if padding == 0 {
+                result.push_str(
+                    format!(
+                        "  {} {};\n",
+                        variant.typ().sv_identifier()?,
+                        variant.identifier()
+                    )
+                    .as_str(),
+                );
+            } else {
+                result.push_str(
+                    format!(
+                        "  struct packed {{\n    logic [{}:0] padding;\n    {} {};\n  }} {};\n",
+                        padding - 1,
+                        variant.typ().sv_identifier()?,
+                        variant.identifier(),
+                        variant.identifier()
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        result.push_str(format!("}} {};\n\n", data).as_str());
+    }
+
+    result.push_str("typedef struct packed {\n");
+    if let Some(tag) = tag {
+        push_doc(&mut result, tag.doc(), "  ");
+        result
+            .push_str(format!("  {} {};\n", tag.typ().sv_identifier()?, tag.identifier()).as_str());
+    }
+    if !variants.is_empty() {
+        result.push_str(format!("  {} data;\n", data).as_str());
+    }
+    result.push_str(format!("}} {};", rec.sv_identifier()?).as_str());
+    Ok(result)
+}
+
+impl DeclareSVType for Array {
+    fn declare(&self) -> Result<String> {
+        let dimension = format!("[{}:0]", self.width() - 1);
+        let element = match self.typ() {
+            // Bits and bit vectors are packed along with the array dimension.
+            Type::Bit => format!("logic {}", dimension),
+            Type::BitVec { width, .. } => {
+                let actual_width = if *width == 0 { 1 } else { *width };
+                format!("logic {}[{}:0]", dimension, actual_width - 1)
+            }
+            other => format!("{} {}", other.sv_identifier()?, dimension),
+        };
+        Ok(format!("typedef {} {};", element, self.sv_identifier()?))
+    }
+}
+
+impl DeclareSVType for Type {
+    fn declare(&self) -> Result<String> {
+        match self {
+            Type::Record(rec) => rec.declare(),
+            Type::Union(rec) => declare_union(rec),
+            Type::Array(arr) => arr.declare(),
+            _ => Err(BackEndError(format!(
+                "Type {} does not require a typedef.",
+                self.sv_identifier()?
+            ))),
+        }
+    }
+}
+
+impl Analyze for Type {
+    fn list_typedefs(&self) -> Vec<Type> {
+        match self {
+            Type::Record(rec) | Type::Union(rec) => {
+                let mut result: Vec<Type> =
+                    rec.fields().flat_map(|f| f.typ().list_typedefs()).collect();
+                // Bidirectional records become interfaces instead.
+                if !rec.has_reversed() {
+                    result.push(self.clone());
+                }
+                result
+            }
+            Type::Array(arr) => {
+                let mut result = arr.typ().list_typedefs();
+                result.push(self.clone());
+                result
+            }
+            _ => vec![],
+        }
+    }
+
+    fn list_interfaces(&self) -> Vec<Record> {
+        match self {
+            Type::Record(rec) | Type::Union(rec) if rec.has_reversed() => vec![rec.clone()],
+            _ => vec![],
+        }
+    }
+}
+
+impl Analyze for Component {
+    fn list_typedefs(&self) -> Vec<Type> {
+        self.ports()
+            .iter()
+            .flat_map(|p| p.typ().list_typedefs())
+            .collect()
+    }
+
+    fn list_interfaces(&self) -> Vec<Record> {
+        self.ports()
+            .iter()
+            .flat_map(|p| p.typ().list_interfaces())
+            .collect()
+    }
+}
+
+/// A signal of an interface: its name, type, whether it is reversed, and its documentation.
+type InterfaceSignal = (String, Type, bool, Option<String>);
+
+/// Flatten the fields of a bidirectional record into the signals of an interface. Nested
+/// bidirectional records are flattened as well, the names of their fields are prefixed with the
+/// name of the field that holds them.
+fn interface_signals(rec: &Record, prefix: &str, reversed: bool) -> Vec<InterfaceSignal> {
+    let mut result = vec![];
+    for field in rec.fields() {
+        let name = cat!(prefix, field.identifier());
+        let field_reversed = reversed ^ field.is_reversed();
+        match field.typ() {
+            Type::Record(nested) | Type::Union(nested) if nested.has_reversed() => {
+                result.extend(interface_signals(nested, name.as_str(), field_reversed))
+            }
+            typ => result.push((name, typ.clone(), field_reversed, field.doc())),
+        }
+    }
+    result
+}
+
+/// Declare the interface of a bidirectional record, importing package [package].
+fn declare_interface(rec: &Record, package: &str) -> Result<String> {
+    let signals = interface_signals(rec, "", false);
+
+    let mut result = format!("interface {};\n", rec.sv_identifier()?);
+    result.push_str(format!("  import {}::*;\n\n", package).as_str());
+    for (name, typ, _, doc) in signals.iter() {
+        push_doc(&mut result, doc.clone(), "  ");
+        result.push_str(format!("  {} {};\n", typ.sv_identifier()?, name).as_str());
+    }
+    result.push('\n');
+
+    // The source drives all signals that are not reversed, the sink drives the others.
+    let modport = |name: &str, mode: Mode| -> Result<String> {
+        let mut directions = vec![];
+        for (signal, _, reversed, _) in signals.iter() {
+            let signal_mode = if *reversed { mode.reversed() } else { mode };
+            directions.push(format!("{} {}", signal_mode.sv_identifier()?, signal));
+        }
+        Ok(format!("  modport {}({});\n", name, directions.join(", ")))
+    };
+    result.push_str(modport("source", Mode::Out)?.as_str());
+    result.push_str(modport("sink", Mode::In)?.as_str());
+    result.push_str("endinterface");
+    Ok(result)
+}
+
+impl DeclareSV for Port {
+    fn declare(&self) -> Result<String> {
+        match self.typ() {
+            // Bidirectional types are connected through a modport of their interface.
+            Type::Record(rec) | Type::Union(rec) if rec.has_reversed() => Ok(format!(
+                "{}.{} {}",
+                rec.sv_identifier()?,
+                match self.mode() {
+                    Mode::In => "sink",
+                    Mode::Out => "source",
+                },
+                self.identifier()
+            )),
+            typ => Ok(format!(
+                "{} {} {}",
+                self.mode().sv_identifier()?,
+                typ.sv_identifier()?,
+                self.identifier()
+            )),
+        }
+    }
+}
+
+impl DeclareSVInPackage for Component {
+    fn declare(&self, package: &str) -> Result<String> {
+        let mut result = String::new();
+        push_doc(&mut result, self.doc(), "");
+        result.push_str(format!("module {}\n", self.identifier()).as_str());
+        result.push_str(format!("  import {}::*;\n", package).as_str());
+        result.push_str("(\n");
+        let mut ports = self.ports().iter().peekable();
+        while let Some(port) = ports.next() {
+            push_doc(&mut result, port.doc(), "  ");
+            result.push_str(format!("  {}", port.declare()?).as_str());
+            if ports.peek().is_some() {
+                result.push(',');
+            }
+            result.push('\n');
+        }
+        result.push_str(");\n\n");
+        result.push_str("  // User code\n\n");
+        result.push_str("endmodule\n");
+        Ok(result)
+    }
+}
+
+impl DeclareSV for Package {
+    fn declare(&self) -> Result<String> {
+        let package = package_identifier(self);
+        let mut result = format!("package {};\n\n", package);
+
+        // Whatever generated the common representation is responsible to not to use the same
+        // identifiers for different types. Remember which type identifiers have already been
+        // declared, so they are not declared twice, and produce an error on conflicts.
+        let mut type_ids = HashMap::<String, Type>::new();
+        let mut interfaces = vec![];
+        for c in &self.components {
+            for t in c.list_typedefs() {
+                match type_ids.get(&t.sv_identifier()?) {
+                    None => {
+                        type_ids.insert(t.sv_identifier()?, t.clone());
+                        result.push_str(format!("{}\n\n", t.declare()?).as_str());
+                    }
+                    Some(already_defined_type) => {
+                        if &t != already_defined_type {
+                            return Err(BackEndError(format!(
+                                "Type name conflict: {}",
+                                t.sv_identifier()?
+                            )));
+                        }
+                    }
+                }
+            }
+            for rec in c.list_interfaces() {
+                let t = Type::Record(rec.clone());
+                match type_ids.get(&rec.sv_identifier()?) {
+                    None => {
+                        type_ids.insert(rec.sv_identifier()?, t);
+                        interfaces.push(rec);
+                    }
+                    Some(already_defined_type) => {
+                        if &t != already_defined_type {
+                            return Err(BackEndError(format!(
+                                "Interface name conflict: {}",
+                                rec.sv_identifier()?
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+        result.push_str("endpackage\n");
+
+        // Interfaces can't be declared inside a package, so they follow it.
+        for rec in interfaces {
+            result.push_str(format!("\n{}\n", declare_interface(&rec, package.as_str())?).as_str());
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::generator::common::test::*;
+
+    use super::*;
+
+    #[test]
+    fn mode_decl() {
+        assert_eq!(Mode::In.sv_identifier().unwrap(), "input");
+        assert_eq!(Mode::Out.sv_identifier().unwrap(), "output");
+    }
+
+    #[test]
+    fn prim_type_decl() {
+        assert_eq!(Type::Bit.sv_identifier().unwrap(), "logic");
+        assert_eq!(Type::bitvec(8).sv_identifier().unwrap(), "logic [7:0]");
+    }
+
+    #[test]
+    fn record_type_decl() {
+        assert_eq!(
+            records::rec("rec").declare().unwrap(),
+            concat!(
+                "typedef struct packed {\n",
+                "  logic [41:0] c;\n",
+                "  logic [1336:0] d;\n",
+                "} rec_t;"
+            )
+        );
+        assert_eq!(
+            records::rec_nested("rec").list_typedefs(),
+            vec![
+                records::rec("rec_a"),
+                records::rec("rec_b"),
+                records::rec_nested("rec")
+            ]
+        );
+    }
+
+    #[test]
+    fn union_type_decl() {
+        assert_eq!(
+            records::union("union").declare().unwrap(),
+            concat!(
+                "typedef union packed {\n",
+                "  struct packed {\n",
+                "    logic [1294:0] padding;\n",
+                "    logic [41:0] c;\n",
+                "  } c;\n",
+                "  logic [1336:0] d;\n",
+                "} union_union_t;\n",
+                "\n",
+                "typedef struct packed {\n",
+                "  logic [1:0] tag;\n",
+                "  union_union_t data;\n",
+                "} union_t;"
+            )
+        );
+        assert_eq!(
+            records::union_nested("union").declare().unwrap(),
+            concat!(
+                "typedef union packed {\n",
+                "  union_a_t a;\n",
+                "  union_b_t b;\n",
+                "} union_union_t;\n",
+                "\n",
+                "typedef struct packed {\n",
+                "  logic [1:0] tag;\n",
+                "  union_union_t data;\n",
+                "} union_t;"
+            )
+        );
+        assert_eq!(
+            records::union_nested("union").list_typedefs(),
+            vec![
+                records::union("union_a"),
+                records::union("union_b"),
+                records::union_nested("union")
+            ]
+        );
+    }
+
+    #[test]
+    fn array_type_decl() {
+        assert_eq!(
+            Type::array("arr", Type::bitvec(8), 4).declare().unwrap(),
+            "typedef logic [3:0][7:0] arr_t;"
+        );
+        assert_eq!(
+            Type::array("arr", records::rec("rec"), 4)
+                .declare()
+                .unwrap(),
+            "typedef rec_t [3:0] arr_t;"
+        );
+    }
+
+    #[test]
+    fn interface_decl() {
+        match records::rec_rev_nested("rec") {
+            Type::Record(rec) => assert_eq!(
+                declare_interface(&rec, "test_pkg").unwrap(),
+                concat!(
+                    "interface rec_if;\n",
+                    "  import test_pkg::*;\n",
+                    "\n",
+                    "  rec_a_t a;\n",
+                    "  logic [41:0] b_c;\n",
+                    "  logic [1336:0] b_d;\n",
+                    "\n",
+                    "  modport source(output a, output b_c, input b_d);\n",
+                    "  modport sink(input a, input b_c, output b_d);\n",
+                    "endinterface"
+                )
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn comp_decl() {
+        let c = test_comp().with_doc(" My awesome\n Component".to_string());
+        assert_eq!(
+            c.declare("test_pkg").unwrap(),
+            concat!(
+                "// My awesome\n",
+                "// Component\n",
+                "module test_comp\n",
+                "  import test_pkg::*;\n",
+                "(\n",
+                "  a_if.sink a,\n",
+                "  b_if.source b\n",
+                ");\n",
+                "\n",
+                "  // User code\n",
+                "\n",
+                "endmodule\n"
+            )
+        );
+    }
+
+    #[test]
+    fn package_decl() {
+        let p = Package {
+            identifier: "test".to_string(),
+            components: vec![test_comp()],
+        };
+        assert_eq!(
+            p.declare().unwrap(),
+            concat!(
+                "package test_pkg;\n",
+                "\n",
+                "typedef struct packed {\n",
+                "  logic [41:0] c;\n",
+                "  logic [1336:0] d;\n",
+                "} b_a_t;\n",
+                "\n",
+                "endpackage\n",
+                "\n",
+                "interface a_if;\n",
+                "  import test_pkg::*;\n",
+                "\n",
+                "  logic [41:0] c;\n",
+                "  logic [1336:0] d;\n",
+                "\n",
+                "  modport source(output c, input d);\n",
+                "  modport sink(input c, output d);\n",
+                "endinterface\n",
+                "\n",
+                "interface b_if;\n",
+                "  import test_pkg::*;\n",
+                "\n",
+                "  b_a_t a;\n",
+                "  logic [41:0] b_c;\n",
+                "  logic [1336:0] b_d;\n",
+                "\n",
+                "  modport source(output a, output b_c, input b_d);\n",
+                "  modport sink(input a, input b_c, output b_d);\n",
+                "endinterface\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! SystemVerilog back-end.
+//!
+//! This module contains functionality to convert hardware defined in the common hardware
+//! representation to SystemVerilog source files.
+//!
+//! Every library results in a package holding `typedef struct packed` declarations of all
+//! unidirectional types, followed by an interface for every bidirectional type (e.g. a physical
+//! stream with its valid and ready signals). Such interfaces provide a `source` and `sink`
+//! modport. Every component results in a module stub.
+
+use std::path::Path;
+
+use log::debug;
+#[cfg(feature = "cli")]
+use structopt::StructOpt;
+
+use crate::cat;
+use crate::design::Project;
+use crate::generator::common::convert::Packify;
+use crate::generator::common::*;
+use crate::generator::vhdl::AbstractionLevel;
+use crate::generator::GenerateProject;
+use crate::traits::Identify;
+use crate::Result;
+
+mod impls;
+
+/// Generate trait for generic SystemVerilog declarations.
+pub trait DeclareSV {
+    /// Generate a SystemVerilog declaration from self.
+    fn declare(&self) -> Result<String>;
+}
+
+/// Generate trait for SystemVerilog declarations that use the types of a package.
+pub trait DeclareSVInPackage {
+    /// Generate a SystemVerilog declaration from self, importing package [package].
+    fn declare(&self, package: &str) -> Result<String>;
+}
+
+/// Generate trait for SystemVerilog type declarations.
+pub trait DeclareSVType {
+    /// Generate a SystemVerilog typedef from self.
+    fn declare(&self) -> Result<String>;
+}
+
+/// Generate trait for SystemVerilog identifiers.
+pub trait SVIdentifier {
+    /// Generate a SystemVerilog identifier from self.
+    fn sv_identifier(&self) -> Result<String>;
+}
+
+/// Analyze trait for SystemVerilog objects.
+pub trait Analyze {
+    /// List all types that require a typedef, nested types first.
+    fn list_typedefs(&self) -> Vec<Type>;
+    /// List all types that require an interface.
+    fn list_interfaces(&self) -> Vec<Record>;
+}
+
+/// Returns the identifier of the SystemVerilog package generated from a package.
+pub fn package_identifier(package: &Package) -> String {
+    cat!(package.identifier, "pkg")
+}
+
+/// SystemVerilog back-end configuration parameters.
+#[derive(Debug)]
+#[cfg_attr(feature = "cli", derive(StructOpt))]
+pub struct SVConfig {
+    /// Abstraction level of generated files.
+    /// Possible options: canonical, fancy.
+    ///   canonical: generates the canonical Tydi representation of streamlets as module stubs.
+    ///   fancy: generates the canonical module stubs and user-friendly module stubs using
+    ///          interfaces for every physical stream.
+    #[cfg_attr(feature = "cli", structopt(short, long))]
+    abstraction: Option<AbstractionLevel>,
+
+    /// Suffix of generated files. Default = "gen", such that
+    /// generated files are named <name>.gen.sv.
+    #[cfg_attr(feature = "cli", structopt(short, long))]
+    suffix: Option<String>,
+}
+
+impl SVConfig {
+    pub fn abstraction(&self) -> AbstractionLevel {
+        self.abstraction.unwrap_or_default()
+    }
+}
+
+impl Default for SVConfig {
+    fn default() -> Self {
+        SVConfig {
+            suffix: Some("gen".to_string()),
+            abstraction: Some(AbstractionLevel::Fancy),
+        }
+    }
+}
+
+/// A configurable SystemVerilog back-end entry point.
+#[derive(Default)]
+pub struct SVBackEnd {
+    /// Configuration for the SystemVerilog back-end.
+    config: SVConfig,
+}
+
+impl SVBackEnd {
+    pub fn config(&self) -> &SVConfig {
+        &self.config
+    }
+}
+
+impl From<SVConfig> for SVBackEnd {
+    fn from(config: SVConfig) -> Self {
+        SVBackEnd { config }
+    }
+}
+
+impl GenerateProject for SVBackEnd {
+    fn generate(&self, project: &Project, path: impl AsRef<Path>) -> Result<()> {
+        // Create the project directory.
+        let mut dir = path.as_ref().to_path_buf();
+        dir.push(project.identifier());
+        std::fs::create_dir_all(dir.as_path())?;
+
+        let extension = match self.config.suffix.clone() {
+            None => "sv".to_string(),
+            Some(s) => format!("{}.sv", s),
+        };
+
+        for lib in project.libraries() {
+            let pak = match self.config().abstraction() {
+                AbstractionLevel::Canonical => lib.canonical(),
+                AbstractionLevel::Fancy => lib.fancy(),
+            };
+            let package = package_identifier(&pak);
+
+            let mut pkg = dir.clone();
+            pkg.push(package.as_str());
+            pkg.set_extension(extension.as_str());
+            std::fs::write(pkg.as_path(), pak.declare()?)?;
+            debug!("Wrote {}.", pkg.as_path().to_str().unwrap_or(""));
+
+            for component in pak.components.iter() {
+                let mut module = dir.clone();
+                module.push(component.identifier());
+                module.set_extension(extension.as_str());
+                std::fs::write(module.as_path(), component.declare(package.as_str())?)?;
+                debug!("Wrote {}.", module.as_path().to_str().unwrap_or(""));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::design::Library;
+    use crate::parser::nom::streamlet;
+    use crate::Name;
+
+    use super::*;
+
+    #[test]
+    fn generate() -> Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let mut prj = Project::new(Name::try_new("sv_test")?);
+        prj.add_lib(Library::try_new(
+            Name::try_new("test")?,
+            vec![],
+            vec![
+                streamlet(
+                    "Streamlet x (a : in Stream<Group<b: Bits<4>, c: Bits<8>>, d=1>, \
+                     d : out Stream<Bits<32>, t=2.0>, \
+                     e : in Stream<Union<f: Bits<4>, g: Bits<8>>>)",
+                )
+                .unwrap()
+                .1,
+            ],
+        )?)?;
+
+        for abstraction in [AbstractionLevel::Canonical, AbstractionLevel::Fancy].iter() {
+            let sv: SVBackEnd = SVConfig {
+                abstraction: Some(*abstraction),
+                suffix: Some("gen".to_string()),
+            }
+            .into();
+            sv.generate(&prj, tmpdir.path())?;
+        }
+
+        let dir = tmpdir.path().join("sv_test");
+        let pkg = std::fs::read_to_string(dir.join("test_pkg.gen.sv"))?;
+        assert!(pkg.contains(concat!(
+            "typedef struct packed {\n",
+            "  logic [3:0] b;\n",
+            "  logic [7:0] c;\n",
+            "} x_a_data_t;\n"
+        )));
+        // Unions hold a tag and the variants padded to the width of the widest variant.
+        assert!(pkg.contains(concat!(
+            "typedef union packed {\n",
+            "  struct packed {\n",
+            "    logic [3:0] padding;\n",
+            "    logic [3:0] f;\n",
+            "  } f;\n",
+            "  logic [7:0] g;\n",
+            "} x_e_data_union_t;\n",
+            "\n",
+            "typedef struct packed {\n",
+            "  // Variants: f, g\n",
+            "  logic [0:0] tag;\n",
+            "  x_e_data_union_t data;\n",
+            "} x_e_data_t;\n"
+        )));
+        assert!(pkg.contains("typedef logic [1:0][31:0] x_d_data_array_t;\n"));
+        assert!(pkg.contains("interface x_e_if;\n"));
+        assert!(pkg.contains("  x_e_data_t data;\n"));
+
+        // The canonical union has the same width as the packed union.
+        let com = std::fs::read_to_string(dir.join("x_com.gen.sv"))?;
+        assert!(com.starts_with("module x_com\n"));
+        assert!(com.contains("  input logic [11:0] a_data,\n"));
+        assert!(com.contains("  input logic [8:0] e_data,\n"));
+        assert!(com.contains("  output logic [63:0] d_data\n"));
+
+        let fancy = std::fs::read_to_string(dir.join("x.gen.sv"))?;
+        assert!(fancy.contains(concat!(
+            "  x_a_if.sink a,\n",
+            "  x_e_if.sink e,\n",
+            "  x_d_if.source d\n"
+        )));
+        Ok(())
+    }
+}
@@ -3,7 +3,9 @@
 use std::collections::HashMap;
 
 use crate::error::Error::BackEndError;
-use crate::generator::common::{Array, Component, Mode, Package, Port, Record, Type};
+use crate::generator::common::{
+    Array, Component, Mode, Package, Parameter, ParameterValue, Port, Record, Type,
+};
 use crate::generator::vhdl::{
     Analyze, Declare, DeclareType, DeclareUsings, Split, Usings, VHDLIdentifier,
 };
@@ -21,20 +23,82 @@ impl VHDLIdentifier for Mode {
     }
 }
 
-fn declare_rec(rec: &Record) -> Result<String> {
-    let mut children = String::new();
+/// Type declarations that were already emitted, by VHDL identifier. Used to declare types that
+/// are nested in multiple other types, e.g. named types, only once.
+type Declared = HashMap<String, String>;
+
+/// Returns [decl] if no type with identifier [id] was declared yet, or an empty string if the
+/// same declaration was emitted before.
+fn declare_once(id: String, decl: String, declared: &mut Declared) -> Result<String> {
+    match declared.get(&id) {
+        None => {
+            declared.insert(id, decl.clone());
+            Ok(decl)
+        }
+        Some(existing) if existing == &decl => Ok(String::new()),
+        Some(_) => Err(BackEndError(format!("Type name conflict: {}", id))),
+    }
+}
+
+/// Join type declarations, skipping empty ones.
+fn join_decls(decls: Vec<String>) -> String {
+    decls
+        .into_iter()
+        .filter(|d| !d.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+/// Returns the type of the elements of records and arrays of type [typ]. Bit vectors of which
+/// the width depends on generics are left unconstrained, as the generics are not visible in the
+/// package that declares the record or array type.
+fn element_type(typ: &Type) -> Result<String> {
+    match typ {
+        Type::BitVec {
+            generic: Some(_), ..
+        } => Ok("std_logic_vector".to_string()),
+        Type::Record(rec) | Type::Union(rec) => rec.vhdl_identifier(),
+        Type::Array(arr) => arr.vhdl_identifier(),
+        _ => typ.vhdl_identifier(),
+    }
+}
+
+/// Returns the constraint of the elements of [typ] that were left unconstrained because their
+/// width depends on generics, if any, e.g. `(data(W-1 downto 0))` for a record.
+fn constraint(typ: &Type) -> Option<String> {
+    match typ {
+        Type::BitVec {
+            generic: Some(generic),
+            ..
+        } => Some(format!("({} downto 0)", generic.expression(-1))),
+        Type::Record(rec) | Type::Union(rec) => {
+            let fields = rec
+                .fields()
+                .filter_map(|f| constraint(f.typ()).map(|c| format!("{}{}", f.identifier(), c)))
+                .collect::<Vec<String>>();
+            if fields.is_empty() {
+                None
+            } else {
+                Some(format!("({})", fields.join(", ")))
+            }
+        }
+        Type::Array(arr) => constraint(arr.typ()).map(|c| format!("(open){}", c)),
+        _ => None,
+    }
+}
+
+fn declare_rec(rec: &Record, declared: &mut Declared) -> Result<String> {
+    let mut children = vec![];
     let mut this = format!("type {} is record\n", cat!(rec.vhdl_identifier()?));
 
     for field in rec.fields() {
         // Declare all nested record types first.
         match field.typ() {
             Type::Record(nested) | Type::Union(nested) => {
-                children.push_str(nested.declare(false)?.clone().as_str());
-                children.push_str("\n\n");
+                children.push(declare_record(nested, false, declared)?);
             }
             Type::Array(nested) => {
-                children.push_str(nested.declare(false)?.clone().as_str());
-                children.push_str("\n\n");
+                children.push(declare_array(nested, false, declared)?);
             }
             _ => (),
         }
@@ -50,21 +114,18 @@ fn declare_rec(rec: &Record) -> Result<String> {
             format!(
                 "  {} : {};\n",
                 field.identifier(),
-                field.typ().vhdl_identifier()?
+                element_type(field.typ())?
             )
             .as_str(),
         );
     }
     this.push_str("end record;");
-    if !children.is_empty() {
-        Ok(format!("{}{}", children, this))
-    } else {
-        Ok(this)
-    }
+    children.push(declare_once(rec.vhdl_identifier()?, this, declared)?);
+    Ok(join_decls(children))
 }
 
-fn declare_arr(arr: &Array) -> Result<String> {
-    let mut children = String::new();
+fn declare_arr(arr: &Array, declared: &mut Declared) -> Result<String> {
+    let mut children = vec![];
     let mut this = format!(
         "type {} is array ({} to {}) of ",
         arr.vhdl_identifier()?,
@@ -72,72 +133,86 @@ fn declare_arr(arr: &Array) -> Result<String> {
         arr.width() - 1
     );
 
-    fn rec_declare_children(children: &mut String, rec: Record, this: &mut String) -> Result<()> {
-        children.push_str(declare_rec(&rec)?.as_str());
-        children.push_str("\n\n");
-        this.push_str(rec.vhdl_identifier()?.as_str());
-        Ok(())
-    }
-
     match arr.typ() {
         Type::Bit => return Err(BackEndError("Unexpected, Bit in Array".to_string())),
-        Type::BitVec { width: _ } => this.push_str(arr.typ().declare(false)?.clone().as_str()),
-        Type::Record(rec) => rec_declare_children(&mut children, rec.clone(), &mut this)?,
-        Type::Union(rec) => rec_declare_children(&mut children, rec.clone(), &mut this)?,
+        Type::BitVec { .. } => this.push_str(element_type(arr.typ())?.as_str()),
+        Type::Record(rec) | Type::Union(rec) => {
+            children.push(declare_rec(rec, declared)?);
+            this.push_str(rec.vhdl_identifier()?.as_str());
+        }
         Type::Array(arr) => {
-            children.push_str(arr.declare(false)?.clone().as_str());
-            children.push_str("\n\n");
+            children.push(declare_array(arr, false, declared)?);
             this.push_str(arr.vhdl_identifier()?.as_str());
         }
     }
 
-    this.push_str(";");
-    if !children.is_empty() {
-        Ok(format!("{}{}", children, this))
+    this.push(';');
+    children.push(declare_once(arr.vhdl_identifier()?, this, declared)?);
+    Ok(join_decls(children))
+}
+
+fn declare_record(rec: &Record, is_root_type: bool, declared: &mut Declared) -> Result<String> {
+    if rec.has_reversed() {
+        // Nested named types without reversed fields are not split, and keep their names.
+        let suffixed_dn = rec
+            .append_name_split(if is_root_type { "dn" } else { "" })
+            .split()
+            .0
+            .unwrap();
+        let suffixed_up = rec
+            .append_name_split(if is_root_type { "up" } else { "" })
+            .split()
+            .1
+            .unwrap();
+        Ok(join_decls(vec![
+            declare_rec(&suffixed_dn, declared)?,
+            declare_rec(&suffixed_up, declared)?,
+        ]))
     } else {
-        Ok(this)
+        declare_rec(rec, declared)
+    }
+}
+
+fn declare_array(arr: &Array, is_root_type: bool, declared: &mut Declared) -> Result<String> {
+    if arr.typ().has_reversed() {
+        // Nested named types without reversed fields are not split, and keep their names.
+        let suffixed_dn = arr
+            .append_name_split(if is_root_type { "dn" } else { "" })
+            .split()
+            .0
+            .unwrap();
+        let suffixed_up = arr
+            .append_name_split(if is_root_type { "up" } else { "" })
+            .split()
+            .1
+            .unwrap();
+        Ok(join_decls(vec![
+            declare_arr(&suffixed_dn, declared)?,
+            declare_arr(&suffixed_up, declared)?,
+        ]))
+    } else {
+        declare_arr(arr, declared)
+    }
+}
+
+/// Declare a type and the types nested in it, skipping the types that are already declared.
+fn declare_type(typ: &Type, is_root_type: bool, declared: &mut Declared) -> Result<String> {
+    match typ {
+        Type::Record(rec) | Type::Union(rec) => declare_record(rec, is_root_type, declared),
+        Type::Array(arr) => declare_array(arr, is_root_type, declared),
+        _ => typ.declare(is_root_type),
     }
 }
 
 impl DeclareType for Record {
     fn declare(&self, is_root_type: bool) -> Result<String> {
-        let mut result = String::new();
-        if self.has_reversed() {
-            let (dn, up) = self.split();
-            let suffixed_dn = dn
-                .unwrap()
-                .append_name_nested(if is_root_type { "dn" } else { "" });
-            let suffixed_up = up
-                .unwrap()
-                .append_name_nested(if is_root_type { "up" } else { "" });
-            result.push_str(declare_rec(&suffixed_dn)?.as_str());
-            result.push_str("\n\n");
-            result.push_str(declare_rec(&suffixed_up)?.as_str());
-        } else {
-            result.push_str(declare_rec(&self)?.as_str());
-        }
-        Ok(result)
+        declare_record(self, is_root_type, &mut Declared::new())
     }
 }
 
 impl DeclareType for Array {
     fn declare(&self, is_root_type: bool) -> Result<String> {
-        let mut result = String::new();
-        if self.typ().has_reversed() {
-            let (dn, up) = self.split();
-            let suffixed_dn = dn
-                .unwrap()
-                .append_name_nested(if is_root_type { "dn" } else { "" });
-            let suffixed_up = up
-                .unwrap()
-                .append_name_nested(if is_root_type { "up" } else { "" });
-            result.push_str(declare_arr(&suffixed_dn)?.as_str());
-            result.push_str("\n\n");
-            result.push_str(declare_arr(&suffixed_up)?.as_str());
-        } else {
-            result.push_str(declare_arr(&self)?.as_str());
-        }
-        Ok(result)
+        declare_array(self, is_root_type, &mut Declared::new())
     }
 }
 
@@ -145,7 +220,15 @@ impl DeclareType for Type {
     fn declare(&self, is_root_type: bool) -> Result<String> {
         match self {
             Type::Bit => Ok("std_logic".to_string()),
-            Type::BitVec { width } => {
+            Type::BitVec {
+                generic: Some(generic),
+                ..
+            } => Ok(format!(
+                "std_logic_vector({} downto {})",
+                generic.expression(-1),
+                0
+            )),
+            Type::BitVec { width, .. } => {
                 let actual_width = if *width == 0 { 1 } else { *width };
                 Ok(format!(
                     "std_logic_vector({} downto {})",
@@ -162,12 +245,13 @@ impl DeclareType for Type {
 
 impl VHDLIdentifier for Type {
     fn vhdl_identifier(&self) -> Result<String> {
-        // Records and arrays use type definitions.
-        // Any other types are used directly.
+        // Records and arrays use type definitions, constrained where their widths depend on
+        // generics. Any other types are used directly.
         match self {
-            Type::Record(rec) => rec.vhdl_identifier(),
-            Type::Union(rec) => rec.vhdl_identifier(),
-            Type::Array(arr) => arr.vhdl_identifier(),
+            Type::Record(rec) | Type::Union(rec) => {
+                Ok(rec.vhdl_identifier()? + &constraint(self).unwrap_or_default())
+            }
+            Type::Array(arr) => Ok(arr.vhdl_identifier()? + &constraint(self).unwrap_or_default()),
             _ => self.declare(true),
         }
     }
@@ -276,6 +360,31 @@ impl Declare for Vec<Port> {
     }
 }
 
+impl Declare for Parameter {
+    fn declare(&self) -> Result<String> {
+        Ok(match &self.value {
+            ParameterValue::Natural(value) => format!("{} : natural := {}", self.name, value),
+            ParameterValue::String(value) => format!("{} : string := \"{}\"", self.name, value),
+        })
+    }
+}
+
+impl Declare for Vec<Parameter> {
+    fn declare(&self) -> Result<String> {
+        let mut result = String::new();
+        if !self.is_empty() {
+            result.push_str("  generic(\n");
+            let params = self
+                .iter()
+                .map(|p| Ok(format!("    {}", p.declare()?)))
+                .collect::<Result<Vec<String>>>()?;
+            result.push_str(params.join(";\n").as_str());
+            result.push_str("\n  );\n");
+        }
+        Ok(result)
+    }
+}
+
 impl Declare for Component {
     fn declare(&self) -> Result<String> {
         let mut result = String::new();
@@ -285,6 +394,7 @@ impl Declare for Component {
             result.push('\n');
         }
         result.push_str(format!("component {}\n", self.identifier()).as_str());
+        result.push_str(self.parameters().declare()?.as_str());
         result.push_str(self.ports().declare()?.as_str());
         result.push_str("end component;");
         Ok(result)
@@ -312,27 +422,15 @@ impl Declare for Package {
 
         // Whatever generated the common representation is responsible to not to use the same
         // identifiers for different types.
-        // Use a set to remember which type identifiers we've already used, so we don't declare
-        // them twice, and produce an error otherwise.
-        let mut type_ids = HashMap::<String, Type>::new();
+        // Remember which types were already declared, including nested types, so we don't
+        // declare them twice, and produce an error when the same identifier is used for
+        // different types.
+        let mut declared = Declared::new();
         for c in &self.components {
-            let comp_nested = c.list_nested_types();
-            for t in comp_nested.iter() {
-                match type_ids.get(&t.vhdl_identifier()?) {
-                    None => {
-                        type_ids.insert(t.vhdl_identifier()?, t.clone());
-                        result.push_str(format!("{}\n\n", t.declare(true)?).as_str());
-                    }
-                    Some(already_defined_type) => {
-                        if t != already_defined_type {
-                            return Err(BackEndError(format!(
-                                "Type name conflict: {}",
-                                already_defined_type
-                                    .vhdl_identifier()
-                                    .unwrap_or_else(|_| "".to_string())
-                            )));
-                        }
-                    }
+            for t in c.list_nested_types().iter() {
+                let decl = declare_type(t, true, &mut declared)?;
+                if !decl.is_empty() {
+                    result.push_str(format!("{}\n\n", decl).as_str());
                 }
             }
             result.push_str(format!("{}\n\n", c.declare()?).as_str());
@@ -354,7 +452,7 @@ impl ListUsings for Package {
         fn uses_std_logic(t: &Type) -> bool {
             match t {
                 Type::Bit => true,
-                Type::BitVec { width: _ } => true,
+                Type::BitVec { .. } => true,
                 Type::Record(rec) => rec.fields().any(|field| uses_std_logic(field.typ())),
                 Type::Union(rec) => rec.fields().any(|field| uses_std_logic(field.typ())),
                 Type::Array(arr) => uses_std_logic(arr.typ()),
@@ -388,7 +486,7 @@ mod test {
         let t0 = Type::Bit;
         assert_eq!(t0.declare(true).unwrap(), "std_logic");
 
-        let t1 = Type::BitVec { width: 8 };
+        let t1 = Type::bitvec(8);
         assert_eq!(t1.declare(true).unwrap(), "std_logic_vector(7 downto 0)");
     }
 
@@ -439,7 +537,7 @@ mod test {
 
     #[test]
     fn port_decl() {
-        let p = Port::new("test", Mode::In, Type::BitVec { width: 10 });
+        let p = Port::new("test", Mode::In, Type::bitvec(10));
         assert_eq!(
             "test : in std_logic_vector(9 downto 0)",
             p.declare().unwrap()
@@ -466,6 +564,39 @@ end component;"
         );
     }
 
+    #[test]
+    fn comp_decl_generics() {
+        let c = Component::new(
+            "test_comp",
+            vec![
+                Parameter {
+                    name: "W".to_string(),
+                    value: ParameterValue::Natural(8),
+                },
+                Parameter {
+                    name: "re".to_string(),
+                    value: ParameterValue::String("a*".to_string()),
+                },
+            ],
+            vec![Port::new("a", Mode::In, Type::bitvec(8))],
+            None,
+        );
+        assert_eq!(
+            c.declare().unwrap(),
+            concat!(
+                "component test_comp\n",
+                "  generic(\n",
+                "    W : natural := 8;\n",
+                "    re : string := \"a*\"\n",
+                "  );\n",
+                "  port(\n",
+                "    a : in std_logic_vector(7 downto 0)\n",
+                "  );\n",
+                "end component;"
+            )
+        );
+    }
+
     #[test]
     fn package_usings_decl() {
         let p = Package {
@@ -14,11 +14,14 @@ use structopt::StructOpt;
 
 use crate::cat;
 use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::Implementation;
 use crate::design::Project;
 use crate::generator::common::convert::Packify;
 use crate::generator::common::*;
 use crate::generator::GenerateProject;
-use crate::stdlib::utils::fancy_wrapper::generate_fancy_wrapper;
+use crate::stdlib::utils::fancy_wrapper::{generate_canonical_wrapper, generate_fancy_wrapper};
+use crate::stdlib::utils::structural::generate_structural_architecture;
+use crate::stdlib::utils::testbench::generate_testbench;
 use crate::traits::Identify;
 use crate::Name;
 use crate::{Error, Result, Reversed};
@@ -68,8 +71,10 @@ impl Usings {
 
     /// Combine two usings
     pub fn combine(&mut self, other: &Usings) {
-        for (library, using) in other.usings() {
-            self.0.insert(library.clone(), using.clone());
+        for (library, usings) in other.usings() {
+            for using in usings {
+                self.add_using(library.clone(), using.clone());
+            }
         }
     }
 }
@@ -154,12 +159,24 @@ pub struct VHDLConfig {
     /// generated files are named <name>.gen.vhd.
     #[cfg_attr(feature = "cli", structopt(short, long))]
     suffix: Option<String>,
+
+    /// Also generate a testbench for every streamlet, named <name>_tb.
+    /// The testbench reads the transfers of every physical stream from
+    /// <name>_<ports>.txt.
+    #[cfg_attr(feature = "cli", structopt(long))]
+    testbench: bool,
 }
 
 impl VHDLConfig {
     pub fn abstraction(&self) -> AbstractionLevel {
         self.abstraction.unwrap_or_default()
     }
+
+    /// Return this configuration with the generation of testbenches enabled or disabled.
+    pub fn with_testbench(mut self, testbench: bool) -> Self {
+        self.testbench = testbench;
+        self
+    }
 }
 
 impl Default for VHDLConfig {
@@ -167,6 +184,7 @@ impl Default for VHDLConfig {
         VHDLConfig {
             suffix: Some("gen".to_string()),
             abstraction: Some(AbstractionLevel::Fancy),
+            testbench: false,
         }
     }
 }
@@ -210,17 +228,50 @@ impl GenerateProject for VHDLBackEnd {
             };
             std::fs::write(pkg.as_path(), pak.declare()?)?;
             debug!("Wrote {}.", pkg.as_path().to_str().unwrap_or(""));
-            if let AbstractionLevel::Fancy = self.config().abstraction() {
-                for streamlet in lib.streamlets() {
+            for streamlet in lib.streamlets() {
+                // Structural implementations are generated for the canonical component, which
+                // the fancy wrapper wraps. Otherwise, the fancy wrapper implements the canonical
+                // component by wrapping the user implementation of the fancy component.
+                let structural = match streamlet.get_implementation().as_deref() {
+                    Some(Implementation::Structural(graph)) => {
+                        let mut structural = dir.clone();
+                        structural.push(format!("{}_impl", streamlet.identifier()));
+                        structural.set_extension(match self.config.suffix.clone() {
+                            None => "vhd".to_string(),
+                            Some(s) => format!("{}.vhd", s),
+                        });
+                        let arch = generate_structural_architecture(project, &pak, graph)?;
+                        std::fs::write(structural.as_path(), arch.declare()?)?;
+                        debug!("Wrote {}.", structural.as_path().to_str().unwrap_or(""));
+                        true
+                    }
+                    _ => false,
+                };
+                if let AbstractionLevel::Fancy = self.config().abstraction() {
                     let mut wrapper = dir.clone();
                     wrapper.push(format!("{}_wrapper", streamlet.identifier()));
                     wrapper.set_extension(match self.config.suffix.clone() {
                         None => "vhd".to_string(),
                         Some(s) => format!("{}.vhd", s),
                     });
-                    let arch = generate_fancy_wrapper(&pak, &streamlet.key())?;
+                    let arch = if structural {
+                        generate_canonical_wrapper(&pak, &streamlet.key())?
+                    } else {
+                        generate_fancy_wrapper(&pak, &streamlet.key())?
+                    };
                     std::fs::write(wrapper.as_path(), arch.declare()?)?;
                 }
+                if self.config().testbench {
+                    let mut testbench = dir.clone();
+                    testbench.push(format!("{}_tb", streamlet.identifier()));
+                    testbench.set_extension(match self.config.suffix.clone() {
+                        None => "vhd".to_string(),
+                        Some(s) => format!("{}.vhd", s),
+                    });
+                    let arch = generate_testbench(&pak, streamlet)?;
+                    std::fs::write(testbench.as_path(), arch.declare()?)?;
+                    debug!("Wrote {}.", testbench.as_path().to_str().unwrap_or(""));
+                }
             }
         }
         Ok(())
@@ -273,8 +324,8 @@ impl Split for Field {
 
 impl Split for Record {
     fn split(&self) -> (Option<Self>, Option<Self>) {
-        let mut down_rec = Record::new_empty(self.identifier());
-        let mut up_rec = Record::new_empty(self.identifier());
+        let mut down_rec = Record::new_empty(self.identifier()).with_named(self.is_named());
+        let mut up_rec = Record::new_empty(self.identifier()).with_named(self.is_named());
 
         for f in self.fields().into_iter() {
             let (down_field, up_field) = f.split();
@@ -312,30 +363,21 @@ impl Split for Array {
 
 impl Split for Port {
     fn split(&self) -> (Option<Self>, Option<Self>) {
-        let (type_down, type_up) = self.typ().split();
+        // Nested named types without reversed fields are not split, and keep their names.
+        let suffixed = |suffix: &str| match self.typ() {
+            Type::Record(r) => Type::Record(r.append_name_split(suffix)),
+            Type::Union(r) => Type::Union(r.append_name_split(suffix)),
+            t => t,
+        };
         (
-            type_down.map(|t| {
-                Port::new(
-                    cat!(self.identifier(), "dn"),
-                    self.mode(),
-                    match t {
-                        Type::Record(r) => Type::Record(r.append_name_nested("dn")),
-                        Type::Union(r) => Type::Union(r.append_name_nested("dn")),
-                        _ => t,
-                    },
-                )
-            }),
-            type_up.map(|t| {
-                Port::new(
-                    cat!(self.identifier(), "up"),
-                    self.mode().reversed(),
-                    match t {
-                        Type::Record(r) => Type::Record(r.append_name_nested("up")),
-                        Type::Union(r) => Type::Union(r.append_name_nested("up")),
-                        _ => t,
-                    },
-                )
-            }),
+            suffixed("dn")
+                .split()
+                .0
+                .map(|t| Port::new(cat!(self.identifier(), "dn"), self.mode(), t)),
+            suffixed("up")
+                .split()
+                .1
+                .map(|t| Port::new(cat!(self.identifier(), "up"), self.mode().reversed(), t)),
         )
     }
 }
@@ -515,6 +557,24 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn backend_testbench() -> Result<()> {
+        let v = VHDLBackEnd::from(VHDLConfig::default().with_testbench(true));
+
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("__test");
+
+        v.generate(
+            &crate::stdlib::basic::stub::tests::parsed_stub_project()?,
+            &path,
+        )?;
+
+        assert!(fs::metadata(path.join("test_project/passthrough_stub_tb.gen.vhd")).is_ok());
+        assert!(fs::metadata(path.join("test_project/invalid_stub_tb.gen.vhd")).is_ok());
+
+        Ok(())
+    }
+
     #[test]
     fn prj_impl() {
         let _tmpdir = tempfile::tempdir().unwrap();
@@ -24,6 +24,7 @@
 //! - [`cli`] command-line-interface generator tool.
 //! - [`generator`] module for generation of HDL templates.
 //! - [`parser`] module with parser for Streamlet Definition Files.
+//! - [`schema`] module to export and import projects as JSON or YAML.
 //!
 //! # Tools
 //!
@@ -67,6 +68,7 @@
 //! [`design`]: ./design/index.html
 //! [`cli`]: ./design/index.html
 //! [`parser`]: ./parser/index.html
+//! [`schema`]: ./schema/index.html
 //! [`tydi` command-line-interface]: #tydi-command-line-interface
 
 #![doc(html_favicon_url = "https://abs-tudelft.github.io/tydi/tydi_logo.svg")]
@@ -86,7 +88,7 @@ use std::str::FromStr;
 
 // Root re-exports
 // TODO(mb): discuss
-pub use error::{Error, Result};
+pub use error::{Diagnostic, Error, Result};
 pub use traits::{Document, Identify, Reverse, Reversed};
 pub use util::{Logger, UniqueKeyBuilder, UniquelyNamedBuilder};
 
@@ -105,6 +107,8 @@ mod traits;
 pub mod generator;
 #[cfg(feature = "parser")]
 pub mod parser;
+#[cfg(feature = "schema")]
+pub mod schema;
 #[cfg(feature = "stdlib")]
 pub mod stdlib;
 
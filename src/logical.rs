@@ -7,7 +7,7 @@
 use std::str::FromStr;
 use std::{
     convert::{TryFrom, TryInto},
-    error,
+    error, fmt,
 };
 
 use indexmap::IndexMap;
@@ -259,6 +259,21 @@ impl Stream {
         self.throughput
     }
 
+    /// Returns the complexity of this stream.
+    pub fn complexity(&self) -> &Complexity {
+        &self.complexity
+    }
+
+    /// Returns the user type of this stream, if any.
+    pub fn user(&self) -> Option<&LogicalType> {
+        self.user.as_deref()
+    }
+
+    /// Returns true if this stream carries extra information.
+    pub fn keep(&self) -> bool {
+        self.keep
+    }
+
     /// Returns true if this stream is null i.e. it results in no signals.
     ///
     /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#null-detection-function)
@@ -268,6 +283,29 @@ impl Stream {
             && !self.keep
     }
 
+    /// Returns a stream nested in the data of this stream, as it is split off
+    /// this stream, i.e. with its direction, synchronicity, dimensionality and
+    /// throughput relative to this stream applied.
+    ///
+    /// [Reference](https://abs-tudelft.github.io/tydi/specification/logical.html#split-function)
+    pub(crate) fn child(&self, mut stream: Stream) -> Stream {
+        if self.direction == Direction::Reverse {
+            stream.reverse();
+        }
+        if self.synchronicity == Synchronicity::Flatten
+            || self.synchronicity == Synchronicity::FlatDesync
+        {
+            stream.set_synchronicity(Synchronicity::FlatDesync);
+        }
+        if stream.synchronicity != Synchronicity::Flatten
+            && self.synchronicity != Synchronicity::FlatDesync
+        {
+            stream.set_dimensionality(stream.dimensionality + self.dimensionality);
+        };
+        stream.set_throughput(stream.throughput * self.throughput);
+        stream
+    }
+
     /// Set the throughput ratio of this stream.
     fn set_throughput(&mut self, throughput: PositiveReal) {
         self.throughput = throughput;
@@ -623,25 +661,7 @@ impl LogicalType {
                 }
 
                 streams.extend(rest.into_iter().map(|(name, stream)| match stream {
-                    LogicalType::Stream(mut stream) => {
-                        if stream_in.direction == Direction::Reverse {
-                            stream.reverse();
-                        }
-                        if stream_in.synchronicity == Synchronicity::Flatten
-                            || stream_in.synchronicity == Synchronicity::FlatDesync
-                        {
-                            stream.set_synchronicity(Synchronicity::FlatDesync);
-                        }
-                        if stream.synchronicity != Synchronicity::Flatten
-                            && stream_in.synchronicity != Synchronicity::FlatDesync
-                        {
-                            stream.set_dimensionality(
-                                stream.dimensionality + stream_in.dimensionality,
-                            );
-                        };
-                        stream.set_throughput(stream.throughput * stream_in.throughput);
-                        (name, stream.into())
-                    }
+                    LogicalType::Stream(stream) => (name, stream_in.child(stream).into()),
                     _ => unreachable!(),
                 }));
 
@@ -767,33 +787,85 @@ impl LogicalType {
         }
     }
 
+    /// Returns true if a source of this type can be connected to a sink of type [other].
     pub fn compatible(&self, other: &LogicalType) -> bool {
-        self == other
-            || match other {
-                LogicalType::Stream(other) => match self {
-                    LogicalType::Stream(stream) => {
-                        stream.data.compatible(&other.data) && stream.complexity < other.complexity
-                    }
-                    _ => false,
-                },
-                _ => false,
+        self.mismatches(other).is_empty()
+    }
+
+    /// Returns every difference that prevents a source of this type from being connected to a
+    /// sink of type [sink], or nothing if they are compatible.
+    ///
+    /// Types are compatible if they are equal, except for the complexity of streams: a source
+    /// stream can be connected to a sink stream with a higher complexity.
+    pub fn mismatches(&self, sink: &LogicalType) -> Vec<Mismatch> {
+        let mut result = vec![];
+        self.mismatches_at(sink, &mut vec![], &mut result);
+        result
+    }
+
+    fn mismatches_at(&self, sink: &LogicalType, path: &mut Vec<Name>, result: &mut Vec<Mismatch>) {
+        let mut push = |kind| {
+            result.push(Mismatch {
+                path: path.clone(),
+                kind,
+            })
+        };
+        match (self, sink) {
+            (LogicalType::Null, LogicalType::Null) => (),
+            (LogicalType::Bits(a), LogicalType::Bits(b)) if a == b => (),
+            (LogicalType::Group(Group(source)), LogicalType::Group(Group(sink)))
+            | (LogicalType::Union(Union(source)), LogicalType::Union(Union(sink))) => {
+                if source.keys().ne(sink.keys()) {
+                    push(MismatchKind::Fields(
+                        source.keys().cloned().collect(),
+                        sink.keys().cloned().collect(),
+                    ));
+                    return;
+                }
+                for ((name, source), sink) in source.iter().zip(sink.values()) {
+                    path.push(name.clone());
+                    source.mismatches_at(sink, path, result);
+                    path.pop();
+                }
             }
-            || match self {
-                LogicalType::Group(Group(source)) | LogicalType::Union(Union(source)) => {
-                    match other {
-                        LogicalType::Group(Group(sink)) | LogicalType::Union(Union(sink)) => {
-                            source.len() == sink.len()
-                                && source.iter().zip(sink.iter()).all(
-                                    |((name, stream), (name_, stream_))| {
-                                        name == name_ && stream.compatible(&stream_)
-                                    },
-                                )
-                        }
-                        _ => false,
-                    }
+            (LogicalType::Stream(source), LogicalType::Stream(sink)) => {
+                if source.throughput != sink.throughput {
+                    push(MismatchKind::Throughput(source.throughput, sink.throughput));
+                }
+                if source.dimensionality != sink.dimensionality {
+                    push(MismatchKind::Dimensionality(
+                        source.dimensionality,
+                        sink.dimensionality,
+                    ));
+                }
+                if source.synchronicity != sink.synchronicity {
+                    push(MismatchKind::Synchronicity(
+                        source.synchronicity,
+                        sink.synchronicity,
+                    ));
+                }
+                if source.complexity > sink.complexity {
+                    push(MismatchKind::Complexity(
+                        source.complexity.clone(),
+                        sink.complexity.clone(),
+                    ));
                 }
-                _ => false,
+                if source.direction != sink.direction {
+                    push(MismatchKind::Direction(source.direction, sink.direction));
+                }
+                if source.user != sink.user {
+                    push(MismatchKind::User(
+                        source.user.as_deref().cloned(),
+                        sink.user.as_deref().cloned(),
+                    ));
+                }
+                if source.keep != sink.keep {
+                    push(MismatchKind::Keep(source.keep, sink.keep));
+                }
+                source.data.mismatches_at(&sink.data, path, result);
             }
+            (source, sink) => push(MismatchKind::Type(source.clone(), sink.clone())),
+        }
     }
 
     pub fn split(&self) -> std::vec::IntoIter<LogicalSplitItem> {
@@ -825,6 +897,127 @@ impl LogicalType {
     }
 }
 
+/// A difference between the type of a source and the type of a sink, which prevents them from
+/// being connected. Returned by [LogicalType::mismatches].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    path: Vec<Name>,
+    kind: MismatchKind,
+}
+
+impl Mismatch {
+    /// Returns the names of the fields of the groups and unions that lead to the mismatching
+    /// types. The path is empty if the mismatch is at the top level of the types.
+    pub fn path(&self) -> &[Name] {
+        &self.path
+    }
+
+    /// Returns the kind of mismatch.
+    pub fn kind(&self) -> &MismatchKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            let path: Vec<&str> = self.path.iter().map(|name| name.as_ref()).collect();
+            write!(f, "{}: ", path.join("."))?;
+        }
+        let differ = |f: &mut fmt::Formatter<'_>,
+                      what: &str,
+                      source: &dyn fmt::Display,
+                      sink: &dyn fmt::Display| {
+            write!(
+                f,
+                "{} of source ({}) differs from {} of sink ({})",
+                what, source, what, sink
+            )
+        };
+        match &self.kind {
+            MismatchKind::Type(source, sink) => write!(
+                f,
+                "source is {}, sink is {}",
+                describe(source),
+                describe(sink)
+            ),
+            MismatchKind::Fields(source, sink) => {
+                let names = |names: &[Name]| {
+                    names
+                        .iter()
+                        .map(|name| name.as_ref())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                };
+                write!(
+                    f,
+                    "fields of source ({}) differ from fields of sink ({})",
+                    names(source),
+                    names(sink)
+                )
+            }
+            MismatchKind::Throughput(source, sink) => {
+                differ(f, "throughput", &source.get(), &sink.get())
+            }
+            MismatchKind::Dimensionality(source, sink) => differ(f, "dimensionality", source, sink),
+            MismatchKind::Synchronicity(source, sink) => differ(
+                f,
+                "synchronicity",
+                &format!("{:?}", source),
+                &format!("{:?}", sink),
+            ),
+            MismatchKind::Complexity(source, sink) => write!(
+                f,
+                "complexity of source ({}) is higher than complexity of sink ({})",
+                source, sink
+            ),
+            MismatchKind::Direction(source, sink) => differ(
+                f,
+                "direction",
+                &format!("{:?}", source),
+                &format!("{:?}", sink),
+            ),
+            MismatchKind::User(source, sink) => {
+                let user = |user: &Option<LogicalType>| match user {
+                    Some(user) => describe(user),
+                    None => "none".to_string(),
+                };
+                differ(f, "user type", &user(source), &user(sink))
+            }
+            MismatchKind::Keep(source, sink) => differ(f, "keep", source, sink),
+        }
+    }
+}
+
+/// Describe the kind of a logical type, including the width of bits.
+fn describe(typ: &LogicalType) -> String {
+    match typ {
+        LogicalType::Null => "Null".to_string(),
+        LogicalType::Bits(width) => format!("Bits<{}>", width),
+        LogicalType::Group(_) => "Group".to_string(),
+        LogicalType::Union(_) => "Union".to_string(),
+        LogicalType::Stream(_) => "Stream".to_string(),
+    }
+}
+
+/// The kinds of [Mismatch] between a source type and a sink type. Every kind holds the
+/// property of the source, followed by the property of the sink.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MismatchKind {
+    /// The types are of a different kind, or are bits of a different width.
+    Type(LogicalType, LogicalType),
+    /// The groups or unions have different fields, or fields in a different order.
+    Fields(Vec<Name>, Vec<Name>),
+    Throughput(PositiveReal, PositiveReal),
+    Dimensionality(NonNegative, NonNegative),
+    Synchronicity(Synchronicity, Synchronicity),
+    /// The complexity of the source stream is higher than the complexity of the sink stream.
+    Complexity(Complexity, Complexity),
+    Direction(Direction, Direction),
+    User(Option<LogicalType>, Option<LogicalType>),
+    Keep(bool, bool),
+}
+
 /// An element stream with a path name and LogicalType. Contains no nested
 /// streams.
 #[derive(Debug, Clone, PartialEq)]
@@ -1238,4 +1431,68 @@ pub(crate) mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn mismatches() -> Result<()> {
+        use elements::*;
+        let stream = |data: LogicalType, complexity: u32, user: Option<LogicalType>| {
+            LogicalType::from(Stream::new(
+                data,
+                PositiveReal::new(1.).unwrap(),
+                1,
+                Synchronicity::Sync,
+                complexity,
+                Direction::Forward,
+                user,
+                false,
+            ))
+        };
+
+        // A source can be connected to a sink with a higher complexity, but not the other way
+        // around.
+        assert!(stream(prim(8), 4, None).compatible(&stream(prim(8), 4, None)));
+        assert!(stream(prim(8), 2, None).compatible(&stream(prim(8), 7, None)));
+        assert_eq!(
+            stream(prim(8), 7, None).mismatches(&stream(prim(8), 2, None)),
+            vec![Mismatch {
+                path: vec![],
+                kind: MismatchKind::Complexity(Complexity::new_major(7), Complexity::new_major(2)),
+            }]
+        );
+
+        let source = stream(
+            LogicalType::try_new_group(vec![
+                ("a", group()),
+                ("b", stream(group_nested(), 4, None)),
+            ])?,
+            4,
+            Some(prim(2)),
+        );
+        let sink = stream(
+            LogicalType::try_new_group(vec![
+                (
+                    "a",
+                    LogicalType::try_new_union(vec![("c", prim(42)), ("d", prim(1337))])?,
+                ),
+                ("b", stream(group_of_single(), 4, None)),
+            ])?,
+            4,
+            None,
+        );
+        let mismatches = source
+            .mismatches(&sink)
+            .iter()
+            .map(|mismatch| mismatch.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mismatches,
+            vec![
+                "user type of source (Bits<2>) differs from user type of sink (none)",
+                "a: source is Group, sink is Union",
+                "b: fields of source (a, b) differ from fields of sink (a)",
+            ]
+        );
+        assert!(!source.compatible(&sink));
+        Ok(())
+    }
 }
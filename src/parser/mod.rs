@@ -2,13 +2,20 @@
 //!
 //! The parser module is enabled by the `parser` feature flag. It adds some
 //! utitity parser methods and implementations of parsers for Tydi stream and
-//! streamlet types, and libraries with streamlets.
+//! streamlet types, and libraries with streamlets. The [printer] module
+//! implements the inverse, printing them as Streamlet Definition Files. The
+//! [vcd] module reads the traces of the physical streams of streamlets from
+//! Value Change Dump files written by simulators. The [vhdl] module reads
+//! VHDL entity declarations, and reconstructs streamlets from their ports.
 //!
 //! The current parsers are built using [`nom`].
 //!
 //! [`nom`]: https://crates.io/crates/nom
 
 pub mod nom;
+pub mod printer;
+pub mod vcd;
+pub mod vhdl;
 
 #[cfg(test)]
 mod tests {}
@@ -1,62 +1,188 @@
 //! Nom-based parsers for Streamlet Definition Files.
+//!
+//! Parse errors keep track of where in the input they occurred and what was expected there.
+//! Use [diagnostic] to turn them into a [Diagnostic] that can be presented to the user.
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
 
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_until, take_while},
-    character::complete::{digit1, multispace1, none_of, one_of},
-    combinator::{map, map_res, opt, recognize},
-    multi::{many0, many1, separated_list0},
+    character::complete::{char, digit1, multispace1, none_of, one_of},
+    combinator::{cut, eof, map, map_opt, map_res, opt, recognize},
+    error::{context, ContextError, ErrorKind, FromExternalError, ParseError},
+    multi::{many0, separated_list0},
     number::complete::float,
-    sequence::{delimited, preceded, separated_pair, terminated, tuple},
+    sequence::{delimited, pair, preceded, terminated, tuple},
 };
 
-use crate::design::{Interface, Mode, Streamlet};
-use crate::logical::{Direction, Group, LogicalType, Stream, Synchronicity, Union};
+use crate::design::param::{
+    NamedParameter, ParamStream, ParamType, ParamUInt, ParameterStore, ParameterVariant,
+};
+use crate::design::{Interface, LibKey, Mode, Streamlet};
+use crate::error::Diagnostic;
+use crate::logical::{Direction, LogicalType, Synchronicity};
 use crate::physical::Complexity;
-use crate::{Name, PositiveReal};
-
-// #[derive(Debug, PartialEq)]
-// pub struct ParserError<I> {
-//     kind: ErrorKind<I>,
-//     backtrace: Vec<ErrorKind<I>>,
-// }
-//
-// impl<I> ParserError<I> {
-//     fn backtrace(&mut self, other: ParserError<I>) {
-//         self.backtrace.push(other.kind);
-//     }
-// }
-//
-// #[derive(Debug, PartialEq)]
-// enum ErrorKind<I> {
-//     Nom(I, nom::error::ErrorKind),
-//     BadName(String),
-// }
-//
-// impl<I> ParseError<I> for ParserError<I> {
-//     fn from_error_kind(input: I, kind: nom::error::ErrorKind) -> Self {
-//         ParserError {
-//             kind: ErrorKind::Nom(input, kind),
-//             backtrace: vec![],
-//         }
-//     }
-//
-//     fn append(input: I, kind: nom::error::ErrorKind, mut other: Self) -> Self {
-//         other.backtrace(Self::from_error_kind(input, kind));
-//         other
-//     }
-// }
-
-type Result<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
+use crate::{Name, PositiveReal, UniqueKeyBuilder};
 
-fn ws0(input: &str) -> Result<&str, Vec<&str>> {
-    many0(multispace1)(input)
+/// The kinds of errors collected by a [ParserError].
+#[derive(Debug, PartialEq)]
+pub enum ParserErrorKind {
+    /// Something described by the context was expected.
+    Context(&'static str),
+    /// A specific character was expected.
+    Char(char),
+    /// A nom parser failed.
+    Nom(ErrorKind),
+    /// The input was syntactically valid, but could not be converted, e.g. because of an
+    /// invalid bit count.
+    External(String),
+}
+
+/// Error type of the parsers in this module. Like nom's `VerboseError`, it keeps a stack of
+/// errors with the input at which they occurred, innermost first.
+#[derive(Debug, PartialEq)]
+pub struct ParserError<I> {
+    pub errors: Vec<(I, ParserErrorKind)>,
+}
+
+impl<I> ParseError<I> for ParserError<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        ParserError {
+            errors: vec![(input, ParserErrorKind::Nom(kind))],
+        }
+    }
+
+    fn append(input: I, kind: ErrorKind, mut other: Self) -> Self {
+        other.errors.push((input, ParserErrorKind::Nom(kind)));
+        other
+    }
+
+    fn from_char(input: I, c: char) -> Self {
+        ParserError {
+            errors: vec![(input, ParserErrorKind::Char(c))],
+        }
+    }
+}
+
+impl<I> ContextError<I> for ParserError<I> {
+    fn add_context(input: I, ctx: &'static str, mut other: Self) -> Self {
+        other.errors.push((input, ParserErrorKind::Context(ctx)));
+        other
+    }
+}
+
+impl<I, E: fmt::Display> FromExternalError<I, E> for ParserError<I> {
+    fn from_external_error(input: I, _kind: ErrorKind, e: E) -> Self {
+        ParserError {
+            errors: vec![(input, ParserErrorKind::External(e.to_string()))],
+        }
+    }
+}
+
+type Result<I, T> = nom::IResult<I, T, ParserError<I>>;
+
+/// Returns a description of what is expected by a parser with context [ctx], and the values
+/// it accepts if there is a fixed set of them.
+fn expectation(ctx: &'static str) -> (String, Option<&'static str>) {
+    match ctx {
+        "synchronicity" => (ctx.to_string(), Some("Sync, Flatten, Desync, FlatDesync")),
+        "direction" => (ctx.to_string(), Some("Forward, Reverse")),
+        "mode" => ("interface mode".to_string(), Some("in, out")),
+        "bool" => ("boolean".to_string(), Some("true, false")),
+        "stream property" => (ctx.to_string(), Some("t, d, s, c, r, u, x")),
+        "escape sequence" => (ctx.to_string(), Some("\\\", \\\\")),
+        "parameter kind" => (ctx.to_string(), Some("UInt, String, Type")),
+        "logical type" => (
+            "logical type (Null, Bits, Group, Union, Stream, a named type or a Type parameter)"
+                .to_string(),
+            None,
+        ),
+        "complexity" => ("complexity level, e.g. 4 or 7.1".to_string(), None),
+        "uint" => ("unsigned integer or UInt parameter".to_string(), None),
+        "declaration" => (
+            "declaration (`use`, `type` or `Streamlet`)".to_string(),
+            None,
+        ),
+        "name" | "throughput" | "string" => (ctx.to_string(), None),
+        token => (format!("`{}`", token), None),
+    }
+}
+
+/// Returns the token at the start of [input], for use in diagnostics.
+fn found(input: &str) -> &str {
+    let word = input
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .unwrap_or(input.len());
+    if word > 0 {
+        &input[..word]
+    } else {
+        input
+            .chars()
+            .next()
+            .map(|c| &input[..c.len_utf8()])
+            .unwrap_or("")
+    }
 }
 
-fn ws1(input: &str) -> Result<&str, Vec<&str>> {
-    many1(multispace1)(input)
+/// Convert a parse error of [input] into a diagnostic pointing at the location of the error.
+/// [file] is used to refer to the input in the diagnostic.
+pub fn diagnostic(file: Option<&str>, input: &str, err: nom::Err<ParserError<&str>>) -> Diagnostic {
+    let errors = match err {
+        nom::Err::Error(e) | nom::Err::Failure(e) => e.errors,
+        nom::Err::Incomplete(_) => vec![],
+    };
+    // All errors refer to the remainder of the input, so the offset of the error follows from
+    // the length of the remainder.
+    let remainder = errors.first().map(|(i, _)| *i).unwrap_or("");
+    let offset = input.len().saturating_sub(remainder.len());
+    let line_start = input[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = input[offset..]
+        .find('\n')
+        .map(|i| i + offset)
+        .unwrap_or(input.len());
+    let token = found(remainder);
+    let found = if token.is_empty() {
+        "end of input".to_string()
+    } else {
+        format!("`{}`", token)
+    };
+
+    let mut message = format!("unexpected {}", found);
+    let mut hint = None;
+    for (_, kind) in errors.iter() {
+        match kind {
+            ParserErrorKind::External(msg) => {
+                message = msg.clone();
+                break;
+            }
+            ParserErrorKind::Context(ctx) => {
+                let (expected, values) = expectation(ctx);
+                message = format!("expected {}, found {}", expected, found);
+                hint = values.map(|values| {
+                    format!("unknown {} '{}', expected one of {}", ctx, token, values)
+                });
+                break;
+            }
+            _ => (),
+        }
+    }
+
+    Diagnostic {
+        file: file.map(|f| f.to_string()),
+        line: input[..offset].matches('\n').count() + 1,
+        column: input[line_start..offset].chars().count() + 1,
+        width: token.chars().count().max(1),
+        source_line: input[line_start..line_end].trim_end().to_string(),
+        message,
+        hint,
+    }
+}
+
+fn ws0(input: &str) -> Result<&str, Vec<&str>> {
+    many0(multispace1)(input)
 }
 
 fn w<'a, T>(
@@ -65,10 +191,45 @@ fn w<'a, T>(
     terminated(f, ws0)
 }
 
+/// Parses a fixed token, which is reported as expected when it is missing.
+fn token<'a>(t: &'static str) -> impl FnMut(&'a str) -> Result<&'a str, &'a str> {
+    context(t, tag(t))
+}
+
+/// Parses a list of zero or more items separated by [sep]. Once a separator is parsed, another
+/// item is required.
+fn list0<'a, O, S>(
+    mut sep: impl FnMut(&'a str) -> Result<&'a str, S>,
+    mut f: impl FnMut(&'a str) -> Result<&'a str, O>,
+) -> impl FnMut(&'a str) -> Result<&'a str, Vec<O>> {
+    move |input: &'a str| {
+        let mut result = vec![];
+        let mut input = match f(input) {
+            Ok((rest, item)) => {
+                result.push(item);
+                rest
+            }
+            Err(nom::Err::Error(_)) => return Ok((input, result)),
+            Err(e) => return Err(e),
+        };
+        loop {
+            match sep(input) {
+                Ok((rest, _)) => {
+                    let (rest, item) = cut(&mut f)(rest)?;
+                    result.push(item);
+                    input = rest;
+                }
+                Err(nom::Err::Error(_)) => return Ok((input, result)),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
 pub fn name(input: &str) -> Result<&str, Name> {
-    map_res(
+    map_opt(
         take_while(|c: char| c.is_alphanumeric() || c == '_'),
-        |name: &str| Name::try_new(name).map_err(|_| ()),
+        |name: &str| Name::try_new(name).ok(),
     )(input)
 }
 
@@ -123,213 +284,555 @@ pub fn doc(input: &str) -> Result<&str, Option<String>> {
 }
 
 pub fn bool(input: &str) -> Result<&str, bool> {
-    map(alt((tag("true"), tag("false"))), |x: &str| {
-        x.parse::<bool>().unwrap()
-    })(input)
+    context(
+        "bool",
+        map(alt((tag("true"), tag("false"))), |x: &str| {
+            x.parse::<bool>().unwrap()
+        }),
+    )(input)
 }
 
-pub fn null(input: &str) -> Result<&str, LogicalType> {
-    map(tag("Null"), |_| LogicalType::Null)(input)
+pub fn param_uint(input: &str) -> Result<&str, ParamUInt> {
+    context(
+        "uint",
+        alt((
+            map_res(digit1, |x: &str| x.parse().map(ParamUInt::Literal)),
+            map(name, ParamUInt::Param),
+        )),
+    )(input)
 }
 
-pub fn bits(input: &str) -> Result<&str, LogicalType> {
-    map_res(
-        delimited(w(tag("Bits<")), w(digit1), tag(">")),
-        |x: &str| LogicalType::try_new_bits(x.parse().unwrap()).map_err(|_| ()),
+pub fn param_null(input: &str) -> Result<&str, ParamType> {
+    map(tag("Null"), |_| ParamType::Null)(input)
+}
+
+pub fn param_bits(input: &str) -> Result<&str, ParamType> {
+    let width = map_res(param_uint, |width| match width {
+        ParamUInt::Literal(0) => Err("bit count cannot be zero"),
+        width => Ok(width),
+    });
+    map(
+        preceded(w(tag("Bits<")), cut(terminated(w(width), token(">")))),
+        ParamType::Bits,
     )(input)
 }
 
-pub fn logical_stream_type(input: &str) -> Result<&str, LogicalType> {
-    alt((null, bits, group, union, stream))(input)
+/// Parses a logical type which may refer to parameters of the streamlet it is part of.
+pub fn param_type(input: &str) -> Result<&str, ParamType> {
+    context(
+        "logical type",
+        alt((
+            param_null,
+            param_bits,
+            param_group,
+            param_union,
+            param_stream,
+            map(name, ParamType::Param),
+        )),
+    )(input)
 }
 
-fn fields(input: &str) -> Result<&str, Vec<(Name, LogicalType)>> {
-    separated_list0(
-        w(tag(",")),
-        separated_pair(w(name), w(tag(":")), w(logical_stream_type)),
+fn field(input: &str) -> Result<&str, (Name, ParamType)> {
+    pair(
+        w(context("name", name)),
+        preceded(cut(w(token(":"))), cut(w(param_type))),
     )(input)
 }
 
-pub fn group(input: &str) -> Result<&str, LogicalType> {
-    map_res(
-        delimited(w(tag("Group<")), w(fields), tag(">")),
-        |fields: Vec<(Name, LogicalType)>| Group::try_new(fields).map(Into::into).map_err(|_| ()),
+fn fields(input: &str) -> Result<&str, Vec<(Name, ParamType)>> {
+    list0(w(tag(",")), w(field))(input)
+}
+
+pub fn param_group(input: &str) -> Result<&str, ParamType> {
+    map(
+        preceded(w(tag("Group<")), cut(terminated(w(fields), token(">")))),
+        ParamType::Group,
     )(input)
 }
 
-pub fn union(input: &str) -> Result<&str, LogicalType> {
-    map_res(
-        delimited(w(tag("Union<")), w(fields), tag(">")),
-        |fields: Vec<(Name, LogicalType)>| Union::try_new(fields).map(Into::into).map_err(|_| ()),
+pub fn param_union(input: &str) -> Result<&str, ParamType> {
+    map(
+        preceded(w(tag("Union<")), cut(terminated(w(fields), token(">")))),
+        ParamType::Union,
     )(input)
 }
 
-pub fn complexity(input: &str) -> Result<&str, Complexity> {
-    map_res(separated_list0(w(tag(".")), digit1), |level: Vec<&str>| {
-        Complexity::new(level.iter().map(|x| x.parse().unwrap())).map_err(|_| ())
-    })(input)
+/// The optional properties of a stream, e.g. `d=1`.
+enum StreamProperty {
+    Throughput(PositiveReal),
+    Dimensionality(ParamUInt),
+    Synchronicity(Synchronicity),
+    Complexity(Complexity),
+    Direction(Direction),
+    User(ParamType),
+    Keep(bool),
 }
 
-pub fn synchronicity(input: &str) -> Result<&str, Synchronicity> {
-    map(
+fn stream_property(input: &str) -> Result<&str, StreamProperty> {
+    let key = |k: char| pair(w(char(k)), w(token("=")));
+    context(
+        "stream property",
         alt((
-            tag("Sync"),
-            tag("Flatten"),
-            tag("Desync"),
-            tag("FlatDesync"),
+            preceded(
+                key('t'),
+                cut(context(
+                    "throughput",
+                    map_res(map_res(recognize(float), |x: &str| x.parse::<f64>()), |x| {
+                        PositiveReal::new(x).map(StreamProperty::Throughput)
+                    }),
+                )),
+            ),
+            preceded(
+                key('d'),
+                cut(map(param_uint, StreamProperty::Dimensionality)),
+            ),
+            preceded(
+                key('s'),
+                cut(map(synchronicity, StreamProperty::Synchronicity)),
+            ),
+            preceded(key('c'), cut(map(complexity, StreamProperty::Complexity))),
+            preceded(key('r'), cut(map(direction, StreamProperty::Direction))),
+            preceded(key('u'), cut(map(param_type, StreamProperty::User))),
+            preceded(key('x'), cut(map(bool, StreamProperty::Keep))),
         )),
-        |x: &str| x.parse().unwrap(),
+    )(input)
+}
+
+pub fn param_stream(input: &str) -> Result<&str, ParamType> {
+    map(
+        preceded(
+            w(tag("Stream<")),
+            cut(tuple((
+                w(param_type),
+                many0(preceded(w(tag(",")), cut(w(stream_property)))),
+                token(">"),
+            ))),
+        ),
+        |(data, properties, _)| {
+            let mut stream = ParamStream {
+                data,
+                throughput: PositiveReal::new(1.).unwrap(),
+                dimensionality: ParamUInt::Literal(0),
+                synchronicity: Synchronicity::default(),
+                complexity: Complexity::default(),
+                direction: Direction::default(),
+                user: None,
+                keep: false,
+            };
+            for property in properties {
+                match property {
+                    StreamProperty::Throughput(t) => stream.throughput = t,
+                    StreamProperty::Dimensionality(d) => stream.dimensionality = d,
+                    StreamProperty::Synchronicity(s) => stream.synchronicity = s,
+                    StreamProperty::Complexity(c) => stream.complexity = c,
+                    StreamProperty::Direction(r) => stream.direction = r,
+                    StreamProperty::User(u) => stream.user = Some(u),
+                    StreamProperty::Keep(x) => stream.keep = x,
+                }
+            }
+            ParamType::Stream(Box::new(stream))
+        },
+    )(input)
+}
+
+/// Resolves the result of a parser for types that may refer to parameters, failing when the
+/// type refers to any parameter.
+fn resolved<'a>(
+    f: impl FnMut(&'a str) -> Result<&'a str, ParamType>,
+) -> impl FnMut(&'a str) -> Result<&'a str, LogicalType> {
+    map_res(f, |t| LogicalType::try_from(&t))
+}
+
+pub fn null(input: &str) -> Result<&str, LogicalType> {
+    resolved(param_null)(input)
+}
+
+pub fn bits(input: &str) -> Result<&str, LogicalType> {
+    resolved(param_bits)(input)
+}
+
+pub fn logical_stream_type(input: &str) -> Result<&str, LogicalType> {
+    resolved(param_type)(input)
+}
+
+pub fn group(input: &str) -> Result<&str, LogicalType> {
+    resolved(param_group)(input)
+}
+
+pub fn union(input: &str) -> Result<&str, LogicalType> {
+    resolved(param_union)(input)
+}
+
+/// Parses a complexity level, of which the major level must be between 1 and 8.
+pub fn complexity(input: &str) -> Result<&str, Complexity> {
+    context(
+        "complexity",
+        map_res(separated_list0(w(tag(".")), digit1), |level: Vec<&str>| {
+            let complexity = Complexity::new(level.iter().map(|x| x.parse().unwrap()))
+                .map_err(|e| e.to_string())?;
+            if (1..=8).contains(&complexity.major()) {
+                Ok(complexity)
+            } else {
+                Err(format!(
+                    "complexity must be between 1 and 8, found {}",
+                    complexity
+                ))
+            }
+        }),
+    )(input)
+}
+
+pub fn synchronicity(input: &str) -> Result<&str, Synchronicity> {
+    context(
+        "synchronicity",
+        map(
+            alt((
+                tag("Sync"),
+                tag("Flatten"),
+                tag("Desync"),
+                tag("FlatDesync"),
+            )),
+            |x: &str| x.parse().unwrap(),
+        ),
     )(input)
 }
 
 pub fn direction(input: &str) -> Result<&str, Direction> {
-    map(alt((tag("Forward"), tag("Reverse"))), |x: &str| {
-        x.parse().unwrap()
-    })(input)
+    context(
+        "direction",
+        map(alt((tag("Forward"), tag("Reverse"))), |x: &str| {
+            x.parse().unwrap()
+        }),
+    )(input)
 }
 
 pub fn stream(input: &str) -> Result<&str, LogicalType> {
-    map_res(
+    resolved(param_stream)(input)
+}
+
+pub fn mode(input: &str) -> Result<&str, Mode> {
+    context(
+        "mode",
+        map_opt(take_while(|c: char| c.is_alphanumeric()), |x: &str| {
+            x.parse().ok()
+        }),
+    )(input)
+}
+
+/// An interface declaration, of which the type may refer to parameters and named types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceDecl {
+    pub doc: Option<String>,
+    pub name: Name,
+    pub mode: Mode,
+    pub typ: ParamType,
+}
+
+fn interface_decl(input: &str) -> Result<&str, InterfaceDecl> {
+    map(
         tuple((
-            w(tag("Stream<")),
-            w(logical_stream_type),
-            opt(preceded(
-                w(tag(",")),
-                map(
-                    separated_list0(
-                        w(tag(",")),
-                        separated_pair(
-                            w(one_of("tdscrux")),
-                            w(tag("=")),
-                            w(alt((
-                                recognize(float),
-                                recognize(digit1),
-                                recognize(synchronicity),
-                                recognize(complexity),
-                                recognize(direction),
-                                recognize(logical_stream_type),
-                                recognize(bool),
-                            ))),
-                        ),
-                    ),
-                    |opts| opts.into_iter().collect::<HashMap<char, &str>>(),
-                ),
-            )),
-            tag(">"),
+            w(doc),
+            w(context("name", name)),
+            cut(tuple((w(token(":")), mode, multispace1, param_type))),
         )),
-        |(_, data, opt, _)| -> std::result::Result<LogicalType, ()> {
-            let throughput = PositiveReal::new(
-                opt.as_ref()
-                    .and_then(|opts| opts.get(&'t').map(|x| x.parse().ok()))
-                    .flatten()
-                    .unwrap_or(1.),
-            )
-            .map_err(|_| ())?;
-
-            let dimensionality = opt
-                .as_ref()
-                .and_then(|opts| opts.get(&'d').map(|x| x.parse().ok()))
-                .flatten()
-                .unwrap_or(0);
-
-            let synchronicity = opt
-                .as_ref()
-                .and_then(|opts| {
-                    opts.get(&'s')
-                        .map(|x| synchronicity(x).ok().map(|(_, x)| x))
-                })
-                .flatten()
-                .unwrap_or_else(Synchronicity::default);
-
-            let complexity = opt
-                .as_ref()
-                .and_then(|opts| opts.get(&'c').map(|x| complexity(x).ok().map(|(_, x)| x)))
-                .flatten()
-                .unwrap_or_else(Complexity::default);
-
-            let direction = opt
-                .as_ref()
-                .and_then(|opts| opts.get(&'r').map(|x| direction(x).ok().map(|(_, x)| x)))
-                .flatten()
-                .unwrap_or_else(Direction::default);
-
-            let user = opt
-                .as_ref()
-                .and_then(|opts| {
-                    opts.get(&'u')
-                        .map(|x| logical_stream_type(x).ok().map(|(_, x)| x))
-                })
-                .unwrap_or(Option::None);
-
-            let keep = opt
-                .as_ref()
-                .and_then(|opts| opts.get(&'x').map(|x| bool(x).ok().map(|(_, x)| x)))
-                .flatten()
-                .unwrap_or(false);
-
-            Ok(Stream::new(
-                data,
-                throughput,
-                dimensionality,
-                synchronicity,
-                complexity,
-                direction,
-                user,
-                keep,
-            )
-            .into())
+        |(doc, name, (_, mode, _, typ))| InterfaceDecl {
+            doc,
+            name,
+            mode,
+            typ,
         },
     )(input)
 }
 
-pub fn mode(input: &str) -> Result<&str, Mode> {
-    map(alt((tag("in"), tag("out"))), |x: &str| x.parse().unwrap())(input)
+pub fn interface(input: &str) -> Result<&str, Interface> {
+    map_res(interface_decl, |i: InterfaceDecl| {
+        Interface::try_new(
+            i.name,
+            i.mode,
+            LogicalType::try_from(&i.typ)?,
+            i.doc.as_deref(),
+        )
+    })(input)
 }
 
-pub fn interface(input: &str) -> Result<&str, Interface> {
+/// Parses a string between double quotes, in which `\"` and `\\` escape a quote and a backslash.
+pub fn string_literal(input: &str) -> Result<&str, String> {
+    context(
+        "string",
+        map(
+            delimited(
+                char('"'),
+                many0(alt((
+                    preceded(char('\\'), cut(context("escape sequence", one_of("\"\\")))),
+                    none_of("\"\\"),
+                ))),
+                char('"'),
+            ),
+            |chars| chars.into_iter().collect(),
+        ),
+    )(input)
+}
+
+/// Parses a parameter declaration of a streamlet, e.g. `W: UInt = 8`. Every parameter must have
+/// a default value.
+pub fn parameter(input: &str) -> Result<&str, NamedParameter> {
+    let default = |kind| pair(w(tag(kind)), cut(w(token("="))));
     map_res(
         tuple((
             w(doc),
-            w(name),
-            w(tag(":")),
-            mode,
-            multispace1,
-            logical_stream_type,
+            w(context("name", name)),
+            cut(preceded(
+                w(token(":")),
+                context(
+                    "parameter kind",
+                    alt((
+                        preceded(
+                            default("UInt"),
+                            cut(context(
+                                "uint",
+                                map_res(digit1, |x: &str| x.parse().map(ParameterVariant::UInt)),
+                            )),
+                        ),
+                        preceded(
+                            default("String"),
+                            cut(map(string_literal, ParameterVariant::String)),
+                        ),
+                        preceded(
+                            default("Type"),
+                            cut(map(logical_stream_type, ParameterVariant::Type)),
+                        ),
+                    )),
+                ),
+            )),
         )),
-        |(d, n, _, m, _, t): (Option<String>, Name, _, Mode, _, LogicalType)| {
-            Interface::try_new(n, m, t, d.as_deref()).map_err(|_| ())
+        |(d, n, v): (Option<String>, Name, ParameterVariant)| {
+            NamedParameter::try_new(n, v, d.as_deref())
         },
     )(input)
 }
 
-pub fn streamlet(input: &str) -> Result<&str, Streamlet> {
-    map_res(
-        tuple((
-            w(doc),
+/// A streamlet declaration, of which the interface types may still refer to named types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamletDecl {
+    pub doc: Option<String>,
+    pub name: Name,
+    pub params: Vec<NamedParameter>,
+    pub interfaces: Vec<InterfaceDecl>,
+}
+
+impl StreamletDecl {
+    /// Construct the streamlet, using [types] to resolve references to names that are not
+    /// parameters of the streamlet.
+    pub fn into_streamlet(
+        self,
+        types: &mut dyn FnMut(&Name) -> crate::Result<LogicalType>,
+    ) -> crate::Result<Streamlet> {
+        let params =
+            ParameterStore::from_builder(self.name.clone(), self.params.into_iter().collect())?;
+        let mut templates = BTreeMap::new();
+        let mut builder = UniqueKeyBuilder::new();
+        for i in self.interfaces {
+            let typ = i.typ.with_types(Some(&params), types)?;
+            builder = builder.with_item(Interface::try_new(
+                i.name.clone(),
+                i.mode,
+                typ.resolve(&params)?,
+                i.doc.as_deref(),
+            )?);
+            if typ.is_parameterized() {
+                templates.insert(i.name, typ);
+            }
+        }
+        let streamlet = Streamlet::from_builder(self.name, builder, self.doc.as_deref())?;
+        if params.is_empty() {
+            Ok(streamlet)
+        } else {
+            Ok(streamlet.with_parameters(params, templates))
+        }
+    }
+}
+
+/// A named type declaration, e.g. `type Pixel = Bits<24>;`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeDecl {
+    pub doc: Option<String>,
+    pub name: Name,
+    pub typ: ParamType,
+}
+
+/// An import of a type declared in another library, e.g. `use common::Pixel;`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UseDecl {
+    pub lib: LibKey,
+    pub name: Name,
+}
+
+/// The declarations in a Streamlet Definition File.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Definitions {
+    /// The library the declarations belong to, if declared with `namespace` or `library`.
+    pub namespace: Option<LibKey>,
+    pub uses: Vec<UseDecl>,
+    pub types: Vec<TypeDecl>,
+    pub streamlets: Vec<StreamletDecl>,
+}
+
+/// Parses a keyword, which must be followed by whitespace.
+fn keyword<'a>(k: &'static str) -> impl FnMut(&'a str) -> Result<&'a str, &'a str> {
+    terminated(tag(k), multispace1)
+}
+
+fn streamlet_decl(doc: Option<String>) -> impl FnMut(&str) -> Result<&str, StreamletDecl> {
+    move |input| {
+        preceded(
             w(tag("Streamlet")),
-            w(name),
-            w(tag("(")),
-            separated_list0(w(tag(",")), w(interface)),
-            tag(")"),
+            cut(map(
+                tuple((
+                    w(context("name", name)),
+                    opt(delimited(
+                        w(tag("<")),
+                        list0(w(tag(",")), w(parameter)),
+                        w(token(">")),
+                    )),
+                    w(token("(")),
+                    list0(w(tag(",")), w(interface_decl)),
+                    token(")"),
+                )),
+                |(name, params, _, interfaces, _)| StreamletDecl {
+                    doc: doc.clone(),
+                    name,
+                    params: params.unwrap_or_default(),
+                    interfaces,
+                },
+            )),
+        )(input)
+    }
+}
+
+fn type_decl(doc: Option<String>) -> impl FnMut(&str) -> Result<&str, TypeDecl> {
+    move |input| {
+        preceded(
+            keyword("type"),
+            cut(map(
+                tuple((
+                    w(context("name", name)),
+                    w(token("=")),
+                    w(param_type),
+                    token(";"),
+                )),
+                |(name, _, typ, _)| TypeDecl {
+                    doc: doc.clone(),
+                    name,
+                    typ,
+                },
+            )),
+        )(input)
+    }
+}
+
+fn use_decl(input: &str) -> Result<&str, UseDecl> {
+    preceded(
+        keyword("use"),
+        cut(map(
+            tuple((
+                w(context("name", name)),
+                w(token("::")),
+                w(context("name", name)),
+                token(";"),
+            )),
+            |(lib, _, name, _)| UseDecl { lib, name },
         )),
-        |(d, _, n, _, il, _): (Option<String>, _, Name, _, Vec<Interface>, _)| {
-            Streamlet::from_builder(n, il.into_iter().collect(), d.as_deref())
-        },
     )(input)
 }
 
-pub fn list_of_streamlets(input: &str) -> Result<&str, Vec<Streamlet>> {
+fn namespace_decl(input: &str) -> Result<&str, LibKey> {
+    preceded(
+        pair(
+            comment_doc_block,
+            alt((keyword("namespace"), keyword("library"))),
+        ),
+        cut(terminated(w(context("name", name)), token(";"))),
+    )(input)
+}
+
+/// Parses a streamlet declaration. A streamlet can declare parameters between angle brackets,
+/// which can be used in the types of its interfaces, e.g.:
+/// `Streamlet fifo<W: UInt = 8> (i : in Stream<Bits<W>>, o : out Stream<Bits<W>>)`
+///
+/// Names that do not refer to parameters cannot be resolved by this parser, use [definitions]
+/// to parse declarations that refer to named types.
+pub fn streamlet(input: &str) -> Result<&str, Streamlet> {
+    let (input, d) = w(doc)(input)?;
+    map_res(streamlet_decl(d), |s| {
+        s.into_streamlet(&mut |n| {
+            Err(crate::Error::InvalidArgument(format!(
+                "Unknown parameter {}",
+                n
+            )))
+        })
+    })(input)
+}
+
+enum Declaration {
+    Use(UseDecl),
+    Type(TypeDecl),
+    Streamlet(StreamletDecl),
+}
+
+fn declaration(input: &str) -> Result<&str, Declaration> {
+    let (input, d) = w(doc)(input)?;
+    alt((
+        map(use_decl, Declaration::Use),
+        map(type_decl(d.clone()), Declaration::Type),
+        map(streamlet_decl(d), Declaration::Streamlet),
+    ))(input)
+}
+
+/// Parses all declarations in a Streamlet Definition File: an optional `namespace` (or
+/// `library`) declaration, followed by `use` imports, `type` declarations and streamlets.
+/// Fails if the input contains anything other than declarations and comments.
+pub fn definitions(input: &str) -> Result<&str, Definitions> {
     map(
-        preceded(ws0, separated_list0(ws1, streamlet)),
-        |l: Vec<Streamlet>| l,
+        preceded(
+            ws0,
+            terminated(
+                pair(opt(w(namespace_decl)), many0(w(declaration))),
+                pair(comment_doc_block, context("declaration", eof)),
+            ),
+        ),
+        |(namespace, declarations)| {
+            let mut defs = Definitions {
+                namespace,
+                ..Default::default()
+            };
+            for declaration in declarations {
+                match declaration {
+                    Declaration::Use(u) => defs.uses.push(u),
+                    Declaration::Type(t) => defs.types.push(t),
+                    Declaration::Streamlet(s) => defs.streamlets.push(s),
+                }
+            }
+            defs
+        },
     )(input)
 }
 
+/// Parses all streamlets in a Streamlet Definition File. Types declared in the file can be used
+/// by the streamlets, but the file cannot import types from other libraries.
+pub fn list_of_streamlets(input: &str) -> Result<&str, Vec<Streamlet>> {
+    map_res(definitions, |defs| {
+        crate::design::namespace::resolve_streamlets(defs)
+    })(input)
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::design::implementation::composer::GenericComponent;
     use crate::design::streamlet::tests::streamlets;
+    use crate::error::Diagnostic;
+    use crate::logical::{Group, Stream, Union};
     use crate::util::UniquelyNamedBuilder;
-    use crate::UniqueKeyBuilder;
+    use crate::Identify;
 
     use super::*;
 
@@ -611,4 +1114,241 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn parse_definitions() -> crate::Result<()> {
+        let (_, defs) = definitions(concat!(
+            "// The common library.\n",
+            "namespace common;\n",
+            "use other::Byte;\n",
+            "/// A pair of bytes.\n",
+            "type Pair = Group<a: Byte, b: Byte>;\n",
+            "Streamlet a (i: in Pair)\n",
+            "type Word = Bits<16>;",
+        ))
+        .unwrap();
+        assert_eq!(defs.namespace, Some(Name::try_new("common")?));
+        assert_eq!(
+            defs.uses,
+            vec![UseDecl {
+                lib: Name::try_new("other")?,
+                name: Name::try_new("Byte")?
+            }]
+        );
+        assert_eq!(
+            defs.types
+                .iter()
+                .map(|t| t.name.to_string())
+                .collect::<Vec<_>>(),
+            vec!["Pair", "Word"]
+        );
+        assert_eq!(defs.types[0].doc, Some(" A pair of bytes.".to_string()));
+        assert_eq!(
+            defs.streamlets[0].interfaces[0].typ,
+            ParamType::Param(Name::try_new("Pair")?)
+        );
+        // A namespace must be declared before anything else.
+        assert!(definitions("type A = Null; namespace b;").is_err());
+        assert!(definitions("use a;").is_err());
+        // Types declared in the same file can be used by list_of_streamlets.
+        assert_eq!(
+            list_of_streamlets("type T = Null; Streamlet a ( a: in T, b: out Null)"),
+            list_of_streamlets("Streamlet a ( a: in Null, b: out Null)")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_parameter() {
+        assert_eq!(
+            parameter("W: UInt = 8"),
+            Ok((
+                "",
+                NamedParameter::try_new("W", ParameterVariant::UInt(8), None).unwrap()
+            ))
+        );
+        assert_eq!(
+            parameter("re : String = \"a*b\""),
+            Ok((
+                "",
+                NamedParameter::try_new("re", ParameterVariant::String("a*b".to_string()), None)
+                    .unwrap()
+            ))
+        );
+        assert_eq!(
+            parameter("T: Type = Bits<3>"),
+            Ok((
+                "",
+                NamedParameter::try_new(
+                    "T",
+                    ParameterVariant::Type(LogicalType::try_new_bits(3).unwrap()),
+                    None
+                )
+                .unwrap()
+            ))
+        );
+        assert_eq!(
+            parameter("re : String = \"hi \\\"q\\\" \\\\\""),
+            Ok((
+                "",
+                NamedParameter::try_new(
+                    "re",
+                    ParameterVariant::String("hi \"q\" \\".to_string()),
+                    None
+                )
+                .unwrap()
+            ))
+        );
+        assert_eq!(
+            parameter("re : String = \"\""),
+            Ok((
+                "",
+                NamedParameter::try_new("re", ParameterVariant::String(String::new()), None)
+                    .unwrap()
+            ))
+        );
+        // Parameters require a default value.
+        assert!(parameter("W: UInt").is_err());
+        assert!(parameter("W: UInt = \"8\"").is_err());
+    }
+
+    #[test]
+    fn parse_parameterized_streamlet() -> crate::Result<()> {
+        let (_, s) = streamlet(
+            "Streamlet x <W: UInt = 8, D: UInt = 1, T: Type = Null, re: String = \"a\"> (
+                a : in Stream<Bits<W>, d=D>,
+                b : out Stream<Group<x: T, y: Bits<4>>>,
+                c : out Null
+            )",
+        )
+        .unwrap();
+        assert_eq!(s.parameters().unwrap().params().count(), 4);
+        assert_eq!(
+            s.get_interface(Name::try_new("a")?)?.typ(),
+            Stream::new(
+                LogicalType::try_new_bits(8)?,
+                PositiveReal::new(1.).unwrap(),
+                1,
+                Synchronicity::Sync,
+                Complexity::default(),
+                Direction::Forward,
+                None,
+                false
+            )
+            .into()
+        );
+
+        let i = s.instantiate(
+            Name::try_new("y")?,
+            vec![
+                (Name::try_new("W")?, ParameterVariant::UInt(16)),
+                (
+                    Name::try_new("T")?,
+                    ParameterVariant::Type(LogicalType::try_new_bits(2)?),
+                ),
+            ],
+        )?;
+        assert_eq!(i.identifier(), "y");
+        assert_eq!(
+            i.get_interface(Name::try_new("a")?)?.typ(),
+            stream("Stream<Bits<16>, d=1>").unwrap().1
+        );
+        assert_eq!(
+            i.get_interface(Name::try_new("b")?)?.typ(),
+            stream("Stream<Group<x: Bits<2>, y: Bits<4>>>").unwrap().1
+        );
+        assert_eq!(
+            i.parameters().unwrap().get(Name::try_new("W")?)?.item(),
+            &ParameterVariant::UInt(16)
+        );
+        // The original streamlet is not affected.
+        assert_eq!(
+            s.get_interface(Name::try_new("a")?)?.typ(),
+            stream("Stream<Bits<8>, d=1>").unwrap().1
+        );
+
+        assert!(s
+            .instantiate(
+                Name::try_new("z")?,
+                vec![(
+                    Name::try_new("W")?,
+                    ParameterVariant::String("8".to_string())
+                )]
+            )
+            .is_err());
+
+        // Parameters must be declared before they can be used.
+        assert!(streamlet("Streamlet x (a : in Bits<W>)").is_err());
+        Ok(())
+    }
+
+    fn diagnose(input: &str) -> Diagnostic {
+        diagnostic(
+            Some("lib.sdf"),
+            input,
+            list_of_streamlets(input).unwrap_err(),
+        )
+    }
+
+    #[test]
+    fn parse_diagnostics() {
+        let d = diagnose("Streamlet a (x: in Null)\nStreamlet b (y: in Stream<Bits<8>, s=sync>)");
+        assert_eq!((d.line, d.column, d.width), (2, 38, 4));
+        assert_eq!(d.message, "expected synchronicity, found `sync`");
+        assert_eq!(
+            d.to_string(),
+            concat!(
+                "expected synchronicity, found `sync`\n",
+                " --> lib.sdf:2:38\n",
+                "  |\n",
+                "2 | Streamlet b (y: in Stream<Bits<8>, s=sync>)\n",
+                "  |                                      ^^^^\n",
+                "  = hint: unknown synchronicity 'sync', expected one of Sync, Flatten, Desync, ",
+                "FlatDesync"
+            )
+        );
+
+        let d = diagnose("Streamlet a (x: in Stream<Bits<8>, q=1>)");
+        assert_eq!(d.message, "expected stream property, found `q`");
+        assert_eq!(d.column, 36);
+
+        let d = diagnose("Streamlet a (x: in Group<a: Bits<8>, b Null>)");
+        assert_eq!(d.message, "expected `:`, found `Null`");
+
+        let d = diagnose("Streamlet a (x: in Bits<8)");
+        assert_eq!(d.message, "expected `>`, found `)`");
+
+        let d = diagnose("Streamlet a (x: inout Null)");
+        assert_eq!(d.message, "expected interface mode, found `inout`");
+        assert_eq!(
+            d.hint,
+            Some("unknown mode 'inout', expected one of in, out".to_string())
+        );
+
+        let d = diagnose("Streamlet a (x: in Bits<0>)");
+        assert_eq!(d.message, "bit count cannot be zero");
+        assert_eq!((d.line, d.column, d.width), (1, 25, 1));
+
+        let d = diagnose("Streamlet a (x: in Stream<Bits<8>, c=9>)");
+        assert_eq!(d.message, "complexity must be between 1 and 8, found 9");
+        assert_eq!((d.column, d.width), (38, 1));
+
+        let d = diagnose("Streamlet a <S: String = \"\\n\"> (x: in Null)");
+        assert_eq!(d.message, "expected escape sequence, found `n`");
+        assert_eq!(d.column, 28);
+        assert_eq!(
+            d.hint,
+            Some("unknown escape sequence 'n', expected one of \\\", \\\\".to_string())
+        );
+
+        let d = diagnose("Streamlet a (x: in Null)\n\nStreamlt b (y: in Null)");
+        assert_eq!((d.line, d.column), (3, 1));
+        assert_eq!(
+            d.message,
+            "expected declaration (`use`, `type` or `Streamlet`), found `Streamlt`"
+        );
+
+        let d = diagnose("Streamlet a (x: in Null");
+        assert_eq!(d.message, "expected `)`, found end of input");
+    }
 }
@@ -0,0 +1,622 @@
+//! Printer for Streamlet Definition Files.
+//!
+//! The [Printer] is the inverse of the parsers in [crate::parser::nom]: it turns declarations,
+//! streamlets and libraries back into Streamlet Definition File syntax. The output is
+//! normalised, such that parsing and printing it again results in the same text:
+//!
+//! - a file starts with its `namespace` declaration, followed by its `use` imports in
+//!   alphabetical order, its `type` declarations and its streamlets, each in the order they were
+//!   declared,
+//! - streamlets declared in Rust are printed in alphabetical order, as are their interfaces,
+//! - interfaces are printed on their own line, as are parameters, unless a streamlet has a
+//!   single parameter without documentation, which is printed inline,
+//! - properties of streams that have their default value are left out, unless the printer is
+//!   configured to print them explicitly.
+//!
+//! Doc comments are preserved. Other comments are not part of the parsed declarations, so
+//! [format] refuses to format files containing them rather than dropping them.
+
+use std::fmt::Write;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::library::TypeName;
+use crate::design::param::{NamedParameter, ParamStream, ParamType, ParamUInt, ParameterVariant};
+use crate::design::{Library, Mode, Streamlet};
+use crate::error::Error::SDFParsingError;
+use crate::logical::{Direction, LogicalType, Synchronicity};
+use crate::parser::nom::{
+    definitions, diagnostic, Definitions, InterfaceDecl, StreamletDecl, TypeDecl, UseDecl,
+};
+use crate::physical::Complexity;
+use crate::{Document, Error, Identify, Name, PathName, Result};
+
+const INDENT: &str = "    ";
+
+/// Prints declarations in Streamlet Definition File syntax.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Printer {
+    explicit_defaults: bool,
+}
+
+impl Printer {
+    /// Construct a printer that leaves out properties of streams with default values.
+    pub fn new() -> Self {
+        Printer::default()
+    }
+
+    /// Print all properties of streams, including those with default values.
+    pub fn with_explicit_defaults(mut self, explicit_defaults: bool) -> Self {
+        self.explicit_defaults = explicit_defaults;
+        self
+    }
+
+    /// Print a logical type.
+    pub fn logical_type(&self, typ: &LogicalType) -> String {
+        self.param_type(&ParamType::from(typ))
+    }
+
+    /// Print a logical type which may refer to parameters or named types.
+    pub fn param_type(&self, typ: &ParamType) -> String {
+        let fields = |fields: &Vec<(Name, ParamType)>| {
+            fields
+                .iter()
+                .map(|(name, typ)| format!("{}: {}", name, self.param_type(typ)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        match typ {
+            ParamType::Null => "Null".to_string(),
+            ParamType::Bits(width) => format!("Bits<{}>", uint(width)),
+            ParamType::Group(f) => format!("Group<{}>", fields(f)),
+            ParamType::Union(f) => format!("Union<{}>", fields(f)),
+            ParamType::Stream(stream) => {
+                let explicit = self.explicit_defaults;
+                let mut result = format!("Stream<{}", self.param_type(&stream.data));
+                if explicit || stream.throughput.get() != 1. {
+                    write!(result, ", t={}", stream.throughput.get()).unwrap();
+                }
+                if explicit || stream.dimensionality != ParamUInt::Literal(0) {
+                    write!(result, ", d={}", uint(&stream.dimensionality)).unwrap();
+                }
+                if explicit || stream.synchronicity != Synchronicity::default() {
+                    write!(result, ", s={:?}", stream.synchronicity).unwrap();
+                }
+                if explicit || stream.complexity != Complexity::default() {
+                    write!(result, ", c={}", stream.complexity).unwrap();
+                }
+                if explicit || stream.direction != Direction::default() {
+                    write!(result, ", r={:?}", stream.direction).unwrap();
+                }
+                if let Some(user) = &stream.user {
+                    write!(result, ", u={}", self.param_type(user)).unwrap();
+                }
+                if explicit || stream.keep {
+                    write!(result, ", x={}", stream.keep).unwrap();
+                }
+                result.push('>');
+                result
+            }
+            ParamType::Param(name) => name.to_string(),
+        }
+    }
+
+    /// Print a parameter declaration, e.g. `W: UInt = 8`.
+    pub fn parameter(&self, param: &NamedParameter) -> String {
+        let value = match param.item() {
+            ParameterVariant::Type(typ) => self.logical_type(typ),
+            ParameterVariant::String(s) => {
+                format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            ParameterVariant::UInt(u) => u.to_string(),
+        };
+        format!(
+            "{}{}: {} = {}",
+            doc(param.doc(), INDENT),
+            param.key(),
+            param.item().kind(),
+            value
+        )
+    }
+
+    /// Print an interface declaration, e.g. `a: in Stream<Bits<8>>`.
+    pub fn interface_decl(&self, interface: &InterfaceDecl) -> String {
+        format!(
+            "{}{}: {} {}",
+            doc(interface.doc.clone(), INDENT),
+            interface.name,
+            mode(interface.mode),
+            self.param_type(&interface.typ)
+        )
+    }
+
+    /// Print a streamlet declaration.
+    pub fn streamlet_decl(&self, streamlet: &StreamletDecl) -> String {
+        self.streamlet_with(
+            streamlet.doc.clone(),
+            &streamlet.name,
+            streamlet.params.iter().map(|p| self.parameter(p)).collect(),
+            streamlet
+                .interfaces
+                .iter()
+                .map(|i| self.interface_decl(i))
+                .collect(),
+        )
+    }
+
+    /// Print a streamlet. Interfaces of which the type depends on the parameters of the
+    /// streamlet are printed in terms of these parameters. Named types are printed as their
+    /// definitions, use [Printer::library] to refer to them by name.
+    pub fn streamlet(&self, streamlet: &Streamlet) -> String {
+        self.streamlet_in(streamlet, &|_| None)
+    }
+
+    /// Print a streamlet, referring to the named types that [named] returns the definitions of.
+    fn streamlet_in(&self, streamlet: &Streamlet, named: &Named) -> String {
+        let params = match streamlet.parameters() {
+            Some(store) => store.params().map(|p| self.parameter(p)).collect(),
+            None => vec![],
+        };
+        let interfaces = streamlet
+            .interfaces()
+            .map(|i| {
+                let typ = match streamlet.template(i.key()) {
+                    Some(template) => template.clone(),
+                    None => with_names(&i.typ(), &PathName::new_empty(), i.type_names(), named),
+                };
+                self.interface_decl(&InterfaceDecl {
+                    doc: i.doc(),
+                    name: i.key().clone(),
+                    mode: i.mode(),
+                    typ,
+                })
+            })
+            .collect();
+        self.streamlet_with(streamlet.doc(), &streamlet.key(), params, interfaces)
+    }
+
+    fn streamlet_with(
+        &self,
+        streamlet_doc: Option<String>,
+        name: &Name,
+        params: Vec<String>,
+        interfaces: Vec<String>,
+    ) -> String {
+        let list = |items: Vec<String>| {
+            items
+                .iter()
+                .map(|item| format!("{}{}", INDENT, item))
+                .collect::<Vec<String>>()
+                .join(",\n")
+        };
+        let mut result = format!("{}Streamlet {}", doc(streamlet_doc, ""), name);
+        match params.as_slice() {
+            [] => (),
+            [param] if !param.contains('\n') => write!(result, "<{}>", param).unwrap(),
+            _ => write!(result, "<\n{}\n>", list(params)).unwrap(),
+        }
+        if interfaces.is_empty() {
+            result.push_str(" ()");
+        } else {
+            write!(result, " (\n{}\n)", list(interfaces)).unwrap();
+        }
+        result
+    }
+
+    /// Print a named type declaration, e.g. `type Pixel = Bits<24>;`.
+    pub fn type_decl(&self, decl: &TypeDecl) -> String {
+        format!(
+            "{}type {} = {};",
+            doc(decl.doc.clone(), ""),
+            decl.name,
+            self.param_type(&decl.typ)
+        )
+    }
+
+    /// Print all declarations of a Streamlet Definition File.
+    pub fn definitions(&self, defs: &Definitions) -> String {
+        let mut uses = defs.uses.clone();
+        uses.sort_by(|a, b| (&a.lib, &a.name).cmp(&(&b.lib, &b.name)));
+        uses.dedup();
+        self.file(
+            defs.namespace.as_ref(),
+            &uses,
+            defs.types.iter().map(|t| self.type_decl(t)).collect(),
+            defs.streamlets
+                .iter()
+                .map(|s| self.streamlet_decl(s))
+                .collect(),
+        )
+    }
+
+    /// Print a library as a Streamlet Definition File declaring its namespace. Named types
+    /// are printed in the order they were added to the library, streamlets in alphabetical
+    /// order. Types refer to the named types they were declared with.
+    pub fn library(&self, library: &Library) -> String {
+        let named = |name: &TypeName| {
+            if name.lib() == library.key() {
+                library.get_type(name.key()).ok()
+            } else {
+                library
+                    .imports()
+                    .find(|(lib, typ)| *lib == name.lib() && typ.key() == name.key())
+                    .map(|(_, typ)| typ)
+            }
+            .map(|typ| typ.logical_type())
+        };
+        let mut uses: Vec<UseDecl> = library
+            .imports()
+            .map(|(lib, typ)| UseDecl {
+                lib: lib.clone(),
+                name: typ.key().clone(),
+            })
+            .collect();
+        uses.sort_by(|a, b| (&a.lib, &a.name).cmp(&(&b.lib, &b.name)));
+        let mut streamlets: Vec<&Streamlet> = library.streamlets().collect();
+        streamlets.sort_by_key(|s| s.identifier());
+        self.file(
+            Some(library.key()),
+            &uses,
+            library
+                .types()
+                .map(|t| {
+                    self.type_decl(&TypeDecl {
+                        doc: t.doc(),
+                        name: t.key().clone(),
+                        typ: with_names(
+                            t.logical_type(),
+                            &PathName::new_empty(),
+                            t.type_names(),
+                            &named,
+                        ),
+                    })
+                })
+                .collect(),
+            streamlets
+                .into_iter()
+                .map(|s| self.streamlet_in(s, &named))
+                .collect(),
+        )
+    }
+
+    fn file(
+        &self,
+        namespace: Option<&Name>,
+        uses: &[UseDecl],
+        types: Vec<String>,
+        streamlets: Vec<String>,
+    ) -> String {
+        let mut sections = vec![];
+        if let Some(namespace) = namespace {
+            sections.push(format!("namespace {};", namespace));
+        }
+        if !uses.is_empty() {
+            sections.push(
+                uses.iter()
+                    .map(|u| format!("use {}::{};", u.lib, u.name))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            );
+        }
+        if !types.is_empty() {
+            sections.push(types.join("\n"));
+        }
+        sections.extend(streamlets);
+        let mut result = sections.join("\n\n");
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        result
+    }
+}
+
+/// Returns the definition of a named type, if it is known.
+type Named<'a> = dyn Fn(&TypeName) -> Option<&'a LogicalType> + 'a;
+
+/// Convert [typ], used at [path] of a type that is composed of the named types in [names], into
+/// a type that refers to these named types where their definitions are used.
+fn with_names(typ: &LogicalType, path: &PathName, names: &[TypeName], named: &Named) -> ParamType {
+    if let Some(name) = names
+        .iter()
+        .filter(|name| name.path() == path)
+        .find(|name| named(name) == Some(typ))
+    {
+        return ParamType::Param(name.key().clone());
+    }
+    let fields = |fields: &mut dyn Iterator<Item = (&Name, &LogicalType)>| {
+        fields
+            .map(|(field, typ)| {
+                let mut path = path.clone();
+                path.push(field.clone());
+                (field.clone(), with_names(typ, &path, names, named))
+            })
+            .collect()
+    };
+    match typ {
+        LogicalType::Group(group) => ParamType::Group(fields(&mut group.iter())),
+        LogicalType::Union(union) => ParamType::Union(fields(&mut union.iter())),
+        // Streams do not add to the path.
+        LogicalType::Stream(stream) => ParamType::Stream(Box::new(ParamStream {
+            data: with_names(stream.data(), path, names, named),
+            throughput: stream.throughput(),
+            dimensionality: ParamUInt::Literal(stream.dimensionality()),
+            synchronicity: stream.synchronicity(),
+            complexity: stream.complexity().clone(),
+            direction: stream.direction(),
+            user: stream
+                .user()
+                .map(|user| with_names(user, path, names, named)),
+            keep: stream.keep(),
+        })),
+        _ => ParamType::from(typ),
+    }
+}
+
+fn uint(value: &ParamUInt) -> String {
+    match value {
+        ParamUInt::Literal(value) => value.to_string(),
+        ParamUInt::Param(name) => name.to_string(),
+    }
+}
+
+fn mode(mode: Mode) -> &'static str {
+    match mode {
+        Mode::In => "in",
+        Mode::Out => "out",
+    }
+}
+
+/// Print a doc comment followed by a newline and [indent], or nothing if there is no doc.
+fn doc(doc: Option<String>, indent: &str) -> String {
+    match doc {
+        Some(doc) => doc
+            .lines()
+            .map(|line| format!("///{}\n{}", line, indent))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Returns true if [input] contains comments other than doc comments, outside of string
+/// literals.
+fn has_plain_comments(input: &str) -> bool {
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        // Skip escaped quotes and backslashes.
+                        '\\' => {
+                            chars.next();
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            '/' => match chars.peek() {
+                Some('*') => return true,
+                Some('/') => {
+                    chars.next();
+                    if chars.peek() != Some(&'/') {
+                        return true;
+                    }
+                    // Skip the rest of the doc comment.
+                    for c in &mut chars {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+    false
+}
+
+/// Format the contents of a Streamlet Definition File using the default [Printer]. [file] is
+/// used in error messages.
+///
+/// Fails if the input cannot be parsed, or if it contains comments that are not doc comments,
+/// as these would be lost.
+pub fn format(file: Option<&str>, input: &str) -> Result<String> {
+    let defs = definitions(input)
+        .map_err(|e| SDFParsingError(diagnostic(file, input, e)))?
+        .1;
+    if has_plain_comments(input) {
+        return Err(Error::InvalidArgument(format!(
+            "{}contains comments that are not doc comments (///), which cannot be formatted",
+            file.map(|f| format!("{}: ", f)).unwrap_or_default()
+        )));
+    }
+    Ok(Printer::new().definitions(&defs))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::design::namespace::{resolve, Source};
+    use crate::parser::nom::streamlet;
+
+    use super::*;
+
+    #[test]
+    fn types() -> Result<()> {
+        let printer = Printer::new();
+        let parse = |input: &str| crate::parser::nom::param_type(input).unwrap().1;
+        for input in &[
+            "Null",
+            "Bits<8>",
+            "Bits<W>",
+            "Group<a: Bits<1>, b: Union<x: Null, y: Pixel>>",
+            "Stream<Bits<8>>",
+            "Stream<Bits<8>, t=2.5, d=D, s=FlatDesync, c=7.1, r=Reverse, u=Bits<2>, x=true>",
+        ] {
+            assert_eq!(&printer.param_type(&parse(input)), input);
+        }
+        assert_eq!(
+            printer
+                .with_explicit_defaults(true)
+                .param_type(&parse("Stream<Bits<8>>")),
+            "Stream<Bits<8>, t=1, d=0, s=Sync, c=4, r=Forward, x=false>"
+        );
+        assert_eq!(
+            printer.logical_type(&LogicalType::try_from(&parse("Stream<Null, d=2>"))?),
+            "Stream<Null, d=2>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn files() -> Result<()> {
+        let input = "namespace  lib ;
+use other::Y; use other::X;
+/// A pixel.
+type Pixel=Bits<24>;
+/// A streamlet.
+/// With two lines of documentation.
+Streamlet s < /// Width.
+W : UInt = 8, N: String = \"n\", T: Type = Group<a: Null> > (
+  /// Input.
+  i : in Stream<Bits<W>, d=1>, o : out Stream<Pixel, c=4>)
+Streamlet empty()
+";
+        let expected = "namespace lib;
+
+use other::X;
+use other::Y;
+
+/// A pixel.
+type Pixel = Bits<24>;
+
+/// A streamlet.
+/// With two lines of documentation.
+Streamlet s<
+    /// Width.
+    W: UInt = 8,
+    N: String = \"n\",
+    T: Type = Group<a: Null>
+> (
+    /// Input.
+    i: in Stream<Bits<W>, d=1>,
+    o: out Stream<Pixel>
+)
+
+Streamlet empty ()
+";
+        let formatted = format(None, input)?;
+        assert_eq!(formatted, expected);
+        // Formatting is idempotent and does not change the declarations.
+        assert_eq!(format(None, &formatted)?, formatted);
+        assert_eq!(definitions(&formatted).unwrap().1, {
+            let mut defs = definitions(input).unwrap().1;
+            defs.uses.reverse();
+            defs
+        });
+
+        assert_eq!(
+            format(Some("a.sdf"), "// comment\nStreamlet x ()"),
+            Err(Error::InvalidArgument(
+                "a.sdf: contains comments that are not doc comments (///), which cannot be formatted"
+                    .to_string()
+            ))
+        );
+        assert!(format(None, "/* comment */").is_err());
+        assert!(format(None, "Streamlet x<S: String = \"//\"> ()").is_ok());
+        // Quotes and backslashes in strings are escaped, a single parameter is printed inline.
+        assert_eq!(
+            format(None, "Streamlet x < S:String=\"\\\"//\\\\\" > ()")?,
+            "Streamlet x<S: String = \"\\\"//\\\\\"> ()\n"
+        );
+        // Unless it is documented.
+        assert_eq!(
+            format(None, "Streamlet x</// Width.\nW: UInt = 8> ()")?,
+            "Streamlet x<\n    /// Width.\n    W: UInt = 8\n> ()\n"
+        );
+        assert!(format(None, "Streamlet x (").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn libraries() -> Result<()> {
+        let input = "/// Takes a pixel.
+Streamlet b<W: UInt = 8> (i: in Stream<Bits<W>>, p: in Stream<Pixel>)
+Streamlet a (o: out Stream<Bits<1>, d=1>, f: in Frame)
+type Pixel = Group<r: Channel, g: Channel, b: Channel>;
+type Channel = Bits<8>;
+type Frame = Stream<Pixel, d=2>;";
+        let resolve_lib = |input: &str| -> Result<Library> {
+            Ok(resolve(&[Source::new(
+                None,
+                Name::try_new("lib")?,
+                definitions(input).unwrap().1,
+            )])?
+            .remove(0))
+        };
+        let lib = resolve_lib(input)?;
+        let printed = Printer::new().library(&lib);
+        assert_eq!(
+            printed,
+            "namespace lib;
+
+type Channel = Bits<8>;
+type Pixel = Group<r: Channel, g: Channel, b: Channel>;
+type Frame = Stream<Pixel, d=2>;
+
+Streamlet a (
+    f: in Frame,
+    o: out Stream<Bits<1>, d=1>
+)
+
+/// Takes a pixel.
+Streamlet b<W: UInt = 8> (
+    i: in Stream<Bits<W>>,
+    p: in Stream<Pixel>
+)
+"
+        );
+        // The printed library can be parsed into the same library.
+        let parsed = resolve_lib(&printed)?;
+        assert_eq!(Printer::new().library(&parsed), printed);
+        // Without a library, named types are printed as their definitions.
+        let b = lib.get_streamlet(Name::try_new("b")?)?;
+        let printed = Printer::new().streamlet(b);
+        assert!(printed.contains("p: in Stream<Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>>"));
+        let parsed = streamlet(&printed).unwrap().1;
+        assert_eq!(Printer::new().streamlet(&parsed), printed);
+        Ok(())
+    }
+
+    #[test]
+    fn imported_types() -> Result<()> {
+        let libs = resolve(&[
+            Source::new(
+                None,
+                Name::try_new("app")?,
+                definitions("use common::Pixel; Streamlet s (i: in Stream<Group<a: Pixel>>)")
+                    .unwrap()
+                    .1,
+            ),
+            Source::new(
+                None,
+                Name::try_new("common")?,
+                definitions("type Pixel = Bits<24>;").unwrap().1,
+            ),
+        ])?;
+        assert_eq!(
+            Printer::new().library(&libs[0]),
+            "namespace app;
+
+use common::Pixel;
+
+Streamlet s (
+    i: in Stream<Group<a: Pixel>>
+)
+"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,611 @@
+//! Reader for Value Change Dump files.
+//!
+//! This module reads [Value Change Dump] (VCD) files, as written by simulators
+//! such as GHDL, and reconstructs the traces of the physical streams of a
+//! [`Streamlet`] from the canonical port names that the VHDL back-end
+//! generates, e.g. `a_valid`, `a_ready` and `a_data` for an interface `a`.
+//! The traces can then be checked and decoded in Tydi terms with the
+//! [`checker`] module, and their elements split into the values of the
+//! element fields of the physical stream.
+//!
+//! Signals are sampled once per rising edge of the clock, using the values
+//! they had just before the edge. Values with fewer bits than their variable
+//! are extended on the left as the VCD format specifies: with zeros if the
+//! leftmost bit is `0` or `1`, and with the leftmost bit otherwise. Unknown
+//! values, like `x`, `z` and `u`, are read as zeros.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tydi::design::{Interface, Mode, Streamlet};
+//! use tydi::logical::{LogicalType, Stream};
+//! use tydi::parser::vcd::Vcd;
+//! use tydi::physical::transfer::Data;
+//! use tydi::{Name, UniqueKeyBuilder};
+//!
+//! let typ = LogicalType::Stream(Stream::new_basic(LogicalType::try_new_bits(4)?));
+//! let streamlet = Streamlet::from_builder(
+//!     Name::try_new("example")?,
+//!     UniqueKeyBuilder::new().with_items(vec![Interface::try_new("a", Mode::In, typ, None)?]),
+//!     None,
+//! )?;
+//! let vcd: Vcd = "
+//! $scope module dut $end
+//! $var reg 1 ! clk $end
+//! $var reg 1 \" a_valid $end
+//! $var reg 1 # a_ready $end
+//! $var reg 4 $ a_data[3:0] $end
+//! $upscope $end
+//! $enddefinitions $end
+//! #0 0! 1\" 1# b101 $
+//! #5 1!
+//! #10 0! 0\"
+//! #15 1!
+//! ".parse()?;
+//!
+//! let traces = vcd.traces("dut", &streamlet)?;
+//! assert_eq!(traces[0].port("valid"), "a_valid");
+//! assert!(traces[0].check().is_empty());
+//! assert_eq!(traces[0].transfer_data()?, vec![Data::element("0101".parse()?)]);
+//! assert_eq!(traces[0].field_data()?[0].to_string(), "{0101}");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [Value Change Dump]: https://en.wikipedia.org/wiki/Value_change_dump
+//! [`Streamlet`]: ../../design/struct.Streamlet.html
+//! [`checker`]: ../../physical/checker/index.html
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::{IFKey, Streamlet};
+use crate::physical::checker::{Checker, Cycle, Violation};
+use crate::physical::transfer::{BitVec, Data, FieldData, Transfer};
+use crate::physical::PhysicalStream;
+use crate::{Error, PathName, Result};
+
+/// A variable declared in a Value Change Dump.
+#[derive(Debug, Clone, PartialEq)]
+struct Variable {
+    /// The index of the identifier code of the variable.
+    code: usize,
+    /// The number of bits of the variable.
+    width: usize,
+}
+
+/// The contents of a Value Change Dump file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vcd {
+    /// The variables by their names, including their scopes, separated by
+    /// dots.
+    variables: HashMap<String, Variable>,
+    /// The number of distinct identifier codes.
+    codes: usize,
+    /// The value changes in order of time, with the index of the identifier
+    /// code and the new value, most significant bit first.
+    changes: Vec<(u64, usize, String)>,
+}
+
+impl Vcd {
+    /// Returns the names of the variables in this dump, including their
+    /// scopes, separated by dots.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.variables.keys().map(|name| name.as_str())
+    }
+
+    /// Returns the values of the variables with the given `names`, sampled
+    /// at every rising edge of the variable named `clock`. Every sample has
+    /// the values in the order of `names`. Returns an error if a value has
+    /// more bits than its variable.
+    pub fn sample(&self, clock: &str, names: &[&str]) -> Result<Vec<Vec<BitVec>>> {
+        let variable = |name: &str| {
+            self.variables.get(name).ok_or_else(|| {
+                Error::InvalidArgument(format!("variable {} does not exist in the dump", name))
+            })
+        };
+        let clock = variable(clock)?.code;
+        let variables = names
+            .iter()
+            .map(|name| variable(name))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut values = vec![String::new(); self.codes];
+        let mut samples = vec![];
+        let mut changes = self.changes.iter().peekable();
+        while let Some((time, _, _)) = changes.peek() {
+            let time = *time;
+            let mut group = vec![];
+            while let Some((_, code, value)) = changes.next_if(|(t, _, _)| *t == time) {
+                group.push((*code, value));
+            }
+            let rising = group
+                .iter()
+                .any(|(code, value)| *code == clock && is_high(value))
+                && !is_high(&values[clock]);
+            if rising {
+                samples.push(
+                    variables
+                        .iter()
+                        .zip(names)
+                        .map(|(variable, name)| {
+                            bit_vec(&values[variable.code], variable.width).map_err(|_| {
+                                Error::ParsingError(format!(
+                                    "value {} of {} at time {} has more than {} bits",
+                                    values[variable.code], name, time, variable.width
+                                ))
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                );
+            }
+            for (code, value) in group {
+                values[code] = value.clone();
+            }
+        }
+        Ok(samples)
+    }
+
+    /// Returns the traces of the physical streams of the interfaces of
+    /// `streamlet`, reconstructed from the canonical ports in `scope`. The
+    /// signals are sampled on the rising edges of the `clk` port.
+    pub fn traces(&self, scope: &str, streamlet: &Streamlet) -> Result<Vec<Trace>> {
+        let name = |port: &str| {
+            if scope.is_empty() {
+                port.to_string()
+            } else {
+                format!("{}.{}", scope, port)
+            }
+        };
+        let clock = name("clk");
+
+        let mut result = vec![];
+        for interface in streamlet.interfaces() {
+            for (path, stream) in interface.typ().synthesize().streams() {
+                let mut trace = Trace {
+                    interface: interface.key().clone(),
+                    path: path.clone(),
+                    stream: stream.clone(),
+                    cycles: vec![],
+                };
+                let transfer = Transfer::new(stream);
+                let signals: Vec<&str> = transfer.signals().map(|(signal, _)| signal).collect();
+                let ports: Vec<String> = ["valid", "ready"]
+                    .iter()
+                    .chain(signals.iter())
+                    .map(|signal| name(&trace.port(signal)))
+                    .collect();
+                let ports: Vec<&str> = ports.iter().map(|port| port.as_str()).collect();
+
+                for sample in self.sample(&clock, &ports)? {
+                    let mut transfer = transfer.clone();
+                    for (signal, value) in signals.iter().zip(sample[2..].iter()) {
+                        transfer.set_signal(signal, value.clone())?;
+                    }
+                    let high = |value: &BitVec| value.get(0);
+                    trace
+                        .cycles
+                        .push(Cycle::new(high(&sample[0]), high(&sample[1]), transfer));
+                }
+                result.push(trace);
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl FromStr for Vcd {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        fn error<T>(message: String) -> Result<T> {
+            Err(Error::ParsingError(message))
+        }
+        fn skip(tokens: &mut std::str::SplitWhitespace, keyword: &str) -> Result<()> {
+            if tokens.any(|token| token == "$end") {
+                Ok(())
+            } else {
+                error(format!("missing $end after {}", keyword))
+            }
+        }
+        let mut tokens = input.split_whitespace();
+
+        let mut variables = HashMap::new();
+        let mut codes: HashMap<String, usize> = HashMap::new();
+        let mut scopes: Vec<String> = vec![];
+        loop {
+            match tokens.next() {
+                Some("$scope") => {
+                    match (tokens.next(), tokens.next(), tokens.next()) {
+                        (Some(_), Some(name), Some("$end")) => scopes.push(name.to_string()),
+                        _ => return error("malformed $scope declaration".to_string()),
+                    };
+                }
+                Some("$upscope") => {
+                    if scopes.pop().is_none() {
+                        return error("$upscope outside of a scope".to_string());
+                    }
+                    skip(&mut tokens, "$upscope")?;
+                }
+                Some("$var") => {
+                    let (width, code, reference) =
+                        match (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+                            (Some(_), Some(width), Some(code), Some(reference)) => {
+                                (width, code, reference)
+                            }
+                            _ => return error("malformed $var declaration".to_string()),
+                        };
+                    let width = match width.parse::<usize>() {
+                        Ok(width) => width,
+                        Err(_) => {
+                            return error(format!("invalid width {} of {}", width, reference))
+                        }
+                    };
+                    // Strip the bit range, e.g. [7:0], which may be a separate token.
+                    let reference = reference.split('[').next().unwrap_or(reference);
+                    skip(&mut tokens, "$var")?;
+                    let count = codes.len();
+                    let code = *codes.entry(code.to_string()).or_insert(count);
+                    let mut name = scopes.join(".");
+                    if !name.is_empty() {
+                        name.push('.');
+                    }
+                    name.push_str(reference);
+                    variables.insert(name, Variable { code, width });
+                }
+                Some("$enddefinitions") => {
+                    skip(&mut tokens, "$enddefinitions")?;
+                    break;
+                }
+                Some(keyword) if keyword.starts_with('$') => skip(&mut tokens, keyword)?,
+                Some(token) => return error(format!("unexpected {} in declarations", token)),
+                None => return error("missing $enddefinitions".to_string()),
+            }
+        }
+
+        let code = |code: &str| match codes.get(code) {
+            Some(code) => Ok(*code),
+            None => error(format!("undeclared identifier code {}", code)),
+        };
+        let mut changes = vec![];
+        let mut time = 0;
+        while let Some(token) = tokens.next() {
+            let mut chars = token.chars();
+            match chars.next() {
+                Some('#') => {
+                    time = match token[1..].parse() {
+                        Ok(time) => time,
+                        Err(_) => return error(format!("invalid time {}", token)),
+                    }
+                }
+                Some('$') => match token {
+                    "$comment" => skip(&mut tokens, token)?,
+                    // The values in these sections are regular value changes.
+                    "$dumpvars" | "$dumpall" | "$dumpon" | "$dumpoff" | "$end" => (),
+                    _ => return error(format!("unexpected {} in value changes", token)),
+                },
+                Some('b') | Some('B') => match tokens.next() {
+                    Some(id) => changes.push((time, code(id)?, token[1..].to_lowercase())),
+                    None => return error(format!("missing identifier code after {}", token)),
+                },
+                Some('r') | Some('R') => {
+                    // Real values can't be signals of physical streams.
+                    tokens.next();
+                }
+                Some(value) if "01xzuwlh-".contains(value.to_ascii_lowercase()) => changes.push((
+                    time,
+                    code(chars.as_str())?,
+                    value.to_lowercase().to_string(),
+                )),
+                _ => return error(format!("invalid value change {}", token)),
+            }
+        }
+        changes.sort_by_key(|(time, _, _)| *time);
+
+        Ok(Vcd {
+            variables,
+            codes: codes.len(),
+            changes,
+        })
+    }
+}
+
+/// Returns true if a value of a single bit is high.
+fn is_high(value: &str) -> bool {
+    value == "1" || value == "h"
+}
+
+/// Returns the bits of a value of a variable with `width` bits. Values with
+/// fewer bits are extended on the left with zeros if their leftmost bit is
+/// `0` or `1`, and with their leftmost bit otherwise. Unknown bits are read as
+/// zeros. Returns an error if the value has more than `width` bits.
+fn bit_vec(value: &str, width: usize) -> Result<BitVec> {
+    let count = value.chars().count();
+    if count > width {
+        return Err(Error::InvalidArgument(format!(
+            "value {} has more than {} bits",
+            value, width
+        )));
+    }
+    let extension = match value.chars().next() {
+        Some('0') | Some('1') | None => '0',
+        Some(c) => c,
+    };
+    Ok(extension
+        .to_string()
+        .repeat(width - count)
+        .chars()
+        .chain(value.chars())
+        .rev()
+        .map(|c| c == '1' || c == 'h')
+        .collect::<Vec<bool>>()
+        .into())
+}
+
+/// The trace of a physical stream of an interface of a streamlet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    interface: IFKey,
+    path: PathName,
+    stream: PhysicalStream,
+    cycles: Vec<Cycle>,
+}
+
+impl Trace {
+    /// Returns the key of the interface.
+    pub fn interface(&self) -> &IFKey {
+        &self.interface
+    }
+
+    /// Returns the path of the physical stream within the interface.
+    pub fn path(&self) -> &PathName {
+        &self.path
+    }
+
+    /// Returns the physical stream.
+    pub fn stream(&self) -> &PhysicalStream {
+        &self.stream
+    }
+
+    /// Returns the values of the signals in every clock cycle.
+    pub fn cycles(&self) -> &[Cycle] {
+        &self.cycles
+    }
+
+    /// Returns the canonical name of the port of `signal` of this stream,
+    /// e.g. `a_valid` or `a_b__c_data`.
+    pub fn port(&self, signal: &str) -> String {
+        [self.interface.to_string(), self.path.to_string()]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .chain(std::iter::once(&signal.to_string()))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+
+    /// Checks this trace against the rules of the physical stream.
+    pub fn check(&self) -> Vec<Violation> {
+        self.stream.check(&self.cycles)
+    }
+
+    /// Returns the complete items of the handshaked transfers of this trace,
+    /// as decoded by [`Decoder`]. This is raw transfer data: items are nested
+    /// in sequences according to the `last` flags of the transfers, but
+    /// elements are the bits of all element fields of the physical stream,
+    /// concatenated with the first field in the least significant bits.
+    /// Returns an error at the first violation of the rules of the physical
+    /// stream.
+    ///
+    /// [`Decoder`]: ../../physical/transfer/struct.Decoder.html
+    pub fn transfer_data(&self) -> Result<Vec<Data>> {
+        let mut checker = Checker::new(&self.stream);
+        for cycle in &self.cycles {
+            checker.push(cycle.clone());
+            if let Some(violation) = checker.violations().first() {
+                return Err(Error::ProtocolError(violation.to_string()));
+            }
+        }
+        Ok(checker.items().to_vec())
+    }
+
+    /// Returns the items of [`transfer_data`](#method.transfer_data),
+    /// decoded into the logical elements of the physical stream: every
+    /// element is split into the values of the element fields of the stream,
+    /// e.g. `b` and `c` for a stream of `Group<b: Bits<4>, c: Bits<2>>`.
+    pub fn field_data(&self) -> Result<Vec<FieldData>> {
+        self.transfer_data()?
+            .iter()
+            .map(|item| item.fields(&self.stream))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::design::{Interface, Mode};
+    use crate::logical::{Direction, Group, LogicalType, Stream, Synchronicity};
+    use crate::{Name, PositiveReal, UniqueKeyBuilder};
+
+    /// Returns a dump of the transfers of `ports` of a component in scope
+    /// tb.dut, with the values of the ports at every clock cycle.
+    fn dump(ports: &[(&str, usize)], cycles: &[&[&str]]) -> String {
+        let mut result = "$timescale 1 ns $end\n$scope module tb $end\n$scope module dut $end\n\
+                          $var reg 1 ! clk $end\n"
+            .to_string();
+        let code = |i: usize| ((b'"' + i as u8) as char).to_string();
+        for (i, (port, width)) in ports.iter().enumerate() {
+            result.push_str(&format!(
+                "$var reg {} {} {} [{}:0] $end\n",
+                width,
+                code(i),
+                port,
+                width - 1
+            ));
+        }
+        result.push_str("$upscope $end\n$upscope $end\n$enddefinitions $end\n$dumpvars\n0!\n");
+        for (t, values) in cycles.iter().enumerate() {
+            result.push_str(&format!("#{}\n1!\n", t * 10));
+            for (i, value) in values.iter().enumerate() {
+                result.push_str(&format!("b{} {}\n", value, code(i)));
+            }
+            result.push_str(&format!("#{}\n0!\n", t * 10 + 5));
+        }
+        result.push_str(&format!("#{}\n1!\n", cycles.len() * 10));
+        result
+    }
+
+    #[test]
+    fn parse() -> Result<()> {
+        let vcd: Vcd = dump(&[("a", 1), ("b", 4)], &[&["1", "10"], &["x", "z1"]]).parse()?;
+        let mut variables: Vec<&str> = vcd.variables().collect();
+        variables.sort();
+        assert_eq!(variables, vec!["tb.dut.a", "tb.dut.b", "tb.dut.clk"]);
+
+        // Values are sampled just before the rising edges, so the first edge sees the initial
+        // values.
+        let samples = vcd.sample("tb.dut.clk", &["tb.dut.a", "tb.dut.b"])?;
+        let bits = |s: &str| s.parse::<BitVec>().unwrap();
+        assert_eq!(
+            samples,
+            vec![
+                vec![bits("0"), bits("0000")],
+                vec![bits("1"), bits("0010")],
+                vec![bits("0"), bits("0001")],
+            ]
+        );
+
+        assert!(vcd.sample("tb.dut.clk", &["tb.dut.c"]).is_err());
+
+        // Values are extended on the left as the VCD format specifies.
+        assert_eq!(bit_vec("1", 3)?, bits("001"));
+        assert_eq!(bit_vec("x1", 3)?, bits("001"));
+        assert_eq!(bit_vec("h0", 3)?, bits("110"));
+        // Values with more bits than their variable are rejected.
+        assert!(bit_vec("1010", 3).is_err());
+        let vcd: Vcd = dump(&[("a", 2)], &[&["101"]]).parse()?;
+        assert_eq!(
+            vcd.sample("tb.dut.clk", &["tb.dut.a"]),
+            Err(Error::ParsingError(
+                "value 101 of tb.dut.a at time 10 has more than 2 bits".to_string()
+            ))
+        );
+        assert!("$var reg 1 ! a $end".parse::<Vcd>().is_err());
+        assert!("$enddefinitions $end #0 1?".parse::<Vcd>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn traces() -> Result<()> {
+        let typ = LogicalType::Group(Group::try_new(vec![
+            (
+                "x",
+                LogicalType::Stream(Stream::new(
+                    LogicalType::try_new_bits(4)?,
+                    PositiveReal::new(1.).unwrap(),
+                    1,
+                    Synchronicity::Sync,
+                    1,
+                    Direction::Forward,
+                    None,
+                    false,
+                )),
+            ),
+            (
+                "y",
+                LogicalType::Stream(Stream::new_basic(LogicalType::try_new_bits(2)?)),
+            ),
+        ])?);
+        let streamlet = Streamlet::from_builder(
+            Name::try_new("test")?,
+            UniqueKeyBuilder::new().with_items(vec![Interface::try_new(
+                "a",
+                Mode::Out,
+                typ,
+                None,
+            )?]),
+            None,
+        )?;
+        let ports = [
+            ("a_x_valid", 1),
+            ("a_x_ready", 1),
+            ("a_x_data", 4),
+            ("a_x_last", 1),
+            ("a_x_strb", 1),
+            ("a_y_valid", 1),
+            ("a_y_ready", 1),
+            ("a_y_data", 2),
+        ];
+        let vcd: Vcd = dump(
+            &ports,
+            &[
+                &["1", "1", "0001", "0", "1", "1", "0", "11"],
+                &["1", "1", "0010", "1", "1", "1", "1", "11"],
+                &["0", "1", "0000", "0", "0", "0", "1", "00"],
+            ],
+        )
+        .parse()?;
+
+        let traces = vcd.traces("tb.dut", &streamlet)?;
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].port("data"), "a_x_data");
+        assert_eq!(traces[0].path().to_string(), "x");
+        assert_eq!(traces[0].cycles().len(), 4);
+        assert!(traces[0].check().is_empty());
+        assert_eq!(
+            traces[0].transfer_data()?,
+            vec![Data::sequence(vec![
+                Data::element("0001".parse()?),
+                Data::element("0010".parse()?),
+            ])]
+        );
+        assert_eq!(
+            traces[1].transfer_data()?,
+            vec![Data::element("11".parse()?)]
+        );
+
+        // Ports that are missing from the dump are reported.
+        assert!(vcd.traces("tb", &streamlet).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn group_elements() -> Result<()> {
+        let typ = LogicalType::Stream(Stream::new_basic(LogicalType::Group(Group::try_new(
+            vec![
+                ("b", LogicalType::try_new_bits(4)?),
+                ("c", LogicalType::try_new_bits(2)?),
+            ],
+        )?)));
+        let streamlet = Streamlet::from_builder(
+            Name::try_new("test")?,
+            UniqueKeyBuilder::new().with_items(vec![Interface::try_new("g", Mode::In, typ, None)?]),
+            None,
+        )?;
+        let vcd: Vcd = dump(
+            &[("g_valid", 1), ("g_ready", 1), ("g_data", 6)],
+            &[&["1", "1", "100011"], &["1", "1", "011111"]],
+        )
+        .parse()?;
+
+        let traces = vcd.traces("tb.dut", &streamlet)?;
+        assert_eq!(
+            traces[0].transfer_data()?,
+            vec![
+                Data::element("100011".parse()?),
+                Data::element("011111".parse()?)
+            ]
+        );
+        // The first field is in the least significant bits.
+        let elements = traces[0].field_data()?;
+        let field = |name: &str| PathName::try_new(vec![name]).unwrap();
+        assert_eq!(elements[0].field(&field("b")), Some(&"0011".parse()?));
+        assert_eq!(elements[0].field(&field("c")), Some(&"10".parse()?));
+        assert_eq!(
+            elements.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            vec!["{b: 0011, c: 10}", "{b: 1111, c: 01}"]
+        );
+        Ok(())
+    }
+}
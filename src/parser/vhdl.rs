@@ -0,0 +1,1035 @@
+//! Parser for VHDL entity declarations.
+//!
+//! This module reads the entity and component declarations of existing VHDL
+//! sources, and reconstructs a [`Streamlet`] from ports that follow the
+//! canonical naming of the VHDL back-end, e.g. `a_valid`, `a_ready`,
+//! `a_data` and `a_last` for an interface `a`. This allows existing IP to be
+//! added to a [`Project`] and composed with other streamlets.
+//!
+//! Every group of ports with a common prefix and both a `valid` and a `ready`
+//! port becomes an interface with a stream, other ports (except `clk` and
+//! `rst`) become interfaces with plain bits. The properties of every stream
+//! are inferred from the widths of its ports:
+//!
+//! * The dimensionality equals the width of the `last` port.
+//! * The number of element lanes equals the width of the `strb` port. Without
+//!   a `strb` port, it is the largest number of lanes that fits the `stai` and
+//!   `endi` ports and divides the width of the `data` port. Without any of
+//!   these ports, streams have a single element lane.
+//! * The complexity is the default complexity of streams, or the closest
+//!   complexity which matches the ports that are (not) present.
+//!
+//! As streams with different lanes or complexities can have the same ports,
+//! [`Hints`] can specify these for streams where the inference is ambiguous.
+//! Hints can also give values for generics, which are otherwise evaluated
+//! using their default values.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tydi::design::implementation::composer::GenericComponent;
+//! use tydi::design::Mode;
+//! use tydi::parser::vhdl::{Entity, Hints};
+//! use tydi::logical::{Direction, LogicalType, Stream, Synchronicity};
+//! use tydi::{Name, PositiveReal};
+//!
+//! let entity: Entity = "
+//! entity example is
+//!   generic (
+//!     LANES : natural := 4
+//!   );
+//!   port (
+//!     clk : in std_logic;
+//!     rst : in std_logic;
+//!     a_valid : in std_logic;
+//!     a_ready : out std_logic;
+//!     a_data : in std_logic_vector(8*LANES-1 downto 0);
+//!     a_last : in std_logic_vector(0 downto 0);
+//!     a_endi : in std_logic_vector(1 downto 0);
+//!     a_strb : in std_logic_vector(LANES-1 downto 0)
+//!   );
+//! end entity;
+//! ".parse()?;
+//!
+//! let streamlet = entity.to_streamlet(&Hints::new())?;
+//! let a = streamlet.get_interface(Name::try_new("a")?)?;
+//! assert_eq!(a.mode(), Mode::In);
+//! assert_eq!(
+//!     a.typ(),
+//!     LogicalType::from(Stream::new(
+//!         LogicalType::try_new_bits(8)?,
+//!         PositiveReal::new(4.)?,
+//!         1,
+//!         Synchronicity::Sync,
+//!         4,
+//!         Direction::Forward,
+//!         None,
+//!         false,
+//!     ))
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`Streamlet`]: ../../design/struct.Streamlet.html
+//! [`Project`]: ../../design/struct.Project.html
+//! [`Hints`]: ./struct.Hints.html
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+
+use crate::design::{Interface, Mode, Streamlet};
+use crate::logical::{Direction, LogicalType, Stream, Synchronicity};
+use crate::physical::{Complexity, Width};
+use crate::{Error, Name, NonNegative, Positive, PositiveReal, Result, UniqueKeyBuilder};
+
+/// The signals of a physical stream, as used in the canonical port names.
+const STREAM_SIGNALS: [&str; 8] = [
+    "valid", "ready", "data", "last", "stai", "endi", "strb", "user",
+];
+
+/// A generic of a VHDL entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generic {
+    identifier: String,
+    type_name: String,
+    /// The tokens of the default value.
+    default: Option<Vec<String>>,
+}
+
+impl Generic {
+    /// Returns the identifier of the generic, in lowercase.
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+
+    /// Returns the name of the type of the generic, in lowercase.
+    pub fn type_name(&self) -> &str {
+        self.type_name.as_str()
+    }
+}
+
+/// The type of a port of a VHDL entity.
+#[derive(Debug, Clone, PartialEq)]
+enum PortType {
+    /// A `std_logic` or `std_ulogic`.
+    Bit,
+    /// A vector of bits, with the tokens of the bounds of its range.
+    Vector {
+        left: Vec<String>,
+        right: Vec<String>,
+    },
+    /// Any other type, which can't be part of a streamlet.
+    Other(String),
+}
+
+/// A port of a VHDL entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Port {
+    identifier: String,
+    mode: Mode,
+    typ: PortType,
+}
+
+impl Port {
+    /// Returns the identifier of the port, in lowercase.
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+
+    /// Returns the mode of the port.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+}
+
+/// Hints for reconstructing a streamlet from an entity, for what can't be
+/// inferred from its ports.
+#[derive(Debug, Clone, Default)]
+pub struct Hints {
+    /// The name of the streamlet, instead of the identifier of the entity.
+    name: Option<Name>,
+    /// The values of generics, instead of their defaults.
+    generics: HashMap<String, i64>,
+    /// The number of element lanes of streams, by the prefix of their ports.
+    lanes: HashMap<String, Positive>,
+    /// The complexity of streams, by the prefix of their ports.
+    complexity: HashMap<String, Complexity>,
+}
+
+impl Hints {
+    /// Returns hints which don't specify anything.
+    pub fn new() -> Self {
+        Hints::default()
+    }
+
+    /// Returns these hints with the name of the streamlet.
+    pub fn with_name(mut self, name: Name) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Returns these hints with the value of a generic.
+    pub fn with_generic(mut self, identifier: &str, value: i64) -> Self {
+        self.generics.insert(identifier.to_lowercase(), value);
+        self
+    }
+
+    /// Returns these hints with the number of element lanes of the stream
+    /// with ports starting with `prefix`, e.g. `a` for `a_valid`.
+    pub fn with_lanes(mut self, prefix: &str, lanes: Positive) -> Self {
+        self.lanes.insert(prefix.to_lowercase(), lanes);
+        self
+    }
+
+    /// Returns these hints with the complexity of the stream with ports
+    /// starting with `prefix`, e.g. `a` for `a_valid`.
+    pub fn with_complexity(mut self, prefix: &str, complexity: impl Into<Complexity>) -> Self {
+        self.complexity
+            .insert(prefix.to_lowercase(), complexity.into());
+        self
+    }
+}
+
+/// An entity or component declaration in VHDL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    identifier: String,
+    generics: Vec<Generic>,
+    ports: Vec<Port>,
+}
+
+impl Entity {
+    /// Returns the identifier of the entity, in lowercase.
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+
+    /// Returns the generics of the entity.
+    pub fn generics(&self) -> &[Generic] {
+        &self.generics
+    }
+
+    /// Returns the ports of the entity.
+    pub fn ports(&self) -> &[Port] {
+        &self.ports
+    }
+
+    /// Returns the width of a port, evaluating its range with the default
+    /// values of the generics, unless `hints` specify their values.
+    pub fn width(&self, port: &Port, hints: &Hints) -> Result<Width> {
+        match &port.typ {
+            PortType::Bit => Ok(Width::Scalar),
+            PortType::Vector { left, right } => {
+                let generics = self.generic_values(hints);
+                let left = evaluate(left, &generics)?;
+                let right = evaluate(right, &generics)?;
+                // Both "to" and "downto" ranges are accepted.
+                Ok(Width::Vector(((left - right).abs() + 1) as NonNegative))
+            }
+            PortType::Other(typ) => Err(Error::InvalidArgument(format!(
+                "port {} has type {}, which is not a bit or a bit vector",
+                port.identifier, typ
+            ))),
+        }
+    }
+
+    /// Reconstructs a streamlet from the ports of this entity, as described
+    /// in the [module documentation](./index.html).
+    pub fn to_streamlet(&self, hints: &Hints) -> Result<Streamlet> {
+        // The ports of every stream by their prefix, and the other ports,
+        // in the order they are declared.
+        let mut groups: IndexMap<String, HashMap<&str, (&Port, Width)>> = IndexMap::new();
+        let prefixes: Vec<&str> = self
+            .ports
+            .iter()
+            .filter_map(|port| port.identifier.strip_suffix("_valid"))
+            .filter(|prefix| {
+                self.ports
+                    .iter()
+                    .any(|port| port.identifier == format!("{}_ready", prefix))
+            })
+            .collect();
+        for port in &self.ports {
+            if port.identifier == "clk" || port.identifier == "rst" {
+                continue;
+            }
+            let width = self.width(port, hints)?;
+            let stream = STREAM_SIGNALS.iter().find_map(|signal| {
+                port.identifier
+                    .strip_suffix(&format!("_{}", signal))
+                    .filter(|prefix| prefixes.contains(prefix))
+                    .map(|prefix| (prefix, *signal))
+            });
+            match stream {
+                Some((prefix, signal)) => {
+                    groups
+                        .entry(prefix.to_string())
+                        .or_default()
+                        .insert(signal, (port, width));
+                }
+                None => {
+                    groups
+                        .entry(port.identifier.clone())
+                        .or_default()
+                        .insert("", (port, width));
+                }
+            }
+        }
+
+        let mut interfaces = vec![];
+        for (prefix, signals) in &groups {
+            let (mode, typ) = match signals.get("") {
+                Some((port, width)) => (port.mode, LogicalType::try_new_bits(bits(*width))?),
+                None => stream(prefix, signals, hints)?,
+            };
+            interfaces.push(Interface::try_new(prefix.as_str(), mode, typ, None)?);
+        }
+        let name = match &hints.name {
+            Some(name) => name.clone(),
+            None => Name::try_new(self.identifier.as_str())?,
+        };
+        Streamlet::from_builder(name, UniqueKeyBuilder::new().with_items(interfaces), None)
+    }
+
+    /// Returns the values of the generics which have a default value or are
+    /// specified by the hints.
+    fn generic_values(&self, hints: &Hints) -> HashMap<String, i64> {
+        let mut result = HashMap::new();
+        for generic in &self.generics {
+            let value = match hints.generics.get(&generic.identifier) {
+                Some(value) => Some(*value),
+                None => generic
+                    .default
+                    .as_ref()
+                    .and_then(|default| evaluate(default, &result).ok()),
+            };
+            if let Some(value) = value {
+                result.insert(generic.identifier.clone(), value);
+            }
+        }
+        result
+    }
+}
+
+impl FromStr for Entity {
+    type Err = Error;
+
+    /// Parses the first entity or component declaration in `input`.
+    fn from_str(input: &str) -> Result<Self> {
+        entities(input)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::ParsingError("missing entity declaration".to_string()))
+    }
+}
+
+/// Parses all entity and component declarations in `input`, e.g. the
+/// components of a package, ignoring everything else.
+pub fn entities(input: &str) -> Result<Vec<Entity>> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        position: 0,
+    };
+    let mut result = vec![];
+    while let Some(token) = parser.peek() {
+        let token = token.to_string();
+        parser.position += 1;
+        let is_declaration = match token.as_str() {
+            "entity" => parser.tokens.get(parser.position + 1).map(|x| x.as_str()) == Some("is"),
+            // Components are also instantiated as "label: component name".
+            "component" => parser.position < 2 || parser.tokens[parser.position - 2] != ":",
+            _ => false,
+        };
+        if is_declaration {
+            result.push(parser.declaration(&token)?);
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the number of bits of a width.
+fn bits(width: Width) -> NonNegative {
+    match width {
+        Width::Scalar => 1,
+        Width::Vector(width) => width,
+    }
+}
+
+/// Reconstructs the mode and type of an interface with the ports of a
+/// stream, by the signals they carry.
+fn stream(
+    prefix: &str,
+    signals: &HashMap<&str, (&Port, Width)>,
+    hints: &Hints,
+) -> Result<(Mode, LogicalType)> {
+    let error = |message: String| Err(Error::InvalidArgument(message));
+    let (valid, _) = signals[&"valid"];
+    let (ready, _) = signals[&"ready"];
+    let mode = valid.mode;
+    if ready.mode == mode {
+        return error(format!(
+            "{} and {} must have opposite modes",
+            valid.identifier, ready.identifier
+        ));
+    }
+    let mut width = HashMap::new();
+    for (signal, (port, port_width)) in signals {
+        if !["valid", "ready"].contains(signal) && port.mode != mode {
+            return error(format!(
+                "{} must have the same mode as {}",
+                port.identifier, valid.identifier
+            ));
+        }
+        width.insert(*signal, bits(*port_width));
+    }
+    let has = |signal: &str| width.contains_key(signal);
+
+    let dimensionality = width.get("last").copied().unwrap_or(0);
+    let index_width = width.get("stai").or_else(|| width.get("endi")).copied();
+    let lanes = match (hints.lanes.get(prefix), width.get("strb"), index_width) {
+        (Some(lanes), _, _) => lanes.get(),
+        (None, Some(strb), _) => *strb,
+        // The index ports have the width of the (ceiled) log2 of the lanes.
+        (None, None, Some(index)) => (((1 << index) / 2 + 1)..=(1 << index))
+            .rev()
+            .find(|lanes| !matches!(width.get("data"), Some(data) if data % lanes != 0))
+            .unwrap_or(1 << index),
+        (None, None, None) => 1,
+    };
+    let complexity = match hints.complexity.get(prefix) {
+        Some(complexity) => complexity.clone(),
+        None => {
+            let mut lowest = 1;
+            let mut highest = NonNegative::MAX;
+            let mut bound = |present: bool, relevant: bool, complexity: NonNegative| {
+                if relevant && present {
+                    lowest = lowest.max(complexity);
+                } else if relevant {
+                    highest = highest.min(complexity - 1);
+                }
+            };
+            bound(has("stai"), lanes > 1, 6);
+            bound(has("endi"), lanes > 1 && dimensionality == 0, 5);
+            bound(has("strb"), dimensionality == 0, 7);
+            Complexity::new_major(Complexity::default().major().max(lowest).min(highest))
+        }
+    };
+    let data = match width.get("data") {
+        Some(data) if data % lanes != 0 => {
+            return error(format!(
+                "the width of {}_data ({}) is not a multiple of its {} element lanes",
+                prefix, data, lanes
+            ))
+        }
+        Some(data) => LogicalType::try_new_bits(data / lanes)?,
+        None => LogicalType::Null,
+    };
+    let user = match width.get("user") {
+        Some(user) => Some(LogicalType::try_new_bits(*user)?),
+        None => None,
+    };
+    let stream = Stream::new(
+        data,
+        PositiveReal::new(lanes as f64)?,
+        dimensionality,
+        Synchronicity::Sync,
+        complexity.clone(),
+        Direction::Forward,
+        user,
+        // Streams without data and user signals are only kept if they must be.
+        !width.contains_key("data") && !width.contains_key("user"),
+    );
+
+    // Verify that the reconstructed stream has exactly the same ports.
+    let typ = LogicalType::from(stream);
+    let expected = match typ.synthesize().streams().next() {
+        Some((_, physical)) => physical.signal_list(),
+        None => return error(format!("the ports of {} do not carry a stream", prefix)),
+    };
+    let expected = [
+        ("data", expected.data()),
+        ("last", expected.last()),
+        ("stai", expected.stai()),
+        ("endi", expected.endi()),
+        ("strb", expected.strb()),
+        ("user", expected.user()),
+    ];
+    for (signal, expected) in expected.iter() {
+        let expected = expected.as_ref().map(|signal| bits(signal.width()));
+        if expected != width.get(signal).copied() {
+            return error(format!(
+                "{}_{} does not match a stream with {} element lanes, dimensionality {} and complexity {}, \
+                 specify the element lanes and complexity of {} with hints",
+                prefix, signal, lanes, dimensionality, complexity, prefix
+            ));
+        }
+    }
+    Ok((mode, typ))
+}
+
+/// Splits VHDL source into lowercase tokens, without comments.
+fn tokenize(input: &str) -> Result<Vec<String>> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '-' if chars.peek() == Some(&'-') => while chars.next_if(|c| *c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => previous = c,
+                        None => {
+                            return Err(Error::ParsingError("unterminated comment".to_string()))
+                        }
+                    }
+                }
+            }
+            c if c.is_ascii_alphanumeric() => {
+                let mut token = c.to_ascii_lowercase().to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    token.push(c.to_ascii_lowercase());
+                }
+                tokens.push(token);
+            }
+            '"' => {
+                let mut token = c.to_string();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => token.push(c),
+                        None => return Err(Error::ParsingError("unterminated string".to_string())),
+                    }
+                }
+                token.push('"');
+                tokens.push(token);
+            }
+            _ => {
+                let mut token = c.to_string();
+                if let Some(next) = chars.peek() {
+                    if [":=", "**", "=>", "<=", "/=", ">="]
+                        .contains(&format!("{}{}", c, next).as_str())
+                    {
+                        token.push(*next);
+                        chars.next();
+                    }
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Evaluates an integer expression, e.g. `8*LANES-1`.
+fn evaluate(tokens: &[String], generics: &HashMap<String, i64>) -> Result<i64> {
+    let mut parser = Parser {
+        tokens: tokens.to_vec(),
+        position: 0,
+    };
+    let result = parser.expression(generics)?;
+    match parser.peek() {
+        Some(token) => Err(Error::ParsingError(format!(
+            "unexpected {} in expression {}",
+            token,
+            tokens.join(" ")
+        ))),
+        None => Ok(result),
+    }
+}
+
+/// A parser for a sequence of tokens.
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|token| token.as_str())
+    }
+
+    fn next(&mut self) -> Result<String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| Error::ParsingError("unexpected end of input".to_string()))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    /// Skips the next token if it is `token`, and returns whether it was.
+    fn accept(&mut self, token: &str) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        match self.next()? {
+            next if next == token => Ok(()),
+            next => Err(Error::ParsingError(format!(
+                "expected {}, found {}",
+                token, next
+            ))),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        match self.next()? {
+            next if next.starts_with(|c: char| c.is_ascii_alphabetic()) => Ok(next),
+            next => Err(Error::ParsingError(format!(
+                "expected an identifier, found {}",
+                next
+            ))),
+        }
+    }
+
+    /// Returns the tokens up to (but not including) one of `ends`, outside of
+    /// parentheses.
+    fn until(&mut self, ends: &[&str]) -> Result<Vec<String>> {
+        let mut result = vec![];
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                Some(token) if depth == 0 && ends.contains(&token) => return Ok(result),
+                Some("(") => depth += 1,
+                Some(")") => depth -= 1,
+                _ => (),
+            }
+            result.push(self.next()?);
+        }
+    }
+
+    /// Parses an entity or component declaration, after its keyword.
+    fn declaration(&mut self, keyword: &str) -> Result<Entity> {
+        let identifier = self.identifier()?;
+        self.accept("is");
+        let mut generics = vec![];
+        let mut ports = vec![];
+        loop {
+            match self.next()?.as_str() {
+                "generic" => generics = self.interface_list(Parser::generic)?,
+                "port" => ports = self.interface_list(Parser::port)?,
+                "end" => break,
+                token => {
+                    return Err(Error::ParsingError(format!(
+                        "unexpected {} in {} {}",
+                        token, keyword, identifier
+                    )))
+                }
+            }
+        }
+        self.accept(keyword);
+        self.accept(&identifier);
+        self.expect(";")?;
+        Ok(Entity {
+            identifier,
+            generics,
+            ports,
+        })
+    }
+
+    /// Parses a parenthesized list of generics or ports, separated by
+    /// semicolons.
+    fn interface_list<T>(
+        &mut self,
+        element: fn(&mut Parser, String) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        self.expect("(")?;
+        let mut result = vec![];
+        loop {
+            let mut identifiers = vec![self.identifier()?];
+            while self.accept(",") {
+                identifiers.push(self.identifier()?);
+            }
+            self.expect(":")?;
+            let position = self.position;
+            for identifier in identifiers {
+                // Every identifier has the same declaration.
+                self.position = position;
+                result.push(element(self, identifier)?);
+            }
+            if !self.accept(";") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        self.expect(";")?;
+        Ok(result)
+    }
+
+    fn generic(&mut self, identifier: String) -> Result<Generic> {
+        let type_name = self.until(&[":=", ";", ")"])?.join(" ");
+        let default = if self.accept(":=") {
+            Some(self.until(&[";", ")"])?)
+        } else {
+            None
+        };
+        Ok(Generic {
+            identifier,
+            type_name,
+            default,
+        })
+    }
+
+    fn port(&mut self, identifier: String) -> Result<Port> {
+        let mode = match self.peek() {
+            Some("in") => Mode::In,
+            Some("out") | Some("buffer") => Mode::Out,
+            Some("inout") | Some("linkage") => {
+                return Err(Error::ParsingError(format!(
+                    "port {} is bidirectional",
+                    identifier
+                )))
+            }
+            _ => {
+                self.position -= 1;
+                Mode::In
+            }
+        };
+        self.position += 1;
+        let mut typ = self.until(&[":=", ";", ")"])?;
+        if self.accept(":=") {
+            self.until(&[";", ")"])?;
+        }
+        // Strip library and package names, e.g. ieee.std_logic_1164.std_logic
+        while typ.len() > 2 && typ[1] == "." {
+            typ.drain(..2);
+        }
+        let typ = match typ.first().map(|x| x.as_str()) {
+            Some("std_logic") | Some("std_ulogic") if typ.len() == 1 => PortType::Bit,
+            Some("std_logic_vector")
+            | Some("std_ulogic_vector")
+            | Some("unsigned")
+            | Some("signed")
+                if typ.len() > 2 && typ[1] == "(" && typ[typ.len() - 1] == ")" =>
+            {
+                let range = &typ[2..typ.len() - 1];
+                match range
+                    .iter()
+                    .position(|token| token == "downto" || token == "to")
+                {
+                    Some(split) => PortType::Vector {
+                        left: range[..split].to_vec(),
+                        right: range[split + 1..].to_vec(),
+                    },
+                    None => PortType::Other(typ.join(" ")),
+                }
+            }
+            _ => PortType::Other(typ.join(" ")),
+        };
+        Ok(Port {
+            identifier,
+            mode,
+            typ,
+        })
+    }
+
+    /// Parses a sum of terms.
+    fn expression(&mut self, generics: &HashMap<String, i64>) -> Result<i64> {
+        let mut result = self.term(generics)?;
+        loop {
+            if self.accept("+") {
+                result += self.term(generics)?;
+            } else if self.accept("-") {
+                result -= self.term(generics)?;
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    /// Parses a product of factors.
+    fn term(&mut self, generics: &HashMap<String, i64>) -> Result<i64> {
+        let mut result = self.factor(generics)?;
+        loop {
+            if self.accept("*") {
+                result *= self.factor(generics)?;
+            } else if self.accept("/") {
+                match self.factor(generics)? {
+                    0 => return Err(Error::ParsingError("division by zero".to_string())),
+                    divisor => result /= divisor,
+                }
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    /// Parses a (negated) number, generic or parenthesized expression, which
+    /// may be raised to a power.
+    fn factor(&mut self, generics: &HashMap<String, i64>) -> Result<i64> {
+        if self.accept("-") {
+            return Ok(-self.factor(generics)?);
+        }
+        let token = self.next()?;
+        let result = if token == "(" {
+            let result = self.expression(generics)?;
+            self.expect(")")?;
+            result
+        } else if token.starts_with(|c: char| c.is_ascii_digit()) {
+            token
+                .replace('_', "")
+                .parse()
+                .map_err(|_| Error::ParsingError(format!("{} is not an integer", token)))?
+        } else {
+            *generics.get(&token).ok_or_else(|| {
+                Error::ParsingError(format!("{} is not a generic with a value", token))
+            })?
+        };
+        if self.accept("**") {
+            let exponent = self.factor(generics)?;
+            if exponent < 0 {
+                return Err(Error::ParsingError(format!(
+                    "negative exponent {}",
+                    exponent
+                )));
+            }
+            Ok(result.pow(exponent as u32))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::design::implementation::composer::GenericComponent;
+    use crate::Identify;
+
+    use super::*;
+
+    #[test]
+    fn tokens() -> Result<()> {
+        assert_eq!(
+            tokenize(
+                "a_Data : IN std_logic_vector(2**N-1 downto 0) := (others => '0'); -- x\n/* y */ b"
+            )?,
+            vec![
+                "a_data",
+                ":",
+                "in",
+                "std_logic_vector",
+                "(",
+                "2",
+                "**",
+                "n",
+                "-",
+                "1",
+                "downto",
+                "0",
+                ")",
+                ":=",
+                "(",
+                "others",
+                "=>",
+                "'",
+                "0",
+                "'",
+                ")",
+                ";",
+                "b"
+            ]
+        );
+        assert!(tokenize("a /* b").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn expressions() -> Result<()> {
+        let generics = [("n".to_string(), 3)].iter().cloned().collect();
+        let eval = |input: &str| evaluate(&tokenize(input)?, &generics);
+        assert_eq!(eval("8*N-1")?, 23);
+        assert_eq!(eval("2**N-1")?, 7);
+        assert_eq!(eval("-(N+1)*2")?, -8);
+        assert_eq!(eval("N/2")?, 1);
+        assert!(eval("m").is_err());
+        assert!(eval("N/0").is_err());
+        assert!(eval("N N").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn declarations() -> Result<()> {
+        let input = "
+            library ieee;
+            use ieee.std_logic_1164.all;
+            entity a is
+              generic (W, X : natural := 2; Y : string);
+              port (p, q : in std_logic; r : buffer std_logic_vector(W to W+1));
+            end a;
+            architecture rtl of a is
+              component b port (s : out unsigned(1 downto 0)); end component b;
+            begin
+              i : entity work.a port map (p => p);
+              j : component b port map (s => r);
+            end rtl;
+        ";
+        let result = entities(input)?;
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].identifier(), "a");
+        assert_eq!(
+            result[0]
+                .generics()
+                .iter()
+                .map(|x| (x.identifier(), x.type_name()))
+                .collect::<Vec<_>>(),
+            vec![("w", "natural"), ("x", "natural"), ("y", "string")]
+        );
+        assert_eq!(
+            result[0]
+                .ports()
+                .iter()
+                .map(|x| (x.identifier(), x.mode()))
+                .collect::<Vec<_>>(),
+            vec![("p", Mode::In), ("q", Mode::In), ("r", Mode::Out)]
+        );
+        let r = &result[0].ports()[2];
+        assert_eq!(result[0].width(r, &Hints::new())?, Width::Vector(2));
+        assert_eq!(result[1].identifier(), "b");
+        assert_eq!(result[1].ports()[0].mode(), Mode::Out);
+
+        assert!("entity a is port (p : inout std_logic); end;"
+            .parse::<Entity>()
+            .is_err());
+        assert!("entity a is port (p : in std_logic) end;"
+            .parse::<Entity>()
+            .is_err());
+        assert!("architecture rtl of a is begin end;"
+            .parse::<Entity>()
+            .is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "generator")]
+    #[test]
+    fn canonical() -> Result<()> {
+        use crate::generator::common::convert::Componentify;
+        use crate::generator::vhdl::Declare;
+
+        let stream = LogicalType::from(Stream::new(
+            LogicalType::try_new_bits(8)?,
+            PositiveReal::new(3.)?,
+            2,
+            Synchronicity::Sync,
+            7,
+            Direction::Forward,
+            Some(LogicalType::try_new_bits(3)?),
+            false,
+        ));
+        let streamlet = Streamlet::from_builder(
+            Name::try_new("test")?,
+            UniqueKeyBuilder::new().with_items(vec![
+                Interface::try_new("a", Mode::In, stream.clone(), None)?,
+                Interface::try_new("b", Mode::Out, stream, None)?,
+                Interface::try_new("c", Mode::Out, LogicalType::try_new_bits(4)?, None)?,
+            ]),
+            None,
+        )?;
+        let entity: Entity = streamlet.canonical(Some("com")).declare()?.parse()?;
+        assert_eq!(entity.identifier(), "test_com");
+
+        // Without hints, complexity 6 and 7 are ambiguous.
+        let result = entity.to_streamlet(&Hints::new())?;
+        assert_eq!(result.identifier(), "test_com");
+        let a = result.get_interface(Name::try_new("a")?)?;
+        match a.typ() {
+            LogicalType::Stream(stream) => {
+                assert_eq!(stream.throughput(), PositiveReal::new(3.)?);
+                assert_eq!(stream.complexity(), &Complexity::new_major(6));
+            }
+            _ => unreachable!(),
+        }
+
+        let hints = Hints::new()
+            .with_name(Name::try_new("test")?)
+            .with_complexity("a", 7)
+            .with_complexity("b", 7);
+        let result = entity.to_streamlet(&hints)?;
+        assert_eq!(result.identifier(), "test");
+        for interface in streamlet.interfaces() {
+            let other = result.get_interface(Name::try_new(interface.identifier())?)?;
+            assert_eq!(interface.mode(), other.mode());
+            assert_eq!(interface.typ(), other.typ());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn streams() -> Result<()> {
+        let entity: Entity = "
+            entity e is
+              generic (N : natural := 2);
+              port (
+                x_valid : out std_logic;
+                x_ready : in std_logic;
+                x_data : out std_logic_vector(4*N-1 downto 0);
+                x_stai : out std_logic_vector(0 downto 0);
+                x_endi : out std_logic_vector(0 downto 0);
+                y_valid : in std_logic;
+                y_ready : out std_logic;
+                y_last : in std_logic;
+                y_strb : in std_logic
+              );
+            end entity e;
+        "
+        .parse()?;
+        let streamlet = entity.to_streamlet(&Hints::new())?;
+        let x = streamlet.get_interface(Name::try_new("x")?)?;
+        assert_eq!(x.mode(), Mode::Out);
+        match x.typ() {
+            LogicalType::Stream(stream) => {
+                assert_eq!(stream.data(), &LogicalType::try_new_bits(4)?);
+                assert_eq!(stream.throughput(), PositiveReal::new(2.)?);
+                assert_eq!(stream.complexity(), &Complexity::new_major(6));
+            }
+            _ => unreachable!(),
+        }
+        let y = streamlet.get_interface(Name::try_new("y")?)?;
+        assert_eq!(y.mode(), Mode::In);
+        match y.typ() {
+            LogicalType::Stream(stream) => {
+                assert_eq!(stream.data(), &LogicalType::Null);
+                assert_eq!(stream.dimensionality(), 1);
+            }
+            _ => unreachable!(),
+        }
+
+        let streamlet = entity.to_streamlet(&Hints::new().with_generic("N", 4))?;
+        match streamlet.get_interface(Name::try_new("x")?)?.typ() {
+            LogicalType::Stream(stream) => {
+                assert_eq!(stream.data(), &LogicalType::try_new_bits(8)?);
+            }
+            _ => unreachable!(),
+        }
+
+        // The data doesn't fit 3 lanes, and 3 lanes need wider index ports.
+        assert!(entity
+            .to_streamlet(&Hints::new().with_lanes("x", Positive::new(3).unwrap()))
+            .is_err());
+        // A lower complexity doesn't have the stai port.
+        assert!(entity
+            .to_streamlet(&Hints::new().with_complexity("x", 5))
+            .is_err());
+
+        let entity: Entity = "
+            entity e is port (
+              x_valid : out std_logic;
+              x_ready : out std_logic
+            ); end;
+        "
+        .parse()?;
+        assert!(entity.to_streamlet(&Hints::new()).is_err());
+        Ok(())
+    }
+}
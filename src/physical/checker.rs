@@ -0,0 +1,377 @@
+//! Protocol checking of physical streams.
+//!
+//! This module checks traces of the signals of a [`PhysicalStream`], sampled
+//! once per clock cycle, against the rules of the [Tydi specification] for
+//! the complexity of the stream:
+//!
+//! - The source may not release `valid` before a transfer is handshaked,
+//!   and the signals of a transfer may not change while `valid` is asserted
+//!   and `ready` is not.
+//! - Below complexity 3, the source may not release `valid` inside an
+//!   innermost sequence.
+//! - Below complexity 2, the source may only release `valid` after a
+//!   transfer with all `last` flags set, i.e. between outermost sequences.
+//! - The signals of every transfer must be valid for the complexity, see
+//!   [`Transfer::validate`] and [`Decoder::push`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tydi::physical::PhysicalStream;
+//! use tydi::physical::checker::Cycle;
+//! use tydi::physical::transfer::{BitVec, Data};
+//!
+//! let stream = PhysicalStream::try_new(vec![("a", 8)], 1, 1, 2, vec![])?;
+//! let element = |value| Ok(Data::element(BitVec::from_unsigned(value, 8)?));
+//! let transfers = stream.encode(&[Data::sequence(
+//!     (1..=2).map(element).collect::<tydi::Result<Vec<_>>>()?,
+//! )])?;
+//!
+//! // The sink stalls the first transfer, but the source changes it.
+//! let trace = vec![
+//!     Cycle::new(true, false, transfers[1].clone()),
+//!     Cycle::new(true, true, transfers[0].clone()),
+//!     Cycle::new(true, true, transfers[1].clone()),
+//! ];
+//! let violations = stream.check(&trace);
+//! assert_eq!(violations.len(), 1);
+//! assert_eq!(
+//!     violations[0].to_string(),
+//!     "cycle 1: data, last changed while valid was asserted and ready was not"
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`PhysicalStream`]: ../struct.PhysicalStream.html
+//! [`Transfer::validate`]: ../transfer/struct.Transfer.html#method.validate
+//! [`Decoder::push`]: ../transfer/struct.Decoder.html#method.push
+//! [Tydi specification]: https://abs-tudelft.github.io/tydi/specification/physical.html
+
+use std::fmt;
+
+use crate::physical::transfer::{Data, Decoder, Transfer};
+use crate::physical::PhysicalStream;
+use crate::Error;
+
+/// The values of the signals of a physical stream during a single clock
+/// cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cycle {
+    valid: bool,
+    ready: bool,
+    transfer: Transfer,
+}
+
+impl Cycle {
+    /// Returns a cycle with the `valid` and `ready` signals, and the other
+    /// signals in `transfer`.
+    pub fn new(valid: bool, ready: bool, transfer: Transfer) -> Self {
+        Cycle {
+            valid,
+            ready,
+            transfer,
+        }
+    }
+
+    /// Returns the `valid` signal.
+    pub fn valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Returns the `ready` signal.
+    pub fn ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Returns the other signals.
+    pub fn transfer(&self) -> &Transfer {
+        &self.transfer
+    }
+
+    /// Returns true if a transfer is handshaked in this cycle.
+    pub fn handshake(&self) -> bool {
+        self.valid && self.ready
+    }
+}
+
+/// A violation of the rules of a physical stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// The index of the cycle in the trace, starting at 0.
+    cycle: usize,
+    /// The rule that is violated.
+    message: String,
+}
+
+impl Violation {
+    /// Returns the index of the cycle in which the violation occurs.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// Returns a description of the violation.
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle {}: {}", self.cycle, self.message)
+    }
+}
+
+/// Checks a trace of a physical stream, one cycle at a time.
+///
+/// The checker keeps going after a violation, such that all violations of a
+/// trace are reported. The data of the handshaked transfers is decoded along
+/// the way, so it can be compared with a model.
+#[derive(Debug, Clone)]
+pub struct Checker<'a> {
+    stream: &'a PhysicalStream,
+    decoder: Decoder<'a>,
+    previous: Option<Cycle>,
+    cycle: usize,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Checker<'a> {
+    /// Returns a checker for traces of `stream`.
+    pub fn new(stream: &'a PhysicalStream) -> Self {
+        Checker {
+            stream,
+            decoder: Decoder::new(stream),
+            previous: None,
+            cycle: 0,
+            violations: vec![],
+        }
+    }
+
+    /// Returns the violations found so far.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Returns the complete items of the handshaked transfers so far.
+    pub fn items(&self) -> &[Data] {
+        self.decoder.items()
+    }
+
+    /// Checks the next cycle of the trace.
+    pub fn push(&mut self, cycle: Cycle) {
+        let complexity = self.stream.complexity().major();
+        if let Some(previous) = &self.previous {
+            if previous.valid && !previous.ready {
+                if !cycle.valid {
+                    self.report("valid was released before the transfer was handshaked");
+                } else {
+                    let changed: Vec<&str> = previous
+                        .transfer
+                        .signals()
+                        .filter(|(name, value)| cycle.transfer.signal(name) != Some(value))
+                        .map(|(name, _)| name)
+                        .collect();
+                    if !changed.is_empty() {
+                        self.report(format!(
+                            "{} changed while valid was asserted and ready was not",
+                            changed.join(", ")
+                        ));
+                    }
+                }
+            } else if previous.handshake() && !cycle.valid {
+                if complexity < 2 && self.decoder.in_outermost_sequence() {
+                    self.report(
+                        "valid was released inside an outermost sequence, which is not allowed \
+                         below complexity 2",
+                    );
+                } else if complexity < 3 && self.decoder.in_sequence() {
+                    self.report(
+                        "valid was released inside an innermost sequence, which is not allowed \
+                         below complexity 3",
+                    );
+                }
+            }
+        }
+
+        if cycle.valid {
+            let result = if cycle.ready {
+                self.decoder.push(&cycle.transfer)
+            } else {
+                cycle.transfer.validate(self.stream)
+            };
+            match result {
+                Err(Error::ProtocolError(message)) => self.report(message),
+                Err(error) => self.report(error.to_string()),
+                Ok(()) => (),
+            }
+        }
+        self.previous = Some(cycle);
+        self.cycle += 1;
+    }
+
+    /// Returns all violations of the trace.
+    pub fn finish(self) -> Vec<Violation> {
+        self.violations
+    }
+
+    fn report(&mut self, message: impl Into<String>) {
+        self.violations.push(Violation {
+            cycle: self.cycle,
+            message: message.into(),
+        });
+    }
+}
+
+impl PhysicalStream {
+    /// Checks a `trace` of this physical stream, with the values of its
+    /// signals in every clock cycle. Returns the violations of the rules of
+    /// the complexity of this stream.
+    pub fn check(&self, trace: &[Cycle]) -> Vec<Violation> {
+        let mut checker = Checker::new(self);
+        for cycle in trace {
+            checker.push(cycle.clone());
+        }
+        checker.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::transfer::BitVec;
+    use crate::Result;
+
+    fn sequence(stream: &PhysicalStream, values: &[u64]) -> Result<Vec<Transfer>> {
+        stream.encode(&[Data::sequence(
+            values
+                .iter()
+                .map(|v| Ok(Data::element(BitVec::from_unsigned(*v, 4)?)))
+                .collect::<Result<Vec<_>>>()?,
+        )])
+    }
+
+    fn messages(violations: Vec<Violation>) -> Vec<String> {
+        violations.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn handshakes() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 1, 1, 3, vec![])?;
+        let mut transfers = sequence(&stream, &[1])?;
+        transfers.extend(sequence(&stream, &[2])?);
+        let idle = Transfer::new(&stream);
+        let trace = vec![
+            Cycle::new(false, true, idle.clone()),
+            Cycle::new(true, false, transfers[0].clone()),
+            Cycle::new(true, true, transfers[0].clone()),
+            Cycle::new(false, true, idle.clone()),
+            Cycle::new(true, false, transfers[1].clone()),
+            Cycle::new(true, true, transfers[1].clone()),
+        ];
+        assert!(stream.check(&trace).is_empty());
+
+        let mut checker = Checker::new(&stream);
+        for cycle in trace {
+            checker.push(cycle);
+        }
+        assert_eq!(checker.items(), &stream.decode(&transfers)?[..]);
+
+        // Valid may not be released before the handshake.
+        let trace = vec![
+            Cycle::new(true, false, transfers[0].clone()),
+            Cycle::new(false, true, idle.clone()),
+        ];
+        assert_eq!(
+            messages(stream.check(&trace)),
+            vec!["cycle 1: valid was released before the transfer was handshaked"]
+        );
+
+        // At any complexity, transfers may not change while waiting for the sink.
+        let trace = vec![
+            Cycle::new(true, false, transfers[1].clone()),
+            Cycle::new(true, true, transfers[0].clone()),
+        ];
+        for complexity in [1, 3, 8].iter() {
+            let stream = PhysicalStream::try_new(vec![("a", 4)], 1, 1, *complexity, vec![])?;
+            assert_eq!(
+                messages(stream.check(&trace)),
+                vec!["cycle 1: data changed while valid was asserted and ready was not"]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn sequences() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 1, 2, 2, vec![])?;
+        let element = |v| -> Result<Data> { Ok(Data::element(BitVec::from_unsigned(v, 4)?)) };
+        let transfers = stream.encode(&[Data::sequence(vec![
+            Data::sequence(vec![element(1)?, element(2)?]),
+            Data::sequence(vec![element(3)?]),
+        ])])?;
+        let idle = Transfer::new(&stream);
+
+        // Below complexity 3, valid may only be released between innermost sequences.
+        let between = vec![
+            Cycle::new(true, true, transfers[0].clone()),
+            Cycle::new(true, true, transfers[1].clone()),
+            Cycle::new(false, false, idle.clone()),
+            Cycle::new(true, true, transfers[2].clone()),
+        ];
+        assert!(stream.check(&between).is_empty());
+        let inside = vec![
+            Cycle::new(true, true, transfers[0].clone()),
+            Cycle::new(false, false, idle.clone()),
+            Cycle::new(true, true, transfers[1].clone()),
+            Cycle::new(true, true, transfers[2].clone()),
+        ];
+        assert_eq!(
+            messages(stream.check(&inside)),
+            vec![
+                "cycle 1: valid was released inside an innermost sequence, which is not allowed \
+                 below complexity 3"
+            ]
+        );
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 1, 2, 3, vec![])?;
+        assert!(stream.check(&inside).is_empty());
+
+        // Below complexity 2, valid may only be released after the last flags are all set.
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 1, 2, 1, vec![])?;
+        assert_eq!(
+            messages(stream.check(&between)),
+            vec![
+                "cycle 2: valid was released inside an outermost sequence, which is not allowed \
+                 below complexity 2"
+            ]
+        );
+        let mut trace = between.clone();
+        trace.remove(2);
+        trace.push(Cycle::new(false, false, idle));
+        assert!(stream.check(&trace).is_empty());
+
+        // Violations of the rules for the transfers themselves are reported, and checking
+        // continues with the next cycle.
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 4, 1, 4, vec![])?;
+        let mut gap = Transfer::new(&stream);
+        gap.set_signal("data", "0010000000000001".parse()?)?;
+        gap.set_signal("strb", "1001".parse()?)?;
+        gap.set_signal("endi", "11".parse()?)?;
+        gap.set_signal("last", "1".parse()?)?;
+        let mut wide = gap.clone();
+        wide.set_signal("strb", "11111".parse()?)?;
+        let trace = vec![
+            Cycle::new(true, false, gap.clone()),
+            Cycle::new(true, true, wide),
+            Cycle::new(false, false, gap),
+        ];
+        assert_eq!(
+            messages(stream.check(&trace)),
+            vec![
+                "cycle 0: strb (1001) must be all ones or all zeros below complexity 7",
+                "cycle 1: strb changed while valid was asserted and ready was not",
+                "cycle 1: signal strb has 5 bits, expected 4",
+            ]
+        );
+        Ok(())
+    }
+}
@@ -13,6 +13,10 @@
 //! - [`SignalList`] a signal list for the signals in a physical stream.
 //!   [Reference](https://abs-tudelft.github.io/tydi/specification/physical.html#signals)
 //!
+//! The [`transfer`] module maps elements onto the transfers of a physical
+//! stream, and back. The [`checker`] module checks traces of the signals of a
+//! physical stream against the rules of its complexity.
+//!
 //! # Examples
 //!
 //! ## Minimal example
@@ -47,6 +51,8 @@
 //! [`Fields`]: ./struct.Fields.html
 //! [`PhysicalStream`]: ./struct.PhysicalStream.html
 //! [`SignalMap`]: ./struct.SignalMap.html
+//! [`transfer`]: ./transfer/index.html
+//! [`checker`]: ./checker/index.html
 //! [Tydi specification]: https://abs-tudelft.github.io/tydi/specification/physical.html
 
 use std::str::FromStr;
@@ -61,6 +67,9 @@ use indexmap::IndexMap;
 use crate::traits::Identify;
 use crate::{util::log2_ceil, Error, NonNegative, PathName, Positive, Result};
 
+pub mod checker;
+pub mod transfer;
+
 /// Positive number of bits.
 pub type BitCount = Positive;
 
@@ -0,0 +1,1093 @@
+//! Transfers of physical streams.
+//!
+//! This module maps nested sequences of elements onto the transfers of a
+//! [`PhysicalStream`], and back, as described in the [Tydi specification].
+//!
+//! This module defines the following types:
+//! - [`BitVec`] the value of a signal, an element or the user fields.
+//! - [`Data`] an element or a (nested) sequence of elements.
+//! - [`FieldData`] data of which the elements are split into the values of
+//!   the element fields of a physical stream.
+//! - [`Transfer`] the values of the signals of a physical stream during a
+//!   single handshake.
+//! - [`Decoder`] reconstructs elements from transfers, one transfer at a time.
+//!
+//! Elements are encoded using [`PhysicalStream::encode`], which uses as few
+//! transfers as possible, and decoded using [`PhysicalStream::decode`]. Both
+//! check the rules of the complexity level of the physical stream.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tydi::physical::PhysicalStream;
+//! use tydi::physical::transfer::{BitVec, Data};
+//!
+//! // A stream of sequences of 8-bit elements with two element lanes.
+//! let stream = PhysicalStream::try_new(vec![("a", 8)], 2, 1, 4, vec![])?;
+//!
+//! // Two sequences, of three elements and of no elements.
+//! let element = |value| Ok(Data::element(BitVec::from_unsigned(value, 8)?));
+//! let data = vec![
+//!     Data::sequence((1..=3).map(element).collect::<tydi::Result<Vec<_>>>()?),
+//!     Data::sequence(vec![]),
+//! ];
+//!
+//! let transfers = stream.encode(&data)?;
+//! assert_eq!(transfers.len(), 3);
+//! // The second transfer ends the first sequence, using only its first lane.
+//! assert_eq!(transfers[1].last().unwrap().to_string(), "1");
+//! assert_eq!(transfers[1].endi().unwrap().to_string(), "0");
+//! // The third transfer carries the empty sequence.
+//! assert_eq!(transfers[2].strb().unwrap().to_string(), "00");
+//!
+//! assert_eq!(stream.decode(&transfers)?, data);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`BitVec`]: ./struct.BitVec.html
+//! [`Data`]: ./enum.Data.html
+//! [`FieldData`]: ./enum.FieldData.html
+//! [`Transfer`]: ./struct.Transfer.html
+//! [`Decoder`]: ./struct.Decoder.html
+//! [`PhysicalStream`]: ../struct.PhysicalStream.html
+//! [`PhysicalStream::encode`]: ../struct.PhysicalStream.html#method.encode
+//! [`PhysicalStream::decode`]: ../struct.PhysicalStream.html#method.decode
+//! [Tydi specification]: https://abs-tudelft.github.io/tydi/specification/physical.html
+
+use std::fmt;
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+
+use crate::physical::{PhysicalStream, Width};
+use crate::traits::Identify;
+use crate::{Error, NonNegative, PathName, Result};
+
+/// A vector of bits, of which the first bit is the least significant bit.
+///
+/// Bit vectors are displayed and parsed with the most significant bit first.
+///
+/// # Examples
+///
+/// ```rust
+/// use tydi::physical::transfer::BitVec;
+///
+/// let bits = BitVec::from_unsigned(6, 4)?;
+/// assert_eq!(bits.to_string(), "0110");
+/// assert!(!bits.get(0) && bits.get(1));
+/// assert_eq!("0110".parse::<BitVec>()?, bits);
+/// assert!(BitVec::from_unsigned(16, 4).is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitVec(Vec<bool>);
+
+impl BitVec {
+    /// Returns a bit vector of `width` zeros.
+    pub fn zeros(width: usize) -> Self {
+        BitVec(vec![false; width])
+    }
+
+    /// Returns a bit vector of `width` ones.
+    pub fn ones(width: usize) -> Self {
+        BitVec(vec![true; width])
+    }
+
+    /// Returns a bit vector of `width` bits with the unsigned value `value`.
+    /// Returns an error when the value does not fit.
+    pub fn from_unsigned(value: u64, width: usize) -> Result<Self> {
+        if width < 64 && value >> width != 0 {
+            return Err(Error::InvalidArgument(format!(
+                "{} does not fit in {} bits",
+                value, width
+            )));
+        }
+        Ok(BitVec(
+            (0..width)
+                .map(|i| i < 64 && (value >> i) & 1 == 1)
+                .collect(),
+        ))
+    }
+
+    /// Returns the unsigned value of this bit vector, if it fits in 64 bits.
+    pub fn to_unsigned(&self) -> Option<u64> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, bit)| **bit)
+            .try_fold(
+                0,
+                |value, (i, _)| {
+                    if i < 64 {
+                        Some(value | 1 << i)
+                    } else {
+                        None
+                    }
+                },
+            )
+    }
+
+    /// Returns the number of bits.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if this bit vector has no bits.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns bit `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> bool {
+        self.0[index]
+    }
+
+    /// Sets bit `index` to `bit`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, bit: bool) {
+        self.0[index] = bit;
+    }
+
+    /// Returns `width` bits, starting at bit `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn slice(&self, offset: usize, width: usize) -> BitVec {
+        BitVec(self.0[offset..offset + width].to_vec())
+    }
+
+    /// Overwrites the bits starting at bit `offset` with `bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn splice(&mut self, offset: usize, bits: &BitVec) {
+        self.0[offset..offset + bits.len()].copy_from_slice(&bits.0);
+    }
+
+    /// Returns the concatenation of `parts`, the first part in the least
+    /// significant bits.
+    pub fn concat<'a>(parts: impl IntoIterator<Item = &'a BitVec>) -> BitVec {
+        BitVec(
+            parts
+                .into_iter()
+                .flat_map(|p| p.0.iter().copied())
+                .collect(),
+        )
+    }
+
+    /// Returns an iterator over the bits, starting at the least significant
+    /// bit.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl From<Vec<bool>> for BitVec {
+    fn from(bits: Vec<bool>) -> Self {
+        BitVec(bits)
+    }
+}
+
+impl fmt::Display for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in self.0.iter().rev() {
+            write!(f, "{}", if *bit { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BitVec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.chars()
+            .rev()
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(Error::InvalidArgument(format!(
+                    "{} is not a valid bit vector, expected only 0 and 1",
+                    s
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(BitVec)
+    }
+}
+
+/// Data transferred over a physical stream.
+///
+/// The data of a physical stream with dimensionality D consists of items that
+/// are nested D levels deep: elements for dimensionality 0, sequences of
+/// elements for dimensionality 1, and so on. An element is the concatenation
+/// of the element fields of the physical stream, the first field in the least
+/// significant bits.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Data {
+    Element(BitVec),
+    Sequence(Vec<Data>),
+}
+
+impl Data {
+    /// Returns an element.
+    pub fn element(bits: BitVec) -> Self {
+        Data::Element(bits)
+    }
+
+    /// Returns a sequence of `items`.
+    pub fn sequence(items: impl IntoIterator<Item = Data>) -> Self {
+        Data::Sequence(items.into_iter().collect())
+    }
+
+    /// Splits the elements of this data into the values of the element
+    /// fields of `stream`. Returns an error if an element does not have as
+    /// many bits as the element fields together.
+    pub fn fields(&self, stream: &PhysicalStream) -> Result<FieldData> {
+        match self {
+            Data::Element(bits) => {
+                let width: usize = stream
+                    .element_fields()
+                    .values()
+                    .map(|w| w.get() as usize)
+                    .sum();
+                if bits.len() != width {
+                    return Err(Error::InvalidArgument(format!(
+                        "element {} does not have the {} bits of the element fields",
+                        bits, width
+                    )));
+                }
+                let mut offset = 0;
+                let mut values = IndexMap::new();
+                for (path, width) in stream.element_fields().iter() {
+                    let width = width.get() as usize;
+                    values.insert(path.clone(), bits.slice(offset, width));
+                    offset += width;
+                }
+                Ok(FieldData::Element(values))
+            }
+            Data::Sequence(items) => Ok(FieldData::Sequence(
+                items
+                    .iter()
+                    .map(|item| item.fields(stream))
+                    .collect::<Result<_>>()?,
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Data {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Data::Element(bits) => write!(f, "{}", bits),
+            Data::Sequence(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Data of which the elements are split into the values of the element
+/// fields of a physical stream, by the path names of the fields. Elements
+/// are displayed with the names of their fields, e.g. `{b: 0011, c: 10}` for
+/// `Group<b: Bits<4>, c: Bits<2>>`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldData {
+    Element(IndexMap<PathName, BitVec>),
+    Sequence(Vec<FieldData>),
+}
+
+impl FieldData {
+    /// Returns the value of the element field at `path`, if this is an
+    /// element with this field.
+    pub fn field(&self, path: &PathName) -> Option<&BitVec> {
+        match self {
+            FieldData::Element(values) => values.get(path),
+            FieldData::Sequence(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for FieldData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldData::Element(values) => {
+                write!(f, "{{")?;
+                for (i, (path, bits)) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    if path.is_empty() {
+                        write!(f, "{}", bits)?;
+                    } else {
+                        write!(f, "{}: {}", path, bits)?;
+                    }
+                }
+                write!(f, "}}")
+            }
+            FieldData::Sequence(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// A transfer of a physical stream.
+///
+/// Contains the values of the signals of the physical stream during a single
+/// handshake, excluding the `valid` and `ready` signals. Signals that are not
+/// part of the physical stream have no value.
+///
+/// [Reference]
+///
+/// [Reference]: https://abs-tudelft.github.io/tydi/specification/physical.html#signals
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transfer {
+    data: Option<BitVec>,
+    last: Option<BitVec>,
+    stai: Option<BitVec>,
+    endi: Option<BitVec>,
+    strb: Option<BitVec>,
+    user: Option<BitVec>,
+}
+
+impl Transfer {
+    /// Returns a transfer of `stream` with all signals low.
+    pub fn new(stream: &PhysicalStream) -> Self {
+        let mut transfer = Transfer::default();
+        for signal in &stream.signal_list() {
+            let width = match signal.width() {
+                Width::Scalar => 1,
+                Width::Vector(width) => width as usize,
+            };
+            if let Some(value) = transfer.signal_mut(signal.identifier()) {
+                *value = Some(BitVec::zeros(width));
+            }
+        }
+        transfer
+    }
+
+    /// Returns the `data` signal, if applicable for the physical stream.
+    pub fn data(&self) -> Option<&BitVec> {
+        self.data.as_ref()
+    }
+
+    /// Returns the `last` signal, if applicable for the physical stream.
+    pub fn last(&self) -> Option<&BitVec> {
+        self.last.as_ref()
+    }
+
+    /// Returns the `stai` signal, if applicable for the physical stream.
+    pub fn stai(&self) -> Option<&BitVec> {
+        self.stai.as_ref()
+    }
+
+    /// Returns the `endi` signal, if applicable for the physical stream.
+    pub fn endi(&self) -> Option<&BitVec> {
+        self.endi.as_ref()
+    }
+
+    /// Returns the `strb` signal, if applicable for the physical stream.
+    pub fn strb(&self) -> Option<&BitVec> {
+        self.strb.as_ref()
+    }
+
+    /// Returns the `user` signal, if applicable for the physical stream.
+    pub fn user(&self) -> Option<&BitVec> {
+        self.user.as_ref()
+    }
+
+    /// Returns the signal named `name`, if applicable for the physical
+    /// stream.
+    pub fn signal(&self, name: &str) -> Option<&BitVec> {
+        self.signals()
+            .find(|(signal, _)| *signal == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets the signal named `name` to `value`. Returns an error if there is
+    /// no such signal.
+    pub fn set_signal(&mut self, name: &str, value: BitVec) -> Result<()> {
+        match self.signal_mut(name) {
+            Some(signal) => {
+                *signal = Some(value);
+                Ok(())
+            }
+            None => Err(Error::InvalidArgument(format!(
+                "{} is not a signal of a transfer",
+                name
+            ))),
+        }
+    }
+
+    /// Returns the names and values of the signals of this transfer, in the
+    /// order of the [`SignalList`].
+    ///
+    /// [`SignalList`]: ../struct.SignalList.html
+    pub fn signals(&self) -> impl Iterator<Item = (&'static str, &BitVec)> {
+        vec![
+            ("data", &self.data),
+            ("last", &self.last),
+            ("stai", &self.stai),
+            ("endi", &self.endi),
+            ("strb", &self.strb),
+            ("user", &self.user),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_ref().map(|value| (name, value)))
+    }
+
+    fn signal_mut(&mut self, name: &str) -> Option<&mut Option<BitVec>> {
+        match name {
+            "data" => Some(&mut self.data),
+            "last" => Some(&mut self.last),
+            "stai" => Some(&mut self.stai),
+            "endi" => Some(&mut self.endi),
+            "strb" => Some(&mut self.strb),
+            "user" => Some(&mut self.user),
+            _ => None,
+        }
+    }
+
+    /// Returns the `last` flags of this transfer, the innermost dimension
+    /// first.
+    fn last_flags(&self) -> Vec<bool> {
+        self.last.iter().flat_map(|last| last.iter()).collect()
+    }
+
+    /// Returns the start index. Defaults to the first lane.
+    fn start_index(&self) -> usize {
+        self.stai
+            .as_ref()
+            .and_then(BitVec::to_unsigned)
+            .unwrap_or(0) as usize
+    }
+
+    /// Returns the end index. Defaults to the last lane of `stream`.
+    fn end_index(&self, stream: &PhysicalStream) -> usize {
+        match &self.endi {
+            Some(endi) => endi.to_unsigned().unwrap_or(u64::MAX) as usize,
+            None => stream.element_lanes().get() as usize - 1,
+        }
+    }
+
+    /// Returns the lanes of `stream` that carry an element in this transfer.
+    ///
+    /// A lane is active if it is within the start and end index, and its
+    /// strobe bit is set.
+    pub fn active_lanes(&self, stream: &PhysicalStream) -> Vec<usize> {
+        (self.start_index()..=self.end_index(stream))
+            .take_while(|lane| *lane < stream.element_lanes().get() as usize)
+            .filter(|lane| self.strb.as_ref().is_none_or(|strb| strb.get(*lane)))
+            .collect()
+    }
+
+    /// Returns the element on `lane` of `stream`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lane does not exist.
+    pub fn element(&self, stream: &PhysicalStream, lane: usize) -> BitVec {
+        let width = element_width(stream);
+        match &self.data {
+            Some(data) => data.slice(lane * width, width),
+            None => BitVec::default(),
+        }
+    }
+
+    /// Checks that the signals of this transfer match the signals of `stream`,
+    /// and that their values are allowed at the complexity of `stream`.
+    pub fn validate(&self, stream: &PhysicalStream) -> Result<()> {
+        let expected = Transfer::new(stream);
+        for (name, value) in self.signals() {
+            match expected.signal(name) {
+                None => {
+                    return Err(Error::ProtocolError(format!(
+                        "signal {} is not part of the physical stream",
+                        name
+                    )))
+                }
+                Some(expected) if expected.len() != value.len() => {
+                    return Err(Error::ProtocolError(format!(
+                        "signal {} has {} bits, expected {}",
+                        name,
+                        value.len(),
+                        expected.len()
+                    )))
+                }
+                _ => (),
+            }
+        }
+        if let Some((name, _)) = expected
+            .signals()
+            .find(|(name, _)| self.signal(name).is_none())
+        {
+            return Err(Error::ProtocolError(format!("signal {} is missing", name)));
+        }
+
+        let lanes = stream.element_lanes().get() as usize;
+        let complexity = stream.complexity().major();
+        let (stai, endi) = (self.start_index(), self.end_index(stream));
+        if stai >= lanes || endi >= lanes {
+            return Err(Error::ProtocolError(format!(
+                "stai ({}) and endi ({}) must be less than the number of element lanes ({})",
+                stai, endi, lanes
+            )));
+        }
+        if let Some(strb) = &self.strb {
+            if complexity < 7 && strb.iter().any(|bit| bit != strb.get(0)) {
+                return Err(Error::ProtocolError(format!(
+                    "strb ({}) must be all ones or all zeros below complexity 7",
+                    strb
+                )));
+            }
+        }
+        if complexity < 5 && endi != lanes - 1 && !self.last_flags().contains(&true) {
+            return Err(Error::ProtocolError(format!(
+                "endi ({}) must be {} for transfers that do not assert last below complexity 5",
+                endi,
+                lanes - 1
+            )));
+        }
+        // Transfers without active lanes have no meaningful indices.
+        let strobed = self
+            .strb
+            .as_ref()
+            .is_none_or(|strb| strb.iter().any(|bit| bit));
+        if stai > endi && strobed {
+            return Err(Error::ProtocolError(format!(
+                "stai ({}) is greater than endi ({})",
+                stai, endi
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the bit count of a single element of `stream`.
+fn element_width(stream: &PhysicalStream) -> usize {
+    stream
+        .element_fields()
+        .values()
+        .map(|b| b.get() as usize)
+        .sum()
+}
+
+/// Packs elements into as few transfers as possible.
+struct Encoder<'a> {
+    stream: &'a PhysicalStream,
+    /// The elements of the current transfer.
+    elements: Vec<BitVec>,
+    /// The last flags of the current transfer.
+    last: Vec<bool>,
+    transfers: Vec<Transfer>,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(stream: &'a PhysicalStream) -> Self {
+        Encoder {
+            stream,
+            elements: vec![],
+            last: vec![false; stream.dimensionality() as usize],
+            transfers: vec![],
+        }
+    }
+
+    /// Encodes `item`, which has `dimensionality` levels of nesting.
+    fn item(&mut self, item: &Data, dimensionality: NonNegative) -> Result<()> {
+        match (item, dimensionality) {
+            (Data::Element(element), 0) => {
+                if element.len() != element_width(self.stream) {
+                    return Err(Error::InvalidArgument(format!(
+                        "element {} has {} bits, expected {}",
+                        element,
+                        element.len(),
+                        element_width(self.stream)
+                    )));
+                }
+                // Elements following the end of a sequence start a new transfer.
+                if self.last.contains(&true)
+                    || self.elements.len() == self.stream.element_lanes().get() as usize
+                {
+                    self.flush()?;
+                }
+                self.elements.push(element.clone());
+                Ok(())
+            }
+            (Data::Sequence(items), d) if d > 0 => {
+                for item in items {
+                    self.item(item, d - 1)?;
+                }
+                // The end of a sequence is signaled on the transfer with its last element, unless
+                // that transfer already ends a sequence of this or an outer dimension.
+                let dimension = d as usize - 1;
+                if self.last[dimension..].contains(&true) {
+                    self.flush()?;
+                }
+                self.last[dimension] = true;
+                Ok(())
+            }
+            (Data::Element(element), d) => Err(Error::InvalidArgument(format!(
+                "expected a sequence of dimensionality {}, found element {}",
+                d, element
+            ))),
+            (Data::Sequence(_), _) => Err(Error::InvalidArgument(format!(
+                "expected an element, found sequence {}",
+                item
+            ))),
+        }
+    }
+
+    /// Completes the current transfer.
+    fn flush(&mut self) -> Result<()> {
+        let mut transfer = Transfer::new(self.stream);
+        let count = self.elements.len();
+        if count > 0
+            && count < self.stream.element_lanes().get() as usize
+            && transfer.endi.is_none()
+        {
+            return Err(Error::ProtocolError(format!(
+                "cannot transfer {} element(s) on {} element lanes at complexity {}, which \
+                 requires all lanes to be used",
+                count,
+                self.stream.element_lanes(),
+                self.stream.complexity()
+            )));
+        }
+        if let Some(data) = &mut transfer.data {
+            data.splice(0, &BitVec::concat(&self.elements));
+        }
+        if let Some(last) = &mut transfer.last {
+            *last = BitVec::from(self.last.clone());
+        }
+        if let Some(endi) = &mut transfer.endi {
+            *endi = BitVec::from_unsigned(count.saturating_sub(1) as u64, endi.len())?;
+        }
+        if let Some(strb) = &mut transfer.strb {
+            // Lanes beyond the end index are inactive, so the strobe is either all ones or, for
+            // transfers that only end a sequence, all zeros.
+            if count > 0 {
+                *strb = BitVec::ones(strb.len());
+            }
+        }
+        self.transfers.push(transfer);
+        self.elements.clear();
+        self.last.iter_mut().for_each(|last| *last = false);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<Transfer>> {
+        if !self.elements.is_empty() || self.last.contains(&true) {
+            self.flush()?;
+        }
+        Ok(self.transfers)
+    }
+}
+
+/// Reconstructs the data of a physical stream from its transfers.
+///
+/// Transfers are pushed one at a time, checking the rules of the complexity
+/// level of the physical stream. Complete items are available as soon as
+/// their last transfer is pushed.
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    stream: &'a PhysicalStream,
+    /// The sequence that is open in every dimension, the innermost dimension
+    /// first.
+    open: Vec<Option<Vec<Data>>>,
+    items: Vec<Data>,
+}
+
+impl<'a> Decoder<'a> {
+    /// Returns a decoder for transfers of `stream`.
+    pub fn new(stream: &'a PhysicalStream) -> Self {
+        Decoder {
+            stream,
+            open: vec![None; stream.dimensionality() as usize],
+            items: vec![],
+        }
+    }
+
+    /// Returns the complete items decoded so far.
+    pub fn items(&self) -> &[Data] {
+        &self.items
+    }
+
+    /// Returns true if elements of an innermost sequence have been decoded,
+    /// but the sequence has not ended yet.
+    pub fn in_sequence(&self) -> bool {
+        matches!(self.open.first(), Some(Some(_)))
+    }
+
+    /// Returns true if an outermost sequence has started, but has not ended
+    /// yet, i.e. the last transfer did not have all its `last` flags set.
+    pub fn in_outermost_sequence(&self) -> bool {
+        self.open.iter().any(Option::is_some)
+    }
+
+    /// Decodes `transfer`.
+    pub fn push(&mut self, transfer: &Transfer) -> Result<()> {
+        transfer.validate(self.stream)?;
+        let lanes = transfer.active_lanes(self.stream);
+        let last = transfer.last_flags();
+        if lanes.is_empty() && self.stream.complexity().major() < 4 {
+            // Below complexity 4, the end of a sequence may not be postponed to a transfer that
+            // does not carry its last element, so such transfers can only end empty sequences.
+            match last.iter().position(|last| *last) {
+                None => {
+                    return Err(Error::ProtocolError(
+                        "transfers without active lanes must end a sequence below complexity 4"
+                            .to_string(),
+                    ))
+                }
+                Some(dimension) if self.open[dimension].is_some() => {
+                    return Err(Error::ProtocolError(format!(
+                        "the end of a sequence of dimension {} is postponed to a transfer \
+                         without active lanes, which is not allowed below complexity 4",
+                        dimension
+                    )))
+                }
+                _ => (),
+            }
+        }
+
+        for lane in lanes {
+            let element = Data::Element(transfer.element(self.stream, lane));
+            match self.open.first_mut() {
+                Some(open) => open.get_or_insert_with(Vec::new).push(element),
+                None => self.items.push(element),
+            }
+        }
+        for (dimension, last) in last.into_iter().enumerate() {
+            if !last {
+                continue;
+            }
+            if let Some(inner) = self.open[..dimension].iter().position(Option::is_some) {
+                return Err(Error::ProtocolError(format!(
+                    "a sequence of dimension {} ends while a sequence of dimension {} is open",
+                    dimension, inner
+                )));
+            }
+            let sequence = Data::Sequence(self.open[dimension].take().unwrap_or_default());
+            match self.open.get_mut(dimension + 1) {
+                Some(outer) => outer.get_or_insert_with(Vec::new).push(sequence),
+                None => self.items.push(sequence),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the decoded items. Returns an error if a sequence has not
+    /// ended.
+    pub fn finish(self) -> Result<Vec<Data>> {
+        match self.open.iter().rposition(Option::is_some) {
+            Some(dimension) => Err(Error::ProtocolError(format!(
+                "the transfers end inside a sequence of dimension {}",
+                dimension
+            ))),
+            None => Ok(self.items),
+        }
+    }
+}
+
+impl PhysicalStream {
+    /// Encodes `data` into transfers of this physical stream. Every item of
+    /// `data` must be nested as deep as the dimensionality of this stream.
+    ///
+    /// Elements are packed into as few transfers as possible, starting at the
+    /// first lane. The end of a sequence is signaled on the transfer with its
+    /// last element. Returns an error if this is not possible at the
+    /// complexity of this stream. User signals are low.
+    pub fn encode(&self, data: &[Data]) -> Result<Vec<Transfer>> {
+        let mut encoder = Encoder::new(self);
+        for item in data {
+            encoder.item(item, self.dimensionality())?;
+        }
+        encoder.finish()
+    }
+
+    /// Decodes `transfers` of this physical stream. Returns an error if a
+    /// transfer violates the rules of the complexity of this stream, or if
+    /// the transfers end inside a sequence.
+    pub fn decode(&self, transfers: &[Transfer]) -> Result<Vec<Data>> {
+        let mut decoder = Decoder::new(self);
+        for transfer in transfers {
+            decoder.push(transfer)?;
+        }
+        decoder.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(value: u64) -> Data {
+        Data::element(BitVec::from_unsigned(value, 4).unwrap())
+    }
+
+    fn sequence(values: &[u64]) -> Data {
+        Data::sequence(values.iter().map(|v| element(*v)))
+    }
+
+    /// Returns the signals of `transfers`, e.g. `data=0001 last=1`.
+    fn signals(transfers: &[Transfer]) -> Vec<String> {
+        transfers
+            .iter()
+            .map(|t| {
+                t.signals()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bit_vec() -> Result<()> {
+        let bits = BitVec::from_unsigned(5, 4)?;
+        assert_eq!(bits.to_string(), "0101");
+        assert_eq!(bits.to_unsigned(), Some(5));
+        assert_eq!(bits.slice(1, 3).to_string(), "010");
+        assert_eq!(
+            BitVec::concat(&[bits.clone(), BitVec::ones(2)]).to_string(),
+            "110101"
+        );
+        assert_eq!("110101".parse::<BitVec>()?.to_unsigned(), Some(53));
+        assert!("12".parse::<BitVec>().is_err());
+        assert_eq!(
+            BitVec::from_unsigned(u64::MAX, 64)?.to_unsigned(),
+            Some(u64::MAX)
+        );
+        assert_eq!(BitVec::ones(65).to_unsigned(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn elements() -> Result<()> {
+        let data: Vec<Data> = (1..=3).map(element).collect();
+
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 2, 0, 1, vec![("u", 1)])?;
+        // Without endi, the element lanes of all transfers must be used.
+        assert!(stream.encode(&data).is_err());
+        let transfers = stream.encode(&data[..2])?;
+        assert_eq!(signals(&transfers), vec!["data=00100001 user=0"]);
+        assert_eq!(stream.decode(&transfers)?, data[..2].to_vec());
+
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 2, 0, 5, vec![])?;
+        let transfers = stream.encode(&data)?;
+        assert_eq!(
+            signals(&transfers),
+            vec!["data=00100001 endi=1", "data=00000011 endi=0"]
+        );
+        assert_eq!(stream.decode(&transfers)?, data);
+
+        assert!(stream.encode(&[sequence(&[1])]).is_err());
+        assert!(stream.encode(&[Data::element(BitVec::zeros(3))]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn sequences() -> Result<()> {
+        let data = vec![
+            Data::sequence(vec![sequence(&[1, 2, 3]), sequence(&[]), sequence(&[4])]),
+            Data::sequence(vec![]),
+            Data::sequence(vec![sequence(&[])]),
+        ];
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 2, 2, 1, vec![])?;
+        let transfers = stream.encode(&data)?;
+        assert_eq!(
+            signals(&transfers),
+            vec![
+                "data=00100001 last=00 endi=1 strb=11",
+                "data=00000011 last=01 endi=0 strb=11",
+                "data=00000000 last=01 endi=0 strb=00",
+                "data=00000100 last=11 endi=0 strb=11",
+                "data=00000000 last=10 endi=0 strb=00",
+                "data=00000000 last=11 endi=0 strb=00",
+            ]
+        );
+        assert_eq!(stream.decode(&transfers)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn complexity() -> Result<()> {
+        let stream =
+            |complexity| PhysicalStream::try_new(vec![("a", 4)], 4, 1, complexity, vec![]).unwrap();
+        let transfer = |signals: &[(&str, &str)]| {
+            let mut transfer = Transfer::new(&stream(8));
+            for (name, value) in signals {
+                transfer.set_signal(name, value.parse().unwrap()).unwrap();
+            }
+            transfer
+        };
+        // Decodes transfers on a stream of some complexity, dropping the signals it lacks.
+        let decode = |complexity, transfers: &[Transfer]| {
+            let stream = stream(complexity);
+            let transfers: Vec<Transfer> = transfers
+                .iter()
+                .map(|t| {
+                    let mut transfer = Transfer::new(&stream);
+                    for (name, value) in t.signals() {
+                        if transfer.signal(name).is_some() {
+                            transfer.set_signal(name, value.clone()).unwrap();
+                        }
+                    }
+                    transfer
+                })
+                .collect();
+            stream.decode(&transfers)
+        };
+        let err = |complexity, transfers: &[Transfer]| {
+            decode(complexity, transfers).unwrap_err().to_string()
+        };
+
+        // Signals must match the physical stream.
+        assert_eq!(
+            stream(1).decode(&[transfer(&[])]).unwrap_err().to_string(),
+            "Protocol error: signal stai is not part of the physical stream"
+        );
+        assert!(Transfer::new(&stream(1))
+            .set_signal("valid", BitVec::ones(1))
+            .is_err());
+
+        // Lanes may be skipped from complexity 7.
+        let skip = transfer(&[
+            ("data", "0010000000000001"),
+            ("endi", "11"),
+            ("strb", "1001"),
+            ("last", "1"),
+        ]);
+        assert!(err(6, std::slice::from_ref(&skip))
+            .contains("strb (1001) must be all ones or all zeros"));
+        assert_eq!(
+            decode(7, &[skip])?,
+            vec![Data::sequence(vec![element(1), element(2)])]
+        );
+
+        // Transfers may start at another lane than the first from complexity 6.
+        let start = transfer(&[
+            ("data", "0010000100000000"),
+            ("stai", "10"),
+            ("endi", "11"),
+            ("strb", "1111"),
+            ("last", "1"),
+        ]);
+        assert_eq!(
+            decode(6, &[start])?,
+            vec![Data::sequence(vec![element(1), element(2)])]
+        );
+
+        // Transfers may end before the last lane without ending a sequence from complexity 5.
+        let partial = transfer(&[
+            ("data", "0000000000000001"),
+            ("endi", "00"),
+            ("strb", "1111"),
+        ]);
+        let end = transfer(&[("last", "1")]);
+        assert!(err(4, &[partial.clone(), end.clone()]).contains("endi (0) must be 3"));
+
+        // The end of a sequence may be postponed from complexity 4.
+        let full = transfer(&[
+            ("data", "0100001100100001"),
+            ("endi", "11"),
+            ("strb", "1111"),
+        ]);
+        assert!(err(3, &[full.clone(), end.clone()]).contains("is postponed"));
+        assert_eq!(
+            decode(4, &[full.clone(), end.clone()])?,
+            vec![sequence(&[1, 2, 3, 4])]
+        );
+        assert_eq!(decode(5, &[partial, end])?, vec![sequence(&[1])]);
+
+        // Empty sequences are allowed at any complexity, idle transfers only from complexity 4.
+        assert_eq!(
+            decode(1, &[transfer(&[("last", "1")])])?,
+            vec![sequence(&[])]
+        );
+        let idle = transfer(&[("endi", "11")]);
+        assert!(err(3, std::slice::from_ref(&idle)).contains("must end a sequence"));
+        assert!(decode(4, &[idle])?.is_empty());
+
+        assert!(err(8, &[full]).contains("end inside a sequence of dimension 0"));
+        assert!(err(
+            8,
+            &[transfer(&[
+                ("stai", "11"),
+                ("endi", "10"),
+                ("strb", "1111")
+            ])]
+        )
+        .contains("stai (3) is greater than endi (2)"));
+        Ok(())
+    }
+
+    #[test]
+    fn nesting() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 4)], 1, 2, 8, vec![])?;
+        let transfer = |data: u64, last: &str| {
+            let mut transfer = Transfer::new(&stream);
+            transfer
+                .set_signal("data", BitVec::from_unsigned(data, 4).unwrap())
+                .unwrap();
+            transfer.set_signal("last", last.parse().unwrap()).unwrap();
+            transfer.set_signal("strb", BitVec::ones(1)).unwrap();
+            transfer
+        };
+        let mut decoder = Decoder::new(&stream);
+        decoder.push(&transfer(1, "01"))?;
+        assert!(decoder.items().is_empty());
+        decoder.push(&transfer(2, "11"))?;
+        assert_eq!(
+            decoder.items(),
+            &[Data::sequence(vec![sequence(&[1]), sequence(&[2])])]
+        );
+        assert_eq!(decoder.items()[0].to_string(), "[[0001], [0010]]");
+        assert!(decoder
+            .push(&transfer(3, "10"))
+            .unwrap_err()
+            .to_string()
+            .contains("a sequence of dimension 1 ends while a sequence of dimension 0 is open"));
+        Ok(())
+    }
+
+    #[test]
+    fn fields() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 3), ("b", 1)], 1, 1, 4, vec![])?;
+        let fields = sequence(&[0b1010, 0b0111]).fields(&stream)?;
+        assert_eq!(fields.to_string(), "[{a: 010, b: 1}, {a: 111, b: 0}]");
+        match &fields {
+            FieldData::Sequence(items) => assert_eq!(
+                items[1].field(&PathName::try_new(vec!["a"])?),
+                Some(&"111".parse()?)
+            ),
+            _ => panic!("expected a sequence"),
+        }
+        assert!(Data::element("101".parse()?).fields(&stream).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,986 @@
+//! Machine-readable representation of projects.
+//!
+//! The types in this module mirror the design model: projects, libraries, named types,
+//! streamlets, interfaces, logical types, parameters and structural implementations. They are
+//! converted from and into the design model, and can be (de)serialized with [serde], e.g. as JSON
+//! or YAML using [to_json], [from_json], [to_yaml] and [from_yaml].
+//!
+//! Kinds of types and parameters, and interface modes, are named like they are in Streamlet
+//! Definition Files. Libraries, streamlets and nodes are listed in alphabetical order, such that
+//! exporting the same project twice gives the same result. The types of interfaces are exported
+//! in full, along with the named types they use.
+//!
+//! Only structural implementations are exported. Their nodes refer to the streamlet they are an
+//! instance of. Nodes that were generated from a pattern refer to the generated streamlet in the
+//! `gen` library, and are imported as plain instances of that streamlet. Nodes list the
+//! parameters they bind, if any.
+
+use std::convert::{TryFrom, TryInto};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::design::implementation::composer::impl_graph::Node as GraphNode;
+use crate::design::implementation::composer::impl_graph::{Edge as GraphEdge, ImplementationGraph};
+use crate::design::implementation::composer::parser::{check_edge, dependency_order};
+use crate::design::implementation::composer::GenericComponent;
+use crate::design::implementation::Implementation as DesignImplementation;
+use crate::design::param::{
+    NamedParameter, ParamStream, ParamType, ParamUInt, ParameterStore, ParameterVariant,
+};
+use crate::design::{
+    self,
+    library::{NamedType as DesignNamedType, TypeName as DesignTypeName},
+};
+use crate::design::{NodeIFHandle, StreamletHandle, THIS_KEY};
+use crate::logical::LogicalType;
+use crate::{
+    Document, Error, Identify, Name, PathName, PositiveReal, Result, UniqueKeyBuilder,
+    UniquelyNamedBuilder,
+};
+
+/// A project, consisting of libraries.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub name: String,
+    pub libraries: Vec<Library>,
+}
+
+/// A library with named types and streamlets.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Library {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<NamedType>,
+    /// Types declared in other libraries that are used by this library.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<Import>,
+    #[serde(default)]
+    pub streamlets: Vec<Streamlet>,
+}
+
+/// A type declared with a name, e.g. `type Pixel = Bits<24>;`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NamedType {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    #[serde(rename = "type")]
+    pub typ: Type,
+    /// The named types this type is composed of.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<TypeName>,
+}
+
+/// A named type that is declared in another library.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Import {
+    pub library: String,
+    #[serde(flatten)]
+    pub typ: NamedType,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Streamlet {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Parameter>,
+    pub interfaces: Vec<Interface>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<Implementation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interface {
+    pub name: String,
+    pub mode: Mode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// The type of the interface, with the default values of the parameters of the streamlet.
+    #[serde(rename = "type")]
+    pub typ: Type,
+    /// The type of the interface in terms of the parameters of the streamlet, if it depends on
+    /// them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<Type>,
+    /// The named types used in the type of the interface.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<TypeName>,
+}
+
+/// A named type used in the type of an interface or of a named type, at the path of the group
+/// and union fields that lead to it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TypeName {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<String>,
+    pub library: String,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    In,
+    Out,
+}
+
+/// A parameter of a streamlet, with its default value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    #[serde(flatten)]
+    pub value: Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
+pub enum Value {
+    Type(Type),
+    String(String),
+    UInt(u32),
+}
+
+/// A logical type, which may refer to parameters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Type {
+    Null,
+    Bits {
+        width: UInt,
+    },
+    Group {
+        fields: Vec<Field>,
+    },
+    Union {
+        fields: Vec<Field>,
+    },
+    Stream(Box<Stream>),
+    /// A reference to a parameter of kind `Type`.
+    Param {
+        name: String,
+    },
+}
+
+/// An unsigned integer, or a reference to a parameter of kind `UInt`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UInt {
+    Literal(u32),
+    Param(String),
+}
+
+impl Default for UInt {
+    fn default() -> Self {
+        UInt::Literal(0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: Type,
+}
+
+/// The properties of a stream. Properties that are left out get their default value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stream {
+    pub data: Type,
+    #[serde(default = "default_throughput")]
+    pub throughput: f64,
+    #[serde(default)]
+    pub dimensionality: UInt,
+    #[serde(default = "default_synchronicity")]
+    pub synchronicity: String,
+    #[serde(default = "default_complexity")]
+    pub complexity: String,
+    #[serde(default = "default_direction")]
+    pub direction: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<Type>,
+    #[serde(default)]
+    pub keep: bool,
+}
+
+fn default_throughput() -> f64 {
+    1.
+}
+
+fn default_synchronicity() -> String {
+    "Sync".to_string()
+}
+
+fn default_complexity() -> String {
+    "4".to_string()
+}
+
+fn default_direction() -> String {
+    "Forward".to_string()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Implementation {
+    Structural { nodes: Vec<Node>, edges: Vec<Edge> },
+}
+
+/// An instance of a streamlet in a structural implementation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    pub name: String,
+    pub library: String,
+    pub streamlet: String,
+    /// The parameters of the streamlet bound by this instance.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Parameter>,
+}
+
+/// A connection between two interfaces, written as `node.interface`. The streamlet that is
+/// implemented is referred to as `this`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Edge {
+    pub source: String,
+    pub sink: String,
+}
+
+/// Export [project] as JSON.
+pub fn to_json(project: &design::Project) -> Result<String> {
+    serde_json::to_string_pretty(&Project::from(project))
+        .map_err(|e| Error::BackEndError(format!("Unable to export project: {}", e)))
+}
+
+/// Import a project from JSON.
+pub fn from_json(input: &str) -> Result<design::Project> {
+    let project: Project =
+        serde_json::from_str(input).map_err(|e| Error::ParsingError(e.to_string()))?;
+    design::Project::try_from(&project)
+}
+
+/// Export [project] as YAML.
+pub fn to_yaml(project: &design::Project) -> Result<String> {
+    serde_yaml::to_string(&Project::from(project))
+        .map_err(|e| Error::BackEndError(format!("Unable to export project: {}", e)))
+}
+
+/// Import a project from YAML.
+pub fn from_yaml(input: &str) -> Result<design::Project> {
+    let project: Project =
+        serde_yaml::from_str(input).map_err(|e| Error::ParsingError(e.to_string()))?;
+    design::Project::try_from(&project)
+}
+
+impl From<&design::Project> for Project {
+    fn from(project: &design::Project) -> Self {
+        let mut libraries: Vec<Library> = project.libraries().map(Library::from).collect();
+        libraries.sort_by(|a, b| a.name.cmp(&b.name));
+        Project {
+            name: project.identifier().to_string(),
+            libraries,
+        }
+    }
+}
+
+impl TryFrom<&Project> for design::Project {
+    type Error = Error;
+
+    fn try_from(project: &Project) -> Result<Self> {
+        let mut result = design::Project::from_builder(
+            Name::try_new(&project.name)?,
+            UniquelyNamedBuilder::new().with_items(
+                project
+                    .libraries
+                    .iter()
+                    .map(design::Library::try_from)
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+        )?;
+
+        // Streamlets are copied into the implementations that instantiate them, so they are
+        // implemented in dependency order.
+        let mut implementations = vec![];
+        for library in project.libraries.iter() {
+            for streamlet in library.streamlets.iter() {
+                if let Some(Implementation::Structural { nodes, edges }) = &streamlet.implementation
+                {
+                    let handle = StreamletHandle {
+                        lib: Name::try_new(&library.name)?,
+                        streamlet: Name::try_new(&streamlet.name)?,
+                    };
+                    let dependencies = nodes
+                        .iter()
+                        .map(|node| {
+                            Ok(StreamletHandle {
+                                lib: Name::try_new(&node.library)?,
+                                streamlet: Name::try_new(&node.streamlet)?,
+                            })
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    implementations.push(((handle, dependencies), (nodes, edges)));
+                }
+            }
+        }
+        let dependencies: Vec<_> = implementations.iter().map(|(d, _)| d.clone()).collect();
+        for index in dependency_order(&dependencies)? {
+            let ((handle, _), (nodes, edges)) = &implementations[index];
+            let graph = implementation_graph(&result, handle, nodes, edges)?;
+            result.add_streamlet_impl(handle.clone(), DesignImplementation::Structural(graph))?;
+        }
+        Ok(result)
+    }
+}
+
+/// Construct the structural implementation of [handle] from its [nodes] and [edges], with the
+/// instantiated streamlets taken from [project].
+fn implementation_graph(
+    project: &design::Project,
+    handle: &StreamletHandle,
+    nodes: &[Node],
+    edges: &[Edge],
+) -> Result<ImplementationGraph> {
+    let mut graph =
+        ImplementationGraph::try_new(handle.clone(), project.get_streamlet(handle.clone())?)?;
+    for node in nodes.iter() {
+        let key = Name::try_new(&node.name)?;
+        let streamlet = StreamletHandle {
+            lib: Name::try_new(&node.library)?,
+            streamlet: Name::try_new(&node.streamlet)?,
+        };
+        let parameters = node
+            .parameters
+            .iter()
+            .map(|param| {
+                let param = NamedParameter::try_from(param)?;
+                Ok((param.key().clone(), param.item().clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let item = project.get_streamlet(streamlet.clone())?;
+        let item = item.instantiate(item.key().clone(), parameters.clone())?;
+        let node = GraphNode {
+            key: key.clone(),
+            item: Rc::new(item),
+            streamlet,
+            parameters,
+        };
+        if graph.nodes.insert(key.clone(), node).is_some() {
+            return Err(Error::ComposerError(format!(
+                "Instance {} already exists.",
+                key
+            )));
+        }
+    }
+    for edge in edges.iter() {
+        let edge = GraphEdge {
+            source: node_if_handle(&edge.source)?,
+            sink: node_if_handle(&edge.sink)?,
+        };
+        check_edge(&graph, &edge, false)?;
+        graph.edges.push(edge);
+    }
+    Ok(graph)
+}
+
+fn node_if_handle(handle: &str) -> Result<NodeIFHandle> {
+    let mut parts = handle.splitn(2, '.');
+    match (parts.next(), parts.next()) {
+        (Some(node), Some(iface)) => Ok(NodeIFHandle::new(
+            Name::try_new(node)?,
+            Name::try_new(iface)?,
+        )),
+        _ => Err(Error::InvalidArgument(format!(
+            "{} is not a valid interface of a node, expected node.interface",
+            handle
+        ))),
+    }
+}
+
+impl From<&design::Library> for Library {
+    fn from(library: &design::Library) -> Self {
+        let mut streamlets: Vec<Streamlet> = library.streamlets().map(Streamlet::from).collect();
+        streamlets.sort_by(|a, b| a.name.cmp(&b.name));
+        Library {
+            name: library.identifier().to_string(),
+            types: library.types().map(NamedType::from).collect(),
+            imports: library
+                .imports()
+                .map(|(lib, typ)| Import {
+                    library: lib.to_string(),
+                    typ: NamedType::from(typ),
+                })
+                .collect(),
+            streamlets,
+        }
+    }
+}
+
+impl TryFrom<&Library> for design::Library {
+    type Error = Error;
+
+    fn try_from(library: &Library) -> Result<Self> {
+        let streamlets = library
+            .streamlets
+            .iter()
+            .map(design::Streamlet::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        // Every parameterized streamlet has a parameter store with its default values.
+        let parameter_stores = streamlets
+            .iter()
+            .filter_map(|s| s.parameters().cloned())
+            .collect::<Vec<ParameterStore>>();
+        let mut result = design::Library::from_builder(
+            Name::try_new(&library.name)?,
+            UniqueKeyBuilder::new().with_items(parameter_stores),
+            UniqueKeyBuilder::new().with_items(streamlets),
+        )?;
+        for typ in library.types.iter() {
+            result.add_type(DesignNamedType::try_from(typ)?)?;
+        }
+        for import in library.imports.iter() {
+            result.add_import(
+                Name::try_new(&import.library)?,
+                DesignNamedType::try_from(&import.typ)?,
+            );
+        }
+        Ok(result)
+    }
+}
+
+impl From<&DesignNamedType> for NamedType {
+    fn from(typ: &DesignNamedType) -> Self {
+        NamedType {
+            name: typ.identifier().to_string(),
+            doc: typ.doc(),
+            typ: Type::from(typ.logical_type()),
+            names: typ.type_names().iter().map(TypeName::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<&NamedType> for DesignNamedType {
+    type Error = Error;
+
+    fn try_from(typ: &NamedType) -> Result<Self> {
+        Ok(DesignNamedType::new(
+            Name::try_new(&typ.name)?,
+            LogicalType::try_from(&typ.typ)?,
+            typ.doc.as_deref(),
+        )
+        .with_type_names(
+            typ.names
+                .iter()
+                .map(DesignTypeName::try_from)
+                .collect::<Result<Vec<_>>>()?,
+        ))
+    }
+}
+
+impl From<&DesignTypeName> for TypeName {
+    fn from(name: &DesignTypeName) -> Self {
+        TypeName {
+            path: name.path().into_iter().map(|n| n.to_string()).collect(),
+            library: name.lib().to_string(),
+            name: name.key().to_string(),
+        }
+    }
+}
+
+impl TryFrom<&TypeName> for DesignTypeName {
+    type Error = Error;
+
+    fn try_from(name: &TypeName) -> Result<Self> {
+        Ok(DesignTypeName::new(
+            PathName::try_new(name.path.iter().map(String::as_str))?,
+            Name::try_new(&name.library)?,
+            Name::try_new(&name.name)?,
+        ))
+    }
+}
+
+impl From<&design::Streamlet> for Streamlet {
+    fn from(streamlet: &design::Streamlet) -> Self {
+        Streamlet {
+            name: streamlet.identifier().to_string(),
+            doc: streamlet.doc(),
+            parameters: streamlet
+                .parameters()
+                .map(|store| store.params().map(Parameter::from).collect())
+                .unwrap_or_default(),
+            interfaces: streamlet
+                .interfaces()
+                .map(|iface| Interface {
+                    name: iface.identifier().to_string(),
+                    mode: match iface.mode() {
+                        design::Mode::In => Mode::In,
+                        design::Mode::Out => Mode::Out,
+                    },
+                    doc: iface.doc(),
+                    typ: Type::from(&iface.typ()),
+                    template: streamlet.template(iface.key()).map(Type::from),
+                    names: iface.type_names().iter().map(TypeName::from).collect(),
+                })
+                .collect(),
+            implementation: match streamlet.get_implementation().as_deref() {
+                Some(DesignImplementation::Structural(graph)) => Some(Implementation::from(graph)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl TryFrom<&Streamlet> for design::Streamlet {
+    type Error = Error;
+
+    fn try_from(streamlet: &Streamlet) -> Result<Self> {
+        let key = Name::try_new(&streamlet.name)?;
+        let interfaces = streamlet
+            .interfaces
+            .iter()
+            .map(|iface| {
+                let mode = match iface.mode {
+                    Mode::In => design::Mode::In,
+                    Mode::Out => design::Mode::Out,
+                };
+                let names = iface
+                    .names
+                    .iter()
+                    .map(DesignTypeName::try_from)
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(design::Interface::try_new(
+                    iface.name.as_str(),
+                    mode,
+                    LogicalType::try_from(&iface.typ)?,
+                    iface.doc.as_deref(),
+                )?
+                .with_type_names(names))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let result = design::Streamlet::from_builder(
+            key.clone(),
+            UniqueKeyBuilder::new().with_items(interfaces),
+            streamlet.doc.as_deref(),
+        )?;
+        if streamlet.parameters.is_empty() {
+            return Ok(result);
+        }
+        let parameters = ParameterStore::from_builder(
+            key,
+            UniqueKeyBuilder::new().with_items(
+                streamlet
+                    .parameters
+                    .iter()
+                    .map(NamedParameter::try_from)
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+        )?;
+        let templates = streamlet
+            .interfaces
+            .iter()
+            .filter_map(|iface| iface.template.as_ref().map(|t| (&iface.name, t)))
+            .map(|(name, template)| Ok((Name::try_new(name)?, ParamType::try_from(template)?)))
+            .collect::<Result<_>>()?;
+        Ok(result.with_parameters(parameters, templates))
+    }
+}
+
+impl From<&NamedParameter> for Parameter {
+    fn from(param: &NamedParameter) -> Self {
+        Parameter {
+            name: param.identifier().to_string(),
+            doc: param.doc(),
+            value: Value::from(param.item()),
+        }
+    }
+}
+
+impl From<&ParameterVariant> for Value {
+    fn from(item: &ParameterVariant) -> Self {
+        match item {
+            ParameterVariant::Type(typ) => Value::Type(Type::from(typ)),
+            ParameterVariant::String(s) => Value::String(s.clone()),
+            ParameterVariant::UInt(u) => Value::UInt(*u),
+        }
+    }
+}
+
+impl TryFrom<&Parameter> for NamedParameter {
+    type Error = Error;
+
+    fn try_from(param: &Parameter) -> Result<Self> {
+        let item = match &param.value {
+            Value::Type(typ) => ParameterVariant::Type(LogicalType::try_from(typ)?),
+            Value::String(s) => ParameterVariant::String(s.clone()),
+            Value::UInt(u) => ParameterVariant::UInt(*u),
+        };
+        NamedParameter::try_new(param.name.as_str(), item, param.doc.as_deref())
+    }
+}
+
+impl From<&ImplementationGraph> for Implementation {
+    fn from(graph: &ImplementationGraph) -> Self {
+        let mut nodes: Vec<Node> = graph
+            .nodes()
+            .filter(|node| node.key() != *THIS_KEY)
+            .map(|node| {
+                let handle = node.streamlet_handle();
+                Node {
+                    name: node.key().to_string(),
+                    library: handle.lib().to_string(),
+                    streamlet: handle.streamlet().to_string(),
+                    parameters: node
+                        .parameters()
+                        .iter()
+                        .map(|(key, item)| Parameter {
+                            name: key.to_string(),
+                            doc: None,
+                            value: Value::from(item),
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        let end = |handle: NodeIFHandle| format!("{}.{}", handle.node(), handle.iface());
+        Implementation::Structural {
+            nodes,
+            edges: graph
+                .edges()
+                .map(|edge| Edge {
+                    source: end(edge.source()),
+                    sink: end(edge.sink()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&LogicalType> for Type {
+    fn from(typ: &LogicalType) -> Self {
+        Type::from(&ParamType::from(typ))
+    }
+}
+
+impl TryFrom<&Type> for LogicalType {
+    type Error = Error;
+
+    fn try_from(typ: &Type) -> Result<Self> {
+        LogicalType::try_from(&ParamType::try_from(typ)?)
+    }
+}
+
+impl From<&ParamType> for Type {
+    fn from(typ: &ParamType) -> Self {
+        let fields = |fields: &Vec<(Name, ParamType)>| {
+            fields
+                .iter()
+                .map(|(name, typ)| Field {
+                    name: name.to_string(),
+                    typ: Type::from(typ),
+                })
+                .collect()
+        };
+        match typ {
+            ParamType::Null => Type::Null,
+            ParamType::Bits(width) => Type::Bits {
+                width: UInt::from(width),
+            },
+            ParamType::Group(f) => Type::Group { fields: fields(f) },
+            ParamType::Union(f) => Type::Union { fields: fields(f) },
+            ParamType::Stream(stream) => Type::Stream(Box::new(Stream {
+                data: Type::from(&stream.data),
+                throughput: stream.throughput.get(),
+                dimensionality: UInt::from(&stream.dimensionality),
+                synchronicity: format!("{:?}", stream.synchronicity),
+                complexity: stream.complexity.to_string(),
+                direction: format!("{:?}", stream.direction),
+                user: stream.user.as_ref().map(Type::from),
+                keep: stream.keep,
+            })),
+            ParamType::Param(name) => Type::Param {
+                name: name.to_string(),
+            },
+        }
+    }
+}
+
+impl TryFrom<&Type> for ParamType {
+    type Error = Error;
+
+    fn try_from(typ: &Type) -> Result<Self> {
+        let fields = |fields: &Vec<Field>| -> Result<Vec<(Name, ParamType)>> {
+            fields
+                .iter()
+                .map(|field| {
+                    Ok((
+                        Name::try_new(&field.name)?,
+                        ParamType::try_from(&field.typ)?,
+                    ))
+                })
+                .collect()
+        };
+        Ok(match typ {
+            Type::Null => ParamType::Null,
+            Type::Bits { width } => ParamType::Bits(width.try_into()?),
+            Type::Group { fields: f } => ParamType::Group(fields(f)?),
+            Type::Union { fields: f } => ParamType::Union(fields(f)?),
+            Type::Stream(stream) => ParamType::Stream(Box::new(ParamStream {
+                data: ParamType::try_from(&stream.data)?,
+                throughput: PositiveReal::new(stream.throughput)?,
+                dimensionality: (&stream.dimensionality).try_into()?,
+                synchronicity: stream.synchronicity.parse()?,
+                complexity: stream.complexity.parse()?,
+                direction: stream.direction.parse()?,
+                user: match &stream.user {
+                    Some(user) => Some(ParamType::try_from(user)?),
+                    None => None,
+                },
+                keep: stream.keep,
+            })),
+            Type::Param { name } => ParamType::Param(Name::try_new(name)?),
+        })
+    }
+}
+
+impl From<&ParamUInt> for UInt {
+    fn from(value: &ParamUInt) -> Self {
+        match value {
+            ParamUInt::Literal(value) => UInt::Literal(*value),
+            ParamUInt::Param(name) => UInt::Param(name.to_string()),
+        }
+    }
+}
+
+impl TryFrom<&UInt> for ParamUInt {
+    type Error = Error;
+
+    fn try_from(value: &UInt) -> Result<Self> {
+        Ok(match value {
+            UInt::Literal(value) => ParamUInt::Literal(*value),
+            UInt::Param(name) => ParamUInt::Param(Name::try_new(name)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::design::implementation::composer::tests::composition_test_proj;
+    use crate::design::namespace::{resolve, Source};
+    use crate::parser::nom::{definitions, logical_stream_type, streamlet};
+
+    use super::*;
+
+    fn project() -> Result<design::Project> {
+        let sources = [
+            Source::new(
+                None,
+                Name::try_new("app")?,
+                definitions(
+                    "use common::Pixel;
+                     /// A frame of pixels.
+                     type Frame = Stream<Pixel, d=2, c=7>;
+                     /// Blurs frames.
+                     Streamlet blur<W: UInt = 8, T: Type = Null> (
+                         /// The input frames.
+                         i: in Frame,
+                         o: out Stream<Group<a: Bits<W>, b: T>, t=2.5, s=Desync>
+                     )",
+                )
+                .unwrap()
+                .1,
+            ),
+            Source::new(
+                None,
+                Name::try_new("common")?,
+                definitions("type Pixel = Union<r: Bits<8>, g: Null>;")
+                    .unwrap()
+                    .1,
+            ),
+        ];
+        design::Project::from_builder(
+            Name::try_new("proj")?,
+            UniquelyNamedBuilder::new().with_items(resolve(&sources)?),
+        )
+    }
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let json = to_json(&project()?)?;
+        assert_eq!(to_json(&from_json(&json)?)?, json);
+        let yaml = to_yaml(&project()?)?;
+        assert_eq!(to_yaml(&from_yaml(&yaml)?)?, yaml);
+        assert_eq!(to_json(&from_yaml(&yaml)?)?, json);
+
+        // Implementations, including the implementations of instantiated streamlets.
+        let mut prj = composition_test_proj()?;
+        prj.add_implementation("impl primitives.Magic structural { this.out <= this.in; }")?;
+        prj.add_implementation(include_str!(
+            "../tests/implementations/composition_example.impl"
+        ))?;
+        let json = to_json(&prj)?;
+        let imported = from_json(&json)?;
+        assert_eq!(to_json(&imported)?, json);
+        let top = imported.get_streamlet(StreamletHandle {
+            lib: Name::try_new("compositions")?,
+            streamlet: Name::try_new("Top_level")?,
+        })?;
+        match top.get_implementation().as_deref() {
+            Some(DesignImplementation::Structural(graph)) => {
+                let magic = graph.get_node(Name::try_new("streamlet_inst")?)?;
+                assert!(magic.component().get_implementation().is_some());
+            }
+            _ => panic!("expected a structural implementation"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bound_parameters() -> Result<()> {
+        let mut prj = design::Project::new(Name::try_new("proj")?);
+        let mut lib = design::Library::new(Name::try_new("lib")?);
+        lib.add_streamlet(
+            streamlet(
+                "Streamlet Fifo<W: UInt = 8> (in: in Stream<Bits<W>>, out: out Stream<Bits<W>>)",
+            )
+            .unwrap()
+            .1,
+        )?;
+        lib.add_streamlet(
+            streamlet("Streamlet top (a: in Stream<Bits<16>>, b: out Stream<Bits<16>>)")
+                .unwrap()
+                .1,
+        )?;
+        prj.add_lib(lib)?;
+        prj.add_implementation(
+            "impl lib.top structural {
+                fifo: lib.Fifo [W := 16];
+                fifo.in <= this.a;
+                this.b <= fifo.out;
+            }",
+        )?;
+        let exported = Project::from(&prj);
+        let lib = exported
+            .libraries
+            .iter()
+            .find(|lib| lib.name == "lib")
+            .unwrap();
+        let top = lib.streamlets.iter().find(|s| s.name == "top").unwrap();
+        match &top.implementation {
+            Some(Implementation::Structural { nodes, .. }) => assert_eq!(
+                nodes[0].parameters,
+                vec![Parameter {
+                    name: "W".to_string(),
+                    doc: None,
+                    value: Value::UInt(16),
+                }]
+            ),
+            _ => panic!("expected a structural implementation"),
+        }
+
+        let json = to_json(&prj)?;
+        let imported = from_json(&json)?;
+        assert_eq!(to_json(&imported)?, json);
+        let top = imported.get_streamlet(StreamletHandle {
+            lib: Name::try_new("lib")?,
+            streamlet: Name::try_new("top")?,
+        })?;
+        match top.get_implementation().as_deref() {
+            Some(DesignImplementation::Structural(graph)) => {
+                let fifo = graph.get_node(Name::try_new("fifo")?)?;
+                assert_eq!(
+                    fifo.iface(Name::try_new("out")?)?.typ(),
+                    logical_stream_type("Stream<Bits<16>>").unwrap().1
+                );
+            }
+            _ => panic!("expected a structural implementation"),
+        }
+
+        // Imported edges are checked like parsed connections.
+        assert!(json.contains("\"value\": 16"));
+        match from_json(&json.replace("\"value\": 16", "\"value\": 8")) {
+            Err(e) => assert!(e.to_string().contains("Type incompatibility")),
+            Ok(_) => panic!("expected an error"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn schema() -> Result<()> {
+        let exported = Project::from(&project()?);
+        let app = &exported.libraries[0];
+        assert_eq!(
+            serde_json::to_value(&app.types[0]).unwrap(),
+            serde_json::json!({
+                "name": "Frame",
+                "doc": " A frame of pixels.",
+                "type": {
+                    "kind": "Stream",
+                    "data": {
+                        "kind": "Union",
+                        "fields": [
+                            { "name": "r", "type": { "kind": "Bits", "width": 8 } },
+                            { "name": "g", "type": { "kind": "Null" } }
+                        ]
+                    },
+                    "throughput": 1.0,
+                    "dimensionality": 2,
+                    "synchronicity": "Sync",
+                    "complexity": "7",
+                    "direction": "Forward",
+                    "keep": false
+                },
+                "names": [{ "library": "common", "name": "Pixel" }]
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&app.streamlets[0].parameters).unwrap(),
+            serde_json::json!([
+                { "name": "W", "kind": "UInt", "value": 8 },
+                { "name": "T", "kind": "Type", "value": { "kind": "Null" } }
+            ])
+        );
+        assert_eq!(
+            serde_json::to_value(&app.streamlets[0].interfaces[1].template).unwrap(),
+            serde_json::json!({
+                "kind": "Stream",
+                "data": {
+                    "kind": "Group",
+                    "fields": [
+                        { "name": "a", "type": { "kind": "Bits", "width": "W" } },
+                        { "name": "b", "type": { "kind": "Param", "name": "T" } }
+                    ]
+                },
+                "throughput": 2.5,
+                "dimensionality": 0,
+                "synchronicity": "Desync",
+                "complexity": "4",
+                "direction": "Forward",
+                "keep": false
+            })
+        );
+
+        // Stream properties have defaults.
+        let typ: Type = serde_json::from_str(r#"{"kind":"Stream","data":{"kind":"Null"}}"#)
+            .map_err(|e| Error::ParsingError(e.to_string()))?;
+        assert_eq!(
+            LogicalType::try_from(&typ)?,
+            crate::parser::nom::logical_stream_type("Stream<Null>")
+                .unwrap()
+                .1
+        );
+
+        match from_json(
+            r#"{"name":"p","libraries":[{"name":"l","streamlets":[{"name":"s","interfaces":[
+            {"name":"i","mode":"in","type":{"kind":"Stream","data":{"kind":"Null"},"synchronicity":"Sink"}}]}]}]}"#,
+        ) {
+            Err(e) => assert_eq!(
+                e.to_string(),
+                "Invalid argument: Sink is not a valid Synchronicity"
+            ),
+            Ok(_) => panic!("expected an error"),
+        }
+        Ok(())
+    }
+}
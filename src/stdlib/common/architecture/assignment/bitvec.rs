@@ -59,7 +59,7 @@ impl BitVecValue {
         match self {
             BitVecValue::Others(_) => Ok(()),
             BitVecValue::Full(full) => {
-                if full.len() == width.try_into().unwrap() {
+                if full.len() == width as usize {
                     Ok(())
                 } else {
                     Err(Error::InvalidArgument(format!(
@@ -3,9 +3,15 @@ use std::convert::{TryFrom, TryInto};
 use super::{AssignDeclaration, Assignment, FieldSelection};
 use crate::{
     stdlib::common::architecture::{
-        assignment::{Assign, ObjectAssignment, RangeConstraint},
+        assignment::{
+            bitvec::BitVecValue, Assign, ObjectAssignment, RangeConstraint, StdLogicValue,
+        },
         declaration::ObjectDeclaration,
         object::ObjectType,
+        statement::{
+            process::{CaseChoice, CaseStatement, Process, SequentialStatement},
+            Statement,
+        },
     },
     Error, Result,
 };
@@ -28,13 +34,15 @@ pub trait FlatAssignment: FlatLength {
         from_field: &Vec<FieldSelection>,
     ) -> Result<Vec<AssignDeclaration>>;
 
-    /// Assigns a complex object to a flat object over multiple assignments
+    /// Assigns a complex object to a flat object over multiple statements
+    ///
+    /// As the variants of a union overlap on the flat object, these are assigned by a process based on the tag of the union
     fn to_flat(
         &self,
         flat_object: &ObjectDeclaration,
         to_field: &Vec<FieldSelection>,
         from_field: &Vec<FieldSelection>,
-    ) -> Result<Vec<AssignDeclaration>>;
+    ) -> Result<Vec<Statement>>;
 }
 
 impl FlatLength for ObjectType {
@@ -124,10 +132,12 @@ impl FlatAssignment for ObjectDeclaration {
                     }
                     Ok(result)
                 }
-                _ => complex_object
-                    .to_flat(self, from_field, to_field)?
+                _ => flat_statements(complex_object, self, from_field, to_field, false)?
                     .iter()
-                    .map(|x| x.reverse())
+                    .map(|x| match x {
+                        SequentialStatement::Assignment(assignment) => assignment.reverse(),
+                        _ => unreachable!(),
+                    })
                     .collect(),
             }
         }
@@ -138,124 +148,252 @@ impl FlatAssignment for ObjectDeclaration {
         flat_object: &ObjectDeclaration,
         to_field: &Vec<FieldSelection>,
         from_field: &Vec<FieldSelection>,
-    ) -> Result<Vec<AssignDeclaration>> {
-        let self_typ = self.typ().get_nested(from_field)?;
-        let flat_typ = flat_object.typ().get_nested(to_field)?;
-        if self_typ.flat_length() != flat_typ.flat_length() {
-            Err(Error::InvalidArgument(format!("Can't assign objects to one another, mismatched length (self ({}{}): {}, flat object ({}{}): {})",
-             self.identifier(), print_fields(from_field), self.flat_length_for(from_field)?,
-             flat_object.identifier(), print_fields(to_field), flat_object.flat_length_for(to_field)?)))
-        } else if !flat_typ.is_flat() {
-            Err(Error::InvalidArgument(format!(
-                "flat_object ({}{}) must be flat, is a {} instead",
-                flat_object.identifier(),
-                print_fields(to_field),
-                flat_typ
-            )))
-        } else {
-            let mut result = vec![];
-            let mut finalize = || -> Result<()> {
-                let mut new_from = from_field.clone();
-                let mut new_to = to_field.clone();
-                // If the length == 1 and one object is a Bit, make sure that both select a Bit (avoid left(1) <= right(0 downto 0))
-                if self_typ.flat_length()? == 1 {
-                    match_bit_field_selection(&flat_typ, &self_typ, &mut new_from);
-                    match_bit_field_selection(&self_typ, &flat_typ, &mut new_to);
+    ) -> Result<Vec<Statement>> {
+        flat_statements(self, flat_object, to_field, from_field, true)?
+            .into_iter()
+            .map(|statement| match statement {
+                SequentialStatement::Assignment(assignment) => Ok((*assignment).into()),
+                SequentialStatement::Case(case) => {
+                    // Unions are multiplexed by a process, which is sensitive to the complex object
+                    let label = selection_label(&format!("{}_mux", case.subject()));
+                    let mut process = Process::new(label).with_sensitivity(self)?;
+                    process.add_statement(case)?;
+                    Ok(process.into())
                 }
-                result.push(
-                    flat_object.assign(
+                SequentialStatement::If(_)
+                | SequentialStatement::Call(_)
+                | SequentialStatement::Report(_, _)
+                | SequentialStatement::Wait(_) => unreachable!(),
+            })
+            .collect()
+    }
+}
+
+/// Assigns a complex object to a flat object, as sequential statements.
+///
+/// If `multiplex` is set, the variants of unions are assigned through a case statement on their tag,
+/// otherwise every variant is assigned (which can be reversed to assign a flat object to the union).
+fn flat_statements(
+    object: &ObjectDeclaration,
+    flat_object: &ObjectDeclaration,
+    to_field: &Vec<FieldSelection>,
+    from_field: &Vec<FieldSelection>,
+    multiplex: bool,
+) -> Result<Vec<SequentialStatement>> {
+    let self_typ = object.typ().get_nested(from_field)?;
+    let flat_typ = flat_object.typ().get_nested(to_field)?;
+    if self_typ.flat_length() != flat_typ.flat_length() {
+        Err(Error::InvalidArgument(format!("Can't assign objects to one another, mismatched length (self ({}{}): {}, flat object ({}{}): {})",
+         object.identifier(), print_fields(from_field), object.flat_length_for(from_field)?,
+         flat_object.identifier(), print_fields(to_field), flat_object.flat_length_for(to_field)?)))
+    } else if !flat_typ.is_flat() {
+        Err(Error::InvalidArgument(format!(
+            "flat_object ({}{}) must be flat, is a {} instead",
+            flat_object.identifier(),
+            print_fields(to_field),
+            flat_typ
+        )))
+    } else {
+        let mut result = vec![];
+        let mut finalize = || -> Result<()> {
+            let mut new_from = from_field.clone();
+            let mut new_to = to_field.clone();
+            // If the length == 1 and one object is a Bit, make sure that both select a Bit (avoid left(1) <= right(0 downto 0))
+            if self_typ.flat_length()? == 1 {
+                match_bit_field_selection(&flat_typ, &self_typ, &mut new_from);
+                match_bit_field_selection(&self_typ, &flat_typ, &mut new_to);
+            }
+            result.push(
+                flat_object
+                    .assign(
                         &Assignment::from(
-                            ObjectAssignment::from(self.clone()).assign_from(&new_from)?,
+                            ObjectAssignment::from(object.clone()).assign_from(&new_from)?,
                         )
                         .to_nested(&new_to),
-                    )?,
-                );
-                Ok(())
-            };
-            match &self_typ {
-                ObjectType::Bit => finalize()?,
-                ObjectType::Array(arr) if arr.is_bitvector() => finalize()?,
-                ObjectType::Array(arr) => {
-                    // If the length is 1, make the last range selection an index selection, or introduce an index selection
-                    if arr.width() == 1 {
-                        let mut new_from = from_field.clone();
-                        if let Some(some) = new_from.last_mut() {
-                            if let FieldSelection::Range(range) = some {
-                                *range = RangeConstraint::Index(range.high());
-                            } else {
-                                unreachable!()
-                            }
+                    )?
+                    .into(),
+            );
+            Ok(())
+        };
+        match &self_typ {
+            ObjectType::Bit => finalize()?,
+            ObjectType::Array(arr) if arr.is_bitvector() => finalize()?,
+            ObjectType::Array(arr) => {
+                // If the length is 1, make the last range selection an index selection, or introduce an index selection
+                if arr.width() == 1 {
+                    let mut new_from = from_field.clone();
+                    if let Some(some) = new_from.last_mut() {
+                        if let FieldSelection::Range(range) = some {
+                            *range = RangeConstraint::Index(range.high());
                         } else {
-                            new_from.push(FieldSelection::index(arr.high()));
-                        };
-                        result.extend(self.to_flat(flat_object, to_field, &new_from)?);
-                    } else {
-                        for index in arr.low()..(arr.high() + 1) {
-                            let normalized_index = (index - arr.low()) as u32;
-                            let typ_length = arr.typ().flat_length()?;
-                            let mut new_from = from_field.clone();
-                            new_from.push(FieldSelection::index(index));
-                            // Subdivide the range selection on the flat object to match the length of each field of the complex object
-                            let mut new_to = to_field.clone();
-                            select_flat_range(
-                                &mut new_to,
-                                normalized_index,
-                                typ_length,
-                                &flat_typ,
-                            )?;
-                            result.extend(self.to_flat(flat_object, &new_to, &new_from)?);
+                            unreachable!()
                         }
+                    } else {
+                        new_from.push(FieldSelection::index(arr.high()));
+                    };
+                    result.extend(flat_statements(
+                        object,
+                        flat_object,
+                        to_field,
+                        &new_from,
+                        multiplex,
+                    )?);
+                } else {
+                    for index in arr.low()..(arr.high() + 1) {
+                        let normalized_index = (index - arr.low()) as u32;
+                        let typ_length = arr.typ().flat_length()?;
+                        let mut new_from = from_field.clone();
+                        new_from.push(FieldSelection::index(index));
+                        // Subdivide the range selection on the flat object to match the length of each field of the complex object
+                        let mut new_to = to_field.clone();
+                        select_flat_range(&mut new_to, normalized_index, typ_length, &flat_typ)?;
+                        result.extend(flat_statements(
+                            object,
+                            flat_object,
+                            &new_to,
+                            &new_from,
+                            multiplex,
+                        )?);
                     }
                 }
-                ObjectType::Record(rec) => {
-                    if rec.is_union() {
-                        // TODO: This is incorrect. Driving the signal from multiple sources doesn't work.
-                        // Ideally, figure out some way to generate an "or" on multiple signals
-                        let tag_length = rec.get_field("tag")?.flat_length()?;
-                        let remainder = self_typ.flat_length()? - tag_length;
-                        for (name, field) in rec.fields() {
-                            let mut new_to = to_field.clone();
-                            let mut new_from = from_field.clone();
-                            new_from.push(FieldSelection::name(name));
-                            if name == "tag" {
-                                select_specific_flat_range(
-                                    &mut new_to,
-                                    remainder,
-                                    tag_length,
-                                    &flat_typ,
-                                )?;
-                            } else {
-                                select_specific_flat_range(
-                                    &mut new_to,
-                                    0,
-                                    field.flat_length()?,
+            }
+            ObjectType::Record(rec) => {
+                if rec.is_union() {
+                    let tag_length = rec.get_field("tag")?.flat_length()?;
+                    let remainder = self_typ.flat_length()? - tag_length;
+                    let mut tag_to = to_field.clone();
+                    let mut tag_from = from_field.clone();
+                    tag_from.push(FieldSelection::name("tag"));
+                    select_specific_flat_range(&mut tag_to, remainder, tag_length, &flat_typ)?;
+                    result.extend(flat_statements(
+                        object,
+                        flat_object,
+                        &tag_to,
+                        &tag_from,
+                        multiplex,
+                    )?);
+                    // The variants overlap on the flat object, so only the variant selected by the tag can drive it.
+                    // NOTE: This assumes the tag of each variant is its position in the union, which does not hold
+                    // if the union has Null variants, as these are not part of the record.
+                    let tag = ObjectAssignment::from(object.clone()).assign_from(&tag_from)?;
+                    let mut case = CaseStatement::new(&tag)?;
+                    for (index, (name, field)) in rec
+                        .fields()
+                        .iter()
+                        .filter(|(name, _)| name.as_str() != "tag")
+                        .enumerate()
+                    {
+                        let mut new_to = to_field.clone();
+                        let mut new_from = from_field.clone();
+                        new_from.push(FieldSelection::name(name));
+                        let field_length = field.flat_length()?;
+                        select_specific_flat_range(&mut new_to, 0, field_length, &flat_typ)?;
+                        let mut statements =
+                            flat_statements(object, flat_object, &new_to, &new_from, multiplex)?;
+                        if multiplex {
+                            if field_length < remainder {
+                                statements.push(clear_flat_range(
+                                    flat_object,
+                                    to_field,
+                                    field_length,
+                                    remainder - field_length,
                                     &flat_typ,
-                                )?;
+                                )?);
                             }
-                            result.extend(self.to_flat(flat_object, &new_to, &new_from)?);
+                            case = case.with_choice(tag_choice(index, &tag.typ()?)?, statements)?;
+                        } else {
+                            result.extend(statements);
                         }
-                    } else {
-                        let mut preceding_length = 0;
-                        for (name, field) in rec.fields() {
-                            let mut new_to = to_field.clone();
-                            let mut new_from = from_field.clone();
-                            new_from.push(FieldSelection::name(name));
-                            let field_length = field.flat_length()?;
-                            select_specific_flat_range(
-                                &mut new_to,
-                                preceding_length,
-                                field_length,
+                    }
+                    // Unions without data (e.g., of streams) only assign their tag
+                    if multiplex && remainder > 0 {
+                        if !case.is_complete()? {
+                            case = case.with_others(vec![clear_flat_range(
+                                flat_object,
+                                to_field,
+                                0,
+                                remainder,
                                 &flat_typ,
-                            )?;
-                            result.extend(self.to_flat(flat_object, &new_to, &new_from)?);
-                            preceding_length += field_length;
+                            )?]);
                         }
+                        result.push(case.into());
+                    }
+                } else {
+                    let mut preceding_length = 0;
+                    for (name, field) in rec.fields() {
+                        let mut new_to = to_field.clone();
+                        let mut new_from = from_field.clone();
+                        new_from.push(FieldSelection::name(name));
+                        let field_length = field.flat_length()?;
+                        select_specific_flat_range(
+                            &mut new_to,
+                            preceding_length,
+                            field_length,
+                            &flat_typ,
+                        )?;
+                        result.extend(flat_statements(
+                            object,
+                            flat_object,
+                            &new_to,
+                            &new_from,
+                            multiplex,
+                        )?);
+                        preceding_length += field_length;
                     }
                 }
             }
-            Ok(result)
+        }
+        Ok(result)
+    }
+}
+
+/// Returns the choice of the tag of a union for the variant at `index`
+fn tag_choice(index: usize, tag_typ: &ObjectType) -> Result<CaseChoice> {
+    match (tag_typ, index) {
+        (ObjectType::Bit, 0) => Ok(StdLogicValue::Logic(false).into()),
+        (ObjectType::Bit, 1) => Ok(StdLogicValue::Logic(true).into()),
+        (ObjectType::Array(arr), _) => Ok(BitVecValue::from_str(&format!(
+            "{:0width$b}",
+            index,
+            width = arr.width() as usize
+        ))?
+        .into()),
+        _ => Err(Error::InvalidArgument(format!(
+            "Tag of type {} cannot select variant {}",
+            tag_typ, index
+        ))),
+    }
+}
+
+/// Assigns zeros to a range of a flat object, used for the bits of a union which are not part of its selected variant
+fn clear_flat_range(
+    flat_object: &ObjectDeclaration,
+    to_field: &[FieldSelection],
+    preceding_length: u32,
+    curr_length: u32,
+    flat_typ: &ObjectType,
+) -> Result<SequentialStatement> {
+    let mut new_to = to_field.to_vec();
+    select_specific_flat_range(&mut new_to, preceding_length, curr_length, flat_typ)?;
+    Ok(flat_object
+        .assign(
+            &Assignment::from(BitVecValue::Others(StdLogicValue::Logic(false))).to_nested(&new_to),
+        )?
+        .into())
+}
+
+/// Turns a selection into a label, e.g. "some_record.a(2)" becomes "some_record_a_2"
+fn selection_label(selection: &str) -> String {
+    let mut result = String::new();
+    for c in selection.chars() {
+        match c {
+            c if c.is_alphanumeric() => result.push(c),
+            '-' => result.push('n'),
+            _ if !result.ends_with('_') => result.push('_'),
+            _ => (),
         }
     }
+    result.trim_end_matches('_').to_string()
 }
 
 fn print_fields(fields: &Vec<FieldSelection>) -> String {
@@ -452,8 +590,18 @@ rec.b.d <= flat(2757 downto 1421);
         }
         assert_eq!(
             r#"flat(1338 downto 1337) <= union.tag;
-flat(41 downto 0) <= union.c;
-flat(1336 downto 0) <= union.d;
+union_tag_mux: process(union)
+begin
+  case union.tag is
+    when "00" =>
+      flat(41 downto 0) <= union.c;
+      flat(1336 downto 42) <= (others => '0');
+    when "01" =>
+      flat(1336 downto 0) <= union.d;
+    when others =>
+      flat(1336 downto 0) <= (others => '0');
+  end case;
+end process union_tag_mux;
 "#,
             full_flat
         );
@@ -495,6 +495,8 @@ pub enum FieldSelection {
     Range(RangeConstraint),
     /// The field of a record
     Name(String),
+    /// An index given by the parameter of an enclosing for-generate statement
+    Parameter(String),
 }
 
 impl fmt::Display for FieldSelection {
@@ -502,6 +504,7 @@ impl fmt::Display for FieldSelection {
         match self {
             FieldSelection::Range(range) => range.fmt(f),
             FieldSelection::Name(name) => write!(f, ".{}", name),
+            FieldSelection::Parameter(parameter) => write!(f, "({})", parameter),
         }
     }
 }
@@ -528,6 +531,11 @@ impl FieldSelection {
     pub fn name(name: impl Into<String>) -> FieldSelection {
         FieldSelection::Name(name.into())
     }
+
+    /// Select an index using the parameter of a for-generate statement
+    pub fn parameter(parameter: impl Into<String>) -> FieldSelection {
+        FieldSelection::Parameter(parameter.into())
+    }
 }
 
 /// A VHDL range constraint
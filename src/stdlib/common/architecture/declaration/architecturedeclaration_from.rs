@@ -1,4 +1,9 @@
-use super::{AliasDeclaration, ArchitectureDeclaration, ObjectDeclaration};
+use crate::generator::common::{Component, Type};
+
+use super::{
+    subprogram::Subprogram, AliasDeclaration, ArchitectureDeclaration, ObjectDeclaration,
+    SubTypeDeclaration,
+};
 
 impl From<ObjectDeclaration> for ArchitectureDeclaration<'_> {
     fn from(object: ObjectDeclaration) -> Self {
@@ -11,3 +16,31 @@ impl<'a> From<AliasDeclaration<'a>> for ArchitectureDeclaration<'a> {
         ArchitectureDeclaration::Alias(alias)
     }
 }
+
+impl From<Type> for ArchitectureDeclaration<'_> {
+    fn from(typ: Type) -> Self {
+        ArchitectureDeclaration::Type(typ)
+    }
+}
+
+impl From<SubTypeDeclaration> for ArchitectureDeclaration<'_> {
+    fn from(subtype: SubTypeDeclaration) -> Self {
+        ArchitectureDeclaration::SubType(subtype)
+    }
+}
+
+impl From<Subprogram> for ArchitectureDeclaration<'_> {
+    fn from(subprogram: Subprogram) -> Self {
+        if subprogram.is_function() {
+            ArchitectureDeclaration::Function(subprogram)
+        } else {
+            ArchitectureDeclaration::Procedure(subprogram)
+        }
+    }
+}
+
+impl From<Component> for ArchitectureDeclaration<'_> {
+    fn from(component: Component) -> Self {
+        ArchitectureDeclaration::Component(component)
+    }
+}
@@ -1,20 +1,174 @@
+use crate::generator::common::Type;
+use crate::generator::vhdl::{Declare, DeclareType, VHDLIdentifier};
+use crate::stdlib::common::architecture::assignment::FieldSelection;
+use crate::stdlib::common::architecture::object::ObjectType;
 use crate::Result;
-use crate::{stdlib::common::architecture::ArchitectureDeclare, Error};
+use crate::{stdlib::common::architecture::ArchitectureDeclare, Document, Error};
 
-use super::{ArchitectureDeclaration, ObjectDeclaration, ObjectKind, ObjectMode};
+use super::subprogram::Subprogram;
+use super::{
+    AliasDeclaration, ArchitectureDeclaration, ObjectDeclaration, ObjectKind, ObjectMode,
+    SubTypeDeclaration,
+};
+
+/// Prefixes every non-empty line of a declaration with pre, and replaces its closing ';' with post
+fn indent(declaration: &str, pre: &str, post: &str) -> String {
+    let declaration = declaration.strip_suffix(';').unwrap_or(declaration);
+    let lines = declaration
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", pre, line)
+            }
+        })
+        .collect::<Vec<String>>();
+    format!("{}{}", lines.join("\n"), post)
+}
 
 impl ArchitectureDeclare for ArchitectureDeclaration<'_> {
     fn declare(&self, pre: &str, post: &str) -> crate::Result<String> {
         match self {
-            ArchitectureDeclaration::Type(_) => todo!(),
-            ArchitectureDeclaration::SubType(_) => todo!(),
-            ArchitectureDeclaration::Procedure(_) => todo!(),
-            ArchitectureDeclaration::Function(_) => todo!(),
+            ArchitectureDeclaration::Type(typ) => match typ {
+                Type::Bit | Type::BitVec { .. } => Err(Error::InvalidArgument(format!(
+                    "{} is not a record or array, and cannot be declared as a type",
+                    typ.declare(true)?
+                ))),
+                _ => Ok(indent(&typ.declare(true)?, pre, post)),
+            },
+            ArchitectureDeclaration::SubType(subtype) => subtype.declare(pre, post),
+            ArchitectureDeclaration::Procedure(procedure) => procedure.declare(pre, post),
+            ArchitectureDeclaration::Function(function) => function.declare(pre, post),
             ArchitectureDeclaration::Object(object) => object.declare(pre, post),
-            ArchitectureDeclaration::Alias(_) => todo!(),
-            ArchitectureDeclaration::Component(_) => todo!(),
-            ArchitectureDeclaration::Custom(_) => todo!(),
+            ArchitectureDeclaration::Alias(alias) => alias.declare(pre, post),
+            ArchitectureDeclaration::Component(component) => {
+                Ok(indent(&component.declare()?, pre, post))
+            }
+            ArchitectureDeclaration::Custom(custom) => {
+                let mut result = pre.to_string();
+                result.push_str(&custom.replace("\n", &format!("\n{}", pre)));
+                result.push_str(post);
+                Ok(result)
+            }
+        }
+    }
+}
+
+impl ArchitectureDeclare for SubTypeDeclaration {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let typ = match self.typ() {
+            ObjectType::Array(array) if array.is_bitvector() => {
+                format!("std_logic_vector({} downto {})", array.high(), array.low())
+            }
+            typ => typ.type_name().to_string(),
+        };
+        Ok(format!(
+            "{}subtype {} is {}{}",
+            pre,
+            self.identifier(),
+            typ,
+            post
+        ))
+    }
+}
+
+impl ArchitectureDeclare for AliasDeclaration<'_> {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let mut result = format!("{}alias {}", pre, self.identifier());
+        // The type name of a range of an array is that of the full array, so the subtype
+        // is either declared with the range, or left to be inferred from the object
+        match (self.typ()?, self.field_selection().last()) {
+            (ObjectType::Array(array), Some(FieldSelection::Range(_))) => {
+                if array.is_bitvector() {
+                    result.push_str(&format!(
+                        " : std_logic_vector({} downto {})",
+                        array.high(),
+                        array.low()
+                    ));
+                }
+            }
+            (typ, _) => result.push_str(&format!(" : {}", typ.type_name())),
+        }
+        result.push_str(" is ");
+        result.push_str(self.object().identifier());
+        for field in self.field_selection() {
+            result.push_str(&field.to_string());
+        }
+        result.push_str(post);
+        Ok(result)
+    }
+}
+
+impl ArchitectureDeclare for Subprogram {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let nested_pre = &format!("{}  ", pre);
+        let kind = if self.is_function() {
+            "function"
+        } else {
+            "procedure"
+        };
+        let mut result = pre.to_string();
+        if let Some(doc) = self.doc() {
+            result.push_str("--");
+            result.push_str(doc.replace("\n", &format!("\n{}--", pre)).as_str());
+            result.push('\n');
+            result.push_str(pre);
+        }
+        result.push_str(&format!("{} {}", kind, self.identifier()));
+        if !self.parameters().is_empty() {
+            let mut parameters = vec![];
+            for parameter in self.parameters() {
+                let object = parameter.object();
+                parameters.push(format!(
+                    "{}{} {} : {} {}",
+                    nested_pre,
+                    match object.kind() {
+                        ObjectKind::Signal => "signal",
+                        ObjectKind::Variable => "variable",
+                        _ => "constant",
+                    },
+                    object.identifier(),
+                    parameter.mode().vhdl_identifier()?,
+                    object.typ().type_name()
+                ));
+            }
+            result.push_str(&format!("(\n{}\n{})", parameters.join(";\n"), pre));
+        }
+        if let Some(return_type) = self.return_type() {
+            result.push_str(&format!(" return {}", return_type.type_name()));
+        }
+        result.push_str(" is\n");
+        for object in self.declarations() {
+            result.push_str(&object.declare(nested_pre, ";\n")?);
         }
+        result.push_str(&format!("{}begin\n", pre));
+        for statement in self.statements() {
+            result.push_str(&statement.declare(nested_pre, ";\n")?);
+        }
+        if let Some(return_type) = self.return_type() {
+            match self.return_value() {
+                Some(value) => result.push_str(&format!(
+                    "{}return {};\n",
+                    nested_pre,
+                    value.declare_for(return_type.type_name(), nested_pre, ";\n")?
+                )),
+                None => {
+                    return Err(Error::BackEndError(format!(
+                        "Function {} does not return a value",
+                        self.identifier()
+                    )))
+                }
+            }
+        }
+        result.push_str(&format!(
+            "{}end {} {}{}",
+            pre,
+            kind,
+            self.identifier(),
+            post
+        ));
+        Ok(result)
     }
 }
 
@@ -58,7 +212,16 @@ impl ArchitectureDeclare for ObjectDeclaration {
 
 #[cfg(test)]
 mod tests {
-    use crate::stdlib::common::architecture::{assignment::StdLogicValue, object::ObjectType};
+    use crate::generator::common::{
+        test::{records, test_comp},
+        Mode,
+    };
+    use crate::stdlib::common::architecture::{
+        assignment::{bitvec::BitVecValue, Assign, StdLogicValue},
+        declaration::subprogram::SubprogramParameter,
+        object::ObjectType,
+        statement::process::{Condition, IfStatement},
+    };
 
     use super::*;
 
@@ -89,4 +252,132 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_type_declarations() -> Result<()> {
+        assert_eq!(
+            r#"  type rec_type is record
+    c : std_logic_vector(41 downto 0);
+    d : std_logic_vector(1336 downto 0);
+  end record;
+"#,
+            ArchitectureDeclaration::from(records::rec("rec")).declare("  ", ";\n")?
+        );
+        assert!(ArchitectureDeclaration::from(records::prim(4))
+            .declare("", ";\n")
+            .is_err());
+
+        let word = SubTypeDeclaration::new("word", ObjectType::bit_vector(31, 0)?)?;
+        assert_eq!(
+            "subtype word is std_logic_vector(31 downto 0);\n",
+            ArchitectureDeclaration::from(word.clone()).declare("", ";\n")?
+        );
+        assert_eq!("word", word.object_type()?.type_name());
+        assert!(SubTypeDeclaration::new("bit", ObjectType::Bit).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_alias_component_custom_declarations() -> Result<()> {
+        let signal = ObjectDeclaration::signal("data", ObjectType::bit_vector(7, 0)?, None);
+        let alias = AliasDeclaration::new(&signal, "upper", vec![FieldSelection::downto(7, 4)?])?;
+        assert_eq!(
+            "alias upper : std_logic_vector(7 downto 4) is data(7 downto 4);\n",
+            ArchitectureDeclaration::from(alias).declare("", ";\n")?
+        );
+        let alias = AliasDeclaration::new(&signal, "msb", vec![FieldSelection::index(7)])?;
+        assert_eq!(
+            "alias msb : std_logic is data(7);\n",
+            ArchitectureDeclaration::from(alias).declare("", ";\n")?
+        );
+
+        assert_eq!(
+            r#"  component test_comp
+    port(
+      a_dn : in a_dn_type;
+      a_up : out a_up_type;
+      b_dn : out b_dn_type;
+      b_up : in b_up_type
+    );
+  end component;
+"#,
+            ArchitectureDeclaration::from(test_comp()).declare("  ", ";\n")?
+        );
+
+        assert_eq!(
+            "  attribute keep : boolean;\n  attribute keep of data : signal is true;\n",
+            ArchitectureDeclaration::Custom(
+                "attribute keep : boolean;\nattribute keep of data : signal is true".to_string()
+            )
+            .declare("  ", ";\n")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_subprogram_declarations() -> Result<()> {
+        let a = SubprogramParameter::new(ObjectKind::Signal, "a", ObjectType::Bit, Mode::In)?;
+        let b = SubprogramParameter::new(ObjectKind::Variable, "b", ObjectType::Bit, Mode::Out)?;
+        let mut procedure = Subprogram::procedure("copy")
+            .with_parameter(a.clone())?
+            .with_parameter(b.clone())?
+            .with_doc("Copies a to b");
+        procedure.add_statement(b.object().assign(a.object())?)?;
+        procedure.validate()?;
+        assert_eq!(
+            r#"  --Copies a to b
+  procedure copy(
+    signal a : in std_logic;
+    variable b : out std_logic
+  ) is
+  begin
+    b := a;
+  end procedure copy;
+"#,
+            ArchitectureDeclaration::from(procedure.clone()).declare("  ", ";\n")?
+        );
+
+        // Inputs cannot be assigned
+        let mut invalid = procedure.clone();
+        invalid.add_statement(a.object().assign(&StdLogicValue::Logic(true))?)?;
+        assert!(invalid.validate().is_err());
+        // Only parameters and local declarations can be used
+        let mut invalid = procedure.clone();
+        let signal = ObjectDeclaration::signal("other", ObjectType::Bit, None);
+        invalid.add_statement(b.object().assign(&signal)?)?;
+        assert!(invalid.validate().is_err());
+        assert!(procedure.add_parameter(a.clone()).is_err());
+
+        let word = SubTypeDeclaration::new("word", ObjectType::bit_vector(3, 0)?)?.object_type()?;
+        assert!(Subprogram::function("invalid", ObjectType::bit_vector(3, 0)?).is_err());
+        let mut function = Subprogram::function("select_word", word.clone())?;
+        assert!(function.add_parameter(b).is_err());
+        function.add_parameter(a.clone())?;
+        let result = ObjectDeclaration::variable("result", word, None);
+        function.add_declaration(result.clone())?;
+        assert!(function.validate().is_err());
+        function.add_statement(IfStatement::new(
+            Condition::equals(a.object(), StdLogicValue::Logic(true))?,
+            vec![result.assign(&BitVecValue::Unsigned(1))?.into()],
+        ))?;
+        function.set_return(BitVecValue::Others(StdLogicValue::Logic(false)))?;
+        function.validate()?;
+        function.set_return(result)?;
+        function.validate()?;
+        assert_eq!(
+            r#"function select_word(
+  signal a : in std_logic
+) return word is
+  variable result : word;
+begin
+  if a = '1' then
+    result := std_logic_vector(to_unsigned(1, result'length));
+  end if;
+  return result;
+end function select_word;
+"#,
+            ArchitectureDeclaration::from(function).declare("", ";\n")?
+        );
+        Ok(())
+    }
 }
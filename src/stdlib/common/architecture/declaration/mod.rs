@@ -6,11 +6,14 @@ use crate::generator::vhdl::Split;
 use crate::{Error, Identify, Result};
 
 use super::assignment::{AssignmentKind, FieldSelection};
-use super::object::ObjectType;
+use super::object::{ObjectType, RecordObject};
+
+use self::subprogram::Subprogram;
 
 pub mod architecturedeclaration_from;
 pub mod declare;
 pub mod impls;
+pub mod subprogram;
 
 // Declarations may typically be any of the following: type, subtype, signal, constant, file, alias, component, attribute, function, procedure, configuration specification. (per: https://www.ics.uci.edu/~jmoorkan/vhdlref/architec.html)
 // Per: https://insights.sigasi.com/tech/vhdl2008.ebnf/#block_declarative_item
@@ -43,10 +46,12 @@ pub mod impls;
 pub enum ArchitectureDeclaration<'a> {
     /// Type declarations within the architecture
     Type(Type),
-    SubType(String), // TODO: Do we want subtypes, or should these just be (part of) types?
-
-    Procedure(String), // TODO: Procedure
-    Function(String),  // TODO: Function
+    /// Subtype declarations, giving a name to an (array or record) object type
+    SubType(SubTypeDeclaration),
+    /// Procedure declarations, including their bodies
+    Procedure(Subprogram),
+    /// Function declarations, including their bodies
+    Function(Subprogram),
     /// Object declaration, covering signals, variables, constants and ports*
     ///
     /// *Ports cannot be declared within the architecture itself, but can be used in the statement part,
@@ -56,7 +61,8 @@ pub enum ArchitectureDeclaration<'a> {
     Alias(AliasDeclaration<'a>),
     /// Component declarations within the architecture
     Component(Component),
-    Custom(String), // TODO: Custom (templates?)
+    /// A declaration which is declared as-is, such as a function from a template
+    Custom(String),
 }
 
 /// The kind of object declared (signal, variable, constant, ports)
@@ -83,12 +89,9 @@ impl fmt::Display for ObjectKind {
     }
 }
 
-// TODO: Currently unused and hard to implement, as the modes of hence undefined objects needs to be changed when they are assigned, which requires persistent mutable references to their declarations.
-// Consider first declaring a signal, then assigning one of the entity's "in" ports to that signal, then assigning that signal to an "in" port of a component.
-// This requires changing the mode of the signal with the first (declared) assignment, and keeping track of it for each subsequent assignment. (To make sure you don't accidentally connect the "in" of the entity to the "out" of the component)
-// Now consider that there can be multiple signals between these steps, and that these signals can consist of multiple fields (records, arrays), each of which can also be assigned objects...
-// So the challenge will be making sure that assigning something like some_record <= (a => some_other_record.a.b.c.d, b => some_array(4 to 8)) forces all objects to which the fields belong into appropriate modes.
-// Basically, this only works if each object declaration is only ever a single mutable reference, which requires some significant rewrites at this point, and dealing with Rust's lifetimes.
+// NOTE: The modes of objects are not updated when they are assigned, as every assignment holds its own copy of the declaration.
+// Instead, the architecture tracks which statements drive and read (the fields of) its objects, see `architecture::drivers`,
+// using the modes of ports to reject assignments to the "in" ports of the entity.
 /// The state of the object, with respect to the architecture
 ///
 /// (E.g., an "in" port on the entity is "Assigned", but so is an "out" port of a component inside the architecture)
@@ -343,6 +346,56 @@ impl<'a> AliasDeclaration<'a> {
     }
 }
 
+/// Declares a name for an object type, such as a bit vector with a specific range
+#[derive(Debug, Clone)]
+pub struct SubTypeDeclaration {
+    identifier: String,
+    /// The type being named
+    typ: ObjectType,
+}
+
+impl SubTypeDeclaration {
+    pub fn new(identifier: impl Into<String>, typ: ObjectType) -> Result<SubTypeDeclaration> {
+        match typ {
+            ObjectType::Bit => Err(Error::InvalidArgument(
+                "Subtypes of std_logic are not supported".to_string(),
+            )),
+            _ => Ok(SubTypeDeclaration {
+                identifier: identifier.into(),
+                typ,
+            }),
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+
+    /// Returns the type being named
+    pub fn typ(&self) -> &ObjectType {
+        &self.typ
+    }
+
+    /// Returns the object type of the subtype itself, which can be used to declare objects of the subtype
+    pub fn object_type(&self) -> Result<ObjectType> {
+        match self.typ() {
+            ObjectType::Bit => unreachable!(),
+            ObjectType::Array(array) => ObjectType::array(
+                array.high(),
+                array.low(),
+                array.typ().clone(),
+                self.identifier(),
+            ),
+            ObjectType::Record(record) if record.is_union() => {
+                Ok(RecordObject::new_union(self.identifier(), record.fields().clone()).into())
+            }
+            ObjectType::Record(record) => {
+                Ok(RecordObject::new(self.identifier(), record.fields().clone()).into())
+            }
+        }
+    }
+}
+
 impl<'a> TryInto<ObjectDeclaration> for AliasDeclaration<'a> {
     type Error = Error;
 
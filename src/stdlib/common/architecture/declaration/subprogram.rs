@@ -0,0 +1,299 @@
+use indexmap::IndexMap;
+
+use crate::{
+    generator::{
+        common::Mode,
+        vhdl::{ListUsings, Usings},
+    },
+    stdlib::common::architecture::{
+        assignment::{Assignment, AssignmentKind},
+        object::ObjectType,
+        statement::process::{Scope, SequentialStatement},
+    },
+    Document, Error, Result,
+};
+
+use super::{ObjectDeclaration, ObjectKind, ObjectMode};
+
+/// A parameter of a procedure or function
+#[derive(Debug, Clone)]
+pub struct SubprogramParameter {
+    /// The parameter as an object, which can be used in the statements of the subprogram
+    object: ObjectDeclaration,
+    mode: Mode,
+}
+
+impl SubprogramParameter {
+    /// Create a parameter, the kind of the parameter can be a signal, variable or constant. Constants can only be inputs.
+    pub fn new(
+        kind: ObjectKind,
+        identifier: impl Into<String>,
+        typ: ObjectType,
+        mode: Mode,
+    ) -> Result<SubprogramParameter> {
+        let identifier = identifier.into();
+        match (kind, mode) {
+            (ObjectKind::Signal, _)
+            | (ObjectKind::Variable, _)
+            | (ObjectKind::Constant, Mode::In) => Ok(SubprogramParameter {
+                object: ObjectDeclaration {
+                    identifier,
+                    typ,
+                    mode: match mode {
+                        Mode::In => ObjectMode::Assigned,
+                        Mode::Out => ObjectMode::Out,
+                    },
+                    default: None,
+                    kind,
+                },
+                mode,
+            }),
+            (ObjectKind::Constant, Mode::Out) => Err(Error::InvalidArgument(format!(
+                "Constant parameter {} cannot be an output",
+                identifier
+            ))),
+            _ => Err(Error::InvalidArgument(format!(
+                "{} object {} cannot be a parameter",
+                kind, identifier
+            ))),
+        }
+    }
+
+    /// Returns the parameter as an object
+    pub fn object(&self) -> &ObjectDeclaration {
+        &self.object
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+}
+
+/// A procedure or function, including its body
+#[derive(Debug, Clone)]
+pub struct Subprogram {
+    identifier: String,
+    parameters: Vec<SubprogramParameter>,
+    /// Variables and constants declared within the subprogram
+    declarations: Vec<ObjectDeclaration>,
+    statements: Vec<SequentialStatement>,
+    /// The return type of a function, None for procedures
+    return_type: Option<ObjectType>,
+    /// The value returned by a function, after its statements
+    return_value: Option<AssignmentKind>,
+    doc: Option<String>,
+}
+
+impl Subprogram {
+    pub fn procedure(identifier: impl Into<String>) -> Subprogram {
+        Subprogram {
+            identifier: identifier.into(),
+            parameters: vec![],
+            declarations: vec![],
+            statements: vec![],
+            return_type: None,
+            return_value: None,
+            doc: None,
+        }
+    }
+
+    /// Create a function, the return type must be a type name (e.g., a subtype), rather than a constrained bit vector
+    pub fn function(identifier: impl Into<String>, return_type: ObjectType) -> Result<Subprogram> {
+        let identifier = identifier.into();
+        if return_type.type_name().contains('(') {
+            return Err(Error::InvalidArgument(format!(
+                "Function {} cannot return {}, declare a subtype for it instead",
+                identifier,
+                return_type.type_name()
+            )));
+        }
+        let mut function = Subprogram::procedure(identifier);
+        function.return_type = Some(return_type);
+        Ok(function)
+    }
+
+    pub fn is_function(&self) -> bool {
+        self.return_type.is_some()
+    }
+
+    pub fn add_parameter(&mut self, parameter: SubprogramParameter) -> Result<()> {
+        if self.is_function()
+            && (parameter.mode() == Mode::Out || parameter.object().kind() == ObjectKind::Variable)
+        {
+            return Err(Error::InvalidArgument(format!(
+                "Parameter {} of function {} must be an input signal or constant",
+                parameter.object().identifier(),
+                self.identifier()
+            )));
+        }
+        self.check_identifier(parameter.object().identifier())?;
+        self.parameters.push(parameter);
+        Ok(())
+    }
+
+    /// Return this subprogram with a parameter added
+    pub fn with_parameter(mut self, parameter: SubprogramParameter) -> Result<Self> {
+        self.add_parameter(parameter)?;
+        Ok(self)
+    }
+
+    /// Declare a variable or constant within the subprogram
+    pub fn add_declaration(&mut self, object: ObjectDeclaration) -> Result<()> {
+        match object.kind() {
+            ObjectKind::Variable | ObjectKind::Constant => {
+                self.check_identifier(object.identifier())?;
+                self.declarations.push(object);
+                Ok(())
+            }
+            _ => Err(Error::InvalidTarget(format!(
+                "{} object {} cannot be declared within a subprogram",
+                object.kind(),
+                object.identifier()
+            ))),
+        }
+    }
+
+    pub fn add_statement(&mut self, statement: impl Into<SequentialStatement>) -> Result<()> {
+        self.statements.push(statement.into());
+        Ok(())
+    }
+
+    /// Set the value returned by a function
+    pub fn set_return(&mut self, value: impl Into<AssignmentKind>) -> Result<()> {
+        match &self.return_type {
+            Some(typ) => {
+                let value = value.into();
+                typ.can_assign(&Assignment::from(value.clone()))?;
+                self.return_value = Some(value);
+                Ok(())
+            }
+            None => Err(Error::InvalidTarget(format!(
+                "Procedure {} cannot return a value",
+                self.identifier()
+            ))),
+        }
+    }
+
+    /// Return this subprogram with documentation added.
+    pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+
+    pub fn parameters(&self) -> &Vec<SubprogramParameter> {
+        &self.parameters
+    }
+
+    pub fn declarations(&self) -> &Vec<ObjectDeclaration> {
+        &self.declarations
+    }
+
+    pub fn statements(&self) -> &Vec<SequentialStatement> {
+        &self.statements
+    }
+
+    pub fn return_type(&self) -> &Option<ObjectType> {
+        &self.return_type
+    }
+
+    pub fn return_value(&self) -> &Option<AssignmentKind> {
+        &self.return_value
+    }
+
+    /// Verifies that the statements of the subprogram only use its parameters and declarations,
+    /// do not assign its inputs, and that functions return a value and do not wait
+    pub fn validate(&self) -> Result<()> {
+        if self.is_function() && self.statements().iter().any(|x| x.contains_wait()) {
+            return Err(Error::BackEndError(format!(
+                "Function {} cannot contain wait statements",
+                self.identifier()
+            )));
+        }
+        let mut variables = vec![];
+        let mut declared = IndexMap::new();
+        let mut read_only = vec![];
+        for parameter in self.parameters() {
+            let object = parameter.object();
+            if object.kind() == ObjectKind::Variable {
+                variables.push(object);
+            } else {
+                declared.insert(object.identifier().to_string(), object.clone());
+            }
+            if parameter.mode() == Mode::In {
+                read_only.push(object.identifier());
+            }
+        }
+        for object in self.declarations() {
+            if object.kind() == ObjectKind::Variable {
+                variables.push(object);
+            } else {
+                declared.insert(object.identifier().to_string(), object.clone());
+            }
+        }
+        let name = if self.is_function() {
+            format!("function {}", self.identifier())
+        } else {
+            format!("procedure {}", self.identifier())
+        };
+        let scope = Scope::new(name, variables, &declared, read_only);
+        for statement in self.statements() {
+            scope.validate_statement(statement)?;
+        }
+        if self.is_function() {
+            match self.return_value() {
+                Some(value) => scope.validate_kind(value)?,
+                None => {
+                    return Err(Error::BackEndError(format!(
+                        "Function {} does not return a value",
+                        self.identifier()
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_identifier(&self, identifier: &str) -> Result<()> {
+        if self
+            .parameters()
+            .iter()
+            .map(|x| x.object())
+            .chain(self.declarations())
+            .any(|x| x.identifier() == identifier)
+        {
+            Err(Error::InvalidArgument(format!(
+                "{} is already declared in {}",
+                identifier,
+                self.identifier()
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Document for Subprogram {
+    fn doc(&self) -> Option<String> {
+        self.doc.clone()
+    }
+}
+
+impl ListUsings for Subprogram {
+    fn list_usings(&self) -> Result<Usings> {
+        let mut usings = Usings::new_empty();
+        for object in self.declarations() {
+            usings.combine(&object.list_usings()?);
+        }
+        for statement in self.statements() {
+            usings.combine(&statement.list_usings()?);
+        }
+        if let Some(value) = self.return_value() {
+            usings.combine(&value.list_usings()?);
+        }
+        Ok(usings)
+    }
+}
@@ -0,0 +1,427 @@
+use indexmap::{IndexMap, IndexSet};
+
+use crate::{Error, Result};
+
+use super::{
+    assignment::{
+        array_assignment::ArrayAssignment, AssignmentKind, DirectAssignment, FieldSelection,
+        RangeConstraint,
+    },
+    declaration::{ObjectDeclaration, ObjectKind, ObjectMode},
+    statement::{
+        generate::{GenerateScheme, IntegerValue},
+        process::{Call, Condition, SequentialStatement},
+        Statement,
+    },
+};
+
+/// A (field of an) object, driven by a statement of the architecture
+#[derive(Debug, Clone)]
+pub struct Driver {
+    /// The fields of the object which are driven, an empty selection drives the entire object
+    selection: Vec<FieldSelection>,
+    /// Identifies the (nested) statement driving the object, in the order statements were added
+    source: usize,
+    /// Describes the statement driving the object, e.g. "process counter"
+    statement: String,
+}
+
+impl Driver {
+    pub fn selection(&self) -> &Vec<FieldSelection> {
+        &self.selection
+    }
+
+    pub fn statement(&self) -> &str {
+        self.statement.as_str()
+    }
+}
+
+/// Tracks which statements of an architecture drive its objects, and which objects are read
+#[derive(Debug, Clone, Default)]
+pub struct Drivers {
+    /// The drivers of each object, by identifier
+    drivers: IndexMap<String, Vec<Driver>>,
+    /// Identifiers of the objects which are read
+    read: IndexSet<String>,
+    /// Identifiers used by custom declarations, aliases and the arguments of calls, which can be read or
+    /// driven without being tracked
+    untracked: IndexSet<String>,
+    /// The number of statements which were added
+    sources: usize,
+}
+
+impl Drivers {
+    /// Returns the drivers of an object
+    pub fn get(&self, identifier: &str) -> Option<&Vec<Driver>> {
+        self.drivers.get(identifier)
+    }
+
+    /// Whether (a field of) the object is driven, or might be driven by a custom declaration or call
+    pub fn is_driven(&self, identifier: &str) -> bool {
+        self.drivers.contains_key(identifier) || self.untracked.contains(identifier)
+    }
+
+    /// Whether (a field of) the object is read, or might be read by a custom declaration or call
+    pub fn is_read(&self, identifier: &str) -> bool {
+        self.read.contains(identifier) || self.untracked.contains(identifier)
+    }
+
+    /// Registers the identifiers in a custom declaration, as these can't be verified
+    pub(crate) fn add_untracked(&mut self, custom: &str) {
+        for identifier in custom.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            if !identifier.is_empty() {
+                self.untracked.insert(identifier.to_string());
+            }
+        }
+    }
+
+    /// Registers the objects driven and read by a statement, given the ranges of the parameters of
+    /// enclosing for-generate statements (if they are known).
+    ///
+    /// Returns an error if the statement drives an input of the entity, a constant, or (a field of)
+    /// an object which is already driven by a different statement.
+    pub(crate) fn add_statement(
+        &mut self,
+        statement: &Statement,
+        parameters: &IndexMap<String, Option<RangeConstraint>>,
+    ) -> Result<()> {
+        match statement {
+            Statement::Assignment(assignment) => {
+                let source =
+                    self.next_source(format!("assignment to {}", assignment.object_string()));
+                self.drive(
+                    assignment.object(),
+                    assignment.assignment().to_field(),
+                    &source,
+                    parameters,
+                )?;
+                self.read_kind(assignment.assignment().kind());
+            }
+            Statement::PortMapping(portmapping) => {
+                let source = self.next_source(format!("port map {}", portmapping.label()));
+                for port in portmapping.ports().keys() {
+                    if let Some(mapping) = portmapping.mappings().get(port) {
+                        let kind = mapping.assignment().kind();
+                        // The "out" ports of a component drive the objects mapped to them
+                        if *mapping.object().mode() == ObjectMode::Assigned {
+                            self.drive_kind(kind, &source, parameters)?;
+                        } else {
+                            self.read_kind(kind);
+                        }
+                    }
+                }
+            }
+            Statement::Process(process) => {
+                let source = self.next_source(format!("process {}", process.label()));
+                for object in process.sensitivity_list() {
+                    self.read.insert(object.identifier().to_string());
+                }
+                for statement in process.statements() {
+                    self.add_sequential(statement, &source, parameters)?;
+                }
+            }
+            Statement::Generate(generate) => {
+                let mut parameters = parameters.clone();
+                if let GenerateScheme::For {
+                    parameter,
+                    low,
+                    high,
+                } = generate.scheme()
+                {
+                    let range = match (low, high) {
+                        (IntegerValue::Literal(low), IntegerValue::Literal(high)) => {
+                            RangeConstraint::downto(*high, *low).ok()
+                        }
+                        _ => None,
+                    };
+                    parameters.insert(parameter.clone(), range);
+                }
+                for statement in generate.statements() {
+                    self.add_statement(statement, &parameters)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn next_source(&mut self, statement: String) -> (usize, String) {
+        self.sources += 1;
+        (self.sources, statement)
+    }
+
+    fn add_sequential(
+        &mut self,
+        statement: &SequentialStatement,
+        source: &(usize, String),
+        parameters: &IndexMap<String, Option<RangeConstraint>>,
+    ) -> Result<()> {
+        match statement {
+            SequentialStatement::Assignment(assignment) => {
+                self.drive(
+                    assignment.object(),
+                    assignment.assignment().to_field(),
+                    source,
+                    parameters,
+                )?;
+                self.read_kind(assignment.assignment().kind());
+            }
+            SequentialStatement::If(if_statement) => {
+                for (condition, statements) in if_statement.branches() {
+                    self.read_condition(condition);
+                    for statement in statements {
+                        self.add_sequential(statement, source, parameters)?;
+                    }
+                }
+                for statement in if_statement.else_statements().iter().flatten() {
+                    self.add_sequential(statement, source, parameters)?;
+                }
+            }
+            SequentialStatement::Case(case) => {
+                self.read
+                    .insert(case.subject().object().identifier().to_string());
+                for (_, statements) in case.choices() {
+                    for statement in statements {
+                        self.add_sequential(statement, source, parameters)?;
+                    }
+                }
+                for statement in case.others().iter().flatten() {
+                    self.add_sequential(statement, source, parameters)?;
+                }
+            }
+            SequentialStatement::Call(call) => self.call(call),
+            SequentialStatement::Report(_, _) => (),
+            SequentialStatement::Wait(wait) => {
+                if let Some(condition) = wait.until_condition() {
+                    self.read_condition(condition);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers the objects assigned to an "out" port of a component as driven
+    fn drive_kind(
+        &mut self,
+        kind: &AssignmentKind,
+        source: &(usize, String),
+        parameters: &IndexMap<String, Option<RangeConstraint>>,
+    ) -> Result<()> {
+        match kind {
+            AssignmentKind::Object(object) => {
+                self.drive(object.object(), object.from_field(), source, parameters)
+            }
+            AssignmentKind::Direct(direct) => {
+                for kind in direct_kinds(direct) {
+                    self.drive_kind(kind, source, parameters)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn drive(
+        &mut self,
+        object: &ObjectDeclaration,
+        selection: &[FieldSelection],
+        source: &(usize, String),
+        parameters: &IndexMap<String, Option<RangeConstraint>>,
+    ) -> Result<()> {
+        let (index, statement) = source;
+        match object.kind() {
+            // Variables are local to their process, and component ports are driven through port mappings
+            ObjectKind::Variable | ObjectKind::ComponentPort => return Ok(()),
+            ObjectKind::Constant => {
+                return Err(Error::InvalidTarget(format!(
+                    "Constant {} cannot be driven by {}",
+                    object.identifier(),
+                    statement
+                )))
+            }
+            ObjectKind::EntityPort if *object.mode() == ObjectMode::Assigned => {
+                return Err(Error::InvalidTarget(format!(
+                    "{} is an input of the entity, and cannot be driven by {}",
+                    object.identifier(),
+                    statement
+                )))
+            }
+            ObjectKind::Signal | ObjectKind::EntityPort => (),
+        }
+        let selection = selection
+            .iter()
+            .map(|field| match field {
+                FieldSelection::Parameter(parameter) => match parameters.get(parameter) {
+                    Some(Some(range)) => FieldSelection::Range(range.clone()),
+                    _ => field.clone(),
+                },
+                _ => field.clone(),
+            })
+            .collect::<Vec<FieldSelection>>();
+        let drivers = self
+            .drivers
+            .entry(object.identifier().to_string())
+            .or_default();
+        if let Some(other) = drivers
+            .iter()
+            .find(|x| x.source != *index && overlaps(&x.selection, &selection))
+        {
+            return Err(Error::BackEndError(format!(
+                "{} is driven by both {} and {}",
+                selection_string(object.identifier(), &selection),
+                other.statement,
+                statement
+            )));
+        }
+        let selected = selection_string(object.identifier(), &selection);
+        if !drivers.iter().any(|x| {
+            x.source == *index && selection_string(object.identifier(), &x.selection) == selected
+        }) {
+            drivers.push(Driver {
+                selection,
+                source: *index,
+                statement: statement.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    fn read_condition(&mut self, condition: &Condition) {
+        match condition {
+            Condition::RisingEdge(object) | Condition::FallingEdge(object) => {
+                self.read.insert(object.object().identifier().to_string());
+            }
+            Condition::Equals(object, value) | Condition::NotEquals(object, value) => {
+                self.read.insert(object.object().identifier().to_string());
+                self.read_kind(value);
+            }
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                self.read_condition(left);
+                self.read_condition(right);
+            }
+            Condition::Not(condition) => self.read_condition(condition),
+            Condition::Call(call) => self.call(call),
+        }
+    }
+
+    /// Calls can read or drive the objects passed to them, depending on their declaration
+    fn call(&mut self, call: &Call) {
+        for argument in call.arguments() {
+            self.untracked
+                .insert(argument.object().identifier().to_string());
+        }
+    }
+
+    fn read_kind(&mut self, kind: &AssignmentKind) {
+        match kind {
+            AssignmentKind::Object(object) => {
+                self.read.insert(object.object().identifier().to_string());
+            }
+            AssignmentKind::Direct(direct) => {
+                for kind in direct_kinds(direct) {
+                    self.read_kind(kind);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the assignments nested in a direct assignment
+fn direct_kinds(direct: &DirectAssignment) -> Vec<&AssignmentKind> {
+    match direct {
+        DirectAssignment::Value(_) => vec![],
+        DirectAssignment::FullRecord(record) => record.values().collect(),
+        DirectAssignment::FullArray(array) => match array {
+            ArrayAssignment::Direct(direct) => direct.iter().collect(),
+            ArrayAssignment::Sliced { direct, others } => {
+                direct.values().chain(others.as_deref()).collect()
+            }
+            ArrayAssignment::Others(value) => vec![value.as_ref()],
+        },
+    }
+}
+
+fn selection_string(identifier: &str, selection: &[FieldSelection]) -> String {
+    let mut result = identifier.to_string();
+    for field in selection {
+        result.push_str(&field.to_string());
+    }
+    result
+}
+
+/// Whether two selections on the same object overlap. Selections using parameters of
+/// for-generate statements with unknown ranges are assumed to overlap.
+fn overlaps(left: &[FieldSelection], right: &[FieldSelection]) -> bool {
+    for (left, right) in left.iter().zip(right) {
+        match (left, right) {
+            (FieldSelection::Name(left), FieldSelection::Name(right)) => {
+                if left != right {
+                    return false;
+                }
+            }
+            (
+                FieldSelection::Range(RangeConstraint::Index(left)),
+                FieldSelection::Range(RangeConstraint::Index(right)),
+            ) => {
+                if left != right {
+                    return false;
+                }
+            }
+            (FieldSelection::Range(left), FieldSelection::Range(right)) => {
+                if !left.overlaps(right) {
+                    return false;
+                }
+            }
+            // The same parameter selects the same element.
+            (FieldSelection::Parameter(left), FieldSelection::Parameter(right))
+                if left == right => {}
+            _ => return true,
+        }
+    }
+    // One selection contains the other
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlaps() -> Result<()> {
+        let a = vec![FieldSelection::name("a"), FieldSelection::downto(3, 0)?];
+        let b = vec![FieldSelection::name("a"), FieldSelection::index(4)];
+        let c = vec![FieldSelection::name("a")];
+        let d = vec![FieldSelection::name("b"), FieldSelection::index(4)];
+        let e = vec![FieldSelection::name("a"), FieldSelection::parameter("i")];
+        assert!(!overlaps(&a, &b));
+        assert!(overlaps(&a, &c));
+        assert!(overlaps(&b, &c));
+        assert!(!overlaps(&b, &d));
+        assert!(overlaps(&a, &e));
+        assert!(overlaps(&[], &d));
+
+        // Selections of overlapping elements continue with the fields they select.
+        let lanes = |range: FieldSelection, field: &str| {
+            vec![
+                FieldSelection::name("lanes"),
+                range,
+                FieldSelection::name(field),
+            ]
+        };
+        assert!(!overlaps(
+            &lanes(FieldSelection::parameter("i"), "a"),
+            &lanes(FieldSelection::parameter("i"), "b")
+        ));
+        assert!(overlaps(
+            &lanes(FieldSelection::parameter("i"), "a"),
+            &lanes(FieldSelection::parameter("j"), "b")
+        ));
+        assert!(!overlaps(
+            &lanes(FieldSelection::downto(3, 0)?, "a"),
+            &lanes(FieldSelection::downto(1, 0)?, "b")
+        ));
+        assert!(overlaps(
+            &lanes(FieldSelection::downto(3, 0)?, "a"),
+            &lanes(FieldSelection::downto(1, 0)?, "a")
+        ));
+        Ok(())
+    }
+}
@@ -44,6 +44,10 @@ impl ListUsings for Architecture<'_> {
 // Any complex logic should probably just be string templates.
 impl<'a> Declare for Architecture<'a> {
     fn declare(&self) -> Result<String> {
+        for warning in self.warnings()? {
+            log::warn!("{}", warning);
+        }
+
         let mut result = String::new();
         result.push_str(self.declare_usings()?.as_str());
 
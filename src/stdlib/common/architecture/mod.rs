@@ -1,21 +1,22 @@
 use indexmap::IndexMap;
 
-use crate::Result;
 use crate::{
     generator::{
-        common::Package,
+        common::{Package, ParameterValue},
         vhdl::{ListUsings, Usings},
     },
-    Identify, Name,
+    Error, Identify, Name, Result,
 };
 
 use super::entity::Entity;
 
-use self::declaration::{ArchitectureDeclaration, ObjectDeclaration};
-use self::statement::Statement;
+use self::declaration::{ArchitectureDeclaration, ObjectDeclaration, ObjectKind, ObjectMode};
+use self::drivers::Drivers;
+use self::statement::{generate::IntegerValue, GenericMapping, Statement};
 
 pub mod assignment;
 pub mod declaration;
+pub mod drivers;
 pub mod impls;
 pub mod object;
 pub mod statement;
@@ -39,6 +40,8 @@ pub struct Architecture<'a> {
     declaration: Vec<ArchitectureDeclaration<'a>>,
     /// The statement part of the architecture
     statement: Vec<Statement>,
+    /// The objects driven and read by the statements
+    drivers: Drivers,
 }
 
 pub trait ArchitectureDeclare {
@@ -73,6 +76,27 @@ impl<'a> Architecture<'a> {
             doc: None,
             declaration: vec![],
             statement: vec![],
+            drivers: Drivers::default(),
+        })
+    }
+
+    /// Create the architecture of an entity which is not part of the package, such as a testbench, which can use the components of the package
+    pub fn from_entity(
+        library_id: Name,
+        identifier: Name,
+        package: &Package,
+        entity: Entity,
+    ) -> Result<Architecture<'a>> {
+        let mut usings = package.list_usings()?;
+        usings.add_using(library_id, format!("{}.all", package.identifier));
+        Ok(Architecture {
+            identifier,
+            entity,
+            usings,
+            doc: None,
+            declaration: vec![],
+            statement: vec![],
+            drivers: Drivers::default(),
         })
     }
 
@@ -103,13 +127,18 @@ impl<'a> Architecture<'a> {
             }
             ArchitectureDeclaration::Alias(alias) => {
                 self.usings.combine(&alias.object().list_usings()?);
+                // Objects used through an alias cannot be tracked
+                self.drivers.add_untracked(alias.object().identifier());
+            }
+            ArchitectureDeclaration::Procedure(subprogram)
+            | ArchitectureDeclaration::Function(subprogram) => {
+                subprogram.validate()?;
+                self.usings.combine(&subprogram.list_usings()?);
             }
+            ArchitectureDeclaration::Custom(custom) => self.drivers.add_untracked(custom),
             ArchitectureDeclaration::Type(_)
             | ArchitectureDeclaration::SubType(_)
-            | ArchitectureDeclaration::Procedure(_)
-            | ArchitectureDeclaration::Function(_)
-            | ArchitectureDeclaration::Component(_)
-            | ArchitectureDeclaration::Custom(_) => (),
+            | ArchitectureDeclaration::Component(_) => (),
         }
         self.declaration.push(declaration);
         Ok(())
@@ -117,15 +146,88 @@ impl<'a> Architecture<'a> {
 
     pub fn add_statement(&mut self, statement: impl Into<Statement>) -> Result<()> {
         let statement = statement.into();
-        match &statement {
-            Statement::Assignment(assignment) => self.usings.combine(&assignment.list_usings()?),
+        let usings = self.check_statement(&statement, &[])?;
+        let mut drivers = self.drivers.clone();
+        drivers.add_statement(&statement, &IndexMap::new())?;
+        self.drivers = drivers;
+        self.usings.combine(&usings);
+        self.statement.push(statement);
+        Ok(())
+    }
+
+    /// Validates a statement, given the parameters of the for-generate statements it is part of, and returns its usings
+    fn check_statement(&self, statement: &Statement, parameters: &[String]) -> Result<Usings> {
+        for parameter in statement.selected_parameters() {
+            if !parameters.contains(&parameter) {
+                return Err(Error::InvalidArgument(format!(
+                    "{} is not the parameter of an enclosing for-generate statement",
+                    parameter
+                )));
+            }
+        }
+        let mut usings = Usings::new_empty();
+        match statement {
+            Statement::Assignment(assignment) => usings.combine(&assignment.list_usings()?),
             Statement::PortMapping(pm) => {
                 for (_, object) in pm.ports() {
-                    self.usings.combine(&object.list_usings()?);
+                    usings.combine(&object.list_usings()?);
+                }
+                for value in pm.generic_mappings().values() {
+                    if let GenericMapping::Integer(value) = value {
+                        self.check_integer(value, parameters)?;
+                    }
+                }
+            }
+            Statement::Process(process) => {
+                let mut declared = self.entity_ports()?;
+                for declaration in self.declarations() {
+                    if let ArchitectureDeclaration::Object(object) = declaration {
+                        declared.insert(object.identifier().to_string(), object.clone());
+                    }
+                }
+                process.validate(&declared)?;
+                usings.combine(&process.list_usings()?);
+            }
+            Statement::Generate(generate) => {
+                for value in generate.values() {
+                    self.check_integer(value, parameters)?;
+                }
+                let mut parameters = parameters.to_vec();
+                if let Some(parameter) = generate.parameter() {
+                    if parameters.iter().any(|x| x == parameter)
+                        || self.entity.parameters().iter().any(|x| x.name == parameter)
+                    {
+                        return Err(Error::InvalidArgument(format!(
+                            "Parameter {} of generate statement {} is already declared",
+                            parameter,
+                            generate.label()
+                        )));
+                    }
+                    parameters.push(parameter.to_string());
+                }
+                for statement in generate.statements() {
+                    usings.combine(&self.check_statement(statement, &parameters)?);
                 }
             }
         }
-        self.statement.push(statement);
+        Ok(usings)
+    }
+
+    /// Verifies that an integer value only refers to natural generics of the entity, or parameters of enclosing for-generate statements
+    fn check_integer(&self, value: &IntegerValue, parameters: &[String]) -> Result<()> {
+        if let IntegerValue::Identifier { identifier, .. } = value {
+            let is_generic =
+                self.entity.parameters().iter().any(|x| {
+                    &x.name == identifier && matches!(x.value, ParameterValue::Natural(_))
+                });
+            if !is_generic && !parameters.contains(identifier) {
+                return Err(Error::InvalidArgument(format!(
+                    "{} is neither a natural generic of entity {}, nor the parameter of an enclosing for-generate statement",
+                    identifier,
+                    self.entity.identifier()
+                )));
+            }
+        }
         Ok(())
     }
 
@@ -137,6 +239,41 @@ impl<'a> Architecture<'a> {
         &self.declaration
     }
 
+    /// Returns which statements drive the objects of the architecture, and which objects are read
+    pub fn drivers(&self) -> &Drivers {
+        &self.drivers
+    }
+
+    /// Returns warnings for signals which are never driven or read, and outputs of the entity which are never driven.
+    ///
+    /// Signals with a default value are not required to be driven.
+    pub fn warnings(&self) -> Result<Vec<String>> {
+        let mut result = vec![];
+        for declaration in self.declarations() {
+            if let ArchitectureDeclaration::Object(object) = declaration {
+                if object.kind() != ObjectKind::Signal {
+                    continue;
+                }
+                if object.default().is_none() && !self.drivers.is_driven(object.identifier()) {
+                    result.push(format!("Signal {} is never driven", object.identifier()));
+                }
+                if !self.drivers.is_read(object.identifier()) {
+                    result.push(format!("Signal {} is never read", object.identifier()));
+                }
+            }
+        }
+        for (identifier, port) in self.entity_ports()? {
+            if *port.mode() == ObjectMode::Out && !self.drivers.is_driven(&identifier) {
+                result.push(format!(
+                    "Output {} of entity {} is never driven",
+                    identifier,
+                    self.entity.identifier()
+                ));
+            }
+        }
+        Ok(result)
+    }
+
     pub fn entity_ports(&self) -> Result<IndexMap<String, ObjectDeclaration>> {
         let mut result = IndexMap::new();
         for port in self.entity.ports() {
@@ -152,6 +289,13 @@ impl<'a> Architecture<'a> {
 #[cfg(test)]
 mod tests {
     use crate::generator::{common::convert::Packify, vhdl::Declare};
+    use crate::stdlib::common::architecture::{
+        assignment::{
+            bitvec::BitVecValue, Assign, Assignment, FieldSelection, ObjectAssignment,
+            StdLogicValue,
+        },
+        object::ObjectType,
+    };
 
     use super::*;
 
@@ -178,4 +322,46 @@ mod tests {
         print!("{}\n\n", package.declare().unwrap());
         print!("{}\n\n", architecture.declare().unwrap());
     }
+
+    #[test]
+    fn test_drivers() -> Result<()> {
+        let package = test_package();
+        let mut architecture = Architecture::new_default(&package, Name::try_new("test")?)?;
+        let ports = architecture.entity_ports()?;
+        let a_dn = ports.get("a_dn").unwrap();
+        let a_up = ports.get("a_up").unwrap();
+        let ready = ObjectDeclaration::signal("ready", ObjectType::Bit, None);
+        let bits = ObjectDeclaration::signal("bits", ObjectType::bit_vector(1, 0)?, None);
+        architecture.add_declaration(ready.clone())?;
+        architecture.add_declaration(bits.clone())?;
+
+        // Inputs of the entity cannot be driven
+        let valid = Assignment::from(StdLogicValue::Logic(true)).to_named("valid");
+        assert!(architecture.add_statement(a_dn.assign(&valid)?).is_err());
+        let a_valid = ObjectAssignment::from(a_dn.clone())
+            .assign_from(&vec![FieldSelection::name("valid")])?;
+        architecture.add_statement(ready.assign(&a_valid)?)?;
+        architecture
+            .add_statement(a_up.assign(&Assignment::from(ready.clone()).to_named("ready"))?)?;
+
+        // Fields can be driven by different statements, as long as they don't overlap
+        let low = Assignment::from(StdLogicValue::Logic(false)).to_index(0);
+        let high = Assignment::from(StdLogicValue::Logic(true)).to_index(1);
+        architecture.add_statement(bits.assign(&low)?)?;
+        architecture.add_statement(bits.assign(&high)?)?;
+        let both =
+            Assignment::from(BitVecValue::Others(StdLogicValue::Logic(false))).to_downto(1, 0)?;
+        assert!(architecture.add_statement(bits.assign(&both)?).is_err());
+        assert!(architecture.add_statement(ready.assign(&a_valid)?).is_err());
+        assert_eq!(2, architecture.drivers().get("bits").unwrap().len());
+
+        assert_eq!(
+            vec![
+                "Signal bits is never read".to_string(),
+                "Output b_dn of entity test is never driven".to_string()
+            ],
+            architecture.warnings()?
+        );
+        Ok(())
+    }
 }
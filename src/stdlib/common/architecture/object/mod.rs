@@ -99,11 +99,13 @@ impl ObjectType {
                 FieldSelection::Name(_) => Err(Error::InvalidTarget(
                     "Cannot select a named field on an array".to_string(),
                 )),
+                // The range of the parameter can depend on generics, so it cannot be verified here
+                FieldSelection::Parameter(_) => Ok(array.typ().clone()),
             },
             ObjectType::Record(record) => match field {
-                FieldSelection::Range(_) => Err(Error::InvalidTarget(
-                    "Cannot select a range on a record".to_string(),
-                )),
+                FieldSelection::Range(_) | FieldSelection::Parameter(_) => Err(
+                    Error::InvalidTarget("Cannot select a range on a record".to_string()),
+                ),
                 FieldSelection::Name(name) => Ok(record
                     .fields()
                     .get(name)
@@ -239,7 +241,7 @@ impl ObjectType {
                     if let ObjectType::Array(to_array) = &to_object {
                         match array {
                             ArrayAssignment::Direct(direct) => {
-                                if to_array.width() == direct.len().try_into().unwrap() {
+                                if to_array.width() == u32::try_from(direct.len()).unwrap() {
                                     for value in direct {
                                         to_array
                                             .typ()
@@ -321,27 +323,14 @@ impl ObjectType {
 
     pub fn try_from_splittable(typ: Type) -> Result<(Option<ObjectType>, Option<ObjectType>)> {
         if typ.has_reversed() {
-            let (dn, up) = typ.split();
-            let dn_obj = if let Some(dn_t) = dn {
-                Some(match dn_t {
-                    Type::Bit | Type::BitVec { width: _ } => unreachable!(),
-                    Type::Record(rec) => Type::Record(rec.append_name_nested("dn")).try_into()?,
-                    Type::Union(rec) => Type::Union(rec.append_name_nested("dn")).try_into()?,
-                    Type::Array(arr) => Type::Array(arr.append_name_nested("dn")).try_into()?,
-                })
-            } else {
-                None
+            // Nested named types without reversed fields are not split, and keep their names.
+            let dn_obj = match typ.append_name_split("dn").split().0 {
+                Some(dn_t) => Some(dn_t.try_into()?),
+                None => None,
             };
-
-            let up_obj = if let Some(up_t) = up {
-                Some(match up_t {
-                    Type::Bit | Type::BitVec { width: _ } => unreachable!(),
-                    Type::Record(rec) => Type::Record(rec.append_name_nested("up")).try_into()?,
-                    Type::Union(rec) => Type::Union(rec.append_name_nested("up")).try_into()?,
-                    Type::Array(arr) => Type::Array(arr.append_name_nested("up")).try_into()?,
-                })
-            } else {
-                None
+            let up_obj = match typ.append_name_split("up").split().1 {
+                Some(up_t) => Some(up_t.try_into()?),
+                None => None,
             };
 
             Ok((dn_obj, up_obj))
@@ -355,7 +344,16 @@ impl TryFrom<Type> for ObjectType {
     fn try_from(typ: Type) -> Result<Self> {
         match typ {
             Type::Bit => Ok(ObjectType::Bit),
-            Type::BitVec { width } => {
+            Type::BitVec {
+                width,
+                generic: Some(_),
+            } => Ok(ObjectType::array(
+                (width - 1).try_into().unwrap(),
+                0,
+                ObjectType::Bit,
+                typ.vhdl_identifier()?,
+            )?),
+            Type::BitVec { width, .. } => {
                 Ok(ObjectType::bit_vector((width - 1).try_into().unwrap(), 0)?)
             }
             Type::Record(record) => Ok(ObjectType::Record(RecordObject::try_from(record)?)),
@@ -368,7 +366,7 @@ impl TryFrom<Type> for ObjectType {
                     );
                 }
                 Ok(ObjectType::Record(RecordObject::new_union(
-                    record.vhdl_identifier()?,
+                    Type::Union(record).vhdl_identifier()?,
                     fields,
                 )))
             }
@@ -445,7 +443,11 @@ impl TryFrom<Record> for RecordObject {
                 ObjectType::try_from(field.typ().clone())?,
             );
         }
-        Ok(RecordObject::new(value.vhdl_identifier()?, fields))
+        // Records of which the widths depend on generics are named with their constraint.
+        Ok(RecordObject::new(
+            Type::Record(value).vhdl_identifier()?,
+            fields,
+        ))
     }
 }
 
@@ -527,7 +529,7 @@ impl TryFrom<Array> for ArrayObject {
             (value.width() - 1).try_into().unwrap(),
             0,
             ObjectType::try_from(value.typ().clone())?,
-            value.vhdl_identifier()?,
+            Type::Array(value).vhdl_identifier()?,
         )?)
     }
 }
@@ -1,15 +1,37 @@
 use crate::{stdlib::common::architecture::ArchitectureDeclare, Error, Result};
 
-use super::{PortMapping, Statement};
+use super::{
+    generate::{GenerateScheme, GenerateStatement},
+    GenericMapping, PortMapping, Statement,
+};
 
 impl ArchitectureDeclare for PortMapping {
     fn declare(&self, pre: &str, post: &str) -> Result<String> {
         let mut result = pre.to_string();
-        result.push_str(&format!(
-            "{}: {} port map(\n",
-            self.label(),
-            self.component_name()
-        ));
+        result.push_str(&format!("{}: {} ", self.label(), self.component_name()));
+        let mut generic_maps = vec![];
+        for (generic, _) in self.generics() {
+            if let Some(value) = self.generic_mappings().get(generic) {
+                generic_maps.push(format!(
+                    "{}  {} => {}",
+                    pre,
+                    generic,
+                    match value {
+                        GenericMapping::Integer(value) => value.to_string(),
+                        GenericMapping::String(value) => format!("\"{}\"", value),
+                        GenericMapping::Generic(generic) => generic.clone(),
+                    }
+                ));
+            }
+        }
+        if !generic_maps.is_empty() {
+            result.push_str(&format!(
+                "generic map(\n{}\n{}) ",
+                generic_maps.join(",\n"),
+                pre
+            ));
+        }
+        result.push_str("port map(\n");
         let mut port_maps = vec![];
         for (port, _) in self.ports() {
             if let Some(port_assign) = self.mappings().get(port) {
@@ -27,11 +49,37 @@ impl ArchitectureDeclare for PortMapping {
     }
 }
 
+impl ArchitectureDeclare for GenerateStatement {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let mut result = format!("{}{}: ", pre, self.label());
+        match self.scheme() {
+            GenerateScheme::For {
+                parameter,
+                low,
+                high,
+            } => result.push_str(&format!("for {} in {} to {}", parameter, low, high)),
+            GenerateScheme::If {
+                left,
+                comparison,
+                right,
+            } => result.push_str(&format!("if {} {} {}", left, comparison, right)),
+        }
+        result.push_str(" generate\n");
+        for statement in self.statements() {
+            result.push_str(&statement.declare(&format!("{}  ", pre), ";\n")?);
+        }
+        result.push_str(&format!("{}end generate {}{}", pre, self.label(), post));
+        Ok(result)
+    }
+}
+
 impl ArchitectureDeclare for Statement {
     fn declare(&self, pre: &str, post: &str) -> Result<String> {
         match self {
             Statement::Assignment(assignment) => assignment.declare(pre, post),
             Statement::PortMapping(portmapping) => portmapping.declare(pre, post),
+            Statement::Process(process) => process.declare(pre, post),
+            Statement::Generate(generate) => generate.declare(pre, post),
         }
     }
 }
@@ -0,0 +1,457 @@
+use std::fmt;
+
+use crate::{
+    stdlib::common::architecture::assignment::{
+        array_assignment::ArrayAssignment, AssignDeclaration, AssignmentKind, DirectAssignment,
+        FieldSelection,
+    },
+    Error, Result,
+};
+
+use super::{
+    process::{Call, Condition, SequentialStatement},
+    Statement,
+};
+
+/// An integer value used by generate statements and generic maps, either a literal or the identifier of
+/// a generic of the entity (or the parameter of an enclosing for-generate statement) with an offset
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegerValue {
+    Literal(i32),
+    /// [identifier]+[offset], e.g. LANES-1
+    Identifier {
+        identifier: String,
+        offset: i32,
+    },
+}
+
+impl IntegerValue {
+    pub fn identifier(identifier: impl Into<String>) -> IntegerValue {
+        IntegerValue::Identifier {
+            identifier: identifier.into(),
+            offset: 0,
+        }
+    }
+
+    /// Return this value with an offset added to it
+    pub fn with_offset(self, offset: i32) -> IntegerValue {
+        match self {
+            IntegerValue::Literal(value) => IntegerValue::Literal(value + offset),
+            IntegerValue::Identifier {
+                identifier,
+                offset: current,
+            } => IntegerValue::Identifier {
+                identifier,
+                offset: current + offset,
+            },
+        }
+    }
+}
+
+impl From<i32> for IntegerValue {
+    fn from(value: i32) -> Self {
+        IntegerValue::Literal(value)
+    }
+}
+
+impl fmt::Display for IntegerValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegerValue::Literal(value) => write!(f, "{}", value),
+            IntegerValue::Identifier { identifier, offset } => {
+                if *offset > 0 {
+                    write!(f, "{}+{}", identifier, offset)
+                } else if *offset < 0 {
+                    write!(f, "{}-{}", identifier, -offset)
+                } else {
+                    write!(f, "{}", identifier)
+                }
+            }
+        }
+    }
+}
+
+/// A comparison between integer values, used by if-generate statements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equals,
+    NotEquals,
+    LessThan,
+    LessOrEqual,
+    GreaterThan,
+    GreaterOrEqual,
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Comparison::Equals => "=",
+            Comparison::NotEquals => "/=",
+            Comparison::LessThan => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::GreaterThan => ">",
+            Comparison::GreaterOrEqual => ">=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// The scheme of a generate statement
+#[derive(Debug, Clone)]
+pub enum GenerateScheme {
+    /// for [parameter] in [low] to [high] generate
+    For {
+        parameter: String,
+        low: IntegerValue,
+        high: IntegerValue,
+    },
+    /// if [left] [comparison] [right] generate
+    If {
+        left: IntegerValue,
+        comparison: Comparison,
+        right: IntegerValue,
+    },
+}
+
+/// A generate statement, which contains concurrent statements that are generated for every value of
+/// its parameter (for-generate), or only if its condition holds (if-generate)
+#[derive(Debug, Clone)]
+pub struct GenerateStatement {
+    label: String,
+    scheme: GenerateScheme,
+    statements: Vec<Statement>,
+}
+
+impl GenerateStatement {
+    /// Create a for-generate statement, the parameter can be used to select indexes of objects within its statements
+    pub fn for_range(
+        label: impl Into<String>,
+        parameter: impl Into<String>,
+        low: impl Into<IntegerValue>,
+        high: impl Into<IntegerValue>,
+    ) -> Result<GenerateStatement> {
+        let low = low.into();
+        let high = high.into();
+        if let (IntegerValue::Literal(low), IntegerValue::Literal(high)) = (&low, &high) {
+            if low > high {
+                return Err(Error::InvalidArgument(format!(
+                    "{} > {}! Low cannot be greater than high",
+                    low, high
+                )));
+            }
+        }
+        Ok(GenerateStatement {
+            label: label.into(),
+            scheme: GenerateScheme::For {
+                parameter: parameter.into(),
+                low,
+                high,
+            },
+            statements: vec![],
+        })
+    }
+
+    /// Create an if-generate statement
+    pub fn if_condition(
+        label: impl Into<String>,
+        left: impl Into<IntegerValue>,
+        comparison: Comparison,
+        right: impl Into<IntegerValue>,
+    ) -> GenerateStatement {
+        GenerateStatement {
+            label: label.into(),
+            scheme: GenerateScheme::If {
+                left: left.into(),
+                comparison,
+                right: right.into(),
+            },
+            statements: vec![],
+        }
+    }
+
+    pub fn add_statement(&mut self, statement: impl Into<Statement>) -> Result<()> {
+        self.statements.push(statement.into());
+        Ok(())
+    }
+
+    /// Return this generate statement with a statement added
+    pub fn with_statement(mut self, statement: impl Into<Statement>) -> Result<Self> {
+        self.add_statement(statement)?;
+        Ok(self)
+    }
+
+    pub fn label(&self) -> &str {
+        self.label.as_str()
+    }
+
+    pub fn scheme(&self) -> &GenerateScheme {
+        &self.scheme
+    }
+
+    pub fn statements(&self) -> &Vec<Statement> {
+        &self.statements
+    }
+
+    /// Returns the parameter of a for-generate statement
+    pub fn parameter(&self) -> Option<&str> {
+        match self.scheme() {
+            GenerateScheme::For { parameter, .. } => Some(parameter.as_str()),
+            GenerateScheme::If { .. } => None,
+        }
+    }
+
+    /// Returns the integer values used by the scheme of this statement
+    pub fn values(&self) -> Vec<&IntegerValue> {
+        match self.scheme() {
+            GenerateScheme::For { low, high, .. } => vec![low, high],
+            GenerateScheme::If { left, right, .. } => vec![left, right],
+        }
+    }
+}
+
+impl Statement {
+    /// Returns the parameters of for-generate statements used to select fields by this statement,
+    /// not including the statements nested in generate statements
+    pub(crate) fn selected_parameters(&self) -> Vec<String> {
+        let mut result = vec![];
+        match self {
+            Statement::Assignment(assignment) => assignment_parameters(assignment, &mut result),
+            Statement::PortMapping(portmapping) => {
+                for assignment in portmapping.mappings().values() {
+                    assignment_parameters(assignment, &mut result);
+                }
+            }
+            Statement::Process(process) => {
+                for statement in process.statements() {
+                    sequential_parameters(statement, &mut result);
+                }
+            }
+            Statement::Generate(_) => (),
+        }
+        result
+    }
+}
+
+fn selection_parameters(fields: &[FieldSelection], result: &mut Vec<String>) {
+    for field in fields {
+        if let FieldSelection::Parameter(parameter) = field {
+            result.push(parameter.clone());
+        }
+    }
+}
+
+fn assignment_parameters(assignment: &AssignDeclaration, result: &mut Vec<String>) {
+    selection_parameters(assignment.assignment().to_field(), result);
+    kind_parameters(assignment.assignment().kind(), result);
+}
+
+fn kind_parameters(kind: &AssignmentKind, result: &mut Vec<String>) {
+    match kind {
+        AssignmentKind::Object(object) => selection_parameters(object.from_field(), result),
+        AssignmentKind::Direct(direct) => match direct {
+            DirectAssignment::Value(_) => (),
+            DirectAssignment::FullRecord(record) => {
+                for (_, value) in record {
+                    kind_parameters(value, result);
+                }
+            }
+            DirectAssignment::FullArray(array) => match array {
+                ArrayAssignment::Direct(direct) => {
+                    for value in direct {
+                        kind_parameters(value, result);
+                    }
+                }
+                ArrayAssignment::Sliced { direct, others } => {
+                    for (_, value) in direct {
+                        kind_parameters(value, result);
+                    }
+                    if let Some(value) = others {
+                        kind_parameters(value, result);
+                    }
+                }
+                ArrayAssignment::Others(value) => kind_parameters(value, result),
+            },
+        },
+    }
+}
+
+fn sequential_parameters(statement: &SequentialStatement, result: &mut Vec<String>) {
+    match statement {
+        SequentialStatement::Assignment(assignment) => assignment_parameters(assignment, result),
+        SequentialStatement::If(if_statement) => {
+            for (condition, statements) in if_statement.branches() {
+                condition_parameters(condition, result);
+                for statement in statements {
+                    sequential_parameters(statement, result);
+                }
+            }
+            for statement in if_statement.else_statements().iter().flatten() {
+                sequential_parameters(statement, result);
+            }
+        }
+        SequentialStatement::Case(case) => {
+            selection_parameters(case.subject().from_field(), result);
+            for (_, statements) in case.choices() {
+                for statement in statements {
+                    sequential_parameters(statement, result);
+                }
+            }
+            for statement in case.others().iter().flatten() {
+                sequential_parameters(statement, result);
+            }
+        }
+        SequentialStatement::Call(call) => call_parameters(call, result),
+        SequentialStatement::Report(_, _) => (),
+        SequentialStatement::Wait(wait) => {
+            if let Some(condition) = wait.until_condition() {
+                condition_parameters(condition, result);
+            }
+        }
+    }
+}
+
+fn call_parameters(call: &Call, result: &mut Vec<String>) {
+    for argument in call.arguments() {
+        selection_parameters(argument.from_field(), result);
+    }
+}
+
+fn condition_parameters(condition: &Condition, result: &mut Vec<String>) {
+    match condition {
+        Condition::RisingEdge(object) | Condition::FallingEdge(object) => {
+            selection_parameters(object.from_field(), result)
+        }
+        Condition::Equals(object, value) | Condition::NotEquals(object, value) => {
+            selection_parameters(object.from_field(), result);
+            kind_parameters(value, result);
+        }
+        Condition::And(left, right) | Condition::Or(left, right) => {
+            condition_parameters(left, result);
+            condition_parameters(right, result);
+        }
+        Condition::Not(condition) => condition_parameters(condition, result),
+        Condition::Call(call) => call_parameters(call, result),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        generator::{
+            common::{Component, Mode, Parameter, ParameterValue, Port, Type},
+            vhdl::Declare,
+        },
+        stdlib::common::{
+            architecture::{
+                assignment::ObjectAssignment, declaration::ObjectDeclaration, object::ObjectType,
+                statement::PortMapping, tests::test_package, Architecture, ArchitectureDeclare,
+            },
+            entity::Entity,
+        },
+        Name,
+    };
+
+    use super::*;
+
+    fn lane_comp() -> Component {
+        Component::new(
+            "lane_comp",
+            vec![Parameter {
+                name: "WIDTH".to_string(),
+                value: ParameterValue::Natural(8),
+            }],
+            vec![Port::new_documented("a", Mode::In, Type::bitvec(8), None)],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_generate() -> Result<()> {
+        let entity = Entity::new(
+            "top",
+            vec![Parameter {
+                name: "LANES".to_string(),
+                value: ParameterValue::Natural(4),
+            }],
+            vec![],
+            None,
+        );
+        let mut architecture = Architecture::from_entity(
+            Name::try_new("work")?,
+            Name::try_new("Behavioral")?,
+            &test_package(),
+            entity,
+        )?;
+        let lanes = ObjectDeclaration::signal(
+            "lanes",
+            ObjectType::array(3, 0, ObjectType::bit_vector(7, 0)?, "lanes_type")?,
+            None,
+        );
+        architecture.add_declaration(lanes.clone())?;
+
+        let mut pm = PortMapping::from_component(&lane_comp(), "lane_inst")?;
+        assert!(pm.map_generic("WIDTH", "8").is_err());
+        assert!(pm.map_generic("WIDTH", -1).is_err());
+        assert!(pm.map_generic("DEPTH", 8).is_err());
+        let lane =
+            ObjectAssignment::from(lanes).assign_from(&vec![FieldSelection::parameter("i")])?;
+        let pm = pm
+            .map_generic("WIDTH", 8)?
+            .map_port("a", &lane)?
+            .clone()
+            .finish()?;
+
+        // The parameter can only be used within the generate statement
+        assert!(architecture.add_statement(pm.clone()).is_err());
+        let generate = GenerateStatement::for_range(
+            "lanes_gen",
+            "i",
+            0,
+            IntegerValue::identifier("LANES").with_offset(-1),
+        )?
+        .with_statement(pm.clone())?;
+        assert_eq!(
+            r#"  lanes_gen: for i in 0 to LANES-1 generate
+    lane_inst: lane_comp generic map(
+      WIDTH => 8
+    ) port map(
+      a => lanes(i)
+    );
+  end generate lanes_gen;
+"#,
+            generate.declare("  ", ";\n")?
+        );
+        architecture.add_statement(generate.clone())?;
+
+        // Generate statements can be nested, but cannot redeclare parameters or use unknown generics
+        let nested = GenerateStatement::if_condition(
+            "multiple_lanes",
+            IntegerValue::identifier("LANES"),
+            Comparison::GreaterThan,
+            1,
+        )
+        .with_statement(generate.clone())?;
+        assert_eq!(
+            "multiple_lanes: if LANES > 1 generate\n",
+            nested
+                .declare("", ";\n")?
+                .lines()
+                .next()
+                .unwrap()
+                .to_string()
+                + "\n"
+        );
+        architecture.add_statement(nested.clone())?;
+        let invalid = GenerateStatement::for_range("outer", "i", 0, 1)?.with_statement(generate)?;
+        assert!(architecture.add_statement(invalid).is_err());
+        let invalid =
+            GenerateStatement::for_range("unknown", "j", 0, IntegerValue::identifier("WIDTH"))?;
+        assert!(architecture.add_statement(invalid).is_err());
+        assert!(GenerateStatement::for_range("reversed", "i", 3, 1).is_err());
+
+        assert!(architecture
+            .declare()?
+            .contains("end generate multiple_lanes;"));
+        Ok(())
+    }
+}
@@ -3,21 +3,29 @@ use std::collections::HashMap;
 use indexmap::IndexMap;
 
 use crate::{
-    generator::common::Component, stdlib::common::architecture::assignment::Assign, Error,
-    Identify, Result,
+    generator::common::{Component, Parameter, ParameterValue},
+    stdlib::common::architecture::assignment::Assign,
+    Error, Identify, Result,
 };
 
+use self::generate::{GenerateStatement, IntegerValue};
+use self::process::Process;
+
 use super::{
     assignment::{AssignDeclaration, Assignment},
     declaration::ObjectDeclaration,
 };
 
 pub mod declare;
+pub mod generate;
+pub mod process;
 
 #[derive(Debug, Clone)]
 pub enum Statement {
     Assignment(AssignDeclaration),
     PortMapping(PortMapping),
+    Process(Process),
+    Generate(GenerateStatement),
 }
 
 impl From<AssignDeclaration> for Statement {
@@ -26,16 +34,68 @@ impl From<AssignDeclaration> for Statement {
     }
 }
 
+impl From<Process> for Statement {
+    fn from(process: Process) -> Self {
+        Statement::Process(process)
+    }
+}
+
+impl From<GenerateStatement> for Statement {
+    fn from(generate: GenerateStatement) -> Self {
+        Statement::Generate(generate)
+    }
+}
+
 impl From<PortMapping> for Statement {
     fn from(portmapping: PortMapping) -> Self {
         Statement::PortMapping(portmapping)
     }
 }
 
+/// The value mapped to a generic of a component
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenericMapping {
+    /// A value for a natural generic
+    Integer(IntegerValue),
+    /// A value for a string generic
+    String(String),
+    /// The generic with this identifier of the enclosing entity, e.g. to forward a generic of a
+    /// wrapper to the component it wraps
+    Generic(String),
+}
+
+impl From<IntegerValue> for GenericMapping {
+    fn from(value: IntegerValue) -> Self {
+        GenericMapping::Integer(value)
+    }
+}
+
+impl From<i32> for GenericMapping {
+    fn from(value: i32) -> Self {
+        GenericMapping::Integer(value.into())
+    }
+}
+
+impl From<&str> for GenericMapping {
+    fn from(value: &str) -> Self {
+        GenericMapping::String(value.to_string())
+    }
+}
+
+impl From<String> for GenericMapping {
+    fn from(value: String) -> Self {
+        GenericMapping::String(value)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PortMapping {
     label: String,
     component_name: String,
+    /// The generics, in the order they were declared on the component
+    generics: IndexMap<String, Parameter>,
+    /// Mappings for those generics, generics which are not mapped use their default value
+    generic_mappings: HashMap<String, GenericMapping>,
     /// The ports, in the order they were declared on the component
     ports: IndexMap<String, ObjectDeclaration>,
     /// Mappings for those ports, will be declared in the order of the original component declaration,
@@ -52,9 +112,16 @@ impl PortMapping {
                 ports.insert(obj.identifier().to_string(), obj);
             }
         }
+        let generics = component
+            .parameters()
+            .iter()
+            .map(|x| (x.name.clone(), x.clone()))
+            .collect();
         Ok(PortMapping {
             label: label.into(),
             component_name: component.identifier().to_string(),
+            generics,
+            generic_mappings: HashMap::new(),
             ports,
             mappings: HashMap::new(),
         })
@@ -68,6 +135,52 @@ impl PortMapping {
         &self.mappings
     }
 
+    pub fn generics(&self) -> &IndexMap<String, Parameter> {
+        &self.generics
+    }
+
+    pub fn generic_mappings(&self) -> &HashMap<String, GenericMapping> {
+        &self.generic_mappings
+    }
+
+    /// Map a value to a generic of the component
+    pub fn map_generic(
+        &mut self,
+        identifier: impl Into<String>,
+        value: impl Into<GenericMapping>,
+    ) -> Result<&mut Self> {
+        let identifier: &str = &identifier.into();
+        let value = value.into();
+        let generic = self
+            .generics()
+            .get(identifier)
+            .ok_or(Error::InvalidArgument(format!(
+                "Generic {} does not exist on this component",
+                identifier
+            )))?;
+        match (&generic.value, &value) {
+            (ParameterValue::Natural(_), GenericMapping::Integer(IntegerValue::Literal(x)))
+                if *x < 0 =>
+            {
+                return Err(Error::InvalidArgument(format!(
+                    "Cannot map {} to natural generic {}",
+                    x, identifier
+                )))
+            }
+            (ParameterValue::Natural(_), GenericMapping::Integer(_))
+            | (ParameterValue::String(_), GenericMapping::String(_))
+            | (_, GenericMapping::Generic(_)) => (),
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "Value does not match the type of generic {}",
+                    identifier
+                )))
+            }
+        }
+        self.generic_mappings.insert(identifier.to_string(), value);
+        Ok(self)
+    }
+
     pub fn map_port(
         &mut self,
         identifier: impl Into<String>,
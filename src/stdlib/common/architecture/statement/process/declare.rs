@@ -0,0 +1,320 @@
+use crate::{
+    stdlib::common::architecture::{assignment::bitvec::BitVecValue, ArchitectureDeclare},
+    Document, Error, Result,
+};
+
+use super::{
+    Call, CaseChoice, CaseStatement, Condition, IfStatement, Process, SequentialStatement,
+    WaitStatement,
+};
+
+/// Declares a sequence of statements, or "null" if there are none
+fn declare_statements(statements: &Vec<SequentialStatement>, pre: &str) -> Result<String> {
+    if statements.is_empty() {
+        return Ok(format!("{}null;\n", pre));
+    }
+    let mut result = String::new();
+    for statement in statements {
+        result.push_str(&statement.declare(pre, ";\n")?);
+    }
+    Ok(result)
+}
+
+impl Condition {
+    /// Declares the condition, nested and/or conditions are parenthesized
+    pub fn declare(&self) -> Result<String> {
+        let nested = |condition: &Condition| -> Result<String> {
+            match condition {
+                Condition::And(_, _) | Condition::Or(_, _) => {
+                    Ok(format!("({})", condition.declare()?))
+                }
+                _ => condition.declare(),
+            }
+        };
+        match self {
+            Condition::RisingEdge(object) => Ok(format!("rising_edge({})", object)),
+            Condition::FallingEdge(object) => Ok(format!("falling_edge({})", object)),
+            Condition::Equals(object, value) => Ok(format!(
+                "{} = {}",
+                object,
+                value.declare_for(object.to_string(), "", "")?
+            )),
+            Condition::NotEquals(object, value) => Ok(format!(
+                "{} /= {}",
+                object,
+                value.declare_for(object.to_string(), "", "")?
+            )),
+            Condition::And(left, right) => Ok(format!("{} and {}", nested(left)?, nested(right)?)),
+            Condition::Or(left, right) => Ok(format!("{} or {}", nested(left)?, nested(right)?)),
+            Condition::Not(condition) => Ok(format!("not ({})", condition.declare()?)),
+            Condition::Call(call) => Ok(call.declare()),
+        }
+    }
+}
+
+impl Call {
+    /// Declares the call, without parentheses if it has no arguments
+    pub fn declare(&self) -> String {
+        if self.arguments().is_empty() {
+            self.identifier().to_string()
+        } else {
+            let arguments = self
+                .arguments()
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>();
+            format!("{}({})", self.identifier(), arguments.join(", "))
+        }
+    }
+}
+
+impl ArchitectureDeclare for WaitStatement {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let mut result = format!("{}wait", pre);
+        if let Some(condition) = self.until_condition() {
+            result.push_str(&format!(" until {}", condition.declare()?));
+        }
+        if let Some(timeout) = self.timeout_ns() {
+            result.push_str(&format!(" for {} ns", timeout));
+        }
+        result.push_str(post);
+        Ok(result)
+    }
+}
+
+impl CaseChoice {
+    pub fn declare(&self) -> Result<String> {
+        match self {
+            CaseChoice::Bit(value) => Ok(format!("'{}'", value)),
+            CaseChoice::BitVec(value @ BitVecValue::Full(_)) => value.declare(),
+            CaseChoice::BitVec(_) => Err(Error::InvalidArgument(
+                "Choices for bit vectors must specify every value".to_string(),
+            )),
+        }
+    }
+}
+
+impl ArchitectureDeclare for IfStatement {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let nested_pre = &format!("{}  ", pre);
+        let mut result = String::new();
+        for (index, (condition, statements)) in self.branches().iter().enumerate() {
+            result.push_str(pre);
+            if index > 0 {
+                result.push_str("els");
+            }
+            result.push_str(&format!("if {} then\n", condition.declare()?));
+            result.push_str(&declare_statements(statements, nested_pre)?);
+        }
+        if let Some(statements) = self.else_statements() {
+            result.push_str(&format!("{}else\n", pre));
+            result.push_str(&declare_statements(statements, nested_pre)?);
+        }
+        result.push_str(&format!("{}end if{}", pre, post));
+        Ok(result)
+    }
+}
+
+impl ArchitectureDeclare for CaseStatement {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let choice_pre = &format!("{}  ", pre);
+        let nested_pre = &format!("{}    ", pre);
+        let mut result = format!("{}case {} is\n", pre, self.subject());
+        for (choice, statements) in self.choices() {
+            result.push_str(&format!("{}when {} =>\n", choice_pre, choice.declare()?));
+            result.push_str(&declare_statements(statements, nested_pre)?);
+        }
+        if let Some(statements) = self.others() {
+            result.push_str(&format!("{}when others =>\n", choice_pre));
+            result.push_str(&declare_statements(statements, nested_pre)?);
+        }
+        result.push_str(&format!("{}end case{}", pre, post));
+        Ok(result)
+    }
+}
+
+impl ArchitectureDeclare for SequentialStatement {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        match self {
+            SequentialStatement::Assignment(assignment) => assignment.declare(pre, post),
+            SequentialStatement::If(if_statement) => if_statement.declare(pre, post),
+            SequentialStatement::Case(case) => case.declare(pre, post),
+            SequentialStatement::Call(call) => Ok(format!("{}{}{}", pre, call.declare(), post)),
+            SequentialStatement::Report(message, severity) => Ok(format!(
+                "{}report \"{}\" severity {}{}",
+                pre,
+                message.replace('"', "\"\""),
+                severity,
+                post
+            )),
+            SequentialStatement::Wait(wait) => wait.declare(pre, post),
+        }
+    }
+}
+
+impl ArchitectureDeclare for Process {
+    fn declare(&self, pre: &str, post: &str) -> Result<String> {
+        let nested_pre = &format!("{}  ", pre);
+        let mut result = pre.to_string();
+        if let Some(doc) = self.doc() {
+            result.push_str("--");
+            result.push_str(doc.replace("\n", &format!("\n{}--", pre)).as_str());
+            result.push('\n');
+            result.push_str(pre);
+        }
+        let sensitivity_list = self
+            .sensitivity_list()
+            .iter()
+            .map(|x| x.identifier())
+            .collect::<Vec<&str>>();
+        if sensitivity_list.is_empty() {
+            result.push_str(&format!("{}: process\n", self.label()));
+        } else {
+            result.push_str(&format!(
+                "{}: process({})\n",
+                self.label(),
+                sensitivity_list.join(", ")
+            ));
+        }
+        for variable in self.variables() {
+            result.push_str(&variable.declare(nested_pre, ";\n")?);
+        }
+        result.push_str(&format!("{}begin\n", pre));
+        result.push_str(&declare_statements(self.statements(), nested_pre)?);
+        result.push_str(&format!("{}end process {}{}", pre, self.label(), post));
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stdlib::common::architecture::{
+        assignment::{Assign, StdLogicValue},
+        declaration::ObjectDeclaration,
+        object::ObjectType,
+        statement::process::Severity,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_clocked_process_declare() -> Result<()> {
+        let clk = ObjectDeclaration::signal("clk", ObjectType::Bit, None);
+        let rst = ObjectDeclaration::signal("rst", ObjectType::Bit, None);
+        let count = ObjectDeclaration::signal("count", ObjectType::bit_vector(1, 0)?, None);
+        let state = ObjectDeclaration::variable("state", ObjectType::Bit, None);
+        let case = CaseStatement::new(&count)?
+            .with_choice(
+                BitVecValue::from_str("11")?,
+                vec![count
+                    .assign(&BitVecValue::Others(StdLogicValue::Logic(false)))?
+                    .into()],
+            )?
+            .with_others(vec![count.assign(&BitVecValue::Unsigned(1))?.into()]);
+        let toggle = IfStatement::new(
+            Condition::equals(&state, StdLogicValue::Logic(false))?,
+            vec![state.assign(&StdLogicValue::Logic(true))?.into()],
+        )
+        .with_elsif(
+            Condition::equals(&count, BitVecValue::from_str("00")?)?
+                .and(Condition::not_equals(&rst, StdLogicValue::Logic(true))?),
+            vec![],
+        )
+        .with_else(vec![
+            state.assign(&StdLogicValue::Logic(false))?.into(),
+            case.into(),
+        ]);
+        let mut process = Process::clocked_with_reset(
+            "counter",
+            &clk,
+            &rst,
+            vec![count
+                .assign(&BitVecValue::Others(StdLogicValue::Logic(false)))?
+                .into()],
+            vec![toggle.into()],
+        )?
+        .with_doc("Counts every other cycle");
+        process.add_variable(state.clone())?;
+        assert_eq!(
+            r#"  --Counts every other cycle
+  counter: process(clk)
+    variable state : std_logic;
+  begin
+    if rising_edge(clk) then
+      if rst = '1' then
+        count <= (others => '0');
+      else
+        if state = '0' then
+          state := '1';
+        elsif count = "00" and rst /= '1' then
+          null;
+        else
+          state := '0';
+          case count is
+            when "11" =>
+              count <= (others => '0');
+            when others =>
+              count <= std_logic_vector(to_unsigned(1, count'length));
+          end case;
+        end if;
+      end if;
+    end if;
+  end process counter;
+"#,
+            process.declare("  ", ";\n")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_condition_declare() -> Result<()> {
+        let a = ObjectDeclaration::signal("a", ObjectType::Bit, None);
+        let b = ObjectDeclaration::signal("b", ObjectType::Bit, None);
+        let condition = !Condition::falling_edge(&a)?
+            .or(Condition::equals(&a, b.clone())?.and(Condition::rising_edge(&b)?));
+        assert_eq!(
+            "not (falling_edge(a) or (a = b and rising_edge(b)))",
+            condition.declare()?
+        );
+        let condition = !Condition::from(Call::new("matches").with_argument(&a).with_argument(&b));
+        assert_eq!("not (matches(a, b))", condition.declare()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_waiting_process_declare() -> Result<()> {
+        let clk = ObjectDeclaration::signal("clk", ObjectType::Bit, None);
+        let done = ObjectDeclaration::signal("done", ObjectType::Bit, None);
+        let mut process = Process::new("clk_gen");
+        process.add_statement(clk.assign(&StdLogicValue::Logic(false))?)?;
+        process.add_statement(WaitStatement::for_ns(5))?;
+        process.add_statement(Call::new("tick").with_argument(&clk))?;
+        process.add_statement(IfStatement::new(
+            Condition::equals(&done, StdLogicValue::Logic(true))?,
+            vec![
+                SequentialStatement::report("\"clk_gen\" done", Severity::Note),
+                WaitStatement::forever().into(),
+            ],
+        ))?;
+        process.add_statement(
+            WaitStatement::until(Condition::equals(&done, StdLogicValue::Logic(true))?)
+                .with_timeout_ns(100),
+        )?;
+        assert_eq!(
+            r#"clk_gen: process
+begin
+  clk <= '0';
+  wait for 5 ns;
+  tick(clk);
+  if done = '1' then
+    report """clk_gen"" done" severity note;
+    wait;
+  end if;
+  wait until done = '1' for 100 ns;
+end process clk_gen;
+"#,
+            process.declare("", ";\n")?
+        );
+        Ok(())
+    }
+}
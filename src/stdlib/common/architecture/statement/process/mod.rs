@@ -0,0 +1,1050 @@
+use std::{fmt, ops::Not};
+
+use indexmap::IndexMap;
+
+use crate::{
+    generator::vhdl::{ListUsings, Usings},
+    stdlib::common::architecture::{
+        assignment::{
+            array_assignment::ArrayAssignment, bitvec::BitVecValue, AssignDeclaration, Assignment,
+            AssignmentKind, DirectAssignment, ObjectAssignment, StdLogicValue, ValueAssignment,
+        },
+        declaration::{ObjectDeclaration, ObjectKind},
+        object::ObjectType,
+    },
+    Document, Error, Result,
+};
+
+pub mod declare;
+
+/// A process statement, containing sequential statements
+#[derive(Debug, Clone)]
+pub struct Process {
+    /// Label of the process
+    label: String,
+    /// Signals (and ports) the process is sensitive to
+    sensitivity_list: Vec<ObjectDeclaration>,
+    /// Variables declared within the process
+    variables: Vec<ObjectDeclaration>,
+    /// The sequential statements of the process
+    statements: Vec<SequentialStatement>,
+    doc: Option<String>,
+}
+
+impl Process {
+    pub fn new(label: impl Into<String>) -> Process {
+        Process {
+            label: label.into(),
+            sensitivity_list: vec![],
+            variables: vec![],
+            statements: vec![],
+            doc: None,
+        }
+    }
+
+    /// Create a process which is sensitive to `clk`, and executes `statements` on its rising edge
+    pub fn clocked(
+        label: impl Into<String>,
+        clk: &ObjectDeclaration,
+        statements: Vec<SequentialStatement>,
+    ) -> Result<Process> {
+        let mut process = Process::new(label);
+        process.add_sensitivity(clk)?;
+        process.add_statement(IfStatement::new(Condition::rising_edge(clk)?, statements))?;
+        Ok(process)
+    }
+
+    /// Create a process which is sensitive to `clk`, and on its rising edge either executes `reset` when `rst` is '1', or `statements` otherwise
+    pub fn clocked_with_reset(
+        label: impl Into<String>,
+        clk: &ObjectDeclaration,
+        rst: &ObjectDeclaration,
+        reset: Vec<SequentialStatement>,
+        statements: Vec<SequentialStatement>,
+    ) -> Result<Process> {
+        let reset = IfStatement::new(Condition::equals(rst, StdLogicValue::Logic(true))?, reset)
+            .with_else(statements);
+        Process::clocked(label, clk, vec![reset.into()])
+    }
+
+    /// Add an object to the sensitivity list, only signals and ports can be added
+    pub fn add_sensitivity(&mut self, object: &ObjectDeclaration) -> Result<()> {
+        match object.kind() {
+            ObjectKind::Signal | ObjectKind::EntityPort => {
+                if !self
+                    .sensitivity_list
+                    .iter()
+                    .any(|x| x.identifier() == object.identifier())
+                {
+                    self.sensitivity_list.push(object.clone());
+                }
+                Ok(())
+            }
+            _ => Err(Error::InvalidTarget(format!(
+                "{} object {} cannot be part of a sensitivity list",
+                object.kind(),
+                object.identifier()
+            ))),
+        }
+    }
+
+    /// Return this process with an object added to its sensitivity list
+    pub fn with_sensitivity(mut self, object: &ObjectDeclaration) -> Result<Self> {
+        self.add_sensitivity(object)?;
+        Ok(self)
+    }
+
+    /// Declare a variable within the process
+    pub fn add_variable(&mut self, variable: ObjectDeclaration) -> Result<()> {
+        if variable.kind() != ObjectKind::Variable {
+            return Err(Error::InvalidTarget(format!(
+                "{} object {} cannot be declared within a process",
+                variable.kind(),
+                variable.identifier()
+            )));
+        }
+        if self
+            .variables
+            .iter()
+            .any(|x| x.identifier() == variable.identifier())
+        {
+            return Err(Error::InvalidArgument(format!(
+                "Variable {} is already declared",
+                variable.identifier()
+            )));
+        }
+        self.variables.push(variable);
+        Ok(())
+    }
+
+    /// Add a sequential statement to the process
+    pub fn add_statement(&mut self, statement: impl Into<SequentialStatement>) -> Result<()> {
+        self.statements.push(statement.into());
+        Ok(())
+    }
+
+    /// Return this process with documentation added.
+    pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        self.label.as_str()
+    }
+
+    pub fn sensitivity_list(&self) -> &Vec<ObjectDeclaration> {
+        &self.sensitivity_list
+    }
+
+    pub fn variables(&self) -> &Vec<ObjectDeclaration> {
+        &self.variables
+    }
+
+    pub fn statements(&self) -> &Vec<SequentialStatement> {
+        &self.statements
+    }
+
+    /// Verifies that the process only uses objects which are either declared within the process,
+    /// or in `declared` (the objects declared in the architecture and the ports of its entity),
+    /// and that it only assigns signals, ports and variables
+    pub fn validate(&self, declared: &IndexMap<String, ObjectDeclaration>) -> Result<()> {
+        let waits = self.statements().iter().any(|x| x.contains_wait());
+        if self.sensitivity_list().is_empty() && !waits {
+            return Err(Error::BackEndError(format!(
+                "Process {} does not have a sensitivity list or wait statements",
+                self.label()
+            )));
+        }
+        if !self.sensitivity_list().is_empty() && waits {
+            return Err(Error::BackEndError(format!(
+                "Process {} cannot have both a sensitivity list and wait statements",
+                self.label()
+            )));
+        }
+        let scope = Scope::new(
+            format!("process {}", self.label()),
+            self.variables().iter().collect(),
+            declared,
+            vec![],
+        );
+        for object in self.sensitivity_list() {
+            scope.validate_object(object)?;
+        }
+        for variable in self.variables() {
+            if declared.contains_key(variable.identifier()) {
+                return Err(Error::InvalidArgument(format!(
+                    "Variable {} of process {} is already declared in the architecture",
+                    variable.identifier(),
+                    self.label()
+                )));
+            }
+        }
+        for statement in self.statements() {
+            scope.validate_statement(statement)?;
+        }
+        Ok(())
+    }
+}
+
+/// The objects which can be used by sequential statements, such as the statements of a process
+#[derive(Debug, Clone)]
+pub(crate) struct Scope<'s> {
+    /// Describes what the statements are part of, e.g. "process counter"
+    name: String,
+    /// The variables declared within the scope
+    variables: Vec<&'s ObjectDeclaration>,
+    /// Other objects which can be used within the scope
+    declared: &'s IndexMap<String, ObjectDeclaration>,
+    /// Identifiers of objects which cannot be assigned
+    read_only: Vec<&'s str>,
+}
+
+impl<'s> Scope<'s> {
+    pub(crate) fn new(
+        name: impl Into<String>,
+        variables: Vec<&'s ObjectDeclaration>,
+        declared: &'s IndexMap<String, ObjectDeclaration>,
+        read_only: Vec<&'s str>,
+    ) -> Scope<'s> {
+        Scope {
+            name: name.into(),
+            variables,
+            declared,
+            read_only,
+        }
+    }
+
+    pub(crate) fn validate_statement(&self, statement: &SequentialStatement) -> Result<()> {
+        match statement {
+            SequentialStatement::Assignment(assignment) => {
+                let object = assignment.object();
+                match object.kind() {
+                    ObjectKind::Signal | ObjectKind::EntityPort | ObjectKind::Variable => {
+                        self.validate_object(object)?
+                    }
+                    ObjectKind::Constant | ObjectKind::ComponentPort => {
+                        return Err(Error::InvalidTarget(format!(
+                            "{} object {} cannot be assigned in {}",
+                            object.kind(),
+                            object.identifier(),
+                            self.name
+                        )))
+                    }
+                }
+                if self.read_only.contains(&object.identifier()) {
+                    return Err(Error::InvalidTarget(format!(
+                        "{} cannot be assigned in {}, as it is an input",
+                        object.identifier(),
+                        self.name
+                    )));
+                }
+                self.validate_kind(assignment.assignment().kind())
+            }
+            SequentialStatement::If(if_statement) => {
+                for (condition, statements) in if_statement.branches() {
+                    self.validate_condition(condition)?;
+                    for statement in statements {
+                        self.validate_statement(statement)?;
+                    }
+                }
+                for statement in if_statement.else_statements().iter().flatten() {
+                    self.validate_statement(statement)?;
+                }
+                Ok(())
+            }
+            SequentialStatement::Case(case) => {
+                self.validate_object(case.subject().object())?;
+                // VHDL requires case statements to cover every value of their subject
+                if !case.is_complete()? {
+                    return Err(Error::InvalidArgument(format!(
+                        "Case statement on {} in {} does not cover every value, it requires \
+                         choices for all other values",
+                        case.subject(),
+                        self.name
+                    )));
+                }
+                for (_, statements) in case.choices() {
+                    for statement in statements {
+                        self.validate_statement(statement)?;
+                    }
+                }
+                for statement in case.others().iter().flatten() {
+                    self.validate_statement(statement)?;
+                }
+                Ok(())
+            }
+            SequentialStatement::Call(call) => self.validate_call(call),
+            SequentialStatement::Report(_, _) => Ok(()),
+            SequentialStatement::Wait(wait) => match wait.until_condition() {
+                Some(condition) => self.validate_condition(condition),
+                None => Ok(()),
+            },
+        }
+    }
+
+    fn validate_call(&self, call: &Call) -> Result<()> {
+        for argument in call.arguments() {
+            self.validate_object(argument.object())?;
+        }
+        Ok(())
+    }
+
+    fn validate_condition(&self, condition: &Condition) -> Result<()> {
+        match condition {
+            Condition::RisingEdge(object) | Condition::FallingEdge(object) => {
+                self.validate_object(object.object())
+            }
+            Condition::Equals(object, value) | Condition::NotEquals(object, value) => {
+                self.validate_object(object.object())?;
+                self.validate_kind(value)
+            }
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                self.validate_condition(left)?;
+                self.validate_condition(right)
+            }
+            Condition::Not(condition) => self.validate_condition(condition),
+            Condition::Call(call) => self.validate_call(call),
+        }
+    }
+
+    /// Verifies that all objects assigned from are declared
+    pub(crate) fn validate_kind(&self, kind: &AssignmentKind) -> Result<()> {
+        match kind {
+            AssignmentKind::Object(object) => self.validate_object(object.object()),
+            AssignmentKind::Direct(direct) => match direct {
+                DirectAssignment::Value(_) => Ok(()),
+                DirectAssignment::FullRecord(record) => {
+                    for (_, value) in record {
+                        self.validate_kind(value)?;
+                    }
+                    Ok(())
+                }
+                DirectAssignment::FullArray(array) => match array {
+                    ArrayAssignment::Direct(direct) => {
+                        for value in direct {
+                            self.validate_kind(value)?;
+                        }
+                        Ok(())
+                    }
+                    ArrayAssignment::Sliced { direct, others } => {
+                        for (_, value) in direct {
+                            self.validate_kind(value)?;
+                        }
+                        if let Some(value) = others {
+                            self.validate_kind(value)?;
+                        }
+                        Ok(())
+                    }
+                    ArrayAssignment::Others(value) => self.validate_kind(value),
+                },
+            },
+        }
+    }
+
+    /// Verifies that an object is declared, as a variable of the scope if it is a variable
+    pub(crate) fn validate_object(&self, object: &ObjectDeclaration) -> Result<()> {
+        let exists = if object.kind() == ObjectKind::Variable {
+            self.variables
+                .iter()
+                .any(|x| x.identifier() == object.identifier())
+        } else {
+            self.declared
+                .get(object.identifier())
+                .is_some_and(|x| x.kind() == object.kind())
+        };
+        if exists {
+            Ok(())
+        } else {
+            Err(Error::InvalidArgument(format!(
+                "{} object {} used in {} is not declared",
+                object.kind(),
+                object.identifier(),
+                self.name
+            )))
+        }
+    }
+}
+
+impl Document for Process {
+    fn doc(&self) -> Option<String> {
+        self.doc.clone()
+    }
+}
+
+impl ListUsings for Process {
+    fn list_usings(&self) -> Result<Usings> {
+        let mut usings = Usings::new_empty();
+        for variable in self.variables() {
+            usings.combine(&variable.list_usings()?);
+        }
+        for statement in self.statements() {
+            usings.combine(&statement.list_usings()?);
+        }
+        Ok(usings)
+    }
+}
+
+/// Sequential statements, which can be used within a process
+#[derive(Debug, Clone)]
+pub enum SequentialStatement {
+    /// Assignment to a signal, port or variable
+    Assignment(Box<AssignDeclaration>),
+    /// An if statement, with optional elsif and else branches
+    If(IfStatement),
+    /// A case statement
+    Case(CaseStatement),
+    /// A call of a procedure, such as one declared from a template
+    Call(Call),
+    /// Reports a message with a severity
+    Report(String, Severity),
+    /// A wait statement, which can only be used by processes without a sensitivity list
+    Wait(WaitStatement),
+}
+
+impl SequentialStatement {
+    /// Create a statement which reports a message with a severity
+    pub fn report(message: impl Into<String>, severity: Severity) -> SequentialStatement {
+        SequentialStatement::Report(message.into(), severity)
+    }
+
+    /// Returns whether this statement is or contains a wait statement
+    pub fn contains_wait(&self) -> bool {
+        let any =
+            |statements: &Vec<SequentialStatement>| statements.iter().any(|x| x.contains_wait());
+        match self {
+            SequentialStatement::Wait(_) => true,
+            SequentialStatement::If(if_statement) => {
+                if_statement.branches().iter().any(|(_, x)| any(x))
+                    || if_statement.else_statements().as_ref().is_some_and(any)
+            }
+            SequentialStatement::Case(case) => {
+                case.choices().iter().any(|(_, x)| any(x))
+                    || case.others().as_ref().is_some_and(any)
+            }
+            SequentialStatement::Assignment(_)
+            | SequentialStatement::Call(_)
+            | SequentialStatement::Report(_, _) => false,
+        }
+    }
+}
+
+impl From<AssignDeclaration> for SequentialStatement {
+    fn from(assignment: AssignDeclaration) -> Self {
+        SequentialStatement::Assignment(Box::new(assignment))
+    }
+}
+
+impl From<IfStatement> for SequentialStatement {
+    fn from(if_statement: IfStatement) -> Self {
+        SequentialStatement::If(if_statement)
+    }
+}
+
+impl From<CaseStatement> for SequentialStatement {
+    fn from(case: CaseStatement) -> Self {
+        SequentialStatement::Case(case)
+    }
+}
+
+impl From<Call> for SequentialStatement {
+    fn from(call: Call) -> Self {
+        SequentialStatement::Call(call)
+    }
+}
+
+impl From<WaitStatement> for SequentialStatement {
+    fn from(wait: WaitStatement) -> Self {
+        SequentialStatement::Wait(wait)
+    }
+}
+
+impl ListUsings for SequentialStatement {
+    fn list_usings(&self) -> Result<Usings> {
+        let mut usings = Usings::new_empty();
+        let mut combine = |statements: &Vec<SequentialStatement>| -> Result<()> {
+            for statement in statements {
+                usings.combine(&statement.list_usings()?);
+            }
+            Ok(())
+        };
+        match self {
+            SequentialStatement::Assignment(assignment) => return assignment.list_usings(),
+            SequentialStatement::If(if_statement) => {
+                for (_, statements) in if_statement.branches() {
+                    combine(statements)?;
+                }
+                if let Some(statements) = if_statement.else_statements() {
+                    combine(statements)?;
+                }
+            }
+            SequentialStatement::Case(case) => {
+                for (_, statements) in case.choices() {
+                    combine(statements)?;
+                }
+                if let Some(statements) = case.others() {
+                    combine(statements)?;
+                }
+            }
+            SequentialStatement::Call(_)
+            | SequentialStatement::Report(_, _)
+            | SequentialStatement::Wait(_) => (),
+        }
+        Ok(usings)
+    }
+}
+
+/// A boolean condition, used by if statements
+#[derive(Debug, Clone)]
+pub enum Condition {
+    /// rising_edge([object])
+    RisingEdge(ObjectAssignment),
+    /// falling_edge([object])
+    FallingEdge(ObjectAssignment),
+    /// [object] = [value]
+    Equals(ObjectAssignment, AssignmentKind),
+    /// [object] /= [value]
+    NotEquals(ObjectAssignment, AssignmentKind),
+    /// ([condition]) and ([condition])
+    And(Box<Condition>, Box<Condition>),
+    /// ([condition]) or ([condition])
+    Or(Box<Condition>, Box<Condition>),
+    /// not ([condition])
+    Not(Box<Condition>),
+    /// [function]([arguments]), a call of a function which returns a boolean
+    Call(Call),
+}
+
+impl Condition {
+    /// Create a condition on the rising edge of a bit object
+    pub fn rising_edge(object: &(impl Into<ObjectAssignment> + Clone)) -> Result<Condition> {
+        Ok(Condition::RisingEdge(Condition::edge_object(object)?))
+    }
+
+    /// Create a condition on the falling edge of a bit object
+    pub fn falling_edge(object: &(impl Into<ObjectAssignment> + Clone)) -> Result<Condition> {
+        Ok(Condition::FallingEdge(Condition::edge_object(object)?))
+    }
+
+    /// Create a condition which holds when an object is equal to a value (or another object)
+    pub fn equals(
+        object: &(impl Into<ObjectAssignment> + Clone),
+        value: impl Into<AssignmentKind>,
+    ) -> Result<Condition> {
+        let (object, value) = Condition::comparison(object, value)?;
+        Ok(Condition::Equals(object, value))
+    }
+
+    /// Create a condition which holds when an object is not equal to a value (or another object)
+    pub fn not_equals(
+        object: &(impl Into<ObjectAssignment> + Clone),
+        value: impl Into<AssignmentKind>,
+    ) -> Result<Condition> {
+        let (object, value) = Condition::comparison(object, value)?;
+        Ok(Condition::NotEquals(object, value))
+    }
+
+    pub fn and(self, other: Condition) -> Condition {
+        Condition::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Condition) -> Condition {
+        Condition::Or(Box::new(self), Box::new(other))
+    }
+
+    fn edge_object(object: &(impl Into<ObjectAssignment> + Clone)) -> Result<ObjectAssignment> {
+        let object = object.clone().into();
+        match object.typ()? {
+            ObjectType::Bit => Ok(object),
+            typ => Err(Error::InvalidTarget(format!(
+                "Cannot detect the edge of {}",
+                typ
+            ))),
+        }
+    }
+
+    fn comparison(
+        object: &(impl Into<ObjectAssignment> + Clone),
+        value: impl Into<AssignmentKind>,
+    ) -> Result<(ObjectAssignment, AssignmentKind)> {
+        let object = object.clone().into();
+        let value = value.into();
+        match &value {
+            // An aggregate does not have a type VHDL can resolve in a comparison
+            AssignmentKind::Direct(DirectAssignment::Value(ValueAssignment::BitVec(
+                BitVecValue::Others(_),
+            )))
+            | AssignmentKind::Direct(DirectAssignment::FullRecord(_))
+            | AssignmentKind::Direct(DirectAssignment::FullArray(_)) => {
+                Err(Error::InvalidArgument(format!(
+                    "{} can only be compared to a value or another object",
+                    object
+                )))
+            }
+            AssignmentKind::Object(_) | AssignmentKind::Direct(DirectAssignment::Value(_)) => {
+                object.typ()?.can_assign(&Assignment::from(value.clone()))?;
+                Ok((object, value))
+            }
+        }
+    }
+}
+
+impl From<Call> for Condition {
+    fn from(call: Call) -> Self {
+        Condition::Call(call)
+    }
+}
+
+impl Not for Condition {
+    type Output = Condition;
+
+    fn not(self) -> Condition {
+        Condition::Not(Box::new(self))
+    }
+}
+
+/// An if statement, consisting of an if branch, any number of elsif branches and an optional else branch
+#[derive(Debug, Clone)]
+pub struct IfStatement {
+    /// The conditions and statements of the if and elsif branches
+    branches: Vec<(Condition, Vec<SequentialStatement>)>,
+    /// The statements of the else branch
+    else_statements: Option<Vec<SequentialStatement>>,
+}
+
+impl IfStatement {
+    pub fn new(condition: Condition, statements: Vec<SequentialStatement>) -> IfStatement {
+        IfStatement {
+            branches: vec![(condition, statements)],
+            else_statements: None,
+        }
+    }
+
+    /// Return this if statement with an elsif branch added
+    pub fn with_elsif(
+        mut self,
+        condition: Condition,
+        statements: Vec<SequentialStatement>,
+    ) -> Self {
+        self.branches.push((condition, statements));
+        self
+    }
+
+    /// Return this if statement with an else branch
+    pub fn with_else(mut self, statements: Vec<SequentialStatement>) -> Self {
+        self.else_statements = Some(statements);
+        self
+    }
+
+    pub fn branches(&self) -> &Vec<(Condition, Vec<SequentialStatement>)> {
+        &self.branches
+    }
+
+    pub fn else_statements(&self) -> &Option<Vec<SequentialStatement>> {
+        &self.else_statements
+    }
+}
+
+/// A call of a procedure or function, with objects as its arguments
+#[derive(Debug, Clone)]
+pub struct Call {
+    /// Identifier of the procedure or function
+    identifier: String,
+    /// The arguments, in the order of the parameters of the procedure or function
+    arguments: Vec<ObjectAssignment>,
+}
+
+impl Call {
+    pub fn new(identifier: impl Into<String>) -> Call {
+        Call {
+            identifier: identifier.into(),
+            arguments: vec![],
+        }
+    }
+
+    /// Return this call with an argument added
+    pub fn with_argument(mut self, argument: &(impl Into<ObjectAssignment> + Clone)) -> Self {
+        self.arguments.push(argument.clone().into());
+        self
+    }
+
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+
+    pub fn arguments(&self) -> &Vec<ObjectAssignment> {
+        &self.arguments
+    }
+}
+
+/// The severity of a report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+    Failure,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Note => write!(f, "note"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+            Severity::Failure => write!(f, "failure"),
+        }
+    }
+}
+
+/// A wait statement, which suspends a process until its condition holds or its timeout expires.
+/// Without either, the process is suspended indefinitely.
+#[derive(Debug, Clone, Default)]
+pub struct WaitStatement {
+    /// The condition to wait for
+    until: Option<Condition>,
+    /// The maximum time to wait, in nanoseconds
+    timeout_ns: Option<u64>,
+}
+
+impl WaitStatement {
+    /// Create a wait statement which suspends the process indefinitely
+    pub fn forever() -> WaitStatement {
+        WaitStatement::default()
+    }
+
+    /// Create a wait statement which suspends the process until a condition holds
+    pub fn until(condition: Condition) -> WaitStatement {
+        WaitStatement {
+            until: Some(condition),
+            timeout_ns: None,
+        }
+    }
+
+    /// Create a wait statement which suspends the process for a number of nanoseconds
+    pub fn for_ns(timeout_ns: u64) -> WaitStatement {
+        WaitStatement {
+            until: None,
+            timeout_ns: Some(timeout_ns),
+        }
+    }
+
+    /// Return this wait statement with a timeout, in nanoseconds
+    pub fn with_timeout_ns(mut self, timeout_ns: u64) -> Self {
+        self.timeout_ns = Some(timeout_ns);
+        self
+    }
+
+    pub fn until_condition(&self) -> &Option<Condition> {
+        &self.until
+    }
+
+    pub fn timeout_ns(&self) -> Option<u64> {
+        self.timeout_ns
+    }
+}
+
+/// A choice of a case statement
+#[derive(Debug, Clone)]
+pub enum CaseChoice {
+    /// A choice for a bit object
+    Bit(StdLogicValue),
+    /// A choice for a bit vector object, which must be a full value
+    BitVec(BitVecValue),
+}
+
+impl From<StdLogicValue> for CaseChoice {
+    fn from(value: StdLogicValue) -> Self {
+        CaseChoice::Bit(value)
+    }
+}
+
+impl From<BitVecValue> for CaseChoice {
+    fn from(value: BitVecValue) -> Self {
+        CaseChoice::BitVec(value)
+    }
+}
+
+impl CaseChoice {
+    /// Returns the value of the choice, as a string of std_logic values
+    fn value(&self) -> String {
+        match self {
+            CaseChoice::Bit(value) => value.to_string(),
+            CaseChoice::BitVec(BitVecValue::Full(values)) => {
+                values.iter().map(|x| x.to_string()).collect()
+            }
+            CaseChoice::BitVec(_) => unreachable!(),
+        }
+    }
+}
+
+/// A case statement over a bit or bit vector object
+#[derive(Debug, Clone)]
+pub struct CaseStatement {
+    /// The object the case statement selects on
+    subject: ObjectAssignment,
+    /// The choices and their statements
+    choices: Vec<(CaseChoice, Vec<SequentialStatement>)>,
+    /// The statements for all other choices
+    others: Option<Vec<SequentialStatement>>,
+}
+
+impl CaseStatement {
+    /// Create a case statement, the subject must be a bit or a bit vector
+    pub fn new(subject: &(impl Into<ObjectAssignment> + Clone)) -> Result<CaseStatement> {
+        let subject = subject.clone().into();
+        match subject.typ()? {
+            ObjectType::Bit => (),
+            ObjectType::Array(array) if array.is_bitvector() => (),
+            typ => {
+                return Err(Error::InvalidTarget(format!(
+                    "Cannot use {} as the subject of a case statement",
+                    typ
+                )))
+            }
+        }
+        Ok(CaseStatement {
+            subject,
+            choices: vec![],
+            others: None,
+        })
+    }
+
+    /// Return this case statement with a choice added
+    pub fn with_choice(
+        mut self,
+        choice: impl Into<CaseChoice>,
+        statements: Vec<SequentialStatement>,
+    ) -> Result<Self> {
+        let choice = choice.into();
+        match (self.subject.typ()?, &choice) {
+            (ObjectType::Bit, CaseChoice::Bit(_)) => (),
+            (ObjectType::Array(array), CaseChoice::BitVec(bitvec @ BitVecValue::Full(_))) => {
+                bitvec.validate_width(array.width())?
+            }
+            (_, CaseChoice::BitVec(BitVecValue::Full(_))) | (_, CaseChoice::Bit(_)) => {
+                return Err(Error::InvalidArgument(format!(
+                    "Choice {} does not match the type of {}",
+                    choice.value(),
+                    self.subject
+                )))
+            }
+            (_, CaseChoice::BitVec(_)) => {
+                return Err(Error::InvalidArgument(
+                    "Choices for bit vectors must specify every value".to_string(),
+                ))
+            }
+        }
+        if self
+            .choices
+            .iter()
+            .any(|(x, _)| x.value() == choice.value())
+        {
+            return Err(Error::InvalidArgument(format!(
+                "Choice {} is already part of the case statement",
+                choice.value()
+            )));
+        }
+        self.choices.push((choice, statements));
+        Ok(self)
+    }
+
+    /// Return this case statement with statements for all other choices
+    pub fn with_others(mut self, statements: Vec<SequentialStatement>) -> Self {
+        self.others = Some(statements);
+        self
+    }
+
+    pub fn subject(&self) -> &ObjectAssignment {
+        &self.subject
+    }
+
+    pub fn choices(&self) -> &Vec<(CaseChoice, Vec<SequentialStatement>)> {
+        &self.choices
+    }
+
+    pub fn others(&self) -> &Option<Vec<SequentialStatement>> {
+        &self.others
+    }
+
+    /// Returns whether the choices cover all values of the subject
+    pub fn is_complete(&self) -> Result<bool> {
+        if self.others().is_some() {
+            return Ok(true);
+        }
+        let width = match self.subject().typ()? {
+            ObjectType::Array(array) => array.width(),
+            _ => 1,
+        };
+        // Every bit can have any of the nine std_logic values
+        Ok(9usize
+            .checked_pow(width)
+            .is_some_and(|values| self.choices().len() >= values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        generator::vhdl::Declare,
+        stdlib::common::architecture::{
+            assignment::Assign, object::RecordObject, tests::test_package, Architecture,
+        },
+        Name,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_process_validation() -> Result<()> {
+        let package = test_package();
+        let mut architecture = Architecture::new_default(&package, Name::try_new("test")?)?;
+        let ports = architecture.entity_ports()?;
+        let clk = ports.get("clk").unwrap();
+        let count = ObjectDeclaration::signal("count", ObjectType::bit_vector(1, 0)?, None);
+        let zero = BitVecValue::Others(StdLogicValue::Logic(false));
+
+        // Objects must be declared in the architecture before they can be used
+        let process = Process::clocked("counter", clk, vec![count.assign(&zero)?.into()])?;
+        assert!(architecture.add_statement(process.clone()).is_err());
+        architecture.add_declaration(count.clone())?;
+        architecture.add_statement(process)?;
+
+        // Variables must be declared in the process
+        let count_next =
+            ObjectDeclaration::signal("count_next", ObjectType::bit_vector(1, 0)?, None);
+        architecture.add_declaration(count_next.clone())?;
+        let variable = ObjectDeclaration::variable("next", ObjectType::bit_vector(1, 0)?, None);
+        let mut process = Process::clocked(
+            "counter_next",
+            clk,
+            vec![
+                variable.assign(&BitVecValue::Unsigned(1))?.into(),
+                count_next.assign(&variable)?.into(),
+            ],
+        )?;
+        assert!(architecture.add_statement(process.clone()).is_err());
+        process.add_variable(variable.clone())?;
+        assert!(process.add_variable(variable.clone()).is_err());
+        architecture.add_statement(process)?;
+
+        // Constants cannot be assigned, and processes require a sensitivity list
+        let constant =
+            ObjectDeclaration::constant("limit", ObjectType::Bit, StdLogicValue::Logic(true));
+        architecture.add_declaration(constant.clone())?;
+        let process = Process::clocked(
+            "constant",
+            clk,
+            vec![
+                AssignDeclaration::new(constant.clone(), StdLogicValue::Logic(false).into()).into(),
+            ],
+        )?;
+        assert!(architecture.add_statement(process).is_err());
+        let count_copy =
+            ObjectDeclaration::signal("count_copy", ObjectType::bit_vector(1, 0)?, None);
+        architecture.add_declaration(count_copy.clone())?;
+        let mut process = Process::new("unclocked");
+        process.add_statement(count_copy.assign(&count)?)?;
+        assert!(architecture.add_statement(process.clone()).is_err());
+        assert!(process.add_sensitivity(&variable).is_err());
+        assert!(process.add_sensitivity(&constant).is_err());
+        process.add_sensitivity(&count)?;
+        architecture.add_statement(process.clone())?;
+
+        // Processes either have a sensitivity list or wait statements
+        process.add_statement(WaitStatement::forever())?;
+        assert!(architecture.add_statement(process).is_err());
+        let count_wait =
+            ObjectDeclaration::signal("count_wait", ObjectType::bit_vector(1, 0)?, None);
+        architecture.add_declaration(count_wait.clone())?;
+        let mut process = Process::new("waiting");
+        process.add_statement(count_wait.assign(&count)?)?;
+        process.add_statement(WaitStatement::until(Condition::rising_edge(clk)?))?;
+        architecture.add_statement(process)?;
+
+        assert!(architecture
+            .declare()?
+            .contains("counter_next: process(clk)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_incomplete_case() -> Result<()> {
+        let package = test_package();
+        let mut architecture = Architecture::new_default(&package, Name::try_new("test")?)?;
+        let select = ObjectDeclaration::signal("select", ObjectType::Bit, None);
+        let output = ObjectDeclaration::signal("output", ObjectType::Bit, None);
+        architecture.add_declaration(select.clone())?;
+        architecture.add_declaration(output.clone())?;
+        let case = CaseStatement::new(&select)?
+            .with_choice(
+                StdLogicValue::Logic(false),
+                vec![output.assign(&StdLogicValue::Logic(true))?.into()],
+            )?
+            .with_choice(
+                StdLogicValue::Logic(true),
+                vec![output.assign(&StdLogicValue::Logic(false))?.into()],
+            )?;
+        let process = |case: CaseStatement| -> Result<Process> {
+            let mut process = Process::new("select");
+            process.add_sensitivity(&select)?;
+            process.add_statement(case)?;
+            Ok(process)
+        };
+
+        // The choices do not cover every std_logic value
+        assert!(architecture
+            .add_statement(process(case.clone())?)
+            .unwrap_err()
+            .to_string()
+            .contains("Case statement on select in process select does not cover every value"));
+        architecture.add_statement(process(
+            case.with_others(vec![output.assign(&StdLogicValue::DontCare)?.into()]),
+        )?)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_case_choices() -> Result<()> {
+        let bit = ObjectDeclaration::signal("bit", ObjectType::Bit, None);
+        let bits = ObjectDeclaration::signal("bits", ObjectType::bit_vector(1, 0)?, None);
+
+        let case = CaseStatement::new(&bit)?
+            .with_choice(StdLogicValue::Logic(false), vec![])?
+            .with_choice(StdLogicValue::Logic(true), vec![])?;
+        assert!(!case.is_complete()?);
+        assert!(case
+            .clone()
+            .with_choice(StdLogicValue::Logic(true), vec![])
+            .is_err());
+        assert!(case
+            .clone()
+            .with_choice(BitVecValue::from_str("1")?, vec![])
+            .is_err());
+        assert!(case.with_others(vec![]).is_complete()?);
+
+        let case = CaseStatement::new(&bits)?;
+        assert!(case
+            .clone()
+            .with_choice(BitVecValue::from_str("101")?, vec![])
+            .is_err());
+        assert!(case
+            .clone()
+            .with_choice(BitVecValue::Unsigned(1), vec![])
+            .is_err());
+        assert!(case
+            .with_choice(StdLogicValue::Logic(true), vec![])
+            .is_err());
+
+        let record = ObjectDeclaration::signal(
+            "record",
+            RecordObject::new("rec", IndexMap::new()).into(),
+            None,
+        );
+        assert!(CaseStatement::new(&record).is_err());
+        assert!(Condition::rising_edge(&bits).is_err());
+        assert!(Condition::equals(&bits, StdLogicValue::Logic(true)).is_err());
+        assert!(Condition::equals(&bits, BitVecValue::Others(StdLogicValue::Logic(true))).is_err());
+        Ok(())
+    }
+}
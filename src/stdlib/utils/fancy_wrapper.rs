@@ -11,7 +11,7 @@ use crate::{
         architecture::{
             assignment::{flatten::FlatAssignment, Assign, FieldSelection},
             declaration::{ObjectDeclaration, ObjectMode},
-            statement::PortMapping,
+            statement::{GenericMapping, PortMapping, Statement},
             Architecture,
         },
         entity::Entity,
@@ -27,14 +27,21 @@ pub fn generate_fancy_wrapper<'a>(
         Architecture::new_default(package, cat!(streamlet_key, CANON_SUFFIX.unwrap()))?;
     let mut portmap =
         PortMapping::from_component(&package.get_component(streamlet_key.clone())?, "fancy")?;
+    // The generics of the wrapper are those of the component it wraps, forward them.
+    for generic in package.get_component(streamlet_key.clone())?.parameters() {
+        portmap.map_generic(
+            generic.name.clone(),
+            GenericMapping::Generic(generic.name.clone()),
+        )?;
+    }
     let mut fancy_wires = IndexMap::new();
     let mut fancy_assigns = vec![];
     let mut fixed_assign = |signal: &ObjectDeclaration, port_name: &str| -> Result<()> {
-        fancy_assigns.push(
+        fancy_assigns.push(Statement::from(
             signal.assign(architecture.entity_ports()?.get(port_name).ok_or(
                 Error::BackEndError(format!("Entity does not have a {} signal", port_name)),
             )?)?,
-        );
+        ));
         Ok(())
     };
     for (port_name, object) in portmap.ports() {
@@ -51,6 +58,9 @@ pub fn generate_fancy_wrapper<'a>(
      -> Result<()> {
         fancy_assigns.extend(if to_complex {
             port.to_complex(signal, &vec![FieldSelection::name(field_name)], &vec![])?
+                .into_iter()
+                .map(Statement::from)
+                .collect()
         } else {
             signal.to_flat(port, &vec![], &vec![FieldSelection::name(field_name)])?
         });
@@ -86,6 +96,72 @@ pub fn generate_fancy_wrapper<'a>(
     Ok(architecture)
 }
 
+/// Generate an architecture for the fancy component of a streamlet which wraps its canonical
+/// component, for streamlets of which the canonical component is implemented, e.g. by a
+/// structural architecture. This is the reverse of [generate_fancy_wrapper].
+pub fn generate_canonical_wrapper<'a>(
+    package: &'a Package,
+    streamlet_key: &StreamletKey,
+) -> Result<Architecture<'a>> {
+    let mut architecture = Architecture::new_default(package, streamlet_key.to_string())?;
+    let canonical = package.get_component(cat!(streamlet_key, CANON_SUFFIX.unwrap()))?;
+    let mut portmap = PortMapping::from_component(&canonical, "canonical")?;
+    for generic in canonical.parameters() {
+        portmap.map_generic(
+            generic.name.clone(),
+            GenericMapping::Generic(generic.name.clone()),
+        )?;
+    }
+    let entity_ports = architecture.entity_ports()?;
+    let mut canonical_wires = IndexMap::new();
+    let mut canonical_assigns = vec![];
+    for (port_name, object) in portmap.ports() {
+        let signal = ObjectDeclaration::signal(cat!(port_name, "wire"), object.typ().clone(), None);
+        if port_name == "clk" || port_name == "rst" {
+            canonical_assigns.push(Statement::from(
+                signal.assign(entity_ports.get(port_name).ok_or(Error::BackEndError(
+                    format!("Entity does not have a {} signal", port_name),
+                ))?)?,
+            ));
+        }
+        canonical_wires.insert(port_name.to_string(), signal);
+    }
+    for (port_name, entity_port) in &entity_ports {
+        let base_name = port_name.replace("_dn", "").replace("_up", "");
+        for (canon_name, wire) in &canonical_wires {
+            if canon_name.starts_with(&base_name) {
+                let field_name = canon_name.trim_start_matches(&format!("{}_", base_name));
+                if entity_port
+                    .typ()
+                    .get_field(&FieldSelection::name(field_name))
+                    .is_ok()
+                {
+                    let field = vec![FieldSelection::name(field_name)];
+                    if *entity_port.mode() == ObjectMode::Assigned {
+                        canonical_assigns.extend(entity_port.to_flat(wire, &vec![], &field)?);
+                    } else {
+                        canonical_assigns.extend(
+                            wire.to_complex(entity_port, &field, &vec![])?
+                                .into_iter()
+                                .map(Statement::from),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    for (port_name, wire) in canonical_wires {
+        portmap.map_port(port_name, &wire)?;
+        architecture.add_declaration(wire)?;
+    }
+    for assign in canonical_assigns {
+        architecture.add_statement(assign)?;
+    }
+    architecture.add_statement(portmap)?;
+
+    Ok(architecture)
+}
+
 #[cfg(test)]
 mod tests {
     use std::convert::TryFrom;
@@ -200,29 +276,109 @@ begin
    in_pass2_ready <= in_pass2_up_wire.ready;
    out_pass_valid <= out_pass_dn_wire.valid;
    out_pass_data(32 downto 32) <= out_pass_dn_wire.data(0).tag;
-   out_pass_data(31 downto 0) <= out_pass_dn_wire.data(0).a;
-   out_pass_data(7 downto 0) <= out_pass_dn_wire.data(0).b;
+   out_pass_dn_wire_data_0_tag_mux: process(out_pass_dn_wire)
+   begin
+     case out_pass_dn_wire.data(0).tag is
+       when "0" =>
+         out_pass_data(31 downto 0) <= out_pass_dn_wire.data(0).a;
+       when "1" =>
+         out_pass_data(7 downto 0) <= out_pass_dn_wire.data(0).b;
+         out_pass_data(31 downto 8) <= (others => '0');
+       when others =>
+         out_pass_data(31 downto 0) <= (others => '0');
+     end case;
+   end process out_pass_dn_wire_data_0_tag_mux;
    out_pass_data(65 downto 65) <= out_pass_dn_wire.data(1).tag;
-   out_pass_data(64 downto 33) <= out_pass_dn_wire.data(1).a;
-   out_pass_data(40 downto 33) <= out_pass_dn_wire.data(1).b;
+   out_pass_dn_wire_data_1_tag_mux: process(out_pass_dn_wire)
+   begin
+     case out_pass_dn_wire.data(1).tag is
+       when "0" =>
+         out_pass_data(64 downto 33) <= out_pass_dn_wire.data(1).a;
+       when "1" =>
+         out_pass_data(40 downto 33) <= out_pass_dn_wire.data(1).b;
+         out_pass_data(64 downto 41) <= (others => '0');
+       when others =>
+         out_pass_data(64 downto 33) <= (others => '0');
+     end case;
+   end process out_pass_dn_wire_data_1_tag_mux;
    out_pass_data(98 downto 98) <= out_pass_dn_wire.data(2).tag;
-   out_pass_data(97 downto 66) <= out_pass_dn_wire.data(2).a;
-   out_pass_data(73 downto 66) <= out_pass_dn_wire.data(2).b;
+   out_pass_dn_wire_data_2_tag_mux: process(out_pass_dn_wire)
+   begin
+     case out_pass_dn_wire.data(2).tag is
+       when "0" =>
+         out_pass_data(97 downto 66) <= out_pass_dn_wire.data(2).a;
+       when "1" =>
+         out_pass_data(73 downto 66) <= out_pass_dn_wire.data(2).b;
+         out_pass_data(97 downto 74) <= (others => '0');
+       when others =>
+         out_pass_data(97 downto 66) <= (others => '0');
+     end case;
+   end process out_pass_dn_wire_data_2_tag_mux;
    out_pass_data(131 downto 131) <= out_pass_dn_wire.data(3).tag;
-   out_pass_data(130 downto 99) <= out_pass_dn_wire.data(3).a;
-   out_pass_data(106 downto 99) <= out_pass_dn_wire.data(3).b;
+   out_pass_dn_wire_data_3_tag_mux: process(out_pass_dn_wire)
+   begin
+     case out_pass_dn_wire.data(3).tag is
+       when "0" =>
+         out_pass_data(130 downto 99) <= out_pass_dn_wire.data(3).a;
+       when "1" =>
+         out_pass_data(106 downto 99) <= out_pass_dn_wire.data(3).b;
+         out_pass_data(130 downto 107) <= (others => '0');
+       when others =>
+         out_pass_data(130 downto 99) <= (others => '0');
+     end case;
+   end process out_pass_dn_wire_data_3_tag_mux;
    out_pass_data(164 downto 164) <= out_pass_dn_wire.data(4).tag;
-   out_pass_data(163 downto 132) <= out_pass_dn_wire.data(4).a;
-   out_pass_data(139 downto 132) <= out_pass_dn_wire.data(4).b;
+   out_pass_dn_wire_data_4_tag_mux: process(out_pass_dn_wire)
+   begin
+     case out_pass_dn_wire.data(4).tag is
+       when "0" =>
+         out_pass_data(163 downto 132) <= out_pass_dn_wire.data(4).a;
+       when "1" =>
+         out_pass_data(139 downto 132) <= out_pass_dn_wire.data(4).b;
+         out_pass_data(163 downto 140) <= (others => '0');
+       when others =>
+         out_pass_data(163 downto 132) <= (others => '0');
+     end case;
+   end process out_pass_dn_wire_data_4_tag_mux;
    out_pass_data(197 downto 197) <= out_pass_dn_wire.data(5).tag;
-   out_pass_data(196 downto 165) <= out_pass_dn_wire.data(5).a;
-   out_pass_data(172 downto 165) <= out_pass_dn_wire.data(5).b;
+   out_pass_dn_wire_data_5_tag_mux: process(out_pass_dn_wire)
+   begin
+     case out_pass_dn_wire.data(5).tag is
+       when "0" =>
+         out_pass_data(196 downto 165) <= out_pass_dn_wire.data(5).a;
+       when "1" =>
+         out_pass_data(172 downto 165) <= out_pass_dn_wire.data(5).b;
+         out_pass_data(196 downto 173) <= (others => '0');
+       when others =>
+         out_pass_data(196 downto 165) <= (others => '0');
+     end case;
+   end process out_pass_dn_wire_data_5_tag_mux;
    out_pass_data(230 downto 230) <= out_pass_dn_wire.data(6).tag;
-   out_pass_data(229 downto 198) <= out_pass_dn_wire.data(6).a;
-   out_pass_data(205 downto 198) <= out_pass_dn_wire.data(6).b;
+   out_pass_dn_wire_data_6_tag_mux: process(out_pass_dn_wire)
+   begin
+     case out_pass_dn_wire.data(6).tag is
+       when "0" =>
+         out_pass_data(229 downto 198) <= out_pass_dn_wire.data(6).a;
+       when "1" =>
+         out_pass_data(205 downto 198) <= out_pass_dn_wire.data(6).b;
+         out_pass_data(229 downto 206) <= (others => '0');
+       when others =>
+         out_pass_data(229 downto 198) <= (others => '0');
+     end case;
+   end process out_pass_dn_wire_data_6_tag_mux;
    out_pass_data(263 downto 263) <= out_pass_dn_wire.data(7).tag;
-   out_pass_data(262 downto 231) <= out_pass_dn_wire.data(7).a;
-   out_pass_data(238 downto 231) <= out_pass_dn_wire.data(7).b;
+   out_pass_dn_wire_data_7_tag_mux: process(out_pass_dn_wire)
+   begin
+     case out_pass_dn_wire.data(7).tag is
+       when "0" =>
+         out_pass_data(262 downto 231) <= out_pass_dn_wire.data(7).a;
+       when "1" =>
+         out_pass_data(238 downto 231) <= out_pass_dn_wire.data(7).b;
+         out_pass_data(262 downto 239) <= (others => '0');
+       when others =>
+         out_pass_data(262 downto 231) <= (others => '0');
+     end case;
+   end process out_pass_dn_wire_data_7_tag_mux;
    out_pass_stai <= out_pass_dn_wire.stai;
    out_pass_endi <= out_pass_dn_wire.endi;
    out_pass_strb <= out_pass_dn_wire.strb;
@@ -243,4 +399,21 @@ end Behavioral;
         );
         Ok(())
     }
+
+    #[test]
+    fn test_canonical_wrapper() -> Result<()> {
+        let lib_key = Name::try_from("test_library")?;
+        let prj = parsed_stub_project()?;
+        let pak = prj.get_lib(lib_key)?.fancy();
+        let arch = generate_canonical_wrapper(&pak, &StreamletKey::try_from("passthrough_stub")?)?
+            .declare()?;
+        assert!(arch.contains("entity passthrough_stub is\n"));
+        assert!(arch.contains("   signal in_pass2_data_wire : std_logic_vector(117 downto 0);\n"));
+        assert!(arch.contains("   in_pass2_data_wire(117 downto 64) <= in_pass2_dn.data.op2;\n"));
+        assert!(arch.contains("   in_pass_up.ready <= in_pass_ready_wire;\n"));
+        assert!(arch.contains("   out_pass_dn.data(7).b <= out_pass_data_wire(238 downto 231);\n"));
+        assert!(arch.contains("   canonical: passthrough_stub_com port map(\n"));
+        assert!(arch.contains("     out_pass_strb => out_pass_strb_wire\n"));
+        Ok(())
+    }
 }
@@ -1 +1,3 @@
 pub mod fancy_wrapper;
+pub mod structural;
+pub mod testbench;
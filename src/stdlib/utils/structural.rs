@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::ops::Deref;
+
+use crate::{
+    cat,
+    design::{
+        implementation::composer::impl_graph::ImplementationGraph, param::ParameterVariant,
+        ComponentKey, LibKey, NodeKey, Project, THIS_KEY,
+    },
+    generator::common::{
+        convert::{Componentify, Portify, CANON_SUFFIX},
+        Component, Package,
+    },
+    stdlib::common::architecture::{
+        assignment::Assign,
+        declaration::{ObjectDeclaration, ObjectMode},
+        statement::{generate::IntegerValue, PortMapping},
+        Architecture,
+    },
+    Error, Identify, Name, Result,
+};
+
+/// Find the library which contains the streamlet a node in an implementation graph was
+/// instantiated from.
+pub(crate) fn component_library(project: &Project, key: &ComponentKey) -> Result<LibKey> {
+    let mut libs = project
+        .libraries()
+        .filter(|lib| lib.get_streamlet(key.clone()).is_ok())
+        .map(|lib| lib.key().clone());
+    match (libs.next(), libs.next()) {
+        (Some(lib), None) => Ok(lib),
+        (None, _) => Err(Error::BackEndError(format!(
+            "Streamlet {} is not part of any library in project {}",
+            key,
+            project.identifier()
+        ))),
+        (Some(a), Some(b)) => Err(Error::BackEndError(format!(
+            "Streamlet {} is ambiguous, it exists in both library {} and {}",
+            key, a, b
+        ))),
+    }
+}
+
+/// Generate a structural architecture for the canonical component of the streamlet implemented
+/// by the implementation graph.
+///
+/// Every node (other than "this") is instantiated through its canonical component, a signal is
+/// declared for every canonical port of every edge, and edges to or from "this" are wired to the
+/// ports of the entity.
+///
+/// Parameters bound by a node are mapped to the generics of its component. The widths of the
+/// ports of the component depend on its generics, parameters that change the ports in other ways
+/// result in an error.
+pub fn generate_structural_architecture<'a>(
+    project: &Project,
+    package: &'a Package,
+    graph: &ImplementationGraph,
+) -> Result<Architecture<'a>> {
+    let mut architecture = Architecture::new(
+        Name::try_new("work")?,
+        Name::try_new("Structural")?,
+        package,
+        cat!(graph.streamlet_key(), CANON_SUFFIX.unwrap()),
+    )?;
+    let entity_ports = architecture.entity_ports()?;
+    let entity_port = |port_name: &str| -> Result<&ObjectDeclaration> {
+        entity_ports.get(port_name).ok_or_else(|| {
+            Error::BackEndError(format!("Entity does not have a {} port", port_name))
+        })
+    };
+
+    // Signals connected to the ports of the instantiated nodes, by node and port name.
+    let mut node_signals: HashMap<(NodeKey, String), ObjectDeclaration> = HashMap::new();
+    let mut this_assigns = vec![];
+
+    for edge in graph.edges() {
+        let (source, sink) = (edge.source(), edge.sink());
+        let ports = graph
+            .get_node(source.node())?
+            .iface(source.iface())?
+            .canonical("");
+        for port in ports {
+            let signal = ObjectDeclaration::signal(
+                cat!(source.node(), source.iface(), port.identifier()),
+                port.typ().try_into()?,
+                None,
+            );
+            for handle in [&source, &sink].iter() {
+                let port_name = cat!(handle.iface(), port.identifier());
+                if handle.node().deref() == THIS_KEY {
+                    let ent_port = entity_port(&port_name)?;
+                    this_assigns.push(match ent_port.mode() {
+                        ObjectMode::Assigned => signal.assign(ent_port)?,
+                        _ => ent_port.assign(&signal)?,
+                    });
+                } else {
+                    node_signals.insert((handle.node(), port_name), signal.clone());
+                }
+            }
+            architecture.add_declaration(signal)?;
+        }
+    }
+
+    let mut nodes = graph
+        .nodes()
+        .filter(|node| node.key().deref() != THIS_KEY)
+        .collect::<Vec<_>>();
+    nodes.sort_by_key(|node| node.key());
+
+    let mut portmaps = vec![];
+    for node in nodes {
+        let component = node.component();
+        let lib = component_library(project, &component.key())?;
+        architecture.add_using(Name::try_new("work")?, format!("{}.all", lib));
+
+        // The component is instantiated as it is declared in the package of its library, with
+        // the bound parameters mapped to its generics. Its ports for the bound values have to
+        // match the ports of the instance.
+        let declared = project
+            .get_streamlet(node.streamlet_handle())?
+            .canonical(CANON_SUFFIX);
+        let bindings = node
+            .parameters()
+            .iter()
+            .filter_map(|(key, value)| match value {
+                ParameterVariant::UInt(value) => Some((key.to_string(), *value)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let ports = declared.bound_ports(&bindings);
+        if ports
+            != component
+                .streamlet()
+                .canonical(CANON_SUFFIX)
+                .bound_ports(&[])
+        {
+            return Err(Error::BackEndError(format!(
+                "Node {} binds parameters of {} which change its ports in a way that can not \
+                 be expressed in the generics of {}",
+                node.key(),
+                component.key(),
+                declared.identifier()
+            )));
+        }
+        let mut portmap = PortMapping::from_component(
+            &Component::new(
+                declared.identifier(),
+                declared.parameters().clone(),
+                ports,
+                None,
+            ),
+            node.key().to_string(),
+        )?;
+        for (key, value) in node.parameters() {
+            match value {
+                ParameterVariant::UInt(value) => {
+                    portmap.map_generic(key.to_string(), IntegerValue::Literal(*value as i32))?;
+                }
+                ParameterVariant::String(value) => {
+                    portmap.map_generic(key.to_string(), value.as_str())?;
+                }
+                // Types are reflected in the types of the ports.
+                ParameterVariant::Type(_) => (),
+            }
+        }
+        portmap.map_port("clk", entity_port("clk")?)?;
+        portmap.map_port("rst", entity_port("rst")?)?;
+        for iface in component.interfaces() {
+            let mut unconnected = false;
+            for port in iface.canonical("") {
+                let port_name = cat!(iface.identifier(), port.identifier());
+                let signal = match node_signals.get(&(node.key(), port_name.clone())) {
+                    Some(signal) => signal.clone(),
+                    None => {
+                        unconnected = true;
+                        let signal = ObjectDeclaration::signal(
+                            cat!(node.key(), port_name),
+                            port.typ().try_into()?,
+                            None,
+                        );
+                        architecture.add_declaration(signal.clone())?;
+                        signal
+                    }
+                };
+                portmap.map_port(port_name, &signal)?;
+            }
+            if unconnected {
+                log::warn!(
+                    "Interface {} of node {} in the implementation of {} is not connected.",
+                    iface.identifier(),
+                    node.key(),
+                    graph.streamlet_key()
+                );
+            }
+        }
+        portmaps.push(portmap.finish()?);
+    }
+
+    for assign in this_assigns {
+        architecture.add_statement(assign)?;
+    }
+    for portmap in portmaps {
+        architecture.add_statement(portmap)?;
+    }
+
+    Ok(architecture)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::convert::TryFrom;
+
+    use crate::design::implementation::composer::parser::ImplParser;
+    use crate::design::implementation::composer::GenericComponent;
+    use crate::design::implementation::Implementation;
+    use crate::design::{Library, StreamletHandle};
+    use crate::generator::{common::convert::Packify, vhdl::Declare};
+    use crate::parser::nom::streamlet;
+
+    use super::*;
+
+    pub(crate) fn structural_test_proj() -> Result<Project> {
+        let mut prj = Project::new(Name::try_new("structural")?);
+        prj.add_lib(Library::try_new(
+            Name::try_new("test")?,
+            vec![],
+            vec![
+                streamlet("Streamlet top (a : in Stream<Bits<8>>, b : out Stream<Bits<8>>)")
+                    .unwrap()
+                    .1,
+                streamlet("Streamlet pass (i : in Stream<Bits<8>>, o : out Stream<Bits<8>>)")
+                    .unwrap()
+                    .1,
+            ],
+        )?)?;
+        let mut builder = ImplParser::try_new(
+            &mut prj,
+            "impl test.top structural {
+                first: test.pass;
+                second: test.pass;
+                first.i <= this.a;
+                second.i <= first.o;
+                this.b <= second.o;
+            }",
+        )?;
+        builder.transform_body()?;
+        let imp = builder.finish();
+        prj.add_streamlet_impl(
+            StreamletHandle {
+                lib: Name::try_from("test")?,
+                streamlet: Name::try_from("top")?,
+            },
+            imp,
+        )?;
+        Ok(prj)
+    }
+
+    #[test]
+    fn structural_architecture() -> Result<()> {
+        let prj = structural_test_proj()?;
+        let lib = prj.get_lib(Name::try_from("test")?)?;
+        let pak = lib.canonical();
+        let top = lib.get_streamlet(Name::try_from("top")?)?;
+        let arch = match top.get_implementation().as_deref() {
+            Some(Implementation::Structural(graph)) => {
+                generate_structural_architecture(&prj, &pak, graph)?
+            }
+            _ => panic!("Expected a structural implementation"),
+        };
+        assert_eq!(
+            r#"library ieee;
+use ieee.std_logic_1164.all;
+
+library work;
+use work.test.all;
+
+entity top_com is
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    a_valid : in std_logic;
+    a_ready : out std_logic;
+    a_data : in std_logic_vector(7 downto 0);
+    b_valid : out std_logic;
+    b_ready : in std_logic;
+    b_data : out std_logic_vector(7 downto 0)
+  );
+end top_com;
+
+architecture Structural of top_com is
+   signal this_a_valid : std_logic;
+   signal this_a_ready : std_logic;
+   signal this_a_data : std_logic_vector(7 downto 0);
+   signal first_o_valid : std_logic;
+   signal first_o_ready : std_logic;
+   signal first_o_data : std_logic_vector(7 downto 0);
+   signal second_o_valid : std_logic;
+   signal second_o_ready : std_logic;
+   signal second_o_data : std_logic_vector(7 downto 0);
+begin
+   this_a_valid <= a_valid;
+   a_ready <= this_a_ready;
+   this_a_data <= a_data;
+   b_valid <= second_o_valid;
+   second_o_ready <= b_ready;
+   b_data <= second_o_data;
+   first: pass_com port map(
+     clk => clk,
+     rst => rst,
+     i_valid => this_a_valid,
+     i_ready => this_a_ready,
+     i_data => this_a_data,
+     o_valid => first_o_valid,
+     o_ready => first_o_ready,
+     o_data => first_o_data
+   );
+   second: pass_com port map(
+     clk => clk,
+     rst => rst,
+     i_valid => first_o_valid,
+     i_ready => first_o_ready,
+     i_data => first_o_data,
+     o_valid => second_o_valid,
+     o_ready => second_o_ready,
+     o_data => second_o_data
+   );
+end Structural;
+"#,
+            arch.declare()?
+        );
+        Ok(())
+    }
+}
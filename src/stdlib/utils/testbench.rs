@@ -0,0 +1,596 @@
+//! Testbenches for streamlets.
+//!
+//! A testbench instantiates the canonical component of a streamlet, drives
+//! its clock and reset, and has a source for every physical stream going into
+//! the component and a sink for every physical stream coming out of it.
+//!
+//! The sources and sinks read their transfers from text files, with one
+//! transfer per line. These are written by [`write_transfers`], which encodes
+//! the elements of a physical stream with [`PhysicalStream::encode`]. Sources
+//! drive the transfers in order, sinks compare every handshaked transfer with
+//! the next expected transfer. The data of lanes which are not active in an
+//! expected transfer is not compared. The simulation stops when all sources
+//! and sinks are done, and fails when they are not done within
+//! [`TIMEOUT_CYCLES`] clock cycles, e.g. because a sink stalls.
+//!
+//! [`write_transfers`]: ./fn.write_transfers.html
+//! [`TIMEOUT_CYCLES`]: ./constant.TIMEOUT_CYCLES.html
+//! [`PhysicalStream::encode`]: ../../../physical/struct.PhysicalStream.html#method.encode
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use crate::{
+    cat,
+    design::{implementation::composer::GenericComponent, Mode, Streamlet},
+    generator::common::{convert::CANON_SUFFIX, Package},
+    physical::{
+        transfer::{Data, Transfer},
+        PhysicalStream,
+    },
+    stdlib::common::{
+        architecture::{
+            assignment::{Assign, StdLogicValue},
+            declaration::{ArchitectureDeclaration, ObjectDeclaration},
+            object::ObjectType,
+            statement::{
+                process::{
+                    Call, Condition, IfStatement, Process, SequentialStatement, Severity,
+                    WaitStatement,
+                },
+                PortMapping,
+            },
+            Architecture,
+        },
+        entity::Entity,
+    },
+    Error, Identify, Name, PathName, Result,
+};
+
+/// The clock period of testbenches, in nanoseconds.
+pub const CLOCK_PERIOD_NS: u32 = 10;
+
+/// The number of clock cycles after which a testbench fails, if its sources
+/// and sinks are not done.
+pub const TIMEOUT_CYCLES: u64 = 100_000;
+
+/// Returns the name of the file with the transfers of the physical stream
+/// whose ports start with `prefix`, on the canonical component of
+/// `streamlet`.
+pub fn transfer_file(streamlet: &Streamlet, prefix: &str) -> String {
+    format!("{}.txt", cat!(streamlet.identifier(), prefix))
+}
+
+/// Returns the lines of a transfer file, with the transfers which encode
+/// `items` on `stream`.
+///
+/// Every line has the signals of a transfer besides `valid` and `ready`, in
+/// the order of the ports of the canonical component, separated by spaces.
+/// Signals are written with the most significant bit first. If `expected` is
+/// true, the data bits of lanes which are not active are written as `-`.
+pub fn transfer_lines(stream: &PhysicalStream, items: &[Data], expected: bool) -> Result<String> {
+    let mut result = String::new();
+    for transfer in stream.encode(items)? {
+        let signals = transfer
+            .signals()
+            .map(|(name, value)| {
+                if expected && name == "data" {
+                    dont_care_lanes(stream, &transfer)
+                } else {
+                    value.to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+        result.push_str(&signals.join(" "));
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+/// Returns the data signal of `transfer`, with the bits of inactive lanes
+/// replaced by `-`.
+fn dont_care_lanes(stream: &PhysicalStream, transfer: &Transfer) -> String {
+    let data = transfer.data().cloned().unwrap_or_default();
+    let lanes = stream.element_lanes().get() as usize;
+    let width = data.len() / lanes;
+    let active = transfer.active_lanes(stream);
+    (0..data.len())
+        .rev()
+        .map(
+            |bit| match (active.contains(&(bit / width)), data.get(bit)) {
+                (false, _) => '-',
+                (true, true) => '1',
+                (true, false) => '0',
+            },
+        )
+        .collect()
+}
+
+/// Writes the file with the transfers which encode `items` on the physical
+/// stream at `path` of the interface `interface` of `streamlet` into `dir`.
+///
+/// For interfaces going into the streamlet, these are the transfers driven by
+/// the source of the testbench. For interfaces coming out of the streamlet,
+/// these are the transfers expected by its sink.
+pub fn write_transfers(
+    dir: impl AsRef<Path>,
+    streamlet: &Streamlet,
+    interface: &str,
+    path: &PathName,
+    items: &[Data],
+) -> Result<()> {
+    let interface = streamlet.get_interface(Name::try_new(interface)?)?;
+    let synth = interface.typ().synthesize();
+    let stream = synth
+        .streams()
+        .find(|(stream_path, _)| *stream_path == path)
+        .map(|(_, stream)| stream)
+        .ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "Interface {} does not have a physical stream at {}",
+                interface.key(),
+                path
+            ))
+        })?;
+    let mut file = dir.as_ref().to_path_buf();
+    file.push(transfer_file(streamlet, &cat!(interface.key(), path)));
+    std::fs::write(
+        file,
+        transfer_lines(stream, items, interface.mode() == Mode::Out)?,
+    )?;
+    Ok(())
+}
+
+/// Generates a testbench for the canonical component of `streamlet`, which is
+/// declared in `package`.
+pub fn generate_testbench<'a>(
+    package: &'a Package,
+    streamlet: &Streamlet,
+) -> Result<Architecture<'a>> {
+    let component = package.get_component(cat!(streamlet.key(), CANON_SUFFIX.unwrap()))?;
+    let testbench = cat!(streamlet.key(), "tb");
+    let mut architecture = Architecture::from_entity(
+        Name::try_new("work")?,
+        Name::try_new("Behavioral")?,
+        package,
+        Entity::new(
+            &testbench,
+            vec![],
+            vec![],
+            Some(format!("Testbench for {}.", streamlet.key())),
+        ),
+    )?;
+    architecture.add_using(Name::try_new("std")?, "textio.all".to_string());
+
+    let mut portmap = PortMapping::from_component(&component, "dut")?;
+    let mut signals = IndexMap::new();
+    for (port_name, object) in portmap.ports() {
+        signals.insert(
+            port_name.to_string(),
+            ObjectDeclaration::signal(port_name, object.typ().clone(), None),
+        );
+    }
+    for (port_name, signal) in &signals {
+        portmap.map_port(port_name, signal)?;
+    }
+    let signal = |port: &str| {
+        signals.get(port).cloned().ok_or_else(|| {
+            Error::BackEndError(format!(
+                "Component {} does not have a {} port",
+                component.identifier(),
+                port
+            ))
+        })
+    };
+    let clk = signal("clk")?;
+    let rst = signal("rst")?;
+
+    let mut declarations = vec![READ_BITS.to_string(), MATCHES.to_string()];
+    let mut processes = vec![];
+    let mut done = vec![];
+    for interface in streamlet.interfaces() {
+        for (path, stream) in interface.typ().synthesize().streams() {
+            let prefix = cat!(interface.key(), path);
+            let ports = Transfer::new(stream)
+                .signals()
+                .map(|(name, _)| Ok((name, signal(&cat!(prefix, name))?)))
+                .collect::<Result<Vec<_>>>()?;
+            let stream = TestStream {
+                valid: signal(&cat!(prefix, "valid"))?,
+                ready: signal(&cat!(prefix, "ready"))?,
+                done: ObjectDeclaration::signal(
+                    cat!(prefix, "done"),
+                    ObjectType::Bit,
+                    Some(StdLogicValue::Logic(false).into()),
+                ),
+                file: transfer_file(streamlet, &prefix),
+                prefix,
+                ports,
+            };
+            declarations.extend(stream.declarations());
+            processes.push(match interface.mode() {
+                Mode::In => stream.source(&clk, &rst)?,
+                Mode::Out => stream.sink(&clk, &rst)?,
+            });
+            done.push(stream.done);
+        }
+    }
+
+    // Without sources or sinks, the testbench is done once it is out of reset
+    let all_done = match done.split_first() {
+        Some((first, rest)) => rest.iter().try_fold(is_high(first)?, |condition, signal| {
+            Ok::<_, Error>(condition.and(is_high(signal)?))
+        })?,
+        None => Condition::equals(&rst, StdLogicValue::Logic(false))?,
+    };
+
+    for (_, signal) in signals {
+        architecture.add_declaration(signal)?;
+    }
+    for signal in done {
+        architecture.add_declaration(signal)?;
+    }
+    for declaration in declarations {
+        architecture.add_declaration(ArchitectureDeclaration::Custom(declaration))?;
+    }
+    architecture.add_statement(portmap)?;
+    architecture.add_statement(clock(&testbench, &clk, &all_done)?)?;
+    architecture.add_statement(reset(&rst)?)?;
+    architecture.add_statement(timeout(&testbench, &all_done)?)?;
+    for process in processes {
+        architecture.add_statement(process)?;
+    }
+    Ok(architecture)
+}
+
+/// Procedure to read a signal from a line of a transfer file.
+const READ_BITS: &str = "procedure read_bits(l : inout line; value : out std_logic_vector) is
+  variable c : character;
+begin
+  c := ' ';
+  while c = ' ' loop
+    read(l, c);
+  end loop;
+  for i in value'range loop
+    case c is
+      when '0' => value(i) := '0';
+      when '1' => value(i) := '1';
+      when others => value(i) := '-';
+    end case;
+    if i /= value'right then
+      read(l, c);
+    end if;
+  end loop;
+end procedure";
+
+/// Functions to compare a signal with an expected value, ignoring `-` bits.
+const MATCHES: &str = "function matches(actual, expected : std_logic_vector) return boolean is
+begin
+  for i in expected'range loop
+    if expected(i) /= '-' and expected(i) /= actual(i) then
+      return false;
+    end if;
+  end loop;
+  return true;
+end function;
+function matches(actual, expected : std_logic) return boolean is
+begin
+  return expected = '-' or expected = actual;
+end function";
+
+/// Returns a condition which holds when `signal` is '1'.
+fn is_high(signal: &ObjectDeclaration) -> Result<Condition> {
+    Condition::equals(signal, StdLogicValue::Logic(true))
+}
+
+/// Returns the clock process, which stops when `done` holds.
+fn clock(testbench: &str, clk: &ObjectDeclaration, done: &Condition) -> Result<Process> {
+    let half = u64::from(CLOCK_PERIOD_NS / 2);
+    let mut process = Process::new("clk_gen");
+    process.add_statement(clk.assign(&StdLogicValue::Logic(false))?)?;
+    process.add_statement(WaitStatement::for_ns(half))?;
+    process.add_statement(clk.assign(&StdLogicValue::Logic(true))?)?;
+    process.add_statement(WaitStatement::for_ns(half))?;
+    process.add_statement(IfStatement::new(
+        done.clone(),
+        vec![
+            SequentialStatement::report(format!("{} done", testbench), Severity::Note),
+            WaitStatement::forever().into(),
+        ],
+    ))?;
+    Ok(process)
+}
+
+/// Returns the reset process, which resets for two clock cycles.
+fn reset(rst: &ObjectDeclaration) -> Result<Process> {
+    let mut process = Process::new("rst_gen");
+    process.add_statement(rst.assign(&StdLogicValue::Logic(true))?)?;
+    process.add_statement(WaitStatement::for_ns(2 * u64::from(CLOCK_PERIOD_NS)))?;
+    process.add_statement(rst.assign(&StdLogicValue::Logic(false))?)?;
+    process.add_statement(WaitStatement::forever())?;
+    Ok(process)
+}
+
+/// Returns the timeout process, which fails the simulation if `done` does not
+/// hold within [`TIMEOUT_CYCLES`] clock cycles, e.g. because a sink stalls.
+///
+/// [`TIMEOUT_CYCLES`]: ./constant.TIMEOUT_CYCLES.html
+fn timeout(testbench: &str, done: &Condition) -> Result<Process> {
+    let mut process = Process::new("timeout");
+    process.add_statement(
+        WaitStatement::until(done.clone())
+            .with_timeout_ns(TIMEOUT_CYCLES * u64::from(CLOCK_PERIOD_NS)),
+    )?;
+    process.add_statement(IfStatement::new(
+        !done.clone(),
+        vec![SequentialStatement::report(
+            format!("{} timed out", testbench),
+            Severity::Failure,
+        )],
+    ))?;
+    process.add_statement(WaitStatement::forever())?;
+    Ok(process)
+}
+
+/// A physical stream of the component under test, which is driven by a
+/// source or checked by a sink.
+struct TestStream<'s> {
+    /// The prefix of the ports of the stream
+    prefix: String,
+    /// The transfer file of the stream
+    file: String,
+    /// The signals of a transfer besides `valid` and `ready`, by name
+    ports: Vec<(&'s str, ObjectDeclaration)>,
+    valid: ObjectDeclaration,
+    ready: ObjectDeclaration,
+    /// Set when the source or sink of the stream is done
+    done: ObjectDeclaration,
+}
+
+impl TestStream<'_> {
+    /// Returns the declarations of the transfer file, and of the procedure
+    /// which reads its next transfer.
+    ///
+    /// The procedure sets `available` to '0' at the end of the file, and to
+    /// '1' after reading a transfer into its other parameters.
+    fn declarations(&self) -> Vec<String> {
+        let mut parameters = vec!["available : out std_logic".to_string()];
+        let mut scalar = false;
+        let mut reads = String::new();
+        for (name, signal) in &self.ports {
+            if let ObjectType::Bit = signal.typ() {
+                scalar = true;
+                parameters.push(format!("{} : out std_logic", name));
+                reads.push_str(&format!(
+                    "    read_bits(l, scalar);\n    {} := scalar(0);\n",
+                    name
+                ));
+            } else {
+                parameters.push(format!("{} : out std_logic_vector", name));
+                reads.push_str(&format!("    read_bits(l, {});\n", name));
+            }
+        }
+        vec![
+            format!(
+                "file {}_transfers : text open read_mode is \"{}\"",
+                self.prefix, self.file
+            ),
+            format!(
+                "procedure {p}_read({parameters}) is
+  variable l : line;
+{scalar}begin
+  available := '0';
+  if not endfile({p}_transfers) then
+    readline({p}_transfers, l);
+{reads}    available := '1';
+  end if;
+end procedure",
+                p = self.prefix,
+                parameters = parameters.join("; "),
+                scalar = if scalar {
+                    "  variable scalar : std_logic_vector(0 downto 0);\n"
+                } else {
+                    ""
+                },
+                reads = reads,
+            ),
+        ]
+    }
+
+    /// Returns the variables a transfer is read into.
+    fn variables(&self) -> Vec<ObjectDeclaration> {
+        self.ports
+            .iter()
+            .map(|(name, signal)| ObjectDeclaration::variable(*name, signal.typ().clone(), None))
+            .collect()
+    }
+
+    /// Returns a call of the procedure which reads the next transfer.
+    fn read(&self, available: &ObjectDeclaration) -> Call {
+        self.variables().iter().fold(
+            Call::new(cat!(self.prefix, "read")).with_argument(available),
+            |call, variable| call.with_argument(variable),
+        )
+    }
+
+    /// Returns the source process of the stream, which drives the transfers
+    /// in its file.
+    fn source(&self, clk: &ObjectDeclaration, rst: &ObjectDeclaration) -> Result<Process> {
+        let available = ObjectDeclaration::variable("available", ObjectType::Bit, None);
+        let mut drive = vec![];
+        for ((_, signal), variable) in self.ports.iter().zip(self.variables()) {
+            drive.push(signal.assign(&variable)?.into());
+        }
+        drive.push(self.valid.assign(&StdLogicValue::Logic(true))?.into());
+        let next = IfStatement::new(
+            Condition::equals(&self.valid, StdLogicValue::Logic(false))?.or(is_high(&self.ready)?),
+            vec![
+                self.read(&available).into(),
+                IfStatement::new(is_high(&available)?, drive)
+                    .with_else(vec![
+                        self.valid.assign(&StdLogicValue::Logic(false))?.into(),
+                        self.done.assign(&StdLogicValue::Logic(true))?.into(),
+                    ])
+                    .into(),
+            ],
+        );
+        let mut process = Process::clocked_with_reset(
+            cat!(self.prefix, "source"),
+            clk,
+            rst,
+            vec![self.valid.assign(&StdLogicValue::Logic(false))?.into()],
+            vec![next.into()],
+        )?
+        .with_doc(format!("Drives the transfers in {}", self.file));
+        process.add_variable(available)?;
+        for variable in self.variables() {
+            process.add_variable(variable)?;
+        }
+        Ok(process)
+    }
+
+    /// Returns the sink process of the stream, which compares the handshaked
+    /// transfers with the transfers in its file.
+    fn sink(&self, clk: &ObjectDeclaration, rst: &ObjectDeclaration) -> Result<Process> {
+        let low = || Some(StdLogicValue::Logic(false).into());
+        let available = ObjectDeclaration::variable("available", ObjectType::Bit, low());
+        let loaded = ObjectDeclaration::variable("loaded", ObjectType::Bit, low());
+        let mut check = vec![];
+        for ((_, signal), variable) in self.ports.iter().zip(self.variables()) {
+            let matches = Call::new("matches")
+                .with_argument(signal)
+                .with_argument(&variable);
+            check.push(
+                IfStatement::new(
+                    !Condition::from(matches),
+                    vec![SequentialStatement::report(
+                        format!(
+                            "{} does not match the expected transfer",
+                            signal.identifier()
+                        ),
+                        Severity::Error,
+                    )],
+                )
+                .into(),
+            );
+        }
+        check.push(loaded.assign(&StdLogicValue::Logic(false))?.into());
+        let statements = vec![
+            IfStatement::new(is_high(&self.valid)?.and(is_high(&self.ready)?), check).into(),
+            IfStatement::new(
+                Condition::equals(&loaded, StdLogicValue::Logic(false))?,
+                vec![
+                    self.read(&available).into(),
+                    loaded.assign(&StdLogicValue::Logic(true))?.into(),
+                ],
+            )
+            .into(),
+            IfStatement::new(
+                is_high(&available)?,
+                vec![self.ready.assign(&StdLogicValue::Logic(true))?.into()],
+            )
+            .with_else(vec![
+                self.ready.assign(&StdLogicValue::Logic(false))?.into(),
+                self.done.assign(&StdLogicValue::Logic(true))?.into(),
+            ])
+            .into(),
+        ];
+        let mut process = Process::clocked_with_reset(
+            cat!(self.prefix, "sink"),
+            clk,
+            rst,
+            vec![self.ready.assign(&StdLogicValue::Logic(false))?.into()],
+            statements,
+        )?
+        .with_doc(format!("Checks the transfers against {}", self.file));
+        process.add_variable(available)?;
+        process.add_variable(loaded)?;
+        for variable in self.variables() {
+            process.add_variable(variable)?;
+        }
+        Ok(process)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{
+        design::Library,
+        generator::{common::convert::Packify, vhdl::Declare},
+        physical::transfer::BitVec,
+    };
+
+    use super::*;
+
+    #[test]
+    fn lines() -> Result<()> {
+        let stream = PhysicalStream::try_new(vec![("a", 2)], 2, 1, 4, vec![])?;
+        let element = |value| Ok(Data::element(BitVec::from_unsigned(value, 2)?));
+        let items = vec![Data::sequence(
+            (1..=3).map(element).collect::<Result<Vec<_>>>()?,
+        )];
+        assert_eq!(
+            transfer_lines(&stream, &items, false)?,
+            "1001 0 1 11\n0011 1 0 11\n"
+        );
+        assert_eq!(
+            transfer_lines(&stream, &items, true)?,
+            "1001 0 1 11\n--11 1 0 11\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn testbench() -> Result<()> {
+        let (_, streamlet) = crate::parser::nom::streamlet(
+            "Streamlet test (a : in Stream<Bits<1>>, b : out Stream<Bits<2>, d=1, c=1>)",
+        )
+        .unwrap();
+        let lib = Library::try_new(Name::try_from("test")?, vec![], vec![streamlet.clone()])?;
+        let tb = generate_testbench(&lib.fancy(), &streamlet)?.declare()?;
+        assert!(tb.contains(
+            "use std.textio.all;\n\n--Testbench for test.\nentity test_tb is\nend test_tb;"
+        ));
+        assert!(tb.contains("   dut: test_com port map(\n     clk => clk,\n     rst => rst,\n"));
+        assert!(tb.contains(
+            "   file a_transfers : text open read_mode is \"test_a.txt\";\n   \
+             procedure a_read(available : out std_logic; data : out std_logic_vector) is\n"
+        ));
+        assert!(tb.contains(
+            "     wait for 5 ns;\n     if a_done = '1' and b_done = '1' then\n       \
+             report \"test_tb done\" severity note;\n       wait;\n"
+        ));
+        assert!(tb.contains(
+            "     wait until a_done = '1' and b_done = '1' for 1000000 ns;\n     \
+             if not (a_done = '1' and b_done = '1') then\n       \
+             report \"test_tb timed out\" severity failure;\n"
+        ));
+        assert!(tb.contains(
+            "   a_source: process(clk)\n     variable available : std_logic;\n     \
+             variable data : std_logic_vector(0 downto 0);\n"
+        ));
+        assert!(tb.contains(
+            "           a_read(available, data);\n           if available = '1' then\n             \
+             a_data <= data;\n             a_valid <= '1';\n"
+        ));
+        assert!(tb.contains("   b_sink: process(clk)\n"));
+        assert!(tb.contains(
+            "           if not (matches(b_last, last)) then\n             \
+             report \"b_last does not match the expected transfer\" severity error;\n"
+        ));
+        assert!(tb.contains("           b_read(available, data, last, strb);\n"));
+
+        let tmpdir = tempfile::tempdir()?;
+        let item = Data::sequence(vec![Data::element("10".parse()?)]);
+        write_transfers(&tmpdir, &streamlet, "b", &PathName::new_empty(), &[item])?;
+        assert_eq!(
+            std::fs::read_to_string(tmpdir.path().join("test_b.txt"))?,
+            "10 1 1\n"
+        );
+        assert!(write_transfers(&tmpdir, &streamlet, "c", &PathName::new_empty(), &[]).is_err());
+        Ok(())
+    }
+}
@@ -16,6 +16,9 @@ mod tests {
     use tydi::{Name, Result, UniqueKeyBuilder};
     use tydi::design::implementation::composer::parser::ImplParser;
     use tydi::generator::vhdl::VHDLBackEnd;
+    use tydi::design::implementation::composer::GenericComponent;
+    use tydi::design::implementation::Implementation;
+    use tydi::logical::LogicalType;
 
     pub fn spark_example_prj() -> Result<Project> {
         let key1 = LibKey::try_new("primitives").unwrap();
@@ -62,7 +65,7 @@ mod tests {
             .unwrap();*/
 
         let matcher_parsed = lib
-            .add_streamlet(streamlet("Streamlet RegexMatcher (in: in Stream<Bits<8>, t=20, d=2, c=7>, out: out Stream<Bits<1>, d=0>)").unwrap().1)
+            .add_streamlet(streamlet("Streamlet RegexMatcher<regex: String = \".*\"> (in: in Stream<Bits<8>, t=20, d=2, c=7>, out: out Stream<Bits<1>, d=0>)").unwrap().1)
             .unwrap();
 
         /*let _test_op = lib
@@ -110,7 +113,7 @@ mod tests {
 
         let prj = spark_example_prj().unwrap();
         //let prj = pow2_example().unwrap();
-        let dot = DotBackend {};
+        let dot = DotBackend::default();
         // TODO: implement actual test.
 
         assert!(dot.generate(&prj, tmpdir).is_ok());
@@ -203,4 +206,460 @@ mod tests {
 
         assert!(vhdl.generate(&prj, "output").is_ok());
     }
+
+    /// Construct a project with a library of primitives, and a top level streamlet implemented
+    /// through the given implementation.
+    fn pattern_prj(top: &str, top_impl: &str) -> Result<Project> {
+        let mut lib = Library::new(LibKey::try_new("primitives")?);
+        lib.add_streamlet(
+            streamlet("Streamlet Inc (in: in Stream<Bits<8>>, out: out Stream<Bits<8>>)")
+                .unwrap()
+                .1,
+        )?;
+        lib.add_streamlet(
+            streamlet(
+                "Streamlet Add (\
+                 in: in Stream<Group<op1: Bits<8>, op2: Bits<8>>>,\
+                 out: out Stream<Bits<8>>)",
+            )
+            .unwrap()
+            .1,
+        )?;
+        lib.add_streamlet(
+            streamlet(
+                "Streamlet Fifo<W: UInt = 8, depth: UInt = 4> (\
+                 in: in Stream<Bits<W>>,\
+                 out: out Stream<Bits<W>>)",
+            )
+            .unwrap()
+            .1,
+        )?;
+        let mut lib_comp = Library::new(LibKey::try_new("compositions")?);
+        lib_comp.add_streamlet(streamlet(top).unwrap().1)?;
+
+        let mut prj = Project::new(Name::try_new("Patterns")?);
+        prj.add_lib(lib)?;
+        prj.add_lib(lib_comp)?;
+
+        let mut builder = ImplParser::try_new(&mut prj, top_impl)?;
+        builder.transform_body()?;
+        let imp = builder.finish();
+        prj.add_streamlet_impl(
+            StreamletHandle {
+                lib: Name::try_from("compositions")?,
+                streamlet: Name::try_from("Top_level")?,
+            },
+            imp,
+        )?;
+        Ok(prj)
+    }
+
+    /// Returns the type of an interface of the streamlet generated for pattern [node] of
+    /// compositions.Top_level.
+    fn gen_type(prj: &Project, node: &str, iface: &str) -> Result<LogicalType> {
+        Ok(prj
+            .get_lib(Name::try_from("gen")?)?
+            .get_streamlet(Name::try_from(format!("Top_level_{}_gen", node))?)?
+            .get_interface(Name::try_from(iface)?)?
+            .typ())
+    }
+
+    /// Returns the type of an interface parsed from a Streamlet Definition File snippet.
+    fn parsed_type(iface: &str) -> LogicalType {
+        interface(iface).unwrap().1.typ()
+    }
+
+    #[test]
+    fn concat_struct() -> Result<()> {
+        let prj = pattern_prj(
+            "Streamlet Top_level (\
+             a: in Stream<Bits<8>, d=1>,\
+             b: in Stream<Bits<16>, d=1>,\
+             a_out: out Stream<Bits<8>, d=1>,\
+             b_out: out Stream<Bits<16>, d=1>)",
+            "impl compositions.Top_level structural {
+                record: ConcatStructBuilder(a <= this.a, b <= this.b);
+                split: SplitConcatStruct(record.out);
+                this.a_out <= split.a;
+                this.b_out <= split.b;
+            }",
+        )?;
+        assert_eq!(
+            gen_type(&prj, "record", "out")?,
+            parsed_type("out: out Stream<Group<a: Bits<8>, b: Bits<16>>, d=1>")
+        );
+        assert_eq!(
+            gen_type(&prj, "split", "b")?,
+            parsed_type("b: out Stream<Bits<16>, d=1>")
+        );
+        assert!(VHDLBackEnd::default()
+            .generate(&prj, tempfile::tempdir()?)
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn concat_struct_dimensionality() -> Result<()> {
+        assert!(pattern_prj(
+            "Streamlet Top_level (\
+             a: in Stream<Bits<8>, d=1>,\
+             b: in Stream<Bits<16>, d=2>)",
+            "impl compositions.Top_level structural {
+                record: ConcatStructBuilder(a <= this.a, b <= this.b);
+            }",
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn lockstep_throughput_and_complexity() -> Result<()> {
+        for pattern in &["ConcatStructBuilder", "PackedVariantBuilder"] {
+            let err = |streamlet: &str| {
+                pattern_prj(
+                    streamlet,
+                    &format!(
+                        "impl compositions.Top_level structural {{
+                            record: {}(a <= this.a, b <= this.b);
+                        }}",
+                        pattern
+                    ),
+                )
+                .err()
+                .unwrap()
+                .to_string()
+            };
+            assert!(err(
+                "Streamlet Top_level (a: in Stream<Bits<8>, t=4>, b: in Stream<Bits<8>, t=1>)"
+            )
+            .contains(&format!(
+                "Input b of the {} pattern has throughput 1, expected 4.",
+                pattern
+            )));
+            assert!(err(
+                "Streamlet Top_level (a: in Stream<Bits<8>, c=2>, b: in Stream<Bits<8>, c=7>)"
+            )
+            .contains("has complexity 7, expected 2."));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn desync_struct() -> Result<()> {
+        let prj = pattern_prj(
+            "Streamlet Top_level (\
+             a: in Stream<Bits<8>, d=1>,\
+             b: in Stream<Bits<16>, d=2>,\
+             a_out: out Stream<Bits<8>, d=1, s=Desync>,\
+             b_out: out Stream<Bits<16>, d=2, s=Desync>)",
+            "impl compositions.Top_level structural {
+                record: DesyncStructBuilder(a <= this.a b <= this.b);
+                split: SplitDesyncStruct(record.out);
+                this.a_out <= split.a;
+                this.b_out <= split.b;
+            }",
+        )?;
+        assert_eq!(
+            gen_type(&prj, "record", "out")?,
+            parsed_type(
+                "out: out Stream<Group<\
+                 a: Stream<Bits<8>, d=1, s=Desync>,\
+                 b: Stream<Bits<16>, d=2, s=Desync>>>"
+            )
+        );
+        assert!(VHDLBackEnd::default()
+            .generate(&prj, tempfile::tempdir()?)
+            .is_ok());
+        assert!(pattern_prj(
+            "Streamlet Top_level (a: in Stream<Group<a: Bits<8>, b: Bits<16>>>)",
+            "impl compositions.Top_level structural {
+                split: SplitDesyncStruct(this.a);
+            }",
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn packed_variant() -> Result<()> {
+        let prj = pattern_prj(
+            "Streamlet Top_level (\
+             a: in Stream<Bits<8>, d=1>,\
+             b: in Stream<Bits<16>, d=1>,\
+             a_out: out Stream<Bits<8>, d=1>,\
+             b_out: out Stream<Bits<16>, d=1>)",
+            "impl compositions.Top_level structural {
+                variant: PackedVariantBuilder(a <= this.a, b <= this.b);
+                demux: DemuxPackedVariant(variant.out);
+                this.a_out <= demux.a;
+                this.b_out <= demux.b;
+            }",
+        )?;
+        assert_eq!(
+            gen_type(&prj, "variant", "out")?,
+            parsed_type("out: out Stream<Union<a: Bits<8>, b: Bits<16>>, d=1>")
+        );
+        assert!(ChiselBackEnd::default()
+            .generate(&prj, tempfile::tempdir()?)
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn concat_variant() -> Result<()> {
+        let prj = pattern_prj(
+            "Streamlet Top_level (\
+             a: in Stream<Bits<8>, d=1>,\
+             b: in Stream<Bits<16>>,\
+             a_out: out Stream<Bits<8>, d=1>,\
+             b_out: out Stream<Bits<16>>)",
+            "impl compositions.Top_level structural {
+                variant: ConcatVariantBuilder(a <= this.a, b <= this.b);
+                demux: DemuxConcatVariant(variant.out);
+                this.a_out <= demux.a;
+                this.b_out <= demux.b;
+            }",
+        )?;
+        assert_eq!(
+            gen_type(&prj, "variant", "out")?,
+            parsed_type(
+                "out: out Stream<Union<\
+                 a: Stream<Bits<8>, d=1>,\
+                 b: Stream<Bits<16>>>>"
+            )
+        );
+        assert!(VHDLBackEnd::default()
+            .generate(&prj, tempfile::tempdir()?)
+            .is_ok());
+        assert!(ChiselBackEnd::default()
+            .generate(&prj, tempfile::tempdir()?)
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn vectors() -> Result<()> {
+        let prj = pattern_prj(
+            "Streamlet Top_level (\
+             a: in Stream<Bits<8>, t=4, d=1>,\
+             b: in Stream<Bits<8>, t=4, d=1>,\
+             sum: out Stream<Bits<8>, d=1>,\
+             seq: out Stream<Bits<8>, d=2>)",
+            "impl compositions.Top_level structural {
+                map: MapVector(inc: primitives.Inc);
+                reduce: ReduceVector(add: primitives.Add);
+                seq: VectorToSeq(this.b);
+                map.in <= this.a;
+                map <=> reduce;
+                this.sum <= reduce.out;
+                this.seq <= seq.out;
+            }",
+        )?;
+        assert_eq!(
+            gen_type(&prj, "map", "out")?,
+            parsed_type("out: out Stream<Bits<8>, t=4, d=1>")
+        );
+        assert_eq!(
+            gen_type(&prj, "reduce", "out")?,
+            parsed_type("out: out Stream<Bits<8>, d=1>")
+        );
+        assert_eq!(
+            gen_type(&prj, "seq", "out")?,
+            parsed_type("out: out Stream<Bits<8>, d=2>")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn clone_stream() -> Result<()> {
+        let prj = pattern_prj(
+            "Streamlet Top_level (\
+             a: in Stream<Bits<8>, d=1>,\
+             x: out Stream<Bits<8>, d=1>,\
+             y: out Stream<Bits<8>, d=1>)",
+            "impl compositions.Top_level structural {
+                clone: CloneStream(x, y);
+                clone.in <= this.a;
+                this.x <= clone.x;
+                this.y <= clone.y;
+            }",
+        )?;
+        assert_eq!(
+            gen_type(&prj, "clone", "y")?,
+            parsed_type("y: out Stream<Bits<8>, d=1>")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parameterized_instance() -> Result<()> {
+        let top = "Streamlet Top_level (\
+                   a: in Stream<Bits<16>>,\
+                   b: out Stream<Bits<16>>)";
+        let prj = pattern_prj(
+            top,
+            "impl compositions.Top_level structural {
+                fifo: primitives.Fifo [W := 16, depth := 32];
+                fifo.in <= this.a;
+                this.b <= fifo.out;
+            }",
+        )?;
+        // The node remains an instance of the parameterised streamlet.
+        let top_level = prj
+            .get_lib(Name::try_from("compositions")?)?
+            .get_streamlet(Name::try_from("Top_level")?)?;
+        match top_level.get_implementation().as_deref() {
+            Some(Implementation::Structural(graph)) => {
+                let fifo = graph.get_node(Name::try_from("fifo")?)?;
+                assert_eq!(fifo.streamlet_handle().streamlet().to_string(), "Fifo");
+                assert_eq!(
+                    fifo.iface(Name::try_from("out")?)?.typ(),
+                    parsed_type("out: out Stream<Bits<16>>")
+                );
+            }
+            _ => panic!("Expected a structural implementation"),
+        }
+        assert!(prj
+            .get_lib(Name::try_from("gen")?)?
+            .streamlets()
+            .next()
+            .is_none());
+
+        // The bound values are mapped to the generics of the component declared in the package,
+        // of which the port widths depend on the generics.
+        let tmpdir = tempfile::tempdir()?;
+        VHDLBackEnd::default().generate(&prj, tmpdir.path())?;
+        let read = |file: &str| fs::read_to_string(tmpdir.path().join("Patterns").join(file));
+        let arch = read("Top_level_impl.gen.vhd")?;
+        assert!(!arch.contains("component Fifo_com"));
+        assert!(arch.contains("signal fifo_out_data : std_logic_vector(15 downto 0);\n"));
+        assert!(arch.contains("fifo: Fifo_com generic map(\n"));
+        assert!(arch.contains("W => 16,\n"));
+        assert!(arch.contains("depth => 32\n"));
+        let pkg = read("primitives_pkg.gen.vhd")?;
+        assert!(pkg.contains("    in_data : in std_logic_vector(W-1 downto 0);\n"));
+        assert!(pkg.contains("  data : std_logic_vector;\n"));
+        assert!(pkg.contains("    in_dn : in Fifo_in_dn_type(data(W-1 downto 0));\n"));
+        // The fancy component of the structural streamlet wraps its canonical component.
+        let wrapper = read("Top_level_wrapper.gen.vhd")?;
+        assert!(wrapper.contains("entity Top_level is\n"));
+        assert!(wrapper.contains("   a_data_wire <= a_dn.data;\n"));
+        assert!(wrapper.contains("   canonical: Top_level_com port map(\n"));
+        let wrapper = read("Fifo_wrapper.gen.vhd")?;
+        assert!(wrapper.contains("    out_data : out std_logic_vector(W-1 downto 0)\n"));
+        assert!(wrapper.contains("signal in_dn_wire : Fifo_in_dn_type(data(W-1 downto 0));\n"));
+        assert!(wrapper.contains("fancy: Fifo generic map(\n"));
+        assert!(wrapper.contains("W => W,\n"));
+        assert!(wrapper.contains("depth => depth\n"));
+
+        let tmpdir = tempfile::tempdir()?;
+        ChiselBackEnd::default().generate(&prj, tmpdir.path())?;
+        let module = fs::read_to_string(
+            tmpdir
+                .path()
+                .join("Patterns")
+                .join("compositions")
+                .join("compositions.scala"),
+        )?;
+        assert!(module.contains("  val fifo = Module(new Fifo(W = 16, depth = 32))\n"));
+
+        // The default parameter values do not match the interfaces of the top level.
+        assert!(pattern_prj(
+            top,
+            "impl compositions.Top_level structural {
+                fifo: primitives.Fifo;
+                fifo.in <= this.a;
+            }",
+        )
+        .is_err());
+        // Unknown parameter.
+        assert!(pattern_prj(
+            top,
+            "impl compositions.Top_level structural {
+                fifo: primitives.Fifo [width := 16];
+            }",
+        )
+        .is_err());
+        // Value of the wrong kind.
+        assert!(pattern_prj(
+            top,
+            "impl compositions.Top_level structural {
+                fifo: primitives.Fifo [W := \"16\"];
+            }",
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn named_type_parameter() -> Result<()> {
+        use tydi::design::namespace::{resolve, Source};
+        use tydi::parser::nom::definitions;
+
+        let source = |lib: &str, input: &str| -> Result<Source> {
+            Ok(Source::new(
+                None,
+                Name::try_from(lib)?,
+                definitions(input).unwrap().1,
+            ))
+        };
+        let project = || -> Result<Project> {
+            let libs = resolve(&[
+                source(
+                    "common",
+                    "type Pixel = Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>;
+                     Streamlet Buffer<T: Type = Bits<8>> (i: in Stream<T>, o: out Stream<T>)",
+                )?,
+                source(
+                    "app",
+                    "use common::Pixel;
+                     type Gray = Bits<8>;
+                     Streamlet top (a: in Stream<Pixel>, b: out Stream<Pixel>)",
+                )?,
+            ])?;
+            Project::from_builder(
+                Name::try_from("Patterns")?,
+                tydi::UniquelyNamedBuilder::new().with_items(libs),
+            )
+        };
+
+        // Named types imported by the library of the implemented streamlet can be bound to
+        // parameters of kind Type.
+        let mut prj = project()?;
+        prj.add_implementation(
+            "impl app.top structural {
+                buf: common.Buffer [T := Pixel];
+                buf.i <= this.a;
+                this.b <= buf.o;
+            }",
+        )?;
+        let top = prj
+            .get_lib(Name::try_from("app")?)?
+            .get_streamlet(Name::try_from("top")?)?;
+        match top.get_implementation().as_deref() {
+            Some(Implementation::Structural(graph)) => {
+                let buf = graph.get_node(Name::try_from("buf")?)?;
+                assert_eq!(
+                    buf.iface(Name::try_from("o")?)?.typ(),
+                    parsed_type("o: out Stream<Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>>")
+                );
+            }
+            _ => panic!("Expected a structural implementation"),
+        }
+
+        // Named types of the implementing library that do not match the interfaces, and
+        // identifiers that are not named types, are rejected.
+        for value in &["Gray", "Frame"] {
+            assert!(project()?
+                .add_implementation(&format!(
+                    "impl app.top structural {{
+                        buf: common.Buffer [T := {}];
+                        buf.i <= this.a;
+                    }}",
+                    value
+                ))
+                .is_err());
+        }
+        Ok(())
+    }
 }
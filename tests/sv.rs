@@ -0,0 +1,88 @@
+/// Integration tests using the SystemVerilog back-end.
+extern crate tydi;
+
+#[cfg(test)]
+mod tests {
+    use tydi::generator::common::convert::{Componentify, Packify};
+    use tydi::generator::sv::{DeclareSV, DeclareSVInPackage};
+    use tydi::Name;
+
+    #[test]
+    fn streamlet_async() {
+        let (_, streamlet) =
+            tydi::parser::nom::streamlet("Streamlet test (a : in Bits<1>, b : out Bits<2>)")
+                .unwrap();
+        assert_eq!(
+            streamlet.canonical(None).declare("test_pkg").unwrap(),
+            "module test
+  import test_pkg::*;
+(
+  input logic clk,
+  input logic rst,
+  input logic [0:0] a,
+  output logic [1:0] b
+);
+
+  // User code
+
+endmodule
+"
+        );
+    }
+
+    #[test]
+    fn streamlet_stream() {
+        let (_, streamlet) = tydi::parser::nom::streamlet(
+            "Streamlet test (a : in Stream<Group<b: Bits<1>, c: Bits<2>>>)",
+        )
+        .unwrap();
+        let lib = tydi::design::library::Library::try_new(
+            Name::try_new("test").unwrap(),
+            vec![],
+            vec![streamlet],
+        );
+
+        let lib: tydi::generator::common::Package = lib.unwrap().fancy();
+        assert_eq!(
+            lib.declare().unwrap(),
+            "package test_pkg;
+
+typedef struct packed {
+  logic [0:0] b;
+  logic [1:0] c;
+} test_a_data_t;
+
+endpackage
+
+interface test_a_if;
+  import test_pkg::*;
+
+  logic valid;
+  logic ready;
+  test_a_data_t data;
+
+  modport source(output valid, input ready, output data);
+  modport sink(input valid, output ready, input data);
+endinterface
+"
+        );
+        assert_eq!(
+            lib.get_component("test")
+                .unwrap()
+                .declare("test_pkg")
+                .unwrap(),
+            "module test
+  import test_pkg::*;
+(
+  input logic clk,
+  input logic rst,
+  test_a_if.sink a
+);
+
+  // User code
+
+endmodule
+"
+        );
+    }
+}
@@ -384,4 +384,138 @@ end test;"
         let lib: tydi::generator::common::Package = lib.unwrap().fancy();
         print!("{}", lib.declare().unwrap())
     }
+
+    #[test]
+    fn named_types() -> tydi::Result<()> {
+        use tydi::design::namespace::{resolve, Source};
+        use tydi::parser::nom::definitions;
+
+        let source = |lib: &str, sdf: &str| {
+            Source::new(
+                None,
+                Name::try_new(lib).unwrap(),
+                definitions(sdf).unwrap().1,
+            )
+        };
+        let libs = resolve(&[
+            source(
+                "common",
+                "type Pixel = Group<r: Bits<8>, g: Bits<8>, b: Bits<8>>;",
+            ),
+            source(
+                "app",
+                "use common::Pixel;
+                 type Frame = Stream<Pixel, d=2>;
+                 Streamlet blur (i: in Frame, o: out Frame, p: in Stream<Pixel>, q: in Pixel)",
+            ),
+        ])?;
+        // Named types result in a single record, shared by all ports that use them, whether
+        // they are the data of a stream or not.
+        assert_eq!(
+            libs[1].fancy().declare()?,
+            "library ieee;
+use ieee.std_logic_1164.all;
+
+package app is
+
+component blur_com
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    i_valid : in std_logic;
+    i_ready : out std_logic;
+    i_data : in std_logic_vector(23 downto 0);
+    i_last : in std_logic_vector(1 downto 0);
+    i_strb : in std_logic_vector(0 downto 0);
+    p_valid : in std_logic;
+    p_ready : out std_logic;
+    p_data : in std_logic_vector(23 downto 0);
+    q_r : in std_logic_vector(7 downto 0);
+    q_g : in std_logic_vector(7 downto 0);
+    q_b : in std_logic_vector(7 downto 0);
+    o_valid : out std_logic;
+    o_ready : in std_logic;
+    o_data : out std_logic_vector(23 downto 0);
+    o_last : out std_logic_vector(1 downto 0);
+    o_strb : out std_logic_vector(0 downto 0)
+  );
+end component;
+
+type common_Pixel_type is record
+  r : std_logic_vector(7 downto 0);
+  g : std_logic_vector(7 downto 0);
+  b : std_logic_vector(7 downto 0);
+end record;
+
+type Frame_dn_type is record
+  valid : std_logic;
+  data : common_Pixel_type;
+  last : std_logic_vector(1 downto 0);
+  strb : std_logic_vector(0 downto 0);
+end record;
+
+type Frame_up_type is record
+  ready : std_logic;
+end record;
+
+type blur_p_dn_type is record
+  valid : std_logic;
+  data : common_Pixel_type;
+end record;
+
+type blur_p_up_type is record
+  ready : std_logic;
+end record;
+
+component blur
+  port(
+    clk : in std_logic;
+    rst : in std_logic;
+    i_dn : in Frame_dn_type;
+    i_up : out Frame_up_type;
+    p_dn : in blur_p_dn_type;
+    p_up : out blur_p_up_type;
+    q : in common_Pixel_type;
+    o_dn : out Frame_dn_type;
+    o_up : in Frame_up_type
+  );
+end component;
+
+end app;"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn equal_named_types() -> tydi::Result<()> {
+        use tydi::design::namespace::{resolve, Source};
+        use tydi::parser::nom::definitions;
+
+        let libs = resolve(&[Source::new(
+            None,
+            Name::try_new("lib")?,
+            definitions(
+                "type A = Group<x: Bits<8>, y: Bits<8>>;
+                 type B = Group<x: Bits<8>, y: Bits<8>>;
+                 Streamlet s (
+                     a: in Stream<A>,
+                     b: in Stream<B>,
+                     c: in Stream<Group<x: Bits<8>, y: Bits<8>>>
+                 )",
+            )
+            .unwrap()
+            .1,
+        )])?;
+        // Records are named after the type that is used, not after any type with an equal
+        // structure.
+        let package = libs[0].fancy().declare()?;
+        assert!(package
+            .contains("type s_a_dn_type is record\n  valid : std_logic;\n  data : A_type;"));
+        assert!(package
+            .contains("type s_b_dn_type is record\n  valid : std_logic;\n  data : B_type;"));
+        assert!(package.contains(
+            "type s_c_dn_type is record\n  valid : std_logic;\n  data : s_c_data_dn_type;"
+        ));
+        Ok(())
+    }
 }